@@ -0,0 +1,130 @@
+//! ASCII-art animations.
+//!
+//! A fenced block with the `animation` language holds several frames,
+//! separated by lines containing only `---`, that are played in place when
+//! the slide is shown:
+//!
+//! ````markdown
+//! ```animation fps=2 once
+//! [=   ]
+//! ---
+//! [==  ]
+//! ---
+//! [=== ]
+//! ```
+//! ````
+//!
+//! `fps=<n>` sets the frame rate and `once` stops on the last frame instead
+//! of looping.
+
+use ratatui::{
+    style::{Color, Style},
+    text::Line,
+};
+use std::time::Duration;
+
+/// Line that separates two frames.
+const FRAME_SEPARATOR: &str = "---";
+/// Frame rate used when the block does not set one.
+const DEFAULT_FPS: f64 = 4.0;
+/// Fastest frame rate accepted.
+const MAX_FPS: f64 = 60.0;
+
+/// An animation block within a slide.
+pub struct Animation {
+    /// Index of the first slide line the animation occupies
+    pub line: usize,
+    /// Frames in playback order, each as many lines tall as the tallest frame
+    frames: Vec<Vec<Line<'static>>>,
+    /// Time each frame stays on screen
+    interval: Duration,
+    /// Whether playback starts over after the last frame
+    looping: bool,
+}
+
+impl Animation {
+    /// Parses an `animation` block whose first line will be `first_line` of
+    /// the slide, drawing its frames in `color`.
+    ///
+    /// Returns the lines to reserve in the slide, which show the first frame,
+    /// together with the animation.
+    pub fn parse(
+        content: &str,
+        modifiers: &[String],
+        first_line: usize,
+        color: Color,
+    ) -> (Vec<Line<'static>>, Animation) {
+        let mut fps = DEFAULT_FPS;
+        let mut looping = true;
+        for modifier in modifiers {
+            match modifier.split_once('=') {
+                Some(("fps", value)) => {
+                    if let Ok(value) = value.parse::<f64>()
+                        && value > 0.0
+                    {
+                        fps = value.min(MAX_FPS);
+                    }
+                }
+                _ if modifier == "once" => looping = false,
+                _ if modifier == "loop" => looping = true,
+                _ => {}
+            }
+        }
+
+        let style = Style::default().fg(color);
+        let mut frames: Vec<Vec<Line<'static>>> = vec![Vec::new()];
+        for raw in content.lines() {
+            if raw.trim() == FRAME_SEPARATOR {
+                frames.push(Vec::new());
+            } else if let Some(frame) = frames.last_mut() {
+                frame.push(Line::styled(raw.to_string(), style));
+            }
+        }
+        let height = frames.iter().map(Vec::len).max().unwrap_or(0);
+        for frame in &mut frames {
+            frame.resize(height, Line::default());
+        }
+
+        let animation = Animation {
+            line: first_line,
+            frames,
+            // Rates too slow to express as a duration play at the default.
+            interval: Duration::try_from_secs_f64(1.0 / fps)
+                .unwrap_or_else(|_| Duration::from_secs_f64(1.0 / DEFAULT_FPS)),
+            looping,
+        };
+        (animation.frames[0].clone(), animation)
+    }
+
+    /// Number of slide lines the animation occupies.
+    pub fn height(&self) -> usize {
+        self.frames[0].len()
+    }
+
+    /// Index of the frame shown `elapsed` after the slide was entered.
+    fn frame_index(&self, elapsed: Duration) -> usize {
+        let n = (elapsed.as_secs_f64() / self.interval.as_secs_f64()) as usize;
+        if self.looping {
+            n % self.frames.len()
+        } else {
+            n.min(self.frames.len() - 1)
+        }
+    }
+
+    /// Lines of the frame shown `elapsed` after the slide was entered.
+    pub fn frame(&self, elapsed: Duration) -> &[Line<'static>] {
+        &self.frames[self.frame_index(elapsed)]
+    }
+
+    /// Time until the next frame is due, or `None` once playback has stopped.
+    pub fn next_frame_in(&self, elapsed: Duration) -> Option<Duration> {
+        if self.frames.len() < 2
+            || (!self.looping && self.frame_index(elapsed) + 1 >= self.frames.len())
+        {
+            return None;
+        }
+        let interval = self.interval.as_secs_f64();
+        let into_frame = elapsed.as_secs_f64() % interval;
+        Some(Duration::from_secs_f64(interval - into_frame))
+    }
+}
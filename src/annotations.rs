@@ -0,0 +1,65 @@
+//! Presenter annotations kept between sessions.
+//!
+//! While rehearsing, `A` opens a prompt for a quick note on the current
+//! slide, such as "tighten this, cut the second example". Annotations are
+//! saved to the deck's state directory, keyed by slide title, and shown in
+//! the notes panel in later sessions. Unlike speaker notes, they never touch
+//! the deck itself.
+
+use crate::state;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// On-disk annotations of a deck.
+#[derive(Default, Serialize, Deserialize)]
+struct AnnotationsFile {
+    /// Annotations by slide title, oldest first
+    slides: BTreeMap<String, Vec<String>>,
+}
+
+/// Annotations the presenter has made on the slides of a deck.
+#[derive(Default)]
+pub struct Annotations {
+    /// Where the annotations are saved
+    path: PathBuf,
+    /// Annotations by slide title, oldest first
+    slides: BTreeMap<String, Vec<String>>,
+}
+
+impl Annotations {
+    /// Loads the annotations saved for `deck`.
+    pub fn load(deck: &Path) -> Result<Self, Box<dyn Error>> {
+        let path = state::deck_dir(deck).join("annotations.json");
+        let slides = match fs::read_to_string(&path) {
+            Ok(json) => {
+                serde_json::from_str::<AnnotationsFile>(&json)
+                    .map_err(|e| format!("Invalid annotations file '{}': {}", path.display(), e))?
+                    .slides
+            }
+            Err(_) => BTreeMap::new(),
+        };
+        Ok(Annotations { path, slides })
+    }
+
+    /// Returns the annotations on the slide titled `slide`, oldest first.
+    pub fn get(&self, slide: &str) -> &[String] {
+        self.slides.get(slide).map_or(&[], Vec::as_slice)
+    }
+
+    /// Adds `note` to the slide titled `slide` and saves the annotations.
+    pub fn add(&mut self, slide: &str, note: String) -> io::Result<()> {
+        self.slides.entry(slide.to_string()).or_default().push(note);
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = AnnotationsFile {
+            slides: self.slides.clone(),
+        };
+        fs::write(&self.path, serde_json::to_string_pretty(&file)?)
+    }
+}
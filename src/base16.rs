@@ -0,0 +1,109 @@
+//! Base16 color schemes.
+//!
+//! A [base16](https://github.com/tinted-theming/home) scheme is a YAML file
+//! giving 16 colors, `base00` to `base0F`. Thousands of them exist, and any
+//! of them can be used as a theme by passing its file to `--theme` or putting
+//! it in the themes directory. Both the original format, with the colors at
+//! the top level, and the newer one, with them under `palette:`, are read:
+//!
+//! ```yaml
+//! scheme: "Ocean"
+//! author: "Chris Kempson"
+//! base00: "2b303b"
+//! base01: "343d46"
+//! # ...
+//! base0F: "ab7967"
+//! ```
+//!
+//! The slots map onto a theme following the base16 styling guidelines:
+//! backgrounds from `base00` and `base01`, text from `base05`, comments and
+//! borders from `base03` and `base04`, and headings, links, and code from the
+//! accent colors `base08` to `base0F`.
+
+use crate::themes::{self, Borders, Theme};
+use ratatui::style::Color;
+
+/// Builds a theme from the contents of a base16 scheme file.
+///
+/// The theme is left unnamed; it takes the name of its file.
+pub fn parse(text: &str) -> Result<Theme, String> {
+    let mut palette: [Option<(u8, u8, u8)>; 16] = [None; 16];
+    let mut variant = None;
+    for line in text.lines() {
+        let Some((key, raw)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim();
+        let value = value(raw);
+        if key == "variant" {
+            variant = Some(value == "light");
+        }
+        let Some(slot) = key
+            .strip_prefix("base")
+            .filter(|digits| digits.len() == 2)
+            .and_then(|digits| usize::from_str_radix(digits, 16).ok())
+        else {
+            continue;
+        };
+        palette[slot] =
+            Some(hex(value).ok_or_else(|| format!("{key} is not a hex color: '{value}'"))?);
+    }
+    let mut colors = [(0, 0, 0); 16];
+    for (slot, color) in palette.iter().enumerate() {
+        colors[slot] = color.ok_or_else(|| format!("missing base{slot:02X}"))?;
+    }
+    let rgb = |slot: usize| {
+        let (r, g, b) = colors[slot];
+        Color::Rgb(r, g, b)
+    };
+    // Schemes without a variant are light if their background is.
+    let light = variant.unwrap_or_else(|| luminance(colors[0x00]) > 0.5);
+    Ok(Theme {
+        name: String::new(),
+        background: Some(rgb(0x00)),
+        text: rgb(0x05),
+        primary: rgb(0x0D),
+        secondary: rgb(0x0E),
+        tertiary: rgb(0x0B),
+        accent: rgb(0x0A),
+        muted: rgb(0x03),
+        border: rgb(0x04),
+        code: rgb(0x09),
+        code_background: rgb(0x01),
+        code_theme: if light {
+            "base16-ocean.light"
+        } else {
+            "base16-ocean.dark"
+        }
+        .to_string(),
+        bullets: themes::default_bullets(),
+        borders: Borders::Plain,
+    })
+}
+
+/// Returns the value after a key's colon, without quotes or a trailing
+/// comment.
+fn value(raw: &str) -> &str {
+    let raw = raw.trim();
+    for quote in ['"', '\''] {
+        if let Some(rest) = raw.strip_prefix(quote) {
+            return rest.split(quote).next().unwrap_or(rest);
+        }
+    }
+    raw.split(" #").next().unwrap_or(raw).trim()
+}
+
+/// Parses a color written as six hex digits, with or without a leading `#`.
+fn hex(value: &str) -> Option<(u8, u8, u8)> {
+    let digits = value.strip_prefix('#').unwrap_or(value);
+    if digits.len() != 6 || !digits.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Relative brightness of a color, from 0 (black) to 1 (white).
+fn luminance((r, g, b): (u8, u8, u8)) -> f64 {
+    (0.2126 * f64::from(r) + 0.7152 * f64::from(g) + 0.0722 * f64::from(b)) / 255.0
+}
@@ -0,0 +1,105 @@
+//! Timing the work done to show a deck, to guide performance work on large
+//! ones.
+//!
+//! `presentrs bench deck.md` reports how long the deck takes to parse, and
+//! for each slide how long its code blocks take to highlight and the slide
+//! takes to draw, at the terminal's size or at `--simulate WxH`. Every time
+//! is the median of `--runs` runs, which keeps one slow run (a cold cache, a
+//! busy machine) from skewing it. With `--format json`, the report is printed
+//! as JSON to compare runs with other tools.
+
+use serde::Serialize;
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// Runs `work` `runs` times and returns the median time it took.
+pub fn median(runs: usize, mut work: impl FnMut()) -> Duration {
+    let mut samples: Vec<Duration> = (0..runs.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            work();
+            start.elapsed()
+        })
+        .collect();
+    samples.sort();
+    samples[samples.len() / 2]
+}
+
+/// Times of `presentrs bench`, in milliseconds.
+#[derive(Serialize)]
+pub struct Report {
+    /// Path of the deck
+    pub file: String,
+    /// Size of the terminal slides were laid out and drawn for
+    pub size: String,
+    /// Number of runs each time is the median of
+    pub runs: usize,
+    /// Time to parse the whole deck into slides, highlighting included
+    pub parse_ms: f64,
+    /// Times of each slide, in deck order
+    pub slides: Vec<SlideTimes>,
+}
+
+/// Times of one slide.
+#[derive(Serialize)]
+pub struct SlideTimes {
+    /// Number of the slide, from 1
+    pub slide: usize,
+    /// Title of the slide, if it has one
+    pub title: Option<String>,
+    /// Number of code blocks on the slide
+    pub code_blocks: usize,
+    /// Time to highlight the slide's code blocks
+    pub highlight_ms: f64,
+    /// Time to draw the slide, with everything around it
+    pub render_ms: f64,
+}
+
+/// Converts `duration` to milliseconds.
+pub fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {} slides at {}, median of {} runs",
+            self.file,
+            self.slides.len(),
+            self.size,
+            self.runs
+        )?;
+        writeln!(f, "Parse {:>10.2} ms", self.parse_ms)?;
+        writeln!(f)?;
+        writeln!(f, "Slide  Code   Highlight      Render  Title")?;
+        for slide in &self.slides {
+            writeln!(
+                f,
+                "{:>5}  {:>4}  {:>7.2} ms  {:>7.2} ms  {}",
+                slide.slide,
+                slide.code_blocks,
+                slide.highlight_ms,
+                slide.render_ms,
+                slide.title.as_deref().unwrap_or("")
+            )?;
+        }
+        let highlight: f64 = self.slides.iter().map(|slide| slide.highlight_ms).sum();
+        let render: f64 = self.slides.iter().map(|slide| slide.render_ms).sum();
+        writeln!(f, "Total        {highlight:>7.2} ms  {render:>7.2} ms")?;
+        if let Some(slowest) = self
+            .slides
+            .iter()
+            .max_by(|a, b| a.render_ms.total_cmp(&b.render_ms))
+        {
+            write!(
+                f,
+                "Slowest to draw: slide {} ({:.2} ms)",
+                slowest.slide, slowest.render_ms
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+//! Read-only broadcast of the presentation to attendees' terminals.
+//!
+//! `--broadcast <ADDR>` listens for plain TCP connections, so anyone on the
+//! network can follow the talk in their own terminal with `nc host port` (or
+//! `telnet`). Each viewer first receives a full redraw of the screen, and
+//! after that only the cells that changed from one frame to the next, as
+//! ANSI escape sequences. Anything viewers type is ignored.
+//!
+//! Frames are handed to a writer thread per viewer, so a slow or stalled
+//! connection never holds up the presentation. A viewer who falls behind
+//! skips the frames they missed and is sent a full redraw once they catch
+//! up, and viewers who disconnect are forgotten.
+
+use crate::record;
+use ratatui::buffer::Buffer;
+use std::{
+    io::{self, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        Arc,
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+    },
+    thread,
+};
+
+/// Updates queued for a viewer before they are taken to have fallen behind.
+const VIEWER_BACKLOG: usize = 16;
+
+/// Hides the cursor and clears the screen for a new viewer.
+const WELCOME: &str = "\x1b[?25l\x1b[H\x1b[2J";
+
+/// A viewer following the broadcast.
+struct Viewer {
+    /// Updates for the viewer's writer thread
+    updates: SyncSender<Arc<str>>,
+    /// Whether the viewer missed an update and needs a full redraw
+    behind: bool,
+}
+
+/// A running broadcast and its viewers.
+pub struct Broadcast {
+    /// Address the broadcast is listening on
+    addr: SocketAddr,
+    /// Connections accepted but not yet sent a frame
+    arrivals: Receiver<TcpStream>,
+    /// Viewers already following along
+    viewers: Vec<Viewer>,
+    /// Last frame broadcast, which updates are computed against
+    last_frame: Option<Buffer>,
+}
+
+impl Broadcast {
+    /// Binds `addr` (e.g. `0.0.0.0:2323`) and starts accepting viewers in
+    /// the background.
+    pub fn start(addr: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        log::info!("Broadcasting on {addr}");
+        let (sender, arrivals) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if sender.send(stream).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Broadcast {
+            addr,
+            arrivals,
+            viewers: Vec::new(),
+            last_frame: None,
+        })
+    }
+
+    /// Address the broadcast is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Number of viewers following along.
+    pub fn viewers(&self) -> usize {
+        self.viewers.len()
+    }
+
+    /// Sends `buffer` to every viewer: in full to those who just connected
+    /// or fell behind, and as the cells that changed to everyone else.
+    pub fn frame(&mut self, buffer: &Buffer) {
+        for stream in self.arrivals.try_iter() {
+            if let Ok(peer) = stream.peer_addr() {
+                log::info!("Viewer connected from {peer}");
+            }
+            self.viewers.push(Viewer {
+                updates: spawn_writer(stream),
+                behind: true,
+            });
+        }
+        let diff: Option<Arc<str>> = match &self.last_frame {
+            Some(last) if last == buffer => None,
+            Some(last) => Some(record::buffer_diff_to_ansi(last, buffer).into()),
+            None => Some(full_redraw(buffer)),
+        };
+        let mut full: Option<Arc<str>> = None;
+        self.viewers.retain_mut(|viewer| {
+            let update = if viewer.behind {
+                full.get_or_insert_with(|| full_redraw(buffer)).clone()
+            } else if let Some(diff) = &diff {
+                diff.clone()
+            } else {
+                return true;
+            };
+            match viewer.updates.try_send(update) {
+                Ok(()) => {
+                    viewer.behind = false;
+                    true
+                }
+                Err(TrySendError::Full(_)) => {
+                    viewer.behind = true;
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    log::info!("A viewer disconnected");
+                    false
+                }
+            }
+        });
+        self.last_frame = Some(buffer.clone());
+    }
+}
+
+/// The whole of `buffer`, drawn on a cleared screen.
+fn full_redraw(buffer: &Buffer) -> Arc<str> {
+    format!("{WELCOME}{}", record::buffer_to_ansi(buffer)).into()
+}
+
+/// Starts a thread writing the updates it is sent to `stream`, until the
+/// viewer disconnects, and returns the sending end of its queue.
+fn spawn_writer(mut stream: TcpStream) -> SyncSender<Arc<str>> {
+    let (sender, updates) = mpsc::sync_channel::<Arc<str>>(VIEWER_BACKLOG);
+    thread::spawn(move || {
+        // Dropping the queue tells the broadcast the viewer is gone.
+        for update in updates {
+            if stream.write_all(update.as_bytes()).is_err() {
+                break;
+            }
+        }
+    });
+    sender
+}
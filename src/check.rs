@@ -0,0 +1,239 @@
+//! Checks run over a deck without presenting it.
+//!
+//! `presentrs check deck.md` lays every slide out as it would be presented
+//! and reports the ones whose content does not fit on screen, so a deck can
+//! be fixed before the talk rather than scrolled through during it. With
+//! `--simulate 100x30`, slides are laid out for a terminal of that size
+//! instead of the current one, such as the small terminal of a podium
+//! machine. Content that cannot be shown as written, such as raw HTML or a
+//! misspelled directive, is reported too; `--strict` refuses to present it.
+//!
+//! Teams can add rules of their own in the config file, which the check
+//! enforces on every deck:
+//!
+//! ```toml
+//! [check]
+//! max_lines = 20       # non-blank lines of markdown per slide
+//! max_bullets = 6      # list items per slide
+//! max_code_lines = 15  # lines per code block
+//! title_slide = true   # the first slide uses the title layout
+//! headings = true      # every slide has a heading
+//! ```
+//!
+//! With `--format json`, the problems found are printed as JSON for CI.
+
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use std::{fmt, ops::Range, str::FromStr};
+
+/// Size of a terminal, written as `100x30` (columns by rows).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Size {
+    /// Number of columns
+    pub width: u16,
+    /// Number of rows
+    pub height: u16,
+}
+
+impl FromStr for Size {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid size '{text}' (try 100x30)");
+        let (width, height) = text.split_once(['x', '×']).ok_or_else(invalid)?;
+        let width: u16 = width.trim().parse().map_err(|_| invalid())?;
+        let height: u16 = height.trim().parse().map_err(|_| invalid())?;
+        if width == 0 || height == 0 {
+            return Err(invalid());
+        }
+        Ok(Size { width, height })
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+/// A problem `presentrs check` found in a deck.
+#[derive(Serialize)]
+pub struct Finding {
+    /// Rule the deck breaks, such as `overflow` or `max-bullets`
+    pub rule: &'static str,
+    /// Number of the slide, from 1
+    pub slide: usize,
+    /// Line of the markdown file, from 1, for problems on a single line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// What is wrong
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Slide {}", self.slide)?;
+        if let Some(line) = self.line {
+            write!(f, ", line {line}")?;
+        }
+        write!(f, ": {} [{}]", self.message, self.rule)
+    }
+}
+
+/// How `presentrs check` and `presentrs bench` print what they report.
+#[derive(Clone, Copy, Default, clap::ValueEnum)]
+pub enum Format {
+    /// Text to read, such as one line per problem
+    #[default]
+    Text,
+    /// A JSON document, for CI and other tools
+    Json,
+}
+
+/// Report of `presentrs check --format json`.
+#[derive(Serialize)]
+pub struct Report<'a> {
+    /// Path of the deck
+    pub file: &'a str,
+    /// Number of slides in the deck
+    pub slides: usize,
+    /// Problems found, in the order they were checked
+    pub findings: &'a [Finding],
+}
+
+/// Team rules from the `[check]` table of the config file. Rules that are
+/// not set are not enforced.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    /// Most non-blank lines of markdown a slide may have, comments aside
+    pub max_lines: Option<usize>,
+    /// Most list items a slide may have
+    pub max_bullets: Option<usize>,
+    /// Most lines a code block may have
+    pub max_code_lines: Option<usize>,
+    /// Whether the first slide must use the `title` layout
+    pub title_slide: bool,
+    /// Whether every slide must have a heading
+    pub headings: bool,
+}
+
+/// What the rules need to know about a slide.
+pub struct SlideInfo<'a> {
+    /// Byte range of the slide's source within the markdown file
+    pub source: Range<usize>,
+    /// Title of the slide, if it has one
+    pub title: Option<&'a str>,
+    /// Layout the slide picks, if any
+    pub layout: Option<&'a str>,
+}
+
+impl Rules {
+    /// Checks `slides`, whose sources are within `markdown`, against the
+    /// rules.
+    pub fn check(&self, markdown: &str, slides: &[SlideInfo]) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        if self.title_slide
+            && let Some(first) = slides.first()
+            && first.layout != Some("title")
+        {
+            findings.push(Finding {
+                rule: "title-slide",
+                slide: 1,
+                line: None,
+                message: "the first slide does not use the title layout".to_string(),
+            });
+        }
+        for (index, slide) in slides.iter().enumerate() {
+            let number = index + 1;
+            if self.headings && slide.title.is_none() {
+                findings.push(Finding {
+                    rule: "headings",
+                    slide: number,
+                    line: None,
+                    message: "slide has no heading".to_string(),
+                });
+            }
+            let source = &markdown[slide.source.clone()];
+            if let Some(max) = self.max_lines {
+                let lines = source
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !is_comment(line))
+                    .count();
+                if lines > max {
+                    findings.push(Finding {
+                        rule: "max-lines",
+                        slide: number,
+                        line: None,
+                        message: format!("{lines} lines, more than {max}"),
+                    });
+                }
+            }
+            let (bullets, code_blocks) = blocks(source);
+            if let Some(max) = self.max_bullets
+                && bullets > max
+            {
+                findings.push(Finding {
+                    rule: "max-bullets",
+                    slide: number,
+                    line: None,
+                    message: format!("{bullets} bullets, more than {max}"),
+                });
+            }
+            if let Some(max) = self.max_code_lines {
+                for (start, lines) in code_blocks {
+                    if lines > max {
+                        let offset = slide.source.start + start;
+                        findings.push(Finding {
+                            rule: "max-code-lines",
+                            slide: number,
+                            line: Some(line_of(markdown, offset)),
+                            message: format!("code block of {lines} lines, more than {max}"),
+                        });
+                    }
+                }
+            }
+        }
+        findings
+    }
+}
+
+/// Counts the list items of a slide's `source`, and finds its code blocks
+/// as their byte offset within it and their number of lines.
+fn blocks(source: &str) -> (usize, Vec<(usize, usize)>) {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    let mut bullets = 0;
+    let mut code_blocks = Vec::new();
+    let mut code: Option<(usize, String)> = None;
+    for (event, range) in Parser::new_ext(source, options).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Item) => bullets += 1,
+            Event::Start(Tag::CodeBlock(_)) => code = Some((range.start, String::new())),
+            Event::Text(text) => {
+                if let Some((_, content)) = &mut code {
+                    content.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((start, content)) = code.take() {
+                    code_blocks.push((start, content.lines().count()));
+                }
+            }
+            _ => {}
+        }
+    }
+    (bullets, code_blocks)
+}
+
+/// Whether `line` is an HTML comment, such as a directive or notes.
+fn is_comment(line: &str) -> bool {
+    line.starts_with("<!--") && line.ends_with("-->")
+}
+
+/// Line of `markdown` that the byte at `offset` is on, from 1.
+pub fn line_of(markdown: &str, offset: usize) -> usize {
+    markdown[..offset].matches('\n').count() + 1
+}
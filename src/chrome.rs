@@ -0,0 +1,334 @@
+//! Decorations drawn on every slide.
+//!
+//! These are configured once, in the config file, rather than per slide, and
+//! are drawn inside the slide's border around the content.
+
+use crate::images::{self, Placement, Width};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Padding, Paragraph, Widget},
+};
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A corner of the slide.
+#[derive(Clone, Copy, Default, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Corner {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    /// Returns true for the two top corners.
+    fn is_top(self) -> bool {
+        matches!(self, Corner::TopLeft | Corner::TopRight)
+    }
+
+    /// Returns true for the two left corners.
+    fn is_left(self) -> bool {
+        matches!(self, Corner::TopLeft | Corner::BottomLeft)
+    }
+
+    /// The `width` by `height` rectangle in this corner of `area`.
+    fn place(self, area: Rect, width: u16, height: u16) -> Rect {
+        let width = width.min(area.width);
+        let height = height.min(area.height);
+        Rect {
+            x: if self.is_left() {
+                area.x
+            } else {
+                area.right() - width
+            },
+            y: if self.is_top() {
+                area.y
+            } else {
+                area.bottom() - height
+            },
+            width,
+            height,
+        }
+    }
+}
+
+/// The `[logo]` section of the config file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogoConfig {
+    /// An image, or a text file holding ASCII art
+    pub file: PathBuf,
+    /// Corner the logo sits in
+    #[serde(default)]
+    pub corner: Corner,
+    /// Width images are scaled to, in columns
+    #[serde(default = "default_logo_width")]
+    pub width: u32,
+}
+
+/// Width image logos are scaled to when the config does not say.
+fn default_logo_width() -> u32 {
+    12
+}
+
+/// A logo shown in a corner of every slide.
+pub struct Logo {
+    /// Rendered logo
+    lines: Vec<Line<'static>>,
+    /// Corner the logo sits in
+    corner: Corner,
+    /// Width of the widest line, in columns
+    width: u16,
+}
+
+impl Logo {
+    /// Loads the logo described by `config`.
+    ///
+    /// Files that are not images in a supported format are read as ASCII art.
+    pub fn load(config: &LogoConfig) -> Result<Self, String> {
+        let path = &config.file;
+        let lines = if image::ImageFormat::from_path(path).is_ok() {
+            let placement = Placement {
+                width: Some(Width::Columns(config.width)),
+                ..Placement::default()
+            };
+            images::draw(path, config.width as usize, &placement)
+                .map_err(|e| format!("Failed to load logo '{}': {}", path.display(), e))?
+        } else {
+            ascii_art(path)?
+        };
+        let width = lines.iter().map(Line::width).max().unwrap_or(0);
+        Ok(Logo {
+            lines,
+            corner: config.corner,
+            width: u16::try_from(width).unwrap_or(u16::MAX),
+        })
+    }
+
+    /// Number of rows the logo takes up.
+    fn height(&self) -> u16 {
+        u16::try_from(self.lines.len()).unwrap_or(u16::MAX)
+    }
+
+    /// Returns true if the logo fits in `area` while leaving most of it to
+    /// the slide's content.
+    fn fits(&self, area: Rect) -> bool {
+        self.width <= area.width && self.height().saturating_mul(3) <= area.height
+    }
+
+    /// Space to keep free for the logo inside `area`, the slide's content
+    /// area within its border.
+    ///
+    /// The logo gets a band of rows across the top or bottom of the slide,
+    /// so content never runs underneath it.
+    pub fn padding(&self, area: Rect) -> Padding {
+        if !self.fits(area) {
+            Padding::ZERO
+        } else if self.corner.is_top() {
+            Padding::top(self.height())
+        } else {
+            Padding::bottom(self.height())
+        }
+    }
+
+    /// Draws the logo in its corner of `area`.
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        if !self.fits(area) {
+            return;
+        }
+        let target = self.corner.place(area, self.width, self.height());
+        Paragraph::new(self.lines.clone()).render(target, buf);
+    }
+}
+
+/// Reads a text file as ASCII art.
+fn ascii_art(path: &Path) -> Result<Vec<Line<'static>>, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read logo '{}': {}", path.display(), e))?;
+    let style = Style::default().fg(Color::Gray);
+    Ok(text
+        .trim_end()
+        .lines()
+        .map(|line| Line::styled(line.replace('\t', "    "), style))
+        .collect())
+}
+
+/// Where the watermark is drawn.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WatermarkPosition {
+    /// Across the middle of the slide, rising from left to right
+    #[default]
+    Diagonal,
+    /// In the bottom-right corner
+    Corner,
+}
+
+/// The `[watermark]` section of the config file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WatermarkConfig {
+    /// Text of the watermark
+    pub text: String,
+    /// Where the watermark is drawn
+    #[serde(default)]
+    pub position: WatermarkPosition,
+}
+
+/// Faint text drawn across every slide, such as "CONFIDENTIAL".
+pub struct Watermark {
+    /// Text of the watermark
+    text: String,
+    /// Where the watermark is drawn
+    position: WatermarkPosition,
+}
+
+impl Watermark {
+    /// Creates a watermark showing `text`.
+    pub fn new(text: &str, position: WatermarkPosition) -> Self {
+        Watermark {
+            text: text.to_string(),
+            position,
+        }
+    }
+
+    /// Draws the watermark over the content already rendered into `area`.
+    ///
+    /// Only blank cells are written, so the watermark shows between the
+    /// slide's text without hiding any of it.
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        let style = Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::DIM);
+        let chars: Vec<char> = self.text.chars().collect();
+        let len = u16::try_from(chars.len()).unwrap_or(u16::MAX);
+        if len == 0 || len > area.width || area.height == 0 {
+            return;
+        }
+        // Cell of each character. Terminal cells are about twice as tall as
+        // they are wide, so the diagonal climbs one row every two columns.
+        let positions: Vec<(u16, u16)> = match self.position {
+            WatermarkPosition::Diagonal => {
+                let rise = (len / 2).min(area.height - 1);
+                let left = area.x + (area.width - len) / 2;
+                let bottom = area.y + (area.height + rise) / 2;
+                let steps = (len - 1).max(1);
+                (0..len)
+                    .map(|i| (left + i, bottom - rise * i / steps))
+                    .collect()
+            }
+            WatermarkPosition::Corner => {
+                let left = area.right() - len;
+                (0..len).map(|i| (left + i, area.bottom() - 1)).collect()
+            }
+        };
+        for (c, (x, y)) in chars.iter().zip(positions) {
+            if let Some(cell) = buf.cell_mut((x, y))
+                && cell.symbol() == " "
+            {
+                cell.set_char(*c).set_style(style);
+            }
+        }
+    }
+}
+
+/// Returns true if `logo` sits in `corner` and is drawn in `area`.
+fn shares_corner(logo: Option<&Logo>, corner: Corner, area: Rect) -> bool {
+    logo.is_some_and(|logo| {
+        logo.corner.is_top() == corner.is_top()
+            && logo.corner.is_left() == corner.is_left()
+            && logo.fits(area)
+    })
+}
+
+/// Draws `text` (e.g. `12 / 48`) in `corner` of `area`.
+///
+/// When the logo sits in the same corner, the number goes next to it, in
+/// the band kept free for the logo.
+pub fn render_slide_number(
+    text: &str,
+    corner: Corner,
+    logo: Option<&Logo>,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let width = u16::try_from(text.chars().count()).unwrap_or(u16::MAX);
+    let mut target = corner.place(area, width, 1);
+    if let Some(logo) = logo
+        && shares_corner(Some(logo), corner, area)
+    {
+        let gap = logo.width + 1;
+        if corner.is_left() {
+            target.x = target.x.saturating_add(gap);
+        } else {
+            target.x = target.x.saturating_sub(gap).max(area.x);
+        }
+    }
+    Paragraph::new(Line::styled(
+        text.to_string(),
+        Style::default().fg(Color::DarkGray),
+    ))
+    .render(target.intersection(area), buf);
+}
+
+/// Draws the footer (e.g. `Jane Doe · RustConf · 2025`) centered on the
+/// bottom row of `area`.
+///
+/// The footer is shortened as needed to stay clear of a logo or slide
+/// number in either bottom corner.
+pub fn render_footer(
+    text: &str,
+    logo: Option<&Logo>,
+    slide_number: Option<(&str, Corner)>,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    if area.height == 0 {
+        return;
+    }
+    // Columns taken up by the logo and slide number in one bottom corner.
+    let taken = |corner: Corner| {
+        let logo_width = if shares_corner(logo, corner, area) {
+            logo.map_or(0, |logo| logo.width + 1)
+        } else {
+            0
+        };
+        let number_width = match slide_number {
+            Some((number, at))
+                if at.is_top() == corner.is_top() && at.is_left() == corner.is_left() =>
+            {
+                u16::try_from(number.chars().count() + 1).unwrap_or(u16::MAX)
+            }
+            _ => 0,
+        };
+        logo_width.saturating_add(number_width)
+    };
+    let margin = taken(Corner::BottomLeft).max(taken(Corner::BottomRight));
+    let room = usize::from(area.width.saturating_sub(margin.saturating_mul(2)));
+    if room == 0 {
+        return;
+    }
+    let text = if text.chars().count() > room {
+        let mut short: String = text.chars().take(room.saturating_sub(1)).collect();
+        short.push('…');
+        short
+    } else {
+        text.to_string()
+    };
+    let row = Rect {
+        y: area.bottom() - 1,
+        height: 1,
+        ..area
+    };
+    Paragraph::new(Line::styled(text, Style::default().fg(Color::DarkGray)))
+        .alignment(Alignment::Center)
+        .render(row, buf);
+}
@@ -0,0 +1,151 @@
+//! User configuration.
+//!
+//! Settings are read from `$XDG_CONFIG_HOME/presentrs/config.toml` (or
+//! `~/.config/presentrs/config.toml`). Every key is optional and a missing
+//! file means all defaults:
+//!
+//! ```toml
+//! [exec]
+//! confirm = true
+//! allow = ["bash", "sh"]
+//! wrapper = ["docker", "run", "--rm", "-v", "{dir}:/deck", "-w", "/deck", "alpine"]
+//!
+//! [logo]
+//! file = "sponsor.png"
+//! corner = "bottom-right"
+//! ```
+
+use crate::{
+    check::Rules,
+    chrome::{Corner, LogoConfig, WatermarkConfig},
+    density::Density,
+    exec::ExecPolicy,
+    export::ExportConfig,
+    fragments::FragmentStyle,
+    graphics::Protocol,
+    highlight::Backend,
+    html::HtmlPolicy,
+    keys::KeyMap,
+    keywords::Emphasis,
+    layouts::Layout,
+    separator::Separator,
+    spelling::SpellingConfig,
+};
+use serde::Deserialize;
+use std::{collections::HashMap, env, error::Error, fs, io::ErrorKind, path::PathBuf};
+
+/// Contents of the configuration file.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// What exec blocks and terminal panes may run, and how
+    pub exec: ExecPolicy,
+    /// Logo shown in a corner of every slide
+    pub logo: Option<LogoConfig>,
+    /// Faint text drawn across every slide
+    pub watermark: Option<WatermarkConfig>,
+    /// Corner of the slide that shows the slide number, if any
+    pub slide_numbers: Option<Corner>,
+    /// Whether to show the status bar (on by default)
+    pub status_bar: Option<bool>,
+    /// Whether to show the timeline bar above the status bar
+    pub timeline: bool,
+    /// Whether to refuse to present decks with content that cannot be shown
+    pub strict: bool,
+    /// Whether timers pause while the terminal does not have the focus
+    pub pause_on_blur: bool,
+    /// What the status bar says, with placeholders such as `{next}`
+    pub status_template: Option<String>,
+    /// Template for the footer built from the deck's front matter
+    pub footer: Option<String>,
+    /// How fragments not revealed yet are drawn
+    pub fragments: Option<FragmentStyle>,
+    /// How much room is left around the blocks of each slide
+    pub density: Option<Density>,
+    /// What becomes of raw HTML in decks
+    pub html: Option<HtmlPolicy>,
+    /// How images are drawn
+    pub images: Option<Protocol>,
+    /// What separates the slides of decks
+    pub separator: Option<Separator>,
+    /// Keys bound to actions in addition to their built-in ones
+    pub keys: KeyMap,
+    /// Whether revealed spoilers stay revealed when a slide is shown again
+    pub keep_spoilers_revealed: bool,
+    /// Slide layouts by name, added to or replacing the built-in ones
+    pub layouts: HashMap<String, Layout>,
+    /// Color theme to start in
+    pub theme: Option<String>,
+    /// Syntect theme code blocks are highlighted with, in every color theme
+    pub code_theme: Option<String>,
+    /// TextMate `.tmTheme` file code blocks are highlighted with, in every
+    /// color theme
+    pub code_theme_file: Option<PathBuf>,
+    /// Folder of `.sublime-syntax` files to highlight more languages with
+    pub syntaxes: Option<PathBuf>,
+    /// Which highlighter code blocks go through
+    pub highlighter: Backend,
+    /// Words drawn in a color of their own wherever they appear, by word
+    pub keywords: HashMap<String, Emphasis>,
+    /// Dictionary and accepted words for `presentrs check --spelling`
+    pub spelling: SpellingConfig,
+    /// Team rules `presentrs check` enforces on every deck
+    pub check: Rules,
+    /// Font for `presentrs export` to draw images and PDFs with
+    pub export: ExportConfig,
+}
+
+impl Config {
+    /// Loads the configuration file, falling back to the defaults if it does not exist.
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        let Some(path) = config_path() else {
+            return Ok(Config::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => {
+                let mut config: Config = toml::from_str(&text)
+                    .map_err(|e| format!("Invalid config '{}': {}", path.display(), e))?;
+                // Paths in the config are relative to the file itself.
+                if let Some(dir) = path.parent() {
+                    if let Some(logo) = &mut config.logo {
+                        logo.file = dir.join(&logo.file);
+                    }
+                    if let Some(file) = &mut config.code_theme_file {
+                        *file = dir.join(&file);
+                    }
+                    if let Some(syntaxes) = &mut config.syntaxes {
+                        *syntaxes = dir.join(&syntaxes);
+                    }
+                    if let Some(dictionary) = &mut config.spelling.dictionary {
+                        *dictionary = dir.join(&dictionary);
+                    }
+                    if let Some(font) = &mut config.export.font {
+                        *font = dir.join(&font);
+                    }
+                }
+                Ok(config)
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(format!("Failed to read config '{}': {}", path.display(), e).into()),
+        }
+    }
+}
+
+/// Location of the configuration file.
+fn config_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("config.toml"))
+}
+
+/// Directory of theme files available by name, next to the configuration
+/// file.
+pub fn themes_dir() -> Option<PathBuf> {
+    Some(config_dir()?.join("themes"))
+}
+
+/// Directory holding the configuration file.
+fn config_dir() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(dir.join("presentrs"))
+}
@@ -0,0 +1,397 @@
+//! The presenter console: the slide on screen, speaker notes, a timer, and
+//! the next slide, in a terminal of their own.
+//!
+//! A presentation started with `--tmux` listens on a socket on the loopback
+//! interface and opens a tmux split (or window) running `presentrs console`,
+//! which connects back to it. One started with `--console` prints the
+//! command instead, to run in another terminal, so the presenter can keep
+//! the console on their laptop while the audience sees only the slides. The
+//! two speak a line-based protocol:
+//!
+//! 1. The console sends the session's token, which the presentation made up
+//!    and passed to it on the command line, on a line of its own.
+//! 2. The presentation sends a JSON [`ConsoleState`] per line, once when the
+//!    console connects and again whenever the state changes.
+//! 3. The console sends `next` or `previous` lines when the presenter moves
+//!    through the deck from it.
+//!
+//! The console quits when the presentation ends, which closes its pane.
+
+use crate::term::{self, Event, KeyCode};
+use ratatui::{
+    Frame, Terminal,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::hash_map::RandomState,
+    env,
+    error::Error,
+    hash::{BuildHasher, Hasher},
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    process::Command,
+    sync::{
+        Arc, Mutex,
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long a console may take to send the token once connected.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often the console redraws its timer.
+const TICK: Duration = Duration::from_millis(250);
+
+/// Where `--tmux` opens the presenter console.
+#[derive(Clone, Copy, Default, clap::ValueEnum)]
+pub enum TmuxLayout {
+    /// A pane to the right of the presentation
+    #[default]
+    Split,
+    /// A window of its own, to move to another screen
+    Window,
+}
+
+/// What the console shows about the presentation.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConsoleState {
+    /// Number of the slide on screen, starting at 1
+    pub slide: usize,
+    /// Number of slides in the deck
+    pub slides: usize,
+    /// Title of the slide on screen
+    pub title: String,
+    /// Text of the slide on screen, below its title
+    pub text: String,
+    /// Speaker notes of the slide on screen
+    pub notes: String,
+    /// Title of the next slide, if there is one
+    pub next_title: Option<String>,
+    /// Text of the next slide
+    pub next_text: String,
+    /// Seconds since the presentation started
+    pub elapsed: u64,
+}
+
+/// Something the presenter did in the console.
+pub enum ConsoleEvent {
+    /// Move to the next fragment or slide
+    Next,
+    /// Move to the previous fragment or slide
+    Previous,
+}
+
+/// The consoles connected to the presentation and the state they were
+/// last sent.
+#[derive(Default)]
+struct Consoles {
+    /// Connections that passed the handshake
+    streams: Vec<TcpStream>,
+    /// Latest state, as the line sent for it
+    last: Option<String>,
+}
+
+/// The presentation's end of the console protocol.
+pub struct ConsoleLink {
+    /// Address consoles connect to
+    addr: SocketAddr,
+    /// Secret consoles must send first
+    token: String,
+    /// Connected consoles
+    consoles: Arc<Mutex<Consoles>>,
+    /// Events received from consoles
+    events: Receiver<ConsoleEvent>,
+    /// State last published, which is only sent again once it changes
+    published: ConsoleState,
+}
+
+impl ConsoleLink {
+    /// Listens for consoles on a free port of the loopback interface.
+    pub fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let token = new_token();
+        let consoles = Arc::new(Mutex::new(Consoles::default()));
+        let (sender, events) = mpsc::channel();
+        let shared = Arc::clone(&consoles);
+        let expected = token.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let consoles = Arc::clone(&shared);
+                let sender = sender.clone();
+                let expected = expected.clone();
+                thread::spawn(move || {
+                    // A console that fails the handshake is just dropped.
+                    if let Err(e) = accept(stream, &expected, &consoles, &sender) {
+                        log::warn!("Console connection failed: {e}");
+                    }
+                });
+            }
+        });
+        Ok(ConsoleLink {
+            addr,
+            token,
+            consoles,
+            events,
+            published: ConsoleState::default(),
+        })
+    }
+
+    /// The shell command that runs a console connected to this presentation.
+    pub fn command(&self) -> Result<String, String> {
+        let exe = env::current_exe()
+            .map_err(|e| format!("Failed to find the presentrs executable: {}", e))?;
+        Ok([
+            exe.to_string_lossy().as_ref(),
+            "console",
+            &self.addr.to_string(),
+            "--token",
+            &self.token,
+        ]
+        .map(shell_quote)
+        .join(" "))
+    }
+
+    /// Opens a tmux pane or window running the console, connected to this
+    /// presentation. Fails outside tmux.
+    pub fn open_in_tmux(&self, layout: TmuxLayout) -> Result<(), Box<dyn Error>> {
+        if env::var_os("TMUX").is_none() {
+            return Err("--tmux needs the presentation to run inside tmux".into());
+        }
+        let command = self.command()?;
+        // `-d` leaves the presentation's pane focused.
+        let args: &[&str] = match layout {
+            TmuxLayout::Split => &["split-window", "-d", "-h", "-l", "35%"],
+            TmuxLayout::Window => &["new-window", "-d", "-n", "presenter"],
+        };
+        let status = Command::new("tmux")
+            .args(args)
+            .arg(&command)
+            .status()
+            .map_err(|e| format!("Failed to run tmux: {}", e))?;
+        if !status.success() {
+            return Err(format!("tmux could not open the presenter console ({status})").into());
+        }
+        log::info!("Opened the presenter console with tmux {}", args[0]);
+        Ok(())
+    }
+
+    /// Sends `state` to the consoles if it changed since it was last sent.
+    pub fn publish(&mut self, state: ConsoleState) {
+        if state == self.published {
+            return;
+        }
+        let Ok(line) = serde_json::to_string(&state) else {
+            return;
+        };
+        self.published = state;
+        if let Ok(mut consoles) = self.consoles.lock() {
+            consoles
+                .streams
+                .retain_mut(|stream| writeln!(stream, "{line}").is_ok());
+            consoles.last = Some(line);
+        }
+    }
+
+    /// Returns every event received since the last call, without blocking.
+    pub fn drain(&self) -> Vec<ConsoleEvent> {
+        self.events.try_iter().collect()
+    }
+}
+
+/// Makes a token for a session: 128 bits from the randomly keyed hashers of
+/// the standard library, which are seeded by the OS.
+fn new_token() -> String {
+    let half = || RandomState::new().build_hasher().finish();
+    format!("{:016x}{:016x}", half(), half())
+}
+
+/// Quotes `word` for the shell, unless it is safe as it is.
+fn shell_quote(word: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./:".contains(c);
+    if !word.is_empty() && word.chars().all(plain) {
+        return word.to_string();
+    }
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+/// Checks the token a console sends, then sends it the latest state and
+/// relays its commands until it disconnects.
+fn accept(
+    stream: TcpStream,
+    token: &str,
+    consoles: &Mutex<Consoles>,
+    events: &Sender<ConsoleEvent>,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let peer = stream
+        .peer_addr()
+        .map_or_else(|_| "a client".to_string(), |addr| addr.to_string());
+    if line.trim() != token {
+        log::warn!("Console from {peer} sent a wrong token");
+        return Ok(());
+    }
+    log::info!("Console connected from {peer}");
+    stream.set_read_timeout(None)?;
+    {
+        let mut consoles = consoles.lock().map_err(|_| io::ErrorKind::Other)?;
+        let mut writer = stream.try_clone()?;
+        if let Some(last) = &consoles.last {
+            writeln!(writer, "{last}")?;
+        }
+        consoles.streams.push(writer);
+    }
+    for line in reader.lines() {
+        let event = match line?.trim() {
+            "next" => ConsoleEvent::Next,
+            "previous" => ConsoleEvent::Previous,
+            other => {
+                log::debug!("Ignored console command '{other}'");
+                continue;
+            }
+        };
+        if events.send(event).is_err() {
+            break;
+        }
+    }
+    log::info!("Console from {peer} disconnected");
+    Ok(())
+}
+
+/// Runs the presenter console connected to the presentation at `addr`,
+/// until the presentation ends or `q` is pressed.
+pub fn run(addr: &str, token: &str) -> Result<(), Box<dyn Error>> {
+    let mut stream = TcpStream::connect(addr)
+        .map_err(|e| format!("Failed to connect to the presentation at '{}': {}", addr, e))?;
+    writeln!(stream, "{token}")?;
+    let (sender, states) = mpsc::channel();
+    let reader = BufReader::new(stream.try_clone()?);
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if let Ok(state) = serde_json::from_str::<ConsoleState>(&line)
+                && sender.send(state).is_err()
+            {
+                break;
+            }
+        }
+        // The presentation is gone; dropping the sender tells the console.
+    });
+
+    let mut terminal = Terminal::new(term::enter(false)?)?;
+    let result = console_loop(&mut terminal, &mut stream, &states);
+    term::leave(&mut terminal)?;
+    result
+}
+
+/// Draws the console and relays the presenter's keys, until the
+/// presentation ends or `q` is pressed.
+fn console_loop(
+    terminal: &mut Terminal<term::Backend>,
+    stream: &mut TcpStream,
+    states: &Receiver<ConsoleState>,
+) -> Result<(), Box<dyn Error>> {
+    let mut state = ConsoleState::default();
+    // The timer runs on between updates from the presentation.
+    let mut received = Instant::now();
+    loop {
+        loop {
+            match states.try_recv() {
+                Ok(update) => {
+                    state = update;
+                    received = Instant::now();
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => return Ok(()),
+            }
+        }
+        let elapsed = state.elapsed + received.elapsed().as_secs();
+        terminal.draw(|f| draw(f, &state, elapsed))?;
+        if let Some(Event::Key(key)) = term::next_event(terminal, Some(TICK))? {
+            let command = match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') | KeyCode::PageDown => {
+                    "next"
+                }
+                KeyCode::Left | KeyCode::Char('h') | KeyCode::PageUp => "previous",
+                _ => continue,
+            };
+            writeln!(stream, "{command}")?;
+        }
+    }
+}
+
+/// Draws the timer and position, the slide on screen, the notes, and the
+/// next slide.
+fn draw(f: &mut Frame, state: &ConsoleState, elapsed: u64) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Percentage(30),
+            Constraint::Min(3),
+            Constraint::Percentage(25),
+        ])
+        .split(f.area());
+
+    let header = Line::from(vec![
+        Span::styled(
+            format!("{}:{:02}", elapsed / 60, elapsed % 60),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(format!("  Slide {}/{}  ", state.slide, state.slides)),
+        Span::styled(state.title.clone(), Style::default().fg(Color::Cyan)),
+    ]);
+    f.render_widget(
+        Paragraph::new(header).block(Block::default().borders(Borders::ALL).title(" Presenter ")),
+        chunks[0],
+    );
+
+    f.render_widget(
+        Paragraph::new(state.text.clone())
+            .block(Block::default().borders(Borders::ALL).title(" Now "))
+            .wrap(Wrap { trim: false }),
+        chunks[1],
+    );
+
+    let notes = if state.notes.is_empty() {
+        Paragraph::new("No notes for this slide").style(Style::default().fg(Color::DarkGray))
+    } else {
+        Paragraph::new(state.notes.clone())
+    };
+    f.render_widget(
+        notes
+            .block(Block::default().borders(Borders::ALL).title(" Notes "))
+            .wrap(Wrap { trim: false }),
+        chunks[2],
+    );
+
+    let next_title = match &state.next_title {
+        Some(title) => format!(" Next: {title} "),
+        None if state.slide < state.slides => " Next ".to_string(),
+        None => " Last slide ".to_string(),
+    };
+    f.render_widget(
+        Paragraph::new(state.next_text.clone())
+            .style(Style::default().fg(Color::Gray))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(next_title)
+                    .title_bottom(" ← → Move | q Quit console "),
+            )
+            .wrap(Wrap { trim: false }),
+        chunks[3],
+    );
+}
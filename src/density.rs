@@ -0,0 +1,61 @@
+//! How tightly slides are laid out.
+//!
+//! Every blank line the parser leaves between the blocks of a slide, and the
+//! padding inside table cells, is decided here, so that a single setting can
+//! make content-heavy slides fit a 24-row terminal.
+
+use serde::Deserialize;
+
+/// How much room is left around the blocks of a slide.
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Density {
+    /// A blank line after every block, and padded table cells
+    #[default]
+    Comfortable,
+    /// A blank line only under the slide's title, and tables without padding
+    Compact,
+}
+
+/// A kind of element of a slide that blank lines may follow.
+#[derive(Clone, Copy)]
+pub enum Element {
+    /// The heading that gives the slide its title
+    Title,
+    /// Any other heading
+    Heading,
+    Paragraph,
+    /// A whole list, after its outermost level ends
+    List,
+    CodeBlock,
+    Table,
+    /// A horizontal rule
+    Rule,
+    /// The rows kept free for a live terminal pane
+    Terminal,
+}
+
+impl Density {
+    /// Looks up a density by name, as written in a deck's front matter.
+    pub fn named(name: &str) -> Option<Self> {
+        clap::ValueEnum::from_str(name.trim(), true).ok()
+    }
+
+    /// Number of blank lines left after `element`.
+    pub fn gap_after(self, element: Element) -> usize {
+        match (self, element) {
+            (Density::Comfortable, _) => 1,
+            // The title stays apart from what it introduces.
+            (Density::Compact, Element::Title) => 1,
+            (Density::Compact, _) => 0,
+        }
+    }
+
+    /// Number of blank columns on each side of the text in a table cell.
+    pub fn cell_padding(self) -> usize {
+        match self {
+            Density::Comfortable => 1,
+            Density::Compact => 0,
+        }
+    }
+}
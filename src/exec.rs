@@ -0,0 +1,531 @@
+//! Executable code blocks.
+//!
+//! A fenced block tagged with the `exec` modifier (e.g. ```` ```bash exec ````)
+//! can be run from its slide with `e`, using the [`Runner`] configured for its
+//! language. The command runs in the background and
+//! its output is streamed into the slide below the block as it arrives, under
+//! a status line with a spinner. `Ctrl-C` sends an interrupt to the command,
+//! just like it would in a terminal.
+//!
+//! What may run is governed by the [`ExecPolicy`] from the `[exec]` section
+//! of the config file, and `--no-exec` turns execution off entirely.
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::{self, Child, Command, ExitStatus, Stdio},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Most output lines shown below a block; earlier lines are summarized.
+const VISIBLE_OUTPUT_LINES: usize = 15;
+/// Most output lines kept in memory per run.
+const MAX_OUTPUT_LINES: usize = 1000;
+/// Frames of the spinner shown while a command runs.
+const SPINNER: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Rules for running code from a deck, read from the `[exec]` config section.
+///
+/// They apply to exec blocks and terminal panes alike.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExecPolicy {
+    /// Ask before the first command of a presentation runs
+    pub confirm: bool,
+    /// Languages and programs that may run; anything when unset
+    pub allow: Option<Vec<String>>,
+    /// Command every program is run through, such as a container or sandbox;
+    /// `{dir}` is replaced with the deck's directory
+    pub wrapper: Vec<String>,
+    /// Runners by language, overriding and extending the built-in ones
+    pub runners: BTreeMap<String, Runner>,
+}
+
+impl Default for ExecPolicy {
+    fn default() -> Self {
+        ExecPolicy {
+            confirm: true,
+            allow: None,
+            wrapper: Vec::new(),
+            runners: BTreeMap::new(),
+        }
+    }
+}
+
+/// How to run the blocks of one language.
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Runner {
+    /// Program and arguments. `{code}` is replaced with the block's code and
+    /// `{file}` with the path of a temporary file holding it; with neither,
+    /// the code is written to the program's standard input
+    pub command: Vec<String>,
+    /// Extra environment variables
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Working directory, relative to the deck's directory
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    /// Extension of the temporary file passed as `{file}`
+    #[serde(default)]
+    pub extension: Option<String>,
+}
+
+impl Runner {
+    /// A runner with no extra settings.
+    fn new(command: &[&str]) -> Self {
+        Runner {
+            command: command.iter().map(|arg| arg.to_string()).collect(),
+            env: BTreeMap::new(),
+            cwd: None,
+            extension: None,
+        }
+    }
+
+    /// Name of the program the runner starts.
+    pub fn program(&self) -> &str {
+        self.command.first().map_or("", String::as_str)
+    }
+}
+
+/// The built-in runner for `lang`, if there is one.
+fn default_runner(lang: &str) -> Option<Runner> {
+    let command: &[&str] = match lang {
+        "bash" => &["bash", "-c", "{code}"],
+        "zsh" => &["zsh", "-c", "{code}"],
+        "fish" => &["fish", "-c", "{code}"],
+        "sh" | "shell" | "console" => &["sh", "-c", "{code}"],
+        "python" | "py" | "python3" => &["python3", "-"],
+        "ruby" | "rb" => &["ruby", "-"],
+        "perl" | "pl" => &["perl", "-"],
+        "lua" => &["lua", "-"],
+        "javascript" | "js" | "node" => &["node", "-"],
+        "typescript" | "ts" => &["deno", "run", "-"],
+        "rust" | "rs" => {
+            return Some(Runner {
+                extension: Some("rs".to_string()),
+                ..Runner::new(&["cargo", "+nightly", "-Zscript", "{file}"])
+            });
+        }
+        _ => return None,
+    };
+    Some(Runner::new(command))
+}
+
+impl ExecPolicy {
+    /// Returns true if any of `names` (a language or program) is allowed.
+    pub fn allows(&self, names: &[impl AsRef<str>]) -> bool {
+        self.allow.as_ref().is_none_or(|allow| {
+            names
+                .iter()
+                .any(|name| allow.iter().any(|a| a == name.as_ref()))
+        })
+    }
+
+    /// The runner for blocks written in `lang`, if there is one.
+    pub fn runner(&self, lang: &str) -> Option<Runner> {
+        self.runners
+            .get(lang)
+            .cloned()
+            .or_else(|| default_runner(lang))
+    }
+
+    /// Runs `code` with `runner` from the deck directory `dir`.
+    pub fn start(&self, runner: &Runner, code: &str, dir: &Path) -> io::Result<Execution> {
+        let script = if runner.command.iter().any(|arg| arg.contains("{file}")) {
+            Some(write_script(code, runner.extension.as_deref())?)
+        } else {
+            None
+        };
+        let file = script
+            .as_deref()
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let inline = runner
+            .command
+            .iter()
+            .any(|arg| arg.contains("{code}") || arg.contains("{file}"));
+        let argv = runner
+            .command
+            .iter()
+            .map(|arg| arg.replace("{code}", code).replace("{file}", &file))
+            .collect();
+        let argv = self.wrap(argv, dir);
+        let cwd = match &runner.cwd {
+            Some(cwd) => dir.join(cwd),
+            None => dir.to_path_buf(),
+        };
+        Execution::start(&argv, &cwd, &runner.env, (!inline).then_some(code), script)
+    }
+
+    /// Prefixes `argv` with the configured wrapper command.
+    pub fn wrap(&self, argv: Vec<String>, dir: &Path) -> Vec<String> {
+        let dir = dir.to_string_lossy();
+        self.wrapper
+            .iter()
+            .map(|arg| arg.replace("{dir}", &dir))
+            .chain(argv)
+            .collect()
+    }
+}
+
+/// A code block that can be run from the slide.
+pub struct ExecBlock {
+    /// Language from the fence's info string
+    pub lang: Option<String>,
+    /// Source code of the block
+    pub code: String,
+    /// Index of the slide line right after the block, where output is shown
+    pub end: usize,
+}
+
+/// Output of a running command, split into lines as it arrives.
+#[derive(Default)]
+struct OutputBuffer {
+    /// Completed lines
+    lines: Vec<String>,
+    /// Bytes of the line currently being written
+    partial: Vec<u8>,
+    /// Whether the previous byte was a carriage return
+    carriage_return: bool,
+}
+
+impl OutputBuffer {
+    /// Appends raw output. A carriage return not followed by a newline
+    /// restarts the current line, so progress bars redraw in place.
+    fn push(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            if self.carriage_return && byte != b'\n' {
+                self.partial.clear();
+            }
+            self.carriage_return = byte == b'\r';
+            match byte {
+                b'\n' => {
+                    let line = String::from_utf8_lossy(&self.partial).into_owned();
+                    log::debug!("│ {}", strip_ansi(&line));
+                    self.lines.push(line);
+                    self.partial.clear();
+                    if self.lines.len() > MAX_OUTPUT_LINES {
+                        self.lines.remove(0);
+                    }
+                }
+                b'\r' => {}
+                _ => self.partial.push(byte),
+            }
+        }
+    }
+
+    /// All lines including the unfinished one, with escape sequences removed.
+    fn snapshot(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self.lines.iter().map(|l| strip_ansi(l)).collect();
+        if !self.partial.is_empty() {
+            lines.push(strip_ansi(&String::from_utf8_lossy(&self.partial)));
+        }
+        lines
+    }
+}
+
+/// How a finished command ended.
+enum Outcome {
+    /// The command exited with this status
+    Exited(ExitStatus),
+    /// The command could not be waited on
+    Failed(String),
+}
+
+/// A command started from an executable block.
+pub struct Execution {
+    /// The running command
+    child: Child,
+    /// Interleaved stdout and stderr
+    output: Arc<Mutex<OutputBuffer>>,
+    /// Moment the command started
+    started: Instant,
+    /// Outcome and total duration, once the command has finished
+    finished: Option<(Outcome, Duration)>,
+    /// Whether the presenter interrupted the command
+    interrupted: bool,
+    /// Temporary file holding the code, removed once the run is dropped
+    script: Option<PathBuf>,
+}
+
+impl Execution {
+    /// Runs the program and arguments in `argv` with `cwd` as its working
+    /// directory and `env` added to its environment, writing `input` to its
+    /// standard input.
+    fn start(
+        argv: &[String],
+        cwd: &Path,
+        env: &BTreeMap<String, String>,
+        input: Option<&str>,
+        script: Option<PathBuf>,
+    ) -> io::Result<Self> {
+        let (program, args) = argv
+            .split_first()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty command"))?;
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .current_dir(cwd)
+            .envs(env)
+            .stdin(if input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // Run in a process group of its own so an interrupt reaches every
+        // process the command starts, not just the shell.
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+        let mut child = command.spawn()?;
+        log::info!("Running {:?} as process {}", argv, child.id());
+        if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+            // Written from a thread so a program that prints before reading
+            // all of its input cannot deadlock the UI.
+            let input = input.to_string();
+            thread::spawn(move || {
+                if let Err(e) = stdin.write_all(input.as_bytes()) {
+                    log::warn!("Failed to write the code to the command's input: {e}");
+                }
+            });
+        }
+        let output = Arc::new(Mutex::new(OutputBuffer::default()));
+        let streams: [Option<Box<dyn Read + Send>>; 2] = [
+            child
+                .stdout
+                .take()
+                .map(|s| Box::new(s) as Box<dyn Read + Send>),
+            child
+                .stderr
+                .take()
+                .map(|s| Box::new(s) as Box<dyn Read + Send>),
+        ];
+        for mut stream in streams.into_iter().flatten() {
+            let output = Arc::clone(&output);
+            thread::spawn(move || {
+                let mut buf = [0; 4096];
+                while let Ok(n) = stream.read(&mut buf) {
+                    if n == 0 {
+                        break;
+                    }
+                    if let Ok(mut output) = output.lock() {
+                        output.push(&buf[..n]);
+                    }
+                }
+            });
+        }
+
+        Ok(Execution {
+            child,
+            output,
+            started: Instant::now(),
+            finished: None,
+            interrupted: false,
+            script,
+        })
+    }
+
+    /// Returns true while the command is still running.
+    pub fn is_running(&self) -> bool {
+        self.finished.is_none()
+    }
+
+    /// Checks whether the command has finished, without blocking.
+    pub fn poll(&mut self) {
+        if self.finished.is_some() {
+            return;
+        }
+        let outcome = match self.child.try_wait() {
+            Ok(None) => return,
+            Ok(Some(status)) => Outcome::Exited(status),
+            Err(e) => Outcome::Failed(e.to_string()),
+        };
+        let took = self.started.elapsed();
+        match &outcome {
+            Outcome::Exited(status) => log::info!(
+                "Process {} {} after {:.1}s",
+                self.child.id(),
+                status,
+                took.as_secs_f64()
+            ),
+            Outcome::Failed(e) => {
+                log::error!("Failed to wait for process {}: {}", self.child.id(), e)
+            }
+        }
+        self.finished = Some((outcome, took));
+    }
+
+    /// Sends an interrupt (`SIGINT`) to the command and everything it started.
+    pub fn interrupt(&mut self) {
+        if !self.is_running() {
+            return;
+        }
+        self.interrupted = true;
+        log::info!("Interrupting process {}", self.child.id());
+        #[cfg(unix)]
+        self.signal_group(libc::SIGINT);
+        #[cfg(not(unix))]
+        let _ = self.child.kill();
+    }
+
+    /// Sends `signal` to the process group created for the command in `start`.
+    #[cfg(unix)]
+    fn signal_group(&self, signal: libc::c_int) {
+        // SAFETY: `kill` has no memory-safety preconditions; a negative pid
+        // addresses the whole process group.
+        unsafe {
+            libc::kill(-(self.child.id() as libc::pid_t), signal);
+        }
+    }
+
+    /// Renders the status line and the most recent output.
+    pub fn render(&self) -> Vec<Line<'static>> {
+        let dim = Style::default().fg(Color::DarkGray);
+        let status = match &self.finished {
+            None => {
+                let elapsed = self.started.elapsed();
+                let frame = SPINNER[(elapsed.as_millis() / 80) as usize % SPINNER.len()];
+                Line::from(vec![
+                    Span::styled(
+                        format!("{frame} Running "),
+                        Style::default().fg(Color::Cyan),
+                    ),
+                    Span::styled(
+                        format!("{:.1}s · Ctrl-C to interrupt", elapsed.as_secs_f64()),
+                        dim,
+                    ),
+                ])
+            }
+            Some((outcome, took)) => {
+                let took = took.as_secs_f64();
+                let (text, color) = match outcome {
+                    _ if self.interrupted => {
+                        (format!("✗ Interrupted after {took:.1}s"), Color::Yellow)
+                    }
+                    Outcome::Exited(status) if status.success() => {
+                        (format!("✓ Finished in {took:.1}s"), Color::Green)
+                    }
+                    Outcome::Exited(status) => (format!("✗ {status} after {took:.1}s"), Color::Red),
+                    Outcome::Failed(e) => (format!("✗ {e}"), Color::Red),
+                };
+                Line::styled(
+                    text,
+                    Style::default().fg(color).add_modifier(Modifier::BOLD),
+                )
+            }
+        };
+
+        let mut lines = vec![status];
+        let output = self.output.lock().map(|o| o.snapshot()).unwrap_or_default();
+        let hidden = output.len().saturating_sub(VISIBLE_OUTPUT_LINES);
+        if hidden > 0 {
+            lines.push(Line::styled(format!("│ … {hidden} earlier lines"), dim));
+        }
+        for text in &output[hidden..] {
+            lines.push(Line::from(vec![
+                Span::styled("│ ", dim),
+                Span::styled(text.clone(), Style::default().fg(Color::Gray)),
+            ]));
+        }
+        lines
+    }
+}
+
+impl Drop for Execution {
+    fn drop(&mut self) {
+        if self.is_running() {
+            #[cfg(unix)]
+            self.signal_group(libc::SIGKILL);
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+        if let Some(script) = &self.script {
+            let _ = fs::remove_file(script);
+        }
+    }
+}
+
+/// How many names `write_script` tries before giving up.
+const SCRIPT_ATTEMPTS: usize = 100;
+
+/// Writes `code` to a new temporary file with the given extension, readable
+/// only by the current user.
+///
+/// The file is always created afresh: a file or symlink already at the name,
+/// which another user of the machine could have put there, is never written
+/// through, and the next name is tried instead.
+fn write_script(code: &str, extension: Option<&str>) -> io::Result<PathBuf> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    for _ in 0..SCRIPT_ATTEMPTS {
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let mut path = env::temp_dir().join(format!("presentrs-{}-{}", process::id(), n));
+        if let Some(extension) = extension {
+            path.set_extension(extension);
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(&path) {
+            Ok(mut file) => {
+                file.write_all(code.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        "no free name for a temporary script",
+    ))
+}
+
+/// Removes ANSI escape sequences (colors, cursor movement) from `text`.
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            if !c.is_control() || c == '\t' {
+                out.push(c);
+            }
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters until a final byte in @..~
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: until BEL or ST
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next() == Some('\\')) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out.replace('\t', "    ")
+}
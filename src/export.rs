@@ -0,0 +1,626 @@
+//! Exporting a deck to files that can be shared without presentrs.
+//!
+//! Every slide is drawn the way it is presented, fully revealed, and the
+//! frames are written out as one self-contained HTML page with a slide
+//! below the other, as ANSI-styled text files or PNG images, one per slide,
+//! or as a PDF with a page per slide. PowerPoint files are the exception:
+//! they are written from the deck's text, by [`crate::pptx`], so that they
+//! can be edited. `presentrs export --watch` writes them
+//! again whenever the deck changes; the HTML page then reloads itself, so a
+//! browser next to the editor shows the deck as it is written.
+//!
+//! `presentrs export --all` writes every format at once into a folder,
+//! together with the deck's outline, a handout, and a manifest listing the
+//! files, so the artifacts of a talk can be released in one step.
+//!
+//! Images and PDFs are drawn with a monospace font, DejaVu Sans Mono or the
+//! system's own unless the config file names another:
+//!
+//! ```toml
+//! [export]
+//! font = "/usr/share/fonts/truetype/jetbrains-mono/JetBrainsMono-Regular.ttf"
+//! ```
+
+use crate::{check::Size, pptx, record};
+use ab_glyph::{Font, FontVec, PxScale, ScaleFont, point};
+use image::{Rgb, RgbImage};
+use miniz_oxide::deflate::{CompressionLevel, compress_to_vec_zlib};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref};
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+use unicode_width::UnicodeWidthStr;
+
+/// Text color of cells that leave it to the terminal.
+const DEFAULT_FG: (u8, u8, u8) = (0xe5, 0xe5, 0xe5);
+/// Background of cells that leave it to the terminal.
+const DEFAULT_BG: (u8, u8, u8) = (0x00, 0x00, 0x00);
+
+/// Height of a row of text in images, in pixels.
+const FONT_SIZE: f32 = 24.0;
+
+/// Pixels of an image per point of a PDF page.
+const PIXELS_PER_POINT: f32 = 2.0;
+
+/// Monospace fonts looked for when the config file does not name one.
+const FONTS: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf",
+    "/usr/share/fonts/TTF/DejaVuSansMono.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSansMono.ttf",
+    "/usr/share/fonts/dejavu-sans-mono-fonts/DejaVuSansMono.ttf",
+    "/System/Library/Fonts/Menlo.ttc",
+    "C:\\Windows\\Fonts\\consola.ttf",
+];
+
+/// The `[export]` table of the config file.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExportConfig {
+    /// Monospace TrueType font to draw images and PDFs with
+    pub font: Option<PathBuf>,
+}
+
+/// What `presentrs export` writes.
+#[derive(Clone, Copy, Default, clap::ValueEnum)]
+pub enum Format {
+    /// A single HTML page with every slide
+    #[default]
+    Html,
+    /// A folder of ANSI-styled text files, one per slide, to view with `cat`
+    Ansi,
+    /// A folder of PNG images, one per slide
+    Png,
+    /// A PDF with a page per slide
+    Pdf,
+    /// A PowerPoint file with a slide per slide, its text left editable
+    Pptx,
+}
+
+impl Format {
+    /// Where the export of `deck` goes unless told otherwise: `talk.html`,
+    /// `talk.pdf`, `talk.pptx`, or the folder `talk-slides` for `talk.md`.
+    pub fn default_output(self, deck: &Path) -> PathBuf {
+        let stem = stem(deck);
+        match self {
+            Format::Html => PathBuf::from(format!("{stem}.html")),
+            Format::Pdf => PathBuf::from(format!("{stem}.pdf")),
+            Format::Pptx => PathBuf::from(format!("{stem}.pptx")),
+            Format::Ansi | Format::Png => PathBuf::from(format!("{stem}-slides")),
+        }
+    }
+}
+
+/// A deck drawn slide by slide, ready to be written out.
+pub struct Rendered {
+    /// Title of the deck
+    pub title: String,
+    /// Every slide, fully revealed
+    pub frames: Vec<Buffer>,
+    /// What each slide is called in the PDF's bookmarks, such as its title
+    pub labels: Vec<String>,
+    /// Text of every slide, read from the markdown, for PowerPoint
+    pub deck: Vec<pptx::Slide>,
+    /// Font to draw images and PDFs with, if not a default one
+    pub font: Option<PathBuf>,
+}
+
+/// What `presentrs export --all` wrote, saved as `manifest.json`.
+#[derive(Serialize)]
+struct Manifest<'a> {
+    /// Path of the deck
+    deck: &'a str,
+    /// Title of the deck
+    title: &'a str,
+    /// Size of the terminal the slides were drawn for
+    size: String,
+    /// Number of slides
+    slides: usize,
+    /// Files written, relative to the folder
+    files: Vec<String>,
+}
+
+impl Rendered {
+    /// Writes the slides to `output` in `format`, and returns the files
+    /// written. With `live`, the HTML page reloads itself to pick up later
+    /// exports.
+    pub fn write(
+        &self,
+        format: Format,
+        output: &Path,
+        live: bool,
+    ) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        let write_file = |path: &Path, contents: &[u8]| {
+            fs::write(path, contents)
+                .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+        };
+        let create_dir = |dir: &Path| {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))
+        };
+        let slide_path = |index: usize, extension: &str| {
+            output.join(format!("slide-{:03}.{extension}", index + 1))
+        };
+        let mut written = Vec::new();
+        match format {
+            Format::Html => {
+                write_file(output, html(&self.frames, &self.title, live).as_bytes())?;
+                written.push(output.to_path_buf());
+            }
+            Format::Ansi => {
+                create_dir(output)?;
+                for (index, frame) in self.frames.iter().enumerate() {
+                    let path = slide_path(index, "ans");
+                    record::save_screenshot(frame, &path)
+                        .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+                    written.push(path);
+                }
+            }
+            Format::Png => {
+                create_dir(output)?;
+                let font = load_font(self.font.as_deref())?;
+                for (index, frame) in self.frames.iter().enumerate() {
+                    let path = slide_path(index, "png");
+                    rasterize(frame, &font)
+                        .save(&path)
+                        .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+                    written.push(path);
+                }
+            }
+            Format::Pdf => {
+                let font = load_font(self.font.as_deref())?;
+                let images: Vec<RgbImage> = self
+                    .frames
+                    .iter()
+                    .map(|frame| rasterize(frame, &font))
+                    .collect();
+                write_file(output, &pdf(&images, &self.title, &self.labels))?;
+                written.push(output.to_path_buf());
+            }
+            Format::Pptx => {
+                pptx::write(output, &self.title, &self.deck)?;
+                written.push(output.to_path_buf());
+            }
+        }
+        Ok(written)
+    }
+
+    /// Writes the slides in every format into the folder `dir`, along with
+    /// the deck's `outline` and `handout` and a manifest of the files.
+    /// Returns the path of the manifest.
+    pub fn write_all(
+        &self,
+        deck: &str,
+        size: Size,
+        dir: &Path,
+        outline: &str,
+        handout: &str,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+        let stem = stem(Path::new(deck));
+        let mut written = Vec::new();
+        for format in [Format::Html, Format::Pdf, Format::Pptx, Format::Png] {
+            let output = dir.join(format.default_output(Path::new(deck)));
+            written.extend(self.write(format, &output, false)?);
+        }
+        for (name, text) in [("outline", outline), ("handout", handout)] {
+            let path = dir.join(format!("{stem}-{name}.md"));
+            fs::write(&path, text)
+                .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+            written.push(path);
+        }
+        let manifest = Manifest {
+            deck,
+            title: &self.title,
+            size: size.to_string(),
+            slides: self.frames.len(),
+            files: written
+                .iter()
+                .map(|path| {
+                    let path = path.strip_prefix(dir).unwrap_or(path);
+                    path.to_string_lossy().replace('\\', "/")
+                })
+                .collect(),
+        };
+        let path = dir.join("manifest.json");
+        fs::write(&path, serde_json::to_string_pretty(&manifest)? + "\n")
+            .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+        Ok(path)
+    }
+}
+
+/// Name of `deck` without its extension, which exports are named after.
+fn stem(deck: &Path) -> String {
+    deck.file_stem()
+        .map_or("slides".into(), |stem| stem.to_string_lossy().into_owned())
+}
+
+/// Builds an HTML page showing `frames` one below the other, which `→` and
+/// `←` step through a slide at a time.
+fn html(frames: &[Buffer], title: &str, live: bool) -> String {
+    let mut page = String::new();
+    let _ = write!(
+        page,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>\n\
+         body {{ background: #202020; margin: 0; padding: 2em 0; }}\n\
+         section {{ width: max-content; margin: 0 auto 2em; }}\n\
+         pre {{ margin: 0; font: 15px/1.25 ui-monospace, Menlo, Consolas, monospace; \
+         color: {}; background: {}; }}\n\
+         </style>\n</head>\n<body>\n",
+        escape(title),
+        hex(DEFAULT_FG),
+        hex(DEFAULT_BG)
+    );
+    for (index, frame) in frames.iter().enumerate() {
+        let _ = writeln!(
+            page,
+            "<section id=\"slide-{}\"><pre>{}</pre></section>",
+            index + 1,
+            buffer_to_html(frame)
+        );
+    }
+    page.push_str(
+        "<script>\n\
+         addEventListener('keydown', event => {\n\
+         \x20 const step = { ArrowRight: 1, ArrowLeft: -1 }[event.key];\n\
+         \x20 if (!step || event.altKey || event.ctrlKey || event.metaKey) return;\n\
+         \x20 const slides = [...document.querySelectorAll('section')];\n\
+         \x20 const current = slides.findIndex(slide => slide.getBoundingClientRect().bottom > 1);\n\
+         \x20 const target = slides[Math.min(Math.max(current + step, 0), slides.length - 1)];\n\
+         \x20 if (target) { location.hash = target.id; event.preventDefault(); }\n\
+         });\n\
+         </script>\n",
+    );
+    if live {
+        // Reloads every second, keeping the place on the page.
+        page.push_str(
+            "<script>\n\
+             addEventListener('load', () => scrollTo(0, Number(sessionStorage.getItem('y'))));\n\
+             setInterval(() => { sessionStorage.setItem('y', scrollY); location.reload(); }, 1000);\n\
+             </script>\n",
+        );
+    }
+    page.push_str("</body>\n</html>\n");
+    page
+}
+
+/// Serializes a rendered buffer into HTML, a line per row, with a styled
+/// `span` for each run of cells that look the same.
+fn buffer_to_html(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut out = String::new();
+    for y in area.top()..area.bottom() {
+        let mut run = String::new();
+        let mut style = String::new();
+        let mut skip = 0;
+        for x in area.left()..area.right() {
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let Some(cell) = buffer.cell((x, y)) else {
+                continue;
+            };
+            let cell_style = css(cell.fg, cell.bg, cell.modifier);
+            if cell_style != style {
+                push_span(&mut out, &style, &run);
+                run.clear();
+                style = cell_style;
+            }
+            let symbol = cell.symbol();
+            run.push_str(symbol);
+            skip = symbol.width().saturating_sub(1);
+        }
+        push_span(&mut out, &style, &run);
+        out.push('\n');
+    }
+    out
+}
+
+/// Appends `text` to `out`, in a span with the CSS `style` if it has one.
+fn push_span(out: &mut String, style: &str, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    if style.is_empty() {
+        out.push_str(&escape(text));
+    } else {
+        let _ = write!(out, "<span style=\"{}\">{}</span>", style, escape(text));
+    }
+}
+
+/// Inline CSS for a cell with colors `fg` and `bg` and `modifier`.
+fn css(fg: Color, bg: Color, modifier: Modifier) -> String {
+    let (mut fg, mut bg) = (rgb(fg).map(hex), rgb(bg).map(hex));
+    if modifier.contains(Modifier::REVERSED) {
+        (fg, bg) = (
+            Some(bg.unwrap_or_else(|| hex(DEFAULT_BG))),
+            Some(fg.unwrap_or_else(|| hex(DEFAULT_FG))),
+        );
+    }
+    let mut style = String::new();
+    if let Some(fg) = fg {
+        let _ = write!(style, "color:{fg};");
+    }
+    if let Some(bg) = bg {
+        let _ = write!(style, "background:{bg};");
+    }
+    for (flag, rule) in [
+        (Modifier::BOLD, "font-weight:bold;"),
+        (Modifier::DIM, "opacity:0.6;"),
+        (Modifier::ITALIC, "font-style:italic;"),
+        (Modifier::UNDERLINED, "text-decoration:underline;"),
+        (Modifier::CROSSED_OUT, "text-decoration:line-through;"),
+        (Modifier::HIDDEN, "visibility:hidden;"),
+    ] {
+        if modifier.contains(flag) {
+            style.push_str(rule);
+        }
+    }
+    style
+}
+
+/// Writes a color as CSS does, as in `#ff8800`.
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Maps a ratatui color to red, green, and blue, or `None` for the
+/// terminal default. Named and indexed colors use the xterm palette.
+fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    const NAMED: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00),
+        (0xcd, 0x00, 0x00),
+        (0x00, 0xcd, 0x00),
+        (0xcd, 0xcd, 0x00),
+        (0x00, 0x00, 0xee),
+        (0xcd, 0x00, 0xcd),
+        (0x00, 0xcd, 0xcd),
+        (0xe5, 0xe5, 0xe5),
+        (0x7f, 0x7f, 0x7f),
+        (0xff, 0x00, 0x00),
+        (0x00, 0xff, 0x00),
+        (0xff, 0xff, 0x00),
+        (0x5c, 0x5c, 0xff),
+        (0xff, 0x00, 0xff),
+        (0x00, 0xff, 0xff),
+        (0xff, 0xff, 0xff),
+    ];
+    let index = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => return Some((r, g, b)),
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Indexed(index) => index,
+    };
+    Some(match index {
+        0..=15 => NAMED[usize::from(index)],
+        16..=231 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let n = index - 16;
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    })
+}
+
+/// Loads the font images are drawn with: `configured`, or else the first
+/// of the usual monospace fonts that is installed.
+fn load_font(configured: Option<&Path>) -> Result<FontVec, String> {
+    let path = match configured {
+        Some(path) => path.to_path_buf(),
+        None => FONTS
+            .iter()
+            .map(PathBuf::from)
+            .find(|path| path.exists())
+            .ok_or(
+                "No monospace font found to draw slides with \
+                 (set `font` under [export] in the config file)",
+            )?,
+    };
+    let data =
+        fs::read(&path).map_err(|e| format!("Failed to read font '{}': {}", path.display(), e))?;
+    // Font collections such as Menlo.ttc are read for their first font.
+    FontVec::try_from_vec_and_index(data, 0)
+        .map_err(|e| format!("Invalid font '{}': {}", path.display(), e))
+}
+
+/// Draws a rendered buffer as an image, a cell at a time, with `font`.
+fn rasterize(buffer: &Buffer, font: &FontVec) -> RgbImage {
+    let scale = PxScale::from(FONT_SIZE);
+    let scaled = font.as_scaled(scale);
+    let cell_width = scaled.h_advance(font.glyph_id('M')).ceil() as u32;
+    let cell_height = (scaled.ascent() - scaled.descent()).ceil() as u32;
+    let area = buffer.area;
+    let mut image = RgbImage::new(
+        cell_width * u32::from(area.width),
+        cell_height * u32::from(area.height),
+    );
+    for y in 0..area.height {
+        let mut skip = 0;
+        for x in 0..area.width {
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let Some(cell) = buffer.cell((area.x + x, area.y + y)) else {
+                continue;
+            };
+            let mut fg = rgb(cell.fg).unwrap_or(DEFAULT_FG);
+            let mut bg = rgb(cell.bg).unwrap_or(DEFAULT_BG);
+            if cell.modifier.contains(Modifier::REVERSED) {
+                (fg, bg) = (bg, fg);
+            }
+            if cell.modifier.contains(Modifier::DIM) {
+                fg = mix(bg, fg, 0.6);
+            }
+            let symbol = cell.symbol();
+            let columns = symbol.width().max(1);
+            skip = columns - 1;
+            let left = u32::from(x) * cell_width;
+            let top = u32::from(y) * cell_height;
+            let right = (left + cell_width * columns as u32).min(image.width());
+            for py in top..top + cell_height {
+                for px in left..right {
+                    image.put_pixel(px, py, Rgb([bg.0, bg.1, bg.2]));
+                }
+            }
+            if cell.modifier.contains(Modifier::HIDDEN) {
+                continue;
+            }
+            let Some(c) = symbol.chars().next().filter(|c| !c.is_whitespace()) else {
+                continue;
+            };
+            let baseline = top as f32 + scaled.ascent();
+            // Bold text is drawn twice, a pixel apart.
+            let strokes = if cell.modifier.contains(Modifier::BOLD) {
+                2
+            } else {
+                1
+            };
+            for stroke in 0..strokes {
+                let position = point(left as f32 + stroke as f32, baseline);
+                let glyph = font.glyph_id(c).with_scale_and_position(scale, position);
+                let Some(outline) = font.outline_glyph(glyph) else {
+                    continue;
+                };
+                let bounds = outline.px_bounds();
+                outline.draw(|gx, gy, coverage| {
+                    let px = bounds.min.x as i64 + i64::from(gx);
+                    let py = bounds.min.y as i64 + i64::from(gy);
+                    if let (Ok(px), Ok(py)) = (u32::try_from(px), u32::try_from(py))
+                        && px < image.width()
+                        && py < image.height()
+                    {
+                        let Rgb([r, g, b]) = *image.get_pixel(px, py);
+                        let (r, g, b) = mix((r, g, b), fg, coverage);
+                        image.put_pixel(px, py, Rgb([r, g, b]));
+                    }
+                });
+            }
+            if cell.modifier.contains(Modifier::UNDERLINED) {
+                let py = (baseline as u32 + 2).min(top + cell_height - 1);
+                for px in left..right {
+                    image.put_pixel(px, py, Rgb([fg.0, fg.1, fg.2]));
+                }
+            }
+        }
+    }
+    image
+}
+
+/// Blends `amount` of color `to` into color `from`.
+fn mix(from: (u8, u8, u8), to: (u8, u8, u8), amount: f32) -> (u8, u8, u8) {
+    let channel = |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * amount) as u8;
+    (
+        channel(from.0, to.0),
+        channel(from.1, to.1),
+        channel(from.2, to.2),
+    )
+}
+
+/// Builds a PDF titled `title` with a page showing each of `images`, and a
+/// bookmark for each page named by its entry in `labels`.
+fn pdf(images: &[RgbImage], title: &str, labels: &[String]) -> Vec<u8> {
+    let mut pdf = Pdf::new();
+    let catalog = Ref::new(1);
+    let pages = Ref::new(2);
+    let info = Ref::new(3);
+    // Each page takes three objects: itself, its content, and its image.
+    let page_ref = |index: usize, offset: i32| Ref::new(4 + 3 * index as i32 + offset);
+    // The bookmarks come after the pages, one per page.
+    let outline = page_ref(images.len(), 0);
+    let bookmark_ref = |index: usize| Ref::new(outline.get() + 1 + index as i32);
+    let mut document = pdf.catalog(catalog);
+    document.pages(pages);
+    if !images.is_empty() {
+        document.outlines(outline);
+    }
+    document.finish();
+    pdf.document_info(info).title(pdf_writer::TextStr(title));
+    pdf.pages(pages)
+        .kids((0..images.len()).map(|index| page_ref(index, 0)))
+        .count(images.len() as i32);
+    let name = Name(b"Slide");
+    for (index, image) in images.iter().enumerate() {
+        let width = image.width() as f32 / PIXELS_PER_POINT;
+        let height = image.height() as f32 / PIXELS_PER_POINT;
+        let mut page = pdf.page(page_ref(index, 0));
+        page.media_box(Rect::new(0.0, 0.0, width, height));
+        page.parent(pages);
+        page.contents(page_ref(index, 1));
+        page.resources().x_objects().pair(name, page_ref(index, 2));
+        page.finish();
+
+        let mut content = Content::new();
+        content.transform([width, 0.0, 0.0, height, 0.0, 0.0]);
+        content.x_object(name);
+        pdf.stream(page_ref(index, 1), &content.finish());
+
+        let level = CompressionLevel::DefaultLevel as u8;
+        let data = compress_to_vec_zlib(image.as_raw(), level);
+        let mut xobject = pdf.image_xobject(page_ref(index, 2), &data);
+        xobject.filter(Filter::FlateDecode);
+        xobject.width(image.width() as i32);
+        xobject.height(image.height() as i32);
+        xobject.color_space().device_rgb();
+        xobject.bits_per_component(8);
+    }
+    if !images.is_empty() {
+        let last = images.len() - 1;
+        pdf.outline(outline)
+            .first(bookmark_ref(0))
+            .last(bookmark_ref(last))
+            .count(images.len() as i32);
+        for (index, image) in images.iter().enumerate() {
+            let number = (index + 1).to_string();
+            let label = labels.get(index).filter(|label| !label.is_empty());
+            let mut bookmark = pdf.outline_item(bookmark_ref(index));
+            bookmark.title(pdf_writer::TextStr(label.unwrap_or(&number)));
+            bookmark.parent(outline);
+            if index > 0 {
+                bookmark.prev(bookmark_ref(index - 1));
+            }
+            if index < last {
+                bookmark.next(bookmark_ref(index + 1));
+            }
+            let top = image.height() as f32 / PIXELS_PER_POINT;
+            bookmark.dest().page(page_ref(index, 0)).xyz(0.0, top, None);
+        }
+    }
+    pdf.finish()
+}
+
+/// Escapes the characters HTML gives a meaning to.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,193 @@
+//! Flashcard mode: studying a deck one card at a time.
+//!
+//! Every slide that starts with an H1 heading is a card. The heading is the
+//! question; the rest of the slide is the answer, hidden until revealed.
+//! Cards are dealt in shuffled order, and cards marked as known are saved to
+//! the deck's state directory so later sessions can skip them.
+
+use crate::state;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// On-disk list of cards the user has marked as known.
+#[derive(Default, Serialize, Deserialize)]
+struct KnownFile {
+    /// Titles of the known cards
+    known: BTreeSet<String>,
+}
+
+/// A study session over the cards of one deck.
+pub struct Flashcards {
+    /// Where the known cards are saved
+    path: PathBuf,
+    /// Titles of cards marked as known, including those from earlier sessions
+    known: BTreeSet<String>,
+    /// Review cards already marked as known
+    all: bool,
+    /// Deal cards in a random order instead of deck order
+    shuffle: bool,
+    /// Dealt cards as `(slide index, title)`, in study order
+    order: Vec<(usize, String)>,
+    /// Index of the current card within `order`
+    position: usize,
+    /// Whether the answer of the current card is shown
+    revealed: bool,
+}
+
+impl Flashcards {
+    /// Loads the known cards of `deck`.
+    ///
+    /// # Arguments
+    ///
+    /// * `deck` - Path to the markdown file
+    /// * `all` - Deal cards already marked as known as well
+    /// * `shuffle` - Deal cards in a random order
+    pub fn load(deck: &Path, all: bool, shuffle: bool) -> Result<Self, Box<dyn Error>> {
+        let path = state::deck_dir(deck).join("flashcards.json");
+        let known = match fs::read_to_string(&path) {
+            Ok(json) => {
+                serde_json::from_str::<KnownFile>(&json)
+                    .map_err(|e| format!("Invalid flashcards file '{}': {}", path.display(), e))?
+                    .known
+            }
+            Err(_) => BTreeSet::new(),
+        };
+        Ok(Flashcards {
+            path,
+            known,
+            all,
+            shuffle,
+            order: Vec::new(),
+            position: 0,
+            revealed: false,
+        })
+    }
+
+    /// Deals the cards of a deck whose slides have the given `titles` and
+    /// returns the slide index of the first card.
+    ///
+    /// If every card is already known, all of them are dealt so the session
+    /// is never empty.
+    pub fn deal(&mut self, titles: &[Option<String>]) -> Option<usize> {
+        let cards: Vec<(usize, String)> = titles
+            .iter()
+            .enumerate()
+            .filter_map(|(index, title)| Some((index, title.clone()?)))
+            .collect();
+        self.order = cards
+            .iter()
+            .filter(|(_, title)| self.all || !self.known.contains(title))
+            .cloned()
+            .collect();
+        if self.order.is_empty() {
+            self.order = cards;
+        }
+        if self.shuffle {
+            shuffle(&mut self.order);
+        }
+        self.position = 0;
+        self.revealed = false;
+        self.current()
+    }
+
+    /// Slide index of the current card.
+    pub fn current(&self) -> Option<usize> {
+        self.order.get(self.position).map(|(index, _)| *index)
+    }
+
+    /// Index of the current card within the session.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Number of cards dealt this session.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Moves to the card at `position`, clamped to the last card, and returns
+    /// its slide index if the current card changed.
+    pub fn turn_to(&mut self, position: usize) -> Option<usize> {
+        let position = position.min(self.order.len().checked_sub(1)?);
+        if position == self.position {
+            return None;
+        }
+        self.position = position;
+        self.revealed = false;
+        self.current()
+    }
+
+    /// Whether the answer of the current card is shown.
+    pub fn is_revealed(&self) -> bool {
+        self.revealed
+    }
+
+    /// Shows the answer of the current card.
+    pub fn reveal(&mut self) {
+        self.revealed = true;
+    }
+
+    /// Whether the current card is marked as known.
+    pub fn is_known(&self) -> bool {
+        self.order
+            .get(self.position)
+            .is_some_and(|(_, title)| self.known.contains(title))
+    }
+
+    /// Toggles whether the current card is known and returns the new state.
+    pub fn toggle_known(&mut self) -> bool {
+        let Some((_, title)) = self.order.get(self.position) else {
+            return false;
+        };
+        if self.known.remove(title) {
+            false
+        } else {
+            self.known.insert(title.clone());
+            true
+        }
+    }
+
+    /// Number of dealt cards marked as known.
+    pub fn known_count(&self) -> usize {
+        self.order
+            .iter()
+            .filter(|(_, title)| self.known.contains(title))
+            .count()
+    }
+
+    /// Saves the known cards to the deck's state directory.
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+        }
+        let file = KnownFile {
+            known: self.known.clone(),
+        };
+        fs::write(&self.path, serde_json::to_string_pretty(&file)?)
+            .map_err(|e| format!("Failed to write '{}': {}", self.path.display(), e))?;
+        Ok(())
+    }
+}
+
+/// Shuffles `items` in place with a Fisher-Yates shuffle seeded from the clock.
+fn shuffle<T>(items: &mut [T]) {
+    let mut state = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+        | 1;
+    for i in (1..items.len()).rev() {
+        // xorshift64*
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        let random = state.wrapping_mul(0x2545_f491_4f6c_dd1d);
+        items.swap(i, (random % (i as u64 + 1)) as usize);
+    }
+}
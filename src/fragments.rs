@@ -0,0 +1,231 @@
+//! Fragments revealed one step at a time.
+//!
+//! A `<!-- pause -->` comment splits a slide into fragments. The slide opens
+//! with only the content before the first pause; each press of `→` or `Space`
+//! reveals the next fragment, and the press after the last one moves on to
+//! the next slide:
+//!
+//! ```markdown
+//! # Why Rust?
+//!
+//! - Fast
+//! <!-- pause -->
+//! - Reliable
+//! <!-- pause -->
+//! - Productive
+//! ```
+//!
+//! A paragraph of nothing but `. . .`, the way Pandoc decks pause, does the
+//! same.
+//!
+//! A code block marked `step` is walked through the same way, one line per
+//! step: each step reveals and highlights the next line of the block.
+//!
+//! A code block annotated with line sets, as in ```` ```rust {1|3-5|all} ````,
+//! is shown whole with the first set in focus and the rest of the block
+//! dimmed; each step moves the focus to the next set.
+
+use ratatui::{
+    style::{Color, Modifier},
+    text::Line,
+};
+use serde::Deserialize;
+use std::ops::Range;
+
+/// A paragraph that pauses the slide like a `<!-- pause -->` comment.
+pub const PAUSE_DOTS: &str = ". . .";
+
+/// One step in revealing a slide.
+enum Fragment {
+    /// Reveals the slide from this line on, set by `<!-- pause -->`
+    Pause(usize),
+    /// Reveals and highlights this line of a `step` code block
+    CodeLine(usize),
+    /// Moves the focus of an annotated code block to its next line set
+    Focus,
+}
+
+impl Fragment {
+    /// Returns true if slide line `line` stays hidden until this step.
+    fn covers(&self, line: usize) -> bool {
+        match *self {
+            Fragment::Pause(start) => line >= start,
+            Fragment::CodeLine(code) => line == code,
+            Fragment::Focus => false,
+        }
+    }
+}
+
+/// A code block annotated with line sets to focus on in turn.
+struct CodeFocus {
+    /// Slide lines the block occupies
+    block: Range<usize>,
+    /// Slide lines in focus at each step; an empty set means the whole block
+    sets: Vec<Vec<Range<usize>>>,
+    /// Index of the step that moves the focus to the second set
+    first_step: usize,
+}
+
+/// How a slide line is drawn, given the steps taken so far.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    /// Drawn as usual
+    Shown,
+    /// Not revealed yet
+    Concealed,
+    /// Brought into focus by the latest step
+    Highlighted,
+    /// Part of a code block whose focus is elsewhere
+    Dimmed,
+}
+
+/// Everything on a slide that is revealed or brought into focus step by
+/// step, in order.
+#[derive(Default)]
+pub struct Steps {
+    /// Steps in the order they are taken
+    fragments: Vec<Fragment>,
+    /// Code blocks annotated with line sets
+    focus: Vec<CodeFocus>,
+}
+
+impl Steps {
+    /// Number of steps it takes to reveal the whole slide.
+    pub fn len(&self) -> usize {
+        self.fragments.len()
+    }
+
+    /// Adds a `<!-- pause -->` before slide line `line`.
+    pub fn pause(&mut self, line: usize) {
+        self.fragments.push(Fragment::Pause(line));
+    }
+
+    /// Adds a step for each line of a `step` code block occupying `block`.
+    pub fn code_lines(&mut self, block: Range<usize>) {
+        self.fragments.extend(block.map(Fragment::CodeLine));
+    }
+
+    /// Adds a code block occupying `block`, annotated with the line sets in
+    /// `annotation` (e.g. `{1|3-5|all}`).
+    ///
+    /// Returns false, adding nothing, if `annotation` is not a list of line
+    /// sets.
+    pub fn code_focus(&mut self, block: Range<usize>, annotation: &str) -> bool {
+        let Some(sets) = parse_line_sets(annotation, block.start, block.len()) else {
+            return false;
+        };
+        let first_step = self.fragments.len();
+        self.fragments
+            .extend((1..sets.len()).map(|_| Fragment::Focus));
+        self.focus.push(CodeFocus {
+            block,
+            sets,
+            first_step,
+        });
+        true
+    }
+
+    /// Index of the first line hidden by a pause not yet stepped past, or
+    /// `None` if no part of the slide after a pause is hidden.
+    pub fn hidden_from(&self, taken: usize) -> Option<usize> {
+        self.fragments.iter().skip(taken).find_map(|f| match f {
+            Fragment::Pause(start) => Some(*start),
+            Fragment::CodeLine(_) | Fragment::Focus => None,
+        })
+    }
+
+    /// Returns how slide line `line` is drawn after the first `taken` steps.
+    pub fn visibility(&self, taken: usize, line: usize) -> Visibility {
+        if self.fragments.iter().skip(taken).any(|f| f.covers(line)) {
+            return Visibility::Concealed;
+        }
+        if taken
+            .checked_sub(1)
+            .and_then(|latest| self.fragments.get(latest))
+            .is_some_and(|f| matches!(f, Fragment::CodeLine(code) if *code == line))
+        {
+            return Visibility::Highlighted;
+        }
+        match self.focus.iter().find(|focus| focus.block.contains(&line)) {
+            Some(focus) => {
+                let set = &focus.sets[taken
+                    .saturating_sub(focus.first_step)
+                    .min(focus.sets.len() - 1)];
+                if set.is_empty() || set.iter().any(|lines| lines.contains(&line)) {
+                    Visibility::Shown
+                } else {
+                    Visibility::Dimmed
+                }
+            }
+            None => Visibility::Shown,
+        }
+    }
+}
+
+/// Parses line sets written as `{1|3-5,7|all}`, numbered from 1 within a
+/// block of `len` lines, into ranges of slide lines starting at `start`.
+fn parse_line_sets(annotation: &str, start: usize, len: usize) -> Option<Vec<Vec<Range<usize>>>> {
+    let inner = annotation.strip_prefix('{')?.strip_suffix('}')?;
+    inner
+        .split('|')
+        .map(|set| {
+            let set = set.trim();
+            if set == "all" {
+                return Some(Vec::new());
+            }
+            set.split(',')
+                .map(|part| {
+                    let (first, last) = match part.split_once('-') {
+                        Some((first, last)) => (first.trim(), last.trim()),
+                        None => (part.trim(), part.trim()),
+                    };
+                    let first: usize = first.parse().ok()?;
+                    let last: usize = last.parse().ok()?;
+                    (first >= 1 && first <= last)
+                        .then(|| start + (first - 1).min(len)..start + last.min(len))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Draws `line` as the focus of a code walkthrough.
+pub fn highlight(line: &Line<'static>) -> Line<'static> {
+    line.clone()
+        .patch_style(Modifier::BOLD)
+        .style(line.style.bg(Color::DarkGray))
+}
+
+/// How fragments that have not been revealed yet are drawn.
+#[derive(Clone, Copy, Default, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum FragmentStyle {
+    /// Leave their lines blank
+    #[default]
+    Hide,
+    /// Grey them out, so the audience can see the shape of the slide
+    Dim,
+}
+
+impl FragmentStyle {
+    /// Draws `line`, which belongs to a fragment not revealed yet.
+    pub fn conceal(self, line: &Line<'static>) -> Line<'static> {
+        match self {
+            FragmentStyle::Hide => Line::default(),
+            FragmentStyle::Dim => dim(line),
+        }
+    }
+}
+
+/// Greys out `line`.
+pub fn dim(line: &Line<'static>) -> Line<'static> {
+    let mut line = line.clone();
+    for span in &mut line.spans {
+        span.style = span
+            .style
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::DIM)
+            .remove_modifier(Modifier::BOLD);
+    }
+    line
+}
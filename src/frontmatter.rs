@@ -0,0 +1,112 @@
+//! Deck metadata from front matter.
+//!
+//! A deck may start with a block of `key: value` lines between `---` fences,
+//! as in Jekyll or Marp:
+//!
+//! ```markdown
+//! ---
+//! title: Fearless Refactoring
+//! author: Jane Doe
+//! event: RustConf
+//! date: 2025-09-02
+//! ---
+//!
+//! # First slide
+//! ```
+//!
+//! Only flat string values are supported; quotes around a value are removed.
+//! The block itself is never shown as a slide.
+
+use std::collections::BTreeMap;
+
+/// Keys joined into the footer when no footer template is given.
+const DEFAULT_FOOTER_KEYS: &[&str] = &["author", "event", "date"];
+
+/// Metadata from the front matter block at the top of a deck.
+#[derive(Default)]
+pub struct FrontMatter {
+    /// Values by key
+    fields: BTreeMap<String, String>,
+}
+
+impl FrontMatter {
+    /// Reads the front matter at the start of `markdown`, if there is any.
+    pub fn parse(markdown: &str) -> Self {
+        let mut fields = BTreeMap::new();
+        let mut lines = markdown.lines();
+        // As in pulldown-cmark, a blank line right after the opening fence
+        // makes it a thematic break instead.
+        if lines.next().map(str::trim_end) != Some("---")
+            || lines
+                .clone()
+                .next()
+                .is_none_or(|line| line.trim().is_empty())
+        {
+            return FrontMatter::default();
+        }
+        let mut closed = false;
+        for line in lines.by_ref() {
+            let line = line.trim_end();
+            if line == "---" || line == "..." {
+                closed = true;
+                break;
+            }
+            if let Some((key, value)) = line.split_once(':')
+                && !key.starts_with([' ', '#'])
+            {
+                fields.insert(key.trim().to_string(), unquote(value.trim()).to_string());
+            }
+        }
+        if !closed {
+            return FrontMatter::default();
+        }
+        FrontMatter { fields }
+    }
+
+    /// Returns the value of `key`, if the front matter sets it.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(String::as_str)
+    }
+
+    /// Returns true if `key` is set to `true` or `yes`.
+    pub fn flag(&self, key: &str) -> bool {
+        matches!(self.get(key), Some("true" | "yes"))
+    }
+
+    /// Renders the footer shown on every slide.
+    ///
+    /// `{key}` placeholders in `template` are replaced by front matter
+    /// values. Without a template, the author, event, and date are joined
+    /// with `·`. Returns `None` if the footer would be empty.
+    pub fn footer(&self, template: Option<&str>) -> Option<String> {
+        let text = match template {
+            Some(template) => self
+                .fields
+                .iter()
+                .fold(template.to_string(), |text, (key, value)| {
+                    text.replace(&format!("{{{key}}}"), value)
+                }),
+            None => DEFAULT_FOOTER_KEYS
+                .iter()
+                .filter_map(|key| self.get(key))
+                .filter(|value| !value.is_empty())
+                .collect::<Vec<_>>()
+                .join(" · "),
+        };
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+}
+
+/// Removes matching single or double quotes around `value`.
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
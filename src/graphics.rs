@@ -0,0 +1,378 @@
+//! Drawing images with the terminal's own graphics protocol.
+//!
+//! Half blocks show an image in every terminal, but at two pixels a cell.
+//! Some terminals can show the image itself: kitty and the terminals that
+//! speak its graphics protocol, iTerm2 and WezTerm with iTerm2's inline
+//! images, and terminals with sixel graphics such as foot and mlterm.
+//! `--images auto`, the default, uses the protocol of the terminal presentrs
+//! runs in, recognized from the environment it set; `--images kitty`, `iterm`
+//! or `sixel` picks one by hand, and `--images blocks` keeps to half blocks.
+//!
+//! Slides are laid out with the half blocks either way, so an image takes up
+//! the same cells whatever draws it. After a frame that moves, reveals, or
+//! hides an image, the image is drawn over its cells. Images in regions,
+//! grids, and layouts, and images cut off by the edge of the slide, stay
+//! half blocks. Inside tmux, which does not pass graphics through, `auto`
+//! keeps to half blocks too.
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use image::{DynamicImage, ImageFormat, RgbaImage, imageops::FilterType};
+use ratatui::layout::{Alignment, Rect};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    env,
+    io::{self, Cursor, Write},
+    path::{Path, PathBuf},
+};
+
+/// Size of a terminal cell in pixels, when the terminal does not say.
+const DEFAULT_CELL: (u32, u32) = (10, 20);
+/// Most base64 bytes sent in one kitty graphics command.
+const KITTY_CHUNK: usize = 4096;
+/// Pixels with an alpha value below this are left out of sixel images.
+const ALPHA_THRESHOLD: u8 = 128;
+/// Levels of each of red, green, and blue in the sixel palette.
+const SIXEL_LEVELS: u32 = 6;
+
+/// How images are drawn in the terminal.
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Protocol {
+    /// The terminal's own protocol if it is known to have one, else half blocks
+    #[default]
+    Auto,
+    /// Half-block characters, which every terminal shows
+    Blocks,
+    /// kitty's graphics protocol, also spoken by Ghostty and WezTerm
+    Kitty,
+    /// iTerm2's inline images, also shown by WezTerm
+    Iterm,
+    /// Sixel graphics, shown by foot, mlterm, and others
+    Sixel,
+}
+
+impl Protocol {
+    /// Resolves `auto` to the protocol of the terminal presentrs runs in,
+    /// recognized from the environment it set.
+    pub fn detect(self) -> Self {
+        if self != Protocol::Auto {
+            return self;
+        }
+        let var = |name| env::var(name).unwrap_or_default();
+        let term = var("TERM");
+        let program = var("TERM_PROGRAM");
+        if env::var_os("TMUX").is_some() || term.starts_with("screen") || term.starts_with("tmux") {
+            Protocol::Blocks
+        } else if env::var_os("KITTY_WINDOW_ID").is_some()
+            || term == "xterm-kitty"
+            || term == "xterm-ghostty"
+            || program == "ghostty"
+        {
+            Protocol::Kitty
+        } else if program == "iTerm.app" || program == "WezTerm" || var("LC_TERMINAL") == "iTerm2" {
+            Protocol::Iterm
+        } else if term.starts_with("foot") || term.starts_with("mlterm") {
+            Protocol::Sixel
+        } else {
+            Protocol::Blocks
+        }
+    }
+}
+
+/// An image on a slide, laid out with half blocks, that a graphics protocol
+/// can draw instead.
+#[derive(Clone)]
+pub struct Image {
+    /// Location of the image file
+    pub path: PathBuf,
+    /// Index of the first slide line the half blocks are on
+    pub line: usize,
+    /// Columns the half blocks take up
+    pub cols: u16,
+    /// Rows the half blocks take up
+    pub rows: u16,
+    /// Horizontal position on the slide
+    pub align: Alignment,
+}
+
+impl Image {
+    /// Cells the image takes up when its first line is on row `row` of
+    /// `area`, or `None` if it does not fit there whole.
+    pub fn area(&self, area: Rect, row: usize) -> Option<Rect> {
+        let row = u16::try_from(row).ok()?;
+        if row.checked_add(self.rows)? > area.height || self.cols > area.width {
+            return None;
+        }
+        // Offset as a paragraph aligns a line of the image's width.
+        let col = match self.align {
+            Alignment::Left => 0,
+            Alignment::Center => (area.width / 2).saturating_sub(self.cols / 2),
+            Alignment::Right => area.width - self.cols,
+        };
+        Some(Rect::new(area.x + col, area.y + row, self.cols, self.rows))
+    }
+}
+
+/// The images drawn on screen with a graphics protocol.
+pub struct Painter {
+    /// Protocol the images are drawn with
+    protocol: Protocol,
+    /// Escape sequence drawing each image at a size in cells, encoded the
+    /// first time it is drawn, or `None` if it could not be loaded
+    encoded: HashMap<(PathBuf, u16, u16), Option<String>>,
+    /// Images on screen, with the cells they take up
+    shown: Vec<(PathBuf, Rect)>,
+}
+
+impl Default for Painter {
+    fn default() -> Self {
+        Painter::new(Protocol::Blocks)
+    }
+}
+
+impl Painter {
+    pub fn new(protocol: Protocol) -> Self {
+        Painter {
+            protocol: protocol.detect(),
+            encoded: HashMap::new(),
+            shown: Vec::new(),
+        }
+    }
+
+    /// Whether images are drawn with a graphics protocol rather than left
+    /// as half blocks.
+    pub fn is_active(&self) -> bool {
+        self.protocol != Protocol::Blocks
+    }
+
+    /// Whether drawing `images` changes what is on screen.
+    pub fn changed(&self, images: &[(PathBuf, Rect)]) -> bool {
+        self.is_active() && self.shown != images
+    }
+
+    /// Whether the screen must be drawn afresh before images are taken off
+    /// it. iTerm2 and sixel images become the contents of their cells, which
+    /// stay until the cells are written again; kitty's can be deleted.
+    pub fn needs_redraw(&self) -> bool {
+        matches!(self.protocol, Protocol::Iterm | Protocol::Sixel) && !self.shown.is_empty()
+    }
+
+    /// Forgets the images on screen, after the terminal cleared them.
+    pub fn forget(&mut self) {
+        self.shown.clear();
+    }
+
+    /// Draws `images` over the cells given with each, replacing the images
+    /// drawn before.
+    pub fn paint(&mut self, out: &mut impl Write, images: &[(PathBuf, Rect)]) -> io::Result<()> {
+        if !self.is_active() {
+            return Ok(());
+        }
+        if self.protocol == Protocol::Kitty {
+            write!(out, "\x1b_Ga=d,d=A,q=2\x1b\\")?;
+        }
+        for (path, area) in images {
+            let key = (path.clone(), area.width, area.height);
+            let protocol = self.protocol;
+            let Some(sequence) = self
+                .encoded
+                .entry(key)
+                .or_insert_with(|| encode(protocol, path, area.width, area.height))
+            else {
+                continue;
+            };
+            // The cursor is put back where it was, so the next frame's
+            // drawing is not thrown off.
+            write!(
+                out,
+                "\x1b7\x1b[{};{}H{sequence}\x1b8",
+                area.y + 1,
+                area.x + 1
+            )?;
+        }
+        out.flush()?;
+        self.shown = images.to_vec();
+        Ok(())
+    }
+}
+
+/// Escape sequence drawing the image at `path` across `cols` by `rows`
+/// cells with `protocol`, or `None` if the image cannot be loaded.
+fn encode(protocol: Protocol, path: &Path, cols: u16, rows: u16) -> Option<String> {
+    let image = match image::open(path) {
+        Ok(image) => image,
+        Err(e) => {
+            log::warn!("Failed to load image '{}': {}", path.display(), e);
+            return None;
+        }
+    };
+    // Scaled to the pixels of its cells, so that no more is sent than shown.
+    let (cell_width, cell_height) = cell_size().unwrap_or(DEFAULT_CELL);
+    let image = image.resize_exact(
+        u32::from(cols) * cell_width,
+        u32::from(rows) * cell_height,
+        FilterType::Triangle,
+    );
+    match protocol {
+        Protocol::Kitty => png(&image).map(|png| kitty(&png, cols, rows)),
+        Protocol::Iterm => png(&image).map(|png| iterm(&png, cols, rows)),
+        Protocol::Sixel => Some(sixel(&image.to_rgba8())),
+        Protocol::Auto | Protocol::Blocks => None,
+    }
+}
+
+/// `image` as PNG data.
+fn png(image: &DynamicImage) -> Option<Vec<u8>> {
+    let mut data = Cursor::new(Vec::new());
+    match image.write_to(&mut data, ImageFormat::Png) {
+        Ok(()) => Some(data.into_inner()),
+        Err(e) => {
+            log::warn!("Failed to encode image: {e}");
+            None
+        }
+    }
+}
+
+/// kitty graphics commands showing `png` across `cols` by `rows` cells,
+/// sent in chunks as the protocol requires.
+fn kitty(png: &[u8], cols: u16, rows: u16) -> String {
+    let data = BASE64.encode(png);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK).collect();
+    let mut out = String::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(index + 1 < chunks.len());
+        let chunk = String::from_utf8_lossy(chunk);
+        if index == 0 {
+            // Shown at the cursor, which it leaves where it was, with no
+            // replies from the terminal.
+            out.push_str(&format!(
+                "\x1b_Ga=T,f=100,q=2,C=1,c={cols},r={rows},m={more};{chunk}\x1b\\"
+            ));
+        } else {
+            out.push_str(&format!("\x1b_Gm={more};{chunk}\x1b\\"));
+        }
+    }
+    out
+}
+
+/// iTerm2 inline image showing `png` across `cols` by `rows` cells.
+fn iterm(png: &[u8], cols: u16, rows: u16) -> String {
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={cols};height={rows};preserveAspectRatio=0:{}\x07",
+        png.len(),
+        BASE64.encode(png)
+    )
+}
+
+/// Sixel graphics drawing `image`, in a palette of 216 colors. Transparent
+/// pixels are left as they were.
+fn sixel(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+    let level = |c: u8| (u32::from(c) * (SIXEL_LEVELS - 1) + 127) / 255;
+    // Palette index of each pixel, or `None` where it is transparent
+    let indices: Vec<Option<u32>> = image
+        .pixels()
+        .map(|pixel| {
+            let [r, g, b, a] = pixel.0;
+            (a >= ALPHA_THRESHOLD)
+                .then(|| (level(r) * SIXEL_LEVELS + level(g)) * SIXEL_LEVELS + level(b))
+        })
+        .collect();
+
+    // Transparent pixels keep their background, and the image's size is
+    // given in pixels at a 1:1 aspect ratio.
+    let mut out = format!("\x1bP0;1;0q\"1;1;{width};{height}");
+    let colors = SIXEL_LEVELS.pow(3);
+    for index in 0..colors {
+        let percent = |value: u32| value * 100 / (SIXEL_LEVELS - 1);
+        let (r, g, b) = (
+            index / (SIXEL_LEVELS * SIXEL_LEVELS),
+            index / SIXEL_LEVELS % SIXEL_LEVELS,
+            index % SIXEL_LEVELS,
+        );
+        out.push_str(&format!(
+            "#{index};2;{};{};{}",
+            percent(r),
+            percent(g),
+            percent(b)
+        ));
+    }
+    // Each band of six pixel rows is drawn once per color in it.
+    for top in (0..height).step_by(6) {
+        let band = top..(top + 6).min(height);
+        let mut used = vec![false; colors as usize];
+        for y in band.clone() {
+            for x in 0..width {
+                if let Some(index) = indices[(y * width + x) as usize] {
+                    used[index as usize] = true;
+                }
+            }
+        }
+        for index in (0..colors).filter(|&index| used[index as usize]) {
+            out.push_str(&format!("#{index}"));
+            let bits = (0..width).map(|x| {
+                band.clone()
+                    .filter(|&y| indices[(y * width + x) as usize] == Some(index))
+                    .fold(0, |bits, y| bits | 1 << (y - top))
+            });
+            push_runs(&mut out, bits);
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+/// Appends the sixels with the pixels `bits` set, repeats run-length encoded.
+fn push_runs(out: &mut String, bits: impl Iterator<Item = u8>) {
+    let mut run: Option<(u8, usize)> = None;
+    let flush = |out: &mut String, (bits, count): (u8, usize)| {
+        let sixel = char::from(0x3f + bits);
+        if count > 3 {
+            out.push_str(&format!("!{count}{sixel}"));
+        } else {
+            out.extend(std::iter::repeat_n(sixel, count));
+        }
+    };
+    for bits in bits {
+        run = match run {
+            Some((last, count)) if last == bits => Some((last, count + 1)),
+            Some(last) => {
+                flush(out, last);
+                Some((bits, 1))
+            }
+            None => Some((bits, 1)),
+        };
+    }
+    if let Some(last) = run {
+        flush(out, last);
+    }
+}
+
+/// Size of a terminal cell in pixels, if the terminal reports it.
+#[cfg(unix)]
+fn cell_size() -> Option<(u32, u32)> {
+    // SAFETY: `winsize` is plain data, and TIOCGWINSZ only writes to the
+    // one it is given.
+    let size = unsafe {
+        let mut size: libc::winsize = std::mem::zeroed();
+        if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) != 0 {
+            return None;
+        }
+        size
+    };
+    (size.ws_col > 0 && size.ws_row > 0 && size.ws_xpixel > 0 && size.ws_ypixel > 0).then(|| {
+        (
+            u32::from(size.ws_xpixel / size.ws_col),
+            u32::from(size.ws_ypixel / size.ws_row),
+        )
+    })
+}
+
+/// Size of a terminal cell in pixels, if the terminal reports it.
+#[cfg(not(unix))]
+fn cell_size() -> Option<(u32, u32)> {
+    None
+}
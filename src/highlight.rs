@@ -0,0 +1,117 @@
+//! Syntax highlighting backends for code blocks.
+//!
+//! Code is highlighted with syntect's TextMate grammars. Builds with the
+//! `tree-sitter` feature can highlight with tree-sitter grammars instead,
+//! chosen in the config file:
+//!
+//! ```toml
+//! highlighter = "tree-sitter"
+//! ```
+//!
+//! Tree-sitter parses the code rather than matching it line by line with
+//! regular expressions, so constructs such as nested generics, raw strings,
+//! and macros in modern languages come out right. Both backends take their
+//! colors from the same code theme and produce the same spans, so the rest
+//! of the slide cannot tell them apart. Languages tree-sitter has no grammar
+//! for are still highlighted with syntect.
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use syntect::{
+    highlighting::{FontStyle, Style as CodeStyle},
+    parsing::SyntaxSet,
+};
+
+#[cfg(feature = "tree-sitter")]
+use crate::treesitter::Grammars;
+#[cfg(feature = "tree-sitter")]
+use ratatui::text::Line;
+#[cfg(feature = "tree-sitter")]
+use syntect::highlighting::Theme as CodeTheme;
+
+/// Which highlighter code blocks go through, from the config file.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backend {
+    /// syntect's TextMate grammars
+    #[default]
+    Syntect,
+    /// tree-sitter grammars, in builds with the `tree-sitter` feature
+    TreeSitter,
+}
+
+/// Highlights code with the backend chosen in the config file.
+pub struct Highlighter {
+    /// syntect's syntax definitions, used for every language unless
+    /// tree-sitter handles it
+    pub syntaxes: SyntaxSet,
+    /// Tree-sitter grammars, when that backend is chosen
+    #[cfg(feature = "tree-sitter")]
+    grammars: Option<Grammars>,
+}
+
+impl Highlighter {
+    /// Creates a highlighter with `backend` that falls back to `syntaxes`.
+    pub fn new(syntaxes: SyntaxSet, backend: Backend) -> Result<Self, String> {
+        #[cfg(feature = "tree-sitter")]
+        {
+            let grammars = match backend {
+                Backend::TreeSitter => Some(Grammars::load()?),
+                Backend::Syntect => None,
+            };
+            Ok(Highlighter { syntaxes, grammars })
+        }
+        #[cfg(not(feature = "tree-sitter"))]
+        match backend {
+            Backend::TreeSitter => Err("presentrs was built without tree-sitter support \
+                 (rebuild it with `--features tree-sitter`)"
+                .to_string()),
+            Backend::Syntect => Ok(Highlighter { syntaxes }),
+        }
+    }
+
+    /// Highlights `content` with tree-sitter, if that backend is chosen and
+    /// has a grammar for `lang`.
+    #[cfg(feature = "tree-sitter")]
+    pub fn tree_sitter(
+        &self,
+        content: &str,
+        lang: &str,
+        code_theme: &CodeTheme,
+    ) -> Option<Vec<Line<'static>>> {
+        self.grammars.as_ref()?.highlight(content, lang, code_theme)
+    }
+
+    /// Whether tree-sitter is the chosen backend and has a grammar for
+    /// `lang`.
+    #[cfg(feature = "tree-sitter")]
+    pub fn has_grammar(&self, lang: &str) -> bool {
+        self.grammars.as_ref().is_some_and(|grammars| grammars.has(lang))
+    }
+}
+
+impl Default for Highlighter {
+    fn default() -> Self {
+        Highlighter {
+            syntaxes: SyntaxSet::load_defaults_newlines(),
+            #[cfg(feature = "tree-sitter")]
+            grammars: None,
+        }
+    }
+}
+
+/// Converts a style from the code theme into a terminal style.
+pub fn span_style(style: CodeStyle) -> Style {
+    let mut span_style = Style::default().fg(Color::Rgb(
+        style.foreground.r,
+        style.foreground.g,
+        style.foreground.b,
+    ));
+    if style.font_style.contains(FontStyle::BOLD) {
+        span_style = span_style.add_modifier(Modifier::BOLD);
+    }
+    if style.font_style.contains(FontStyle::ITALIC) {
+        span_style = span_style.add_modifier(Modifier::ITALIC);
+    }
+    span_style
+}
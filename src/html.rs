@@ -0,0 +1,346 @@
+//! Raw HTML in decks.
+//!
+//! Terminals cannot draw HTML, yet decks imported from other tools are full
+//! of it: `<br>` to break lines, `<b>` for emphasis, `<img>` for diagrams.
+//! What becomes of it is set by an [`HtmlPolicy`]. By default it is left out,
+//! as before; it can instead be shown as its text, or have a few common tags
+//! drawn the way their markdown equivalents are.
+//!
+//! HTML that is shown is read as the markdown events it stands for, so that
+//! it is laid out, styled, and searched like the rest of the slide. Comments
+//! are always kept, since they hold the slide's directives.
+
+use pulldown_cmark::{CowStr, Event, LinkType, Tag, TagEnd};
+use serde::Deserialize;
+
+/// What to do with raw HTML other than comments.
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum HtmlPolicy {
+    /// Leave it out, tags and text alike
+    #[default]
+    Strip,
+    /// Show the text between the tags, and the alt text of images
+    Text,
+    /// Also draw `<b>`, `<i>`, `<br>`, and `<img>` like their markdown
+    /// equivalents
+    Styled,
+}
+
+/// A tag of an HTML fragment, or the text between two tags.
+enum Piece<'a> {
+    /// A comment, which may hold a directive
+    Comment(&'a str),
+    /// Text, with its character references decoded
+    Text(String),
+    /// An opening or closing tag
+    Tag {
+        /// Name of the element, in lowercase
+        name: String,
+        /// Whether the tag closes the element
+        closing: bool,
+        /// Everything between the name and the closing `>`
+        attributes: &'a str,
+    },
+}
+
+/// Elements after which text starts on a new line.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "center",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "tr",
+    "ul",
+];
+
+impl HtmlPolicy {
+    /// Looks up a policy by name, as written in a deck's front matter.
+    pub fn named(name: &str) -> Option<Self> {
+        clap::ValueEnum::from_str(name.trim(), true).ok()
+    }
+
+    /// Reads `html` as the markdown events that show it under this policy,
+    /// or returns `None` when there is nothing to read: under `strip`, or
+    /// when `html` holds nothing but comments. A `block` of HTML becomes a
+    /// paragraph of its own, with any styles it opens closed at its end;
+    /// inline HTML arrives a tag at a time, so its styles last until the
+    /// closing tag.
+    pub fn translate(self, html: &str, block: bool) -> Option<Vec<Event<'static>>> {
+        let pieces = pieces(html);
+        if self == HtmlPolicy::Strip || pieces.iter().all(|p| matches!(p, Piece::Comment(_))) {
+            return None;
+        }
+        let styled = self == HtmlPolicy::Styled;
+        let mut events = Vec::new();
+        // Whether nothing has been written on the current line yet, so
+        // leading whitespace is dropped
+        let mut line_start = block;
+        let mut bold = false;
+        let mut italic = false;
+        for piece in pieces {
+            match piece {
+                Piece::Comment(comment) => events.push(Event::Html(comment.to_string().into())),
+                Piece::Text(text) => {
+                    let text = collapse_whitespace(&text);
+                    let text = if line_start { text.trim_start() } else { &text };
+                    if !text.is_empty() {
+                        events.push(Event::Text(text.to_string().into()));
+                        line_start = false;
+                    }
+                }
+                Piece::Tag {
+                    name,
+                    closing,
+                    attributes,
+                } => match name.as_str() {
+                    "br" => {
+                        events.push(Event::HardBreak);
+                        line_start = true;
+                    }
+                    "b" | "strong" if styled => {
+                        bold = !closing;
+                        events.push(if bold {
+                            Event::Start(Tag::Strong)
+                        } else {
+                            Event::End(TagEnd::Strong)
+                        });
+                    }
+                    "i" | "em" if styled => {
+                        italic = !closing;
+                        events.push(if italic {
+                            Event::Start(Tag::Emphasis)
+                        } else {
+                            Event::End(TagEnd::Emphasis)
+                        });
+                    }
+                    "img" => {
+                        let alt = attribute(attributes, "alt").unwrap_or_default();
+                        match attribute(attributes, "src") {
+                            Some(src) if styled => {
+                                events.push(Event::Start(Tag::Image {
+                                    link_type: LinkType::Inline,
+                                    dest_url: src.into(),
+                                    title: CowStr::Borrowed(""),
+                                    id: CowStr::Borrowed(""),
+                                }));
+                                events.push(Event::Text(alt.into()));
+                                events.push(Event::End(TagEnd::Image));
+                            }
+                            _ if !alt.is_empty() => {
+                                events.push(Event::Text(alt.into()));
+                                line_start = false;
+                            }
+                            _ => {}
+                        }
+                    }
+                    name if BLOCK_ELEMENTS.contains(&name) && !line_start => {
+                        events.push(Event::HardBreak);
+                        line_start = true;
+                    }
+                    _ => {}
+                },
+            }
+        }
+        if block {
+            // Styles left open end with the block.
+            if bold {
+                events.push(Event::End(TagEnd::Strong));
+            }
+            if italic {
+                events.push(Event::End(TagEnd::Emphasis));
+            }
+            while matches!(events.last(), Some(Event::HardBreak)) {
+                events.pop();
+            }
+            let shown = events
+                .iter()
+                .any(|event| matches!(event, Event::Text(_) | Event::Start(Tag::Image { .. })));
+            if shown {
+                events.insert(0, Event::Start(Tag::Paragraph));
+                events.push(Event::End(TagEnd::Paragraph));
+            } else {
+                events.retain(|event| matches!(event, Event::Html(_)));
+            }
+        }
+        Some(events)
+    }
+}
+
+/// Splits `html` into comments, tags, and the text between them. A `<` that
+/// starts no tag is taken as text.
+fn pieces(html: &str) -> Vec<Piece<'_>> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map_or(rest.len(), |end| end + "-->".len());
+            flush_text(&mut text, &mut pieces);
+            pieces.push(Piece::Comment(&rest[..end]));
+            rest = &rest[end..];
+            continue;
+        }
+        let inner = &rest[1..];
+        let (closing, inner) = match inner.strip_prefix('/') {
+            Some(inner) => (true, inner),
+            None => (false, inner),
+        };
+        let name_len = inner
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(inner.len());
+        let Some(end) = inner.find('>').filter(|_| name_len > 0) else {
+            text.push('<');
+            rest = &rest[1..];
+            continue;
+        };
+        flush_text(&mut text, &mut pieces);
+        pieces.push(Piece::Tag {
+            name: inner[..name_len].to_ascii_lowercase(),
+            closing,
+            attributes: inner[name_len..end].trim_end_matches('/'),
+        });
+        rest = &inner[end + 1..];
+    }
+    text.push_str(rest);
+    flush_text(&mut text, &mut pieces);
+    pieces
+}
+
+/// Moves the text gathered so far into `pieces`, decoded.
+fn flush_text(text: &mut String, pieces: &mut Vec<Piece<'_>>) {
+    if !text.is_empty() {
+        pieces.push(Piece::Text(decode_entities(text)));
+        text.clear();
+    }
+}
+
+/// Returns the value of the attribute `name` in a tag's `attributes`, such
+/// as `src="cat.png"`, quoted or not.
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes.trim_start();
+    while !rest.is_empty() {
+        let key_len = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = &rest[..key_len];
+        rest = rest[key_len..].trim_start();
+        let value = match rest.strip_prefix('=') {
+            Some(after) => {
+                let after = after.trim_start();
+                let (value, remainder) = match after.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let body = &after[1..];
+                        let end = body.find(quote).unwrap_or(body.len());
+                        (&body[..end], body.get(end + 1..).unwrap_or(""))
+                    }
+                    _ => {
+                        let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                        (&after[..end], &after[end..])
+                    }
+                };
+                rest = remainder.trim_start();
+                value
+            }
+            None => "",
+        };
+        if key.eq_ignore_ascii_case(name) {
+            return Some(decode_entities(value));
+        }
+    }
+    None
+}
+
+/// Replaces every run of whitespace in `text` with a single space, as
+/// browsers do.
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            space = true;
+        } else {
+            if space {
+                collapsed.push(' ');
+                space = false;
+            }
+            collapsed.push(c);
+        }
+    }
+    if space {
+        collapsed.push(' ');
+    }
+    collapsed
+}
+
+/// Decodes the character references in `text`: the common named ones and
+/// numeric ones such as `&#8212;`. Others are left as written.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest[1..]
+            .find(';')
+            .map(|end| end + 1)
+            .filter(|&end| end <= 10);
+        let character = end.and_then(|end| match &rest[1..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            "mdash" => Some('—'),
+            "ndash" => Some('–'),
+            "hellip" => Some('…'),
+            "copy" => Some('©'),
+            name => {
+                let number = name.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (character, end) {
+            (Some(character), Some(end)) => {
+                decoded.push(character);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
@@ -0,0 +1,388 @@
+//! Images referenced from markdown.
+//!
+//! `![alt](path)` is resolved relative to the deck and drawn with half-block
+//! characters: every cell shows two pixels stacked on top of each other, the
+//! upper one as the foreground of `▀` and the lower one as its background.
+//! Images that cannot be shown (missing files, unsupported formats, remote
+//! URLs) become a placeholder box with the alt text, so they never vanish
+//! silently.
+//!
+//! An attribute list right after the image sets its size and alignment:
+//! `![alt](img.png){width=60%, align=center}`.
+//!
+//! A `<!-- background: <path> -->` directive instead draws a dimmed image
+//! behind the whole slide, with the text composited on top.
+
+use image::{Rgba, RgbaImage, imageops::FilterType};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use std::path::{Path, PathBuf};
+use unicode_width::UnicodeWidthStr;
+
+/// Most rows an image takes up.
+const MAX_ROWS: u32 = 20;
+/// Pixels with an alpha value below this are treated as transparent.
+const ALPHA_THRESHOLD: u8 = 128;
+/// Percentage of brightness taken off background images by default.
+const DEFAULT_DIM: u8 = 50;
+
+/// Width requested for an image.
+#[derive(Clone, Copy)]
+pub enum Width {
+    /// A number of columns
+    Columns(u32),
+    /// A percentage of the slide's width
+    Percent(u32),
+}
+
+/// Size and alignment hints from an image's attribute list.
+#[derive(Clone, Copy)]
+pub struct Placement {
+    /// Width to scale the image to
+    pub width: Option<Width>,
+    /// Height to scale the image to, in rows
+    pub height: Option<u32>,
+    /// Horizontal position on the slide
+    pub align: Alignment,
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Placement {
+            width: None,
+            height: None,
+            align: Alignment::Left,
+        }
+    }
+}
+
+impl Placement {
+    /// Parses an attribute list such as `{width=60%, align=center}` at the
+    /// start of `text`, returning it with the text that follows.
+    pub fn parse_prefix(text: &str) -> Option<(Placement, &str)> {
+        let (attributes, rest) = text.strip_prefix('{')?.split_once('}')?;
+        let mut placement = Placement::default();
+        for attribute in attributes.split([',', ' ']).filter(|a| !a.is_empty()) {
+            match attribute.split_once('=')? {
+                ("width", value) => {
+                    placement.width = Some(match value.strip_suffix('%') {
+                        Some(percent) => Width::Percent(percent.parse().ok()?),
+                        None => Width::Columns(value.parse().ok()?),
+                    });
+                }
+                ("height", value) => placement.height = Some(value.parse().ok()?),
+                ("align", "left") => placement.align = Alignment::Left,
+                ("align", "center") => placement.align = Alignment::Center,
+                ("align", "right") => placement.align = Alignment::Right,
+                _ => return None,
+            }
+        }
+        Some((placement, rest))
+    }
+}
+
+/// Renders the image at `src` as slide lines at most `max_width` columns wide.
+/// If the image cannot be drawn, the lines are a placeholder with its alt
+/// text, and the reason is returned with them.
+///
+/// # Arguments
+///
+/// * `src` - Image path from the markdown, relative to `base_dir`
+/// * `alt` - Alt text, shown in the placeholder if the image cannot be drawn
+/// * `base_dir` - Directory of the markdown file
+/// * `max_width` - Width available on the slide, in columns
+/// * `placement` - Size and alignment hints
+pub fn render(
+    src: &str,
+    alt: &str,
+    base_dir: &Path,
+    max_width: usize,
+    placement: &Placement,
+) -> (Vec<Line<'static>>, Option<String>) {
+    let result = if src.contains("://") {
+        Err(format!("{src}: remote images are not supported"))
+    } else {
+        draw(&base_dir.join(src), max_width, placement).map_err(|e| format!("{}: {}", src, e))
+    };
+    let (lines, problem) = match result {
+        Ok(lines) => (lines, None),
+        Err(problem) => {
+            log::warn!("Failed to draw image {problem}");
+            (placeholder(alt, &problem, max_width), Some(problem))
+        }
+    };
+    let lines = lines
+        .into_iter()
+        .map(|line| line.alignment(placement.align))
+        .collect();
+    (lines, problem)
+}
+
+/// Loads the image at `path` and draws it with half blocks, at most
+/// `max_width` columns wide.
+pub fn draw(
+    path: &Path,
+    max_width: usize,
+    placement: &Placement,
+) -> image::ImageResult<Vec<Line<'static>>> {
+    Ok(half_blocks(
+        &image::open(path)?.to_rgba8(),
+        max_width,
+        placement,
+    ))
+}
+
+/// Scales `image` to the requested size, or to fit `max_width` columns and
+/// `MAX_ROWS` rows, and draws it with half blocks.
+fn half_blocks(image: &RgbaImage, max_width: usize, placement: &Placement) -> Vec<Line<'static>> {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 || max_width == 0 {
+        return Vec::new();
+    }
+    // One column per pixel horizontally and two pixels per row vertically
+    // keeps the aspect ratio on typical terminal fonts.
+    let max_width = max_width as f64;
+    let fit_width = match placement.width {
+        Some(Width::Columns(cols)) => Some(f64::from(cols).min(max_width)),
+        Some(Width::Percent(percent)) => Some(max_width * f64::from(percent.min(100)) / 100.0),
+        None => None,
+    };
+    let fit_height = placement.height.map(|rows| f64::from(rows * 2));
+    // Requested sizes may enlarge the image; otherwise it only shrinks to fit.
+    let scale = match (fit_width, fit_height) {
+        (None, None) => (max_width / width as f64)
+            .min(f64::from(MAX_ROWS * 2) / height as f64)
+            .min(1.0),
+        (Some(fit_width), None) => fit_width / width as f64,
+        (None, Some(fit_height)) => (fit_height / height as f64).min(max_width / width as f64),
+        (Some(fit_width), Some(fit_height)) => {
+            (fit_width / width as f64).min(fit_height / height as f64)
+        }
+    };
+    let cols = ((width as f64 * scale).round() as u32).max(1);
+    let rows = ((height as f64 * scale / 2.0).ceil() as u32).max(1);
+    let scaled = image::imageops::resize(image, cols, rows * 2, FilterType::Triangle);
+
+    (0..rows)
+        .map(|row| {
+            let spans = (0..cols)
+                .map(|col| {
+                    let top = color(scaled.get_pixel(col, row * 2));
+                    let bottom = color(scaled.get_pixel(col, row * 2 + 1));
+                    match (top, bottom) {
+                        (Some(top), Some(bottom)) => {
+                            Span::styled("▀", Style::default().fg(top).bg(bottom))
+                        }
+                        (Some(top), None) => Span::styled("▀", Style::default().fg(top)),
+                        (None, Some(bottom)) => Span::styled("▄", Style::default().fg(bottom)),
+                        (None, None) => Span::raw(" "),
+                    }
+                })
+                .collect::<Vec<_>>();
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// An image drawn behind a slide's text.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Background {
+    /// Location of the image file
+    pub path: PathBuf,
+    /// Percentage of brightness taken off the image
+    pub dim: u8,
+}
+
+impl Background {
+    /// Parses the value of a `background` directive: an optional `dim=<n>%`
+    /// followed by the image path relative to `base_dir`, e.g.
+    /// `dim=70% images/title.png`.
+    pub fn parse(value: &str, base_dir: &Path) -> Option<Self> {
+        let (dim, path) = match value.split_once(char::is_whitespace) {
+            Some((first, rest)) if first.starts_with("dim=") => {
+                let percent = first["dim=".len()..].trim_end_matches('%');
+                (percent.parse().ok()?, rest.trim())
+            }
+            _ => (DEFAULT_DIM, value.trim()),
+        };
+        (!path.is_empty()).then(|| Background {
+            path: base_dir.join(path),
+            dim: dim.min(100),
+        })
+    }
+}
+
+/// A background image scaled to cover an area of the screen.
+pub struct Backdrop {
+    /// Image the backdrop was made from
+    background: Background,
+    /// Size of the covered area as `(cols, rows)`
+    size: (u16, u16),
+    /// Upper and lower pixel of each cell, row by row; empty if the image
+    /// could not be loaded
+    cells: Vec<(Color, Color)>,
+}
+
+impl Backdrop {
+    /// Loads `background` and scales it to cover `cols` by `rows` cells,
+    /// cropping whatever does not fit.
+    pub fn load(background: &Background, cols: u16, rows: u16) -> Self {
+        let cells = match image::open(&background.path) {
+            Ok(image) if cols > 0 && rows > 0 => {
+                let scaled = image
+                    .resize_to_fill(u32::from(cols), u32::from(rows) * 2, FilterType::Triangle)
+                    .to_rgba8();
+                let brightness = f64::from(100 - background.dim) / 100.0;
+                let dimmed = |pixel: &Rgba<u8>| {
+                    let [r, g, b, a] = pixel.0;
+                    let scale = |c: u8| (f64::from(c) * f64::from(a) / 255.0 * brightness) as u8;
+                    Color::Rgb(scale(r), scale(g), scale(b))
+                };
+                (0..u32::from(rows))
+                    .flat_map(|row| (0..u32::from(cols)).map(move |col| (row, col)))
+                    .map(|(row, col)| {
+                        (
+                            dimmed(scaled.get_pixel(col, row * 2)),
+                            dimmed(scaled.get_pixel(col, row * 2 + 1)),
+                        )
+                    })
+                    .collect()
+            }
+            Ok(_) => Vec::new(),
+            Err(e) => {
+                log::warn!(
+                    "Failed to load background '{}': {}",
+                    background.path.display(),
+                    e
+                );
+                Vec::new()
+            }
+        };
+        Backdrop {
+            background: background.clone(),
+            size: (cols, rows),
+            cells,
+        }
+    }
+
+    /// Returns true if the backdrop shows `background` at the given size.
+    pub fn fits(&self, background: &Background, cols: u16, rows: u16) -> bool {
+        self.background == *background && self.size == (cols, rows)
+    }
+
+    /// Size of the area the backdrop covers as `(cols, rows)`, if it was made
+    /// from `background`.
+    pub fn covers(&self, background: &Background) -> Option<(u16, u16)> {
+        (self.background == *background).then_some(self.size)
+    }
+
+    /// Returns true if the image could not be loaded, so nothing is drawn.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Draws the image behind the text already rendered into `area` of `buf`.
+    ///
+    /// Blank cells show the image; cells holding text keep their character
+    /// and color and take on the image's color as their background. Cells
+    /// with a background of their own, such as code, are left alone.
+    pub fn composite(&self, area: Rect, buf: &mut Buffer) {
+        if self.cells.is_empty() {
+            return;
+        }
+        let (cols, rows) = self.size;
+        for row in 0..rows.min(area.height) {
+            for col in 0..cols.min(area.width) {
+                let (top, bottom) =
+                    self.cells[usize::from(row) * usize::from(cols) + usize::from(col)];
+                let Some(cell) = buf.cell_mut((area.x + col, area.y + row)) else {
+                    continue;
+                };
+                if cell.bg != Color::Reset {
+                    continue;
+                }
+                if cell.symbol() == " " {
+                    cell.set_symbol("▀").set_fg(top).set_bg(bottom);
+                } else {
+                    cell.set_bg(mix(top, bottom));
+                }
+            }
+        }
+    }
+}
+
+/// Average of two RGB colors.
+fn mix(a: Color, b: Color) -> Color {
+    match (a, b) {
+        (Color::Rgb(r1, g1, b1), Color::Rgb(r2, g2, b2)) => Color::Rgb(
+            ((u16::from(r1) + u16::from(r2)) / 2) as u8,
+            ((u16::from(g1) + u16::from(g2)) / 2) as u8,
+            ((u16::from(b1) + u16::from(b2)) / 2) as u8,
+        ),
+        _ => a,
+    }
+}
+
+/// Terminal color of a pixel, or `None` if it is transparent.
+fn color(pixel: &Rgba<u8>) -> Option<Color> {
+    let [r, g, b, a] = pixel.0;
+    (a >= ALPHA_THRESHOLD).then_some(Color::Rgb(r, g, b))
+}
+
+/// A box standing in for an image that cannot be drawn, showing its alt
+/// text and the reason.
+fn placeholder(alt: &str, reason: &str, max_width: usize) -> Vec<Line<'static>> {
+    let border = Style::default().fg(Color::DarkGray);
+    let alt = if alt.trim().is_empty() {
+        "image"
+    } else {
+        alt.trim()
+    };
+    let rows = [
+        (format!("▣ {alt}"), Style::default()),
+        (reason.to_string(), Style::default().fg(Color::DarkGray)),
+    ];
+    let inner = rows
+        .iter()
+        .map(|(text, _)| text.width())
+        .max()
+        .unwrap_or(0)
+        .min(max_width.saturating_sub(4));
+
+    let mut lines = vec![Line::styled(format!("┌{}┐", "─".repeat(inner + 2)), border)];
+    for (text, style) in rows {
+        let text = truncate(&text, inner);
+        let padding = inner - text.width();
+        lines.push(Line::from(vec![
+            Span::styled("│ ", border),
+            Span::styled(text, style),
+            Span::raw(" ".repeat(padding)),
+            Span::styled(" │", border),
+        ]));
+    }
+    lines.push(Line::styled(format!("└{}┘", "─".repeat(inner + 2)), border));
+    lines
+}
+
+/// Cuts `text` to at most `width` columns, ending it with `…` if shortened.
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut out = String::new();
+    for c in text.chars() {
+        if out.width() + c.to_string().width() + 1 > width {
+            break;
+        }
+        out.push(c);
+    }
+    out.push('…');
+    out
+}
@@ -0,0 +1,144 @@
+//! Keys bound to actions in the config file.
+//!
+//! The `[keys]` section of the config file gives actions keys of their own,
+//! in addition to their built-in ones, such as the buttons of a presentation
+//! clicker:
+//!
+//! ```toml
+//! [keys]
+//! next = ["Enter", "x"]
+//! previous = ["Backspace"]
+//! ```
+//!
+//! A bound key is pressed as if it were the action's built-in key, so it
+//! does what that key does wherever it is pressed, in place of whatever it
+//! did before. Keys are single characters or names such as `Enter`, `Tab`,
+//! `PageDown`, or `F5`.
+
+use crate::term::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Actions that can be bound, with the built-in key each is pressed as.
+const ACTIONS: &[(&str, KeyCode)] = &[
+    ("next", KeyCode::PageDown),
+    ("previous", KeyCode::PageUp),
+    ("scroll-down", KeyCode::Char('j')),
+    ("scroll-up", KeyCode::Char('k')),
+    ("first", KeyCode::Home),
+    ("last", KeyCode::End),
+    ("next-section", KeyCode::Char(']')),
+    ("previous-section", KeyCode::Char('[')),
+    ("search", KeyCode::Char('/')),
+    ("command", KeyCode::Char(':')),
+    ("notes", KeyCode::Char('n')),
+    ("footnotes", KeyCode::Char('f')),
+    ("links", KeyCode::Char('o')),
+    ("overview", KeyCode::Char('g')),
+    ("inspect", KeyCode::Char('i')),
+    ("sidebar", KeyCode::Char('b')),
+    ("filmstrip", KeyCode::Char('v')),
+    ("next-theme", KeyCode::Char('c')),
+    ("previous-theme", KeyCode::Char('C')),
+    ("spoilers", KeyCode::Char('r')),
+    ("screenshot", KeyCode::Char('s')),
+    ("copy", KeyCode::Char('y')),
+    ("copy-source", KeyCode::Char('Y')),
+    ("questions", KeyCode::Char('a')),
+    ("remote", KeyCode::Char('R')),
+    ("annotate", KeyCode::Char('A')),
+    ("terminal", KeyCode::Char('t')),
+    ("run", KeyCode::Char('e')),
+    ("quit", KeyCode::Char('q')),
+];
+
+/// Keys bound to actions in the config file.
+#[derive(Clone, Default, Deserialize)]
+#[serde(try_from = "HashMap<String, Vec<String>>")]
+pub struct KeyMap {
+    /// Each bound key with the built-in key it is pressed as
+    bindings: Vec<(KeyCode, KeyCode)>,
+}
+
+impl TryFrom<HashMap<String, Vec<String>>> for KeyMap {
+    type Error = String;
+
+    fn try_from(keys: HashMap<String, Vec<String>>) -> Result<Self, Self::Error> {
+        // In a set order, so that the same file always fails the same way.
+        let mut keys: Vec<_> = keys.into_iter().collect();
+        keys.sort();
+        let mut bound: Vec<(KeyCode, String)> = Vec::new();
+        let mut bindings = Vec::new();
+        for (action, names) in keys {
+            let Some(&(_, builtin)) = ACTIONS.iter().find(|(name, _)| *name == action) else {
+                let actions: Vec<&str> = ACTIONS.iter().map(|(name, _)| *name).collect();
+                return Err(format!(
+                    "unknown action '{action}' (try one of {})",
+                    actions.join(", ")
+                ));
+            };
+            for name in names {
+                let key = parse_key(&name).ok_or_else(|| format!("unknown key '{name}'"))?;
+                if let Some((_, other)) = bound.iter().find(|(code, _)| *code == key)
+                    && *other != action
+                {
+                    return Err(format!(
+                        "key '{name}' is bound to both '{other}' and '{action}'"
+                    ));
+                }
+                bound.push((key, action.clone()));
+                bindings.push((key, builtin));
+            }
+        }
+        Ok(KeyMap { bindings })
+    }
+}
+
+impl KeyMap {
+    /// `key` as the built-in key of the action it is bound to, or as it was
+    /// pressed if it is bound to none. Keys held with Ctrl or Alt are never
+    /// bound.
+    pub fn translate(&self, key: KeyEvent) -> KeyEvent {
+        if key.modifiers.contains(KeyModifiers::CONTROL)
+            || key.modifiers.contains(KeyModifiers::ALT)
+        {
+            return key;
+        }
+        match self.bindings.iter().find(|(bound, _)| *bound == key.code) {
+            Some(&(_, builtin)) => KeyEvent {
+                code: builtin,
+                ..key
+            },
+            None => key,
+        }
+    }
+}
+
+/// The key named `name`: a single character, or the name of a key such as
+/// `Enter` or `F5`, in any case.
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    let key = match name.to_ascii_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "backspace" => KeyCode::Backspace,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "insert" => KeyCode::Insert,
+        "delete" => KeyCode::Delete,
+        other => KeyCode::F(other.strip_prefix('f')?.parse().ok()?),
+    };
+    Some(key)
+}
@@ -0,0 +1,106 @@
+//! Keywords emphasized wherever they appear on a slide.
+//!
+//! The config file can give words a color of their own, so that markers
+//! such as `TODO` or a product name stand out without formatting every
+//! occurrence by hand:
+//!
+//! ```toml
+//! [keywords]
+//! TODO = "red"
+//! DEPRECATED = "yellow"
+//! Presentrs = "#ff8800"
+//! ```
+//!
+//! Once a slide is laid out, every whole-word, case-sensitive occurrence of
+//! a keyword in its text is drawn bold in the keyword's color, whatever
+//! style the text around it has.
+
+use ratatui::{
+    style::{Color, Modifier},
+    text::{Line, Span, Text},
+};
+use serde::{Deserialize, Deserializer, de::Error as _};
+use std::{collections::HashMap, str::FromStr};
+
+/// Color of a keyword, from the config file.
+#[derive(Clone, Copy)]
+pub struct Emphasis(Color);
+
+impl<'de> Deserialize<'de> for Emphasis {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Color::from_str(&name)
+            .map(Emphasis)
+            .map_err(|_| D::Error::custom(format!("unknown color '{name}'")))
+    }
+}
+
+/// Keywords from the config file and their colors.
+#[derive(Default)]
+pub struct Keywords(Vec<(String, Color)>);
+
+impl Keywords {
+    /// Collects the keywords of the config file, longest first so that a
+    /// keyword containing another one wins.
+    pub fn new(configured: HashMap<String, Emphasis>) -> Self {
+        let mut keywords: Vec<(String, Color)> = configured
+            .into_iter()
+            .filter(|(word, _)| !word.is_empty())
+            .map(|(word, Emphasis(color))| (word, color))
+            .collect();
+        keywords.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        Keywords(keywords)
+    }
+
+    /// Emphasizes the keywords in every line of `text`.
+    pub fn apply(&self, text: &mut Text<'static>) {
+        if self.0.is_empty() {
+            return;
+        }
+        for line in &mut text.lines {
+            self.apply_line(line);
+        }
+    }
+
+    /// Splits the spans of `line` around the keywords in them.
+    fn apply_line(&self, line: &mut Line<'static>) {
+        let mut spans = Vec::with_capacity(line.spans.len());
+        for span in line.spans.drain(..) {
+            let content = span.content.as_ref();
+            let mut from = 0;
+            let mut at = 0;
+            while at < content.len() {
+                let found = self.0.iter().find(|(word, _)| {
+                    content[at..].starts_with(word.as_str())
+                        && is_boundary(content[..at].chars().next_back())
+                        && is_boundary(content[at + word.len()..].chars().next())
+                });
+                match found {
+                    Some((word, color)) => {
+                        if from < at {
+                            spans.push(Span::styled(content[from..at].to_string(), span.style));
+                        }
+                        let style = span.style.fg(*color).add_modifier(Modifier::BOLD);
+                        spans.push(Span::styled(word.clone(), style));
+                        at += word.len();
+                        from = at;
+                    }
+                    None => {
+                        at += content[at..].chars().next().map_or(1, char::len_utf8);
+                    }
+                }
+            }
+            if from == 0 {
+                spans.push(span);
+            } else if from < content.len() {
+                spans.push(Span::styled(content[from..].to_string(), span.style));
+            }
+        }
+        line.spans = spans;
+    }
+}
+
+/// Whether `c`, the character next to a possible keyword, ends the word.
+fn is_boundary(c: Option<char>) -> bool {
+    c.is_none_or(|c| !c.is_alphanumeric() && c != '_')
+}
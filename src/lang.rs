@@ -0,0 +1,128 @@
+//! Decks in more than one language.
+//!
+//! One deck can serve audiences in several languages. A `lang` directive on
+//! a line of its own marks what follows as written in a language:
+//!
+//! ```markdown
+//! # Ownership
+//! <!-- lang: en -->
+//! Every value has one owner.
+//! <!-- lang: de -->
+//! Jeder Wert hat genau einen Besitzer.
+//! ```
+//!
+//! The marked content runs until the next `lang` directive, the next slide
+//! (at a heading or the deck's separator), or `<!-- lang: all -->`, after which content is shared again. A directive
+//! just above a slide's heading marks the whole slide, heading included.
+//! `--lang de` (or `lang: de` in the front matter) presents the content in
+//! German and the shared content; without either, everything is shown.
+//!
+//! Content in other languages is blanked out of the markdown rather than
+//! removed, so that every line and offset stays where it was in the file.
+
+use crate::separator::Separator;
+
+/// Value of a `lang` directive that ends marked content.
+const SHARED: &str = "all";
+
+/// Languages a `lang` directive line marks what follows as, or `None` if
+/// `line` is not one. An empty list ends marked content.
+fn directive(line: &str) -> Option<Vec<String>> {
+    let inner = line.trim().strip_prefix("<!--")?.strip_suffix("-->")?;
+    let (key, value) = inner.split_once(':')?;
+    if key.trim() != "lang" {
+        return None;
+    }
+    Some(
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|lang| !lang.is_empty() && *lang != SHARED)
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Whether `line` opens or closes a fenced code block.
+fn is_fence(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("```") || line.starts_with("~~~")
+}
+
+/// Splits `markdown`, whose slides are separated by `separator`, into its
+/// lines, each with the languages it is marked as, empty for shared content.
+/// Separator lines are always shared.
+fn marked_lines<'a>(markdown: &'a str, separator: &Separator) -> Vec<(&'a str, Vec<String>)> {
+    let mut lines = Vec::new();
+    let mut marked: Vec<String> = Vec::new();
+    let mut in_fence = false;
+    // Whether a directive came after the last content, so that it marks the
+    // slide a heading below it starts
+    let mut awaiting_content = false;
+    let mut after_blank = true;
+    for line in markdown.split_inclusive('\n') {
+        let blank = line.trim().is_empty();
+        let is_break = !in_fence && !is_fence(line) && separator.is_break(line, after_blank);
+        after_blank = blank;
+        if is_fence(line) {
+            in_fence = !in_fence;
+        }
+        if is_break {
+            marked.clear();
+            awaiting_content = false;
+            lines.push((line, Vec::new()));
+            continue;
+        }
+        if !in_fence && let Some(langs) = directive(line) {
+            marked = langs;
+            awaiting_content = true;
+            lines.push((line, Vec::new()));
+            continue;
+        }
+        if !in_fence && !blank {
+            if *separator == Separator::Heading && line.starts_with("# ") && !awaiting_content {
+                marked.clear();
+            }
+            awaiting_content = false;
+        }
+        lines.push((line, marked.clone()));
+    }
+    lines
+}
+
+/// Returns `markdown`, whose slides are separated by `separator`, with the
+/// content marked as in another language than `lang` replaced by spaces.
+pub fn select(markdown: &str, lang: &str, separator: &Separator) -> String {
+    let mut selected = String::with_capacity(markdown.len());
+    for (line, langs) in marked_lines(markdown, separator) {
+        if langs.is_empty() || langs.iter().any(|l| l.eq_ignore_ascii_case(lang)) {
+            selected.push_str(line);
+        } else {
+            // Blanked byte for byte, so that offsets stay the same.
+            let content = line.trim_end_matches(['\n', '\r']);
+            selected.extend(std::iter::repeat_n(' ', content.len()));
+            selected.push_str(&line[content.len()..]);
+        }
+    }
+    selected
+}
+
+/// Languages the deck's `lang` directives name, in order of first use.
+pub fn languages(markdown: &str) -> Vec<String> {
+    let mut languages: Vec<String> = Vec::new();
+    let mut in_fence = false;
+    for line in markdown.lines() {
+        if is_fence(line) {
+            in_fence = !in_fence;
+        }
+        let Some(langs) = directive(line).filter(|_| !in_fence) else {
+            continue;
+        };
+        for lang in langs {
+            if !languages.iter().any(|l| l.eq_ignore_ascii_case(&lang)) {
+                languages.push(lang);
+            }
+        }
+    }
+    languages
+}
@@ -0,0 +1,269 @@
+//! Named slide layouts.
+//!
+//! A `<!-- layout: quote -->` directive draws a slide with the alignment,
+//! sizing, and style of a named layout instead of the default, so content
+//! does not have to be arranged by hand. Four layouts are built in, and the
+//! config file can change them or add more:
+//!
+//! ```toml
+//! [layouts.quote]
+//! align = "center"
+//! vertical = "middle"
+//! width = 70
+//! italic = true
+//! color = "gray"
+//! ```
+//!
+//! A layout in the config file replaces a built-in one of the same name.
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Layout as Split, Rect},
+    style::{Color, Modifier},
+    text::{Line, Text},
+    widgets::{Paragraph, Widget, Wrap},
+};
+use serde::{Deserialize, Deserializer, de::Error as _};
+use std::{collections::HashMap, str::FromStr};
+
+/// Columns left blank between neighbouring text columns.
+const COLUMN_GAP: u16 = 3;
+
+/// Horizontal alignment of a layout's content.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Vertical position of a layout's content on the slide.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Vertical {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// A named layout, from the `[layouts.<name>]` tables of the config file.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Layout {
+    /// How each line is aligned
+    align: Align,
+    /// Where the content sits vertically
+    vertical: Vertical,
+    /// Share of the slide's width the content takes, in percent, centered
+    width: Option<u16>,
+    /// Number of columns the content flows through, below the title
+    columns: Option<u16>,
+    /// Color of text that does not set its own
+    #[serde(deserialize_with = "color")]
+    color: Option<Color>,
+    /// Whether text is drawn in bold
+    bold: bool,
+    /// Whether text is drawn in italics
+    italic: bool,
+}
+
+/// Reads a color name such as `gray` or a hex code such as `#ff8800`.
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Color>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    Color::from_str(&name)
+        .map(Some)
+        .map_err(|_| D::Error::custom(format!("unknown color '{name}'")))
+}
+
+impl Layout {
+    /// Draws `text` into `area` the way the layout arranges it.
+    ///
+    /// `title` says whether `text` starts with the slide's title, which
+    /// spans every column.
+    pub fn render(&self, text: Text<'static>, title: bool, area: Rect, buf: &mut Buffer) {
+        let area = match self.width {
+            Some(percent) => {
+                let width = area.width * percent.min(100) / 100;
+                Rect {
+                    x: area.x + (area.width - width) / 2,
+                    width,
+                    ..area
+                }
+            }
+            None => area,
+        };
+        let mut modifier = Modifier::empty();
+        if self.bold {
+            modifier |= Modifier::BOLD;
+        }
+        if self.italic {
+            modifier |= Modifier::ITALIC;
+        }
+        let mut lines: Vec<Line<'static>> = text
+            .lines
+            .into_iter()
+            .map(|mut line| {
+                for span in &mut line.spans {
+                    if let Some(color) = self.color
+                        && matches!(span.style.fg, None | Some(Color::White))
+                    {
+                        span.style = span.style.fg(color);
+                    }
+                    span.style = span.style.add_modifier(modifier);
+                }
+                line
+            })
+            .collect();
+        // Spacing after the last paragraph would push the content off center.
+        while lines.last().is_some_and(|line| line.width() == 0) {
+            lines.pop();
+        }
+        let alignment = match self.align {
+            Align::Left => Alignment::Left,
+            Align::Center => Alignment::Center,
+            Align::Right => Alignment::Right,
+        };
+        let paragraph = |lines: &[Line<'static>]| {
+            Paragraph::new(lines.to_vec())
+                .alignment(alignment)
+                .wrap(Wrap { trim: false })
+        };
+
+        let columns = usize::from(self.columns.unwrap_or(1).max(1));
+        // The title, and the space below it, span every column.
+        let header = if title && columns > 1 {
+            1 + usize::from(lines.get(1).is_some_and(|line| line.width() == 0))
+        } else {
+            0
+        };
+        let (header, mut body) = lines.split_at(header.min(lines.len()));
+        while let Some((first, rest)) = body.split_first()
+            && first.width() == 0
+        {
+            body = rest;
+        }
+        let header_height = height(&paragraph(header), area.width);
+        let body_width =
+            area.width.saturating_sub(COLUMN_GAP * (columns as u16 - 1)) / columns as u16;
+        let chunks = balance(body, columns, |line| {
+            height(&paragraph(std::slice::from_ref(line)), body_width)
+        });
+        let body_height = chunks
+            .iter()
+            .map(|chunk| height(&paragraph(chunk), body_width))
+            .max()
+            .unwrap_or(0);
+        let total = header_height.saturating_add(body_height).min(area.height);
+        let top = match self.vertical {
+            Vertical::Top => area.y,
+            Vertical::Middle => area.y + (area.height - total) / 2,
+            Vertical::Bottom => area.bottom() - total,
+        };
+        let area = Rect {
+            y: top,
+            height: area.bottom() - top,
+            ..area
+        };
+        let [header_area, body_area] =
+            Split::vertical([Constraint::Length(header_height), Constraint::Min(0)]).areas(area);
+        paragraph(header).render(header_area, buf);
+        let column_areas = Split::horizontal(vec![Constraint::Fill(1); columns])
+            .spacing(COLUMN_GAP)
+            .split(body_area);
+        for (column, chunk) in column_areas.iter().zip(chunks) {
+            paragraph(chunk).render(*column, buf);
+        }
+    }
+}
+
+/// Rows `paragraph` takes up when wrapped to `width` columns.
+fn height(paragraph: &Paragraph, width: u16) -> u16 {
+    u16::try_from(paragraph.line_count(width)).unwrap_or(u16::MAX)
+}
+
+/// Splits `lines` into `columns` runs of about the same height, without
+/// breaking any line.
+fn balance<'a>(
+    lines: &'a [Line<'static>],
+    columns: usize,
+    height: impl Fn(&Line<'static>) -> u16,
+) -> Vec<&'a [Line<'static>]> {
+    let heights: Vec<u16> = lines.iter().map(height).collect();
+    let total: u32 = heights.iter().map(|&h| u32::from(h)).sum();
+    let target = total.div_ceil(columns as u32);
+    let mut chunks = Vec::with_capacity(columns);
+    let mut start = 0;
+    let mut filled = 0;
+    for (i, &h) in heights.iter().enumerate() {
+        if filled > 0 && filled + u32::from(h) > target && chunks.len() + 1 < columns {
+            chunks.push(&lines[start..i]);
+            start = i;
+            filled = 0;
+        }
+        filled += u32::from(h);
+    }
+    chunks.push(&lines[start..]);
+    chunks
+}
+
+/// Layouts available to decks: the built-in ones, as changed or added to by
+/// the config file.
+pub struct Layouts(HashMap<String, Layout>);
+
+impl Layouts {
+    /// Combines the built-in layouts with those from the config file.
+    pub fn new(configured: HashMap<String, Layout>) -> Self {
+        let mut layouts = built_in();
+        layouts.extend(configured);
+        Layouts(layouts)
+    }
+
+    /// Returns the layout called `name`, if there is one.
+    pub fn get(&self, name: &str) -> Option<&Layout> {
+        self.0.get(name)
+    }
+}
+
+impl Default for Layouts {
+    fn default() -> Self {
+        Layouts(built_in())
+    }
+}
+
+/// Layouts every deck can use without any configuration.
+fn built_in() -> HashMap<String, Layout> {
+    let centered = Layout {
+        align: Align::Center,
+        vertical: Vertical::Middle,
+        ..Layout::default()
+    };
+    HashMap::from([
+        ("title".to_string(), centered.clone()),
+        (
+            "section-break".to_string(),
+            Layout {
+                color: Some(Color::Yellow),
+                bold: true,
+                ..centered.clone()
+            },
+        ),
+        (
+            "two-column".to_string(),
+            Layout {
+                columns: Some(2),
+                ..Layout::default()
+            },
+        ),
+        (
+            "quote".to_string(),
+            Layout {
+                width: Some(70),
+                italic: true,
+                ..centered
+            },
+        ),
+    ])
+}
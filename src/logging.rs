@@ -0,0 +1,146 @@
+//! Logging to a file, since the terminal belongs to the presentation.
+//!
+//! With `--log-file <FILE>`, what happens behind the slides is written to
+//! `FILE`: warnings about the deck, files being watched, exec blocks starting
+//! and what they print, and connections from consoles, viewers, and phones.
+//! How much is written is set with `--log-level`, or else the `PRESENTRS_LOG`
+//! or `RUST_LOG` environment variable, in the same syntax as `RUST_LOG`: a
+//! level (`warn`), levels for modules (`presentrs::server=trace`), or both,
+//! separated by commas. It is `info` by default.
+//!
+//! Each line starts with the seconds since presentrs started, the level, and
+//! the module that wrote it, so `tail -f` in another terminal follows along.
+
+use log::{LevelFilter, Log, Metadata, Record};
+use std::{
+    env,
+    error::Error,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::Mutex,
+    time::Instant,
+};
+
+/// Level used when no filter is given.
+const DEFAULT_FILTER: &str = "info";
+
+/// Environment variables read for a filter, in order, when `--log-level` is
+/// not given.
+const FILTER_VARIABLES: [&str; 2] = ["PRESENTRS_LOG", "RUST_LOG"];
+
+/// Which records are written: the most verbose level for each module, and for
+/// everything else.
+struct Filter {
+    /// Level for modules no directive names
+    default: LevelFilter,
+    /// Module path prefixes with their levels, longest first
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    /// Parses a `RUST_LOG`-style filter, e.g. `warn,presentrs::exec=debug`.
+    fn parse(spec: &str) -> Result<Self, String> {
+        let mut filter = Filter {
+            default: LevelFilter::Error,
+            modules: Vec::new(),
+        };
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let level = |name: &str| {
+                name.parse::<LevelFilter>()
+                    .map_err(|_| format!("Unknown log level '{name}' in '{spec}'"))
+            };
+            match directive.split_once('=') {
+                Some((module, name)) => filter.modules.push((module.to_string(), level(name)?)),
+                // A bare module name turns on everything it logs.
+                None => match level(directive) {
+                    Ok(level) => filter.default = level,
+                    Err(_) => filter
+                        .modules
+                        .push((directive.to_string(), LevelFilter::Trace)),
+                },
+            }
+        }
+        filter
+            .modules
+            .sort_by_key(|(module, _)| std::cmp::Reverse(module.len()));
+        Ok(filter)
+    }
+
+    /// The most verbose level written for records from `target`.
+    fn level(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .find(|(module, _)| target == module || target.starts_with(&format!("{module}::")))
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    /// The most verbose level written for any module.
+    fn max(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+/// Writes the records the filter lets through to the log file.
+struct FileLogger {
+    file: Mutex<File>,
+    filter: Filter,
+    /// When presentrs started, which lines are timed from
+    start: Instant,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.filter.level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if let Ok(mut file) = self.file.lock() {
+            // A log that cannot be written must not stop the presentation.
+            let _ = writeln!(
+                file,
+                "{:>9.3} {:<5} {}: {}",
+                self.start.elapsed().as_secs_f64(),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Starts appending log records to `path`, filtered by `spec` or else the
+/// environment.
+pub fn init(path: &Path, spec: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let spec = spec.map(str::to_string).unwrap_or_else(|| {
+        FILTER_VARIABLES
+            .iter()
+            .find_map(|name| env::var(name).ok())
+            .unwrap_or_else(|| DEFAULT_FILTER.to_string())
+    });
+    let filter = Filter::parse(&spec)?;
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open log file '{}': {}", path.display(), e))?;
+    log::set_max_level(filter.max());
+    log::set_boxed_logger(Box::new(FileLogger {
+        file: Mutex::new(file),
+        filter,
+        start: Instant::now(),
+    }))?;
+    Ok(())
+}
@@ -14,8 +14,8 @@
 //!
 //! ## Keyboard Controls
 //!
-//! - `→`, `l`, `Space`: Next slide
-//! - `←`, `h`: Previous slide
+//! - `→`, `l`, `Space`: Next fragment or slide
+//! - `←`, `h`: Previous fragment or slide
 //! - `↑`, `k`: Scroll up within slide
 //! - `↓`, `j`: Scroll down within slide
 //! - `q`, `Esc`: Quit
@@ -30,124 +30,1509 @@
 //! - Inline code (`code`)
 //! - Code blocks (```code```)
 
-use clap::Parser;
-use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
-    execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
-};
+mod animation;
+mod annotations;
+mod base16;
+mod bench;
+mod broadcast;
+mod check;
+mod chrome;
+mod config;
+mod density;
+mod console;
+mod exec;
+mod export;
+mod flashcards;
+mod fragments;
+mod frontmatter;
+mod graphics;
+mod highlight;
+mod html;
+mod images;
+mod keys;
+mod keywords;
+mod lang;
+mod layouts;
+mod logging;
+mod notes;
+mod pane;
+mod picker;
+mod pptx;
+mod record;
+mod regions;
+mod scripts;
+mod scaffold;
+mod search;
+mod separator;
+mod server;
+mod session;
+mod spelling;
+mod split;
+mod spoilers;
+mod state;
+mod tags;
+mod term;
+mod themes;
+mod timeline;
+mod timing;
+#[cfg(feature = "tree-sitter")]
+mod treesitter;
+mod typing;
+mod zoom;
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use clap::{Parser, Subcommand};
+use animation::Animation;
+use annotations::Annotations;
+use bench::SlideTimes;
+use broadcast::Broadcast;
+use check::{Finding, Format, Report, Size, SlideInfo};
+use chrome::{Corner, Logo, Watermark};
+use frontmatter::FrontMatter;
+use highlight::{Backend, Highlighter, span_style};
+use html::HtmlPolicy;
+use config::Config;
+use density::{Density, Element};
+use console::{ConsoleEvent, ConsoleLink, ConsoleState, TmuxLayout};
+use exec::{ExecBlock, ExecPolicy, Execution, Runner};
+use export::Rendered;
+use flashcards::Flashcards;
+use fragments::{FragmentStyle, Steps, Visibility};
+use graphics::Painter;
+use images::{Backdrop, Background, Placement};
+use keys::KeyMap;
+use keywords::Keywords;
+use layouts::Layouts;
+use notes::CompanionNotes;
+use pane::{TerminalPane, TerminalRegion};
+use picker::{Candidate, Picker};
+use regions::Region;
+use search::{Match, Search};
+use separator::Separator;
+use themes::{Theme, Themes};
 use pulldown_cmark::{
-    Event as MarkdownEvent, HeadingLevel, Options, Parser as MarkdownParser, Tag, TagEnd,
+    CowStr, Event as MarkdownEvent, HeadingLevel, Options, Parser as MarkdownParser, Tag, TagEnd,
 };
+use qrcode::{EcLevel, QrCode};
 use ratatui::{
-    Frame, Terminal,
-    backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
+    Frame, Terminal, TerminalOptions, Viewport,
+    backend::TestBackend,
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Position, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Padding, Paragraph, Wrap},
 };
+use record::Recorder;
+use serde::{Deserialize, Serialize};
+use server::{RemoteState, Server, ServerEvent};
+use session::{Script, SessionLog};
+use spelling::Dictionary;
+use tags::TagFilter;
+use term::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind};
 use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet, VecDeque},
     error::Error,
     fs,
-    io::{self, Stdout},
+    io::{self, Write},
+    ops::Range,
+    process::{self, Stdio},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant, SystemTime},
 };
 use syntect::{
-    easy::HighlightLines, highlighting::ThemeSet, parsing::SyntaxSet, util::LinesWithEndings,
+    easy::HighlightLines,
+    highlighting::{Theme as CodeTheme, ThemeSet},
+    parsing::{SyntaxReference, SyntaxSet},
+    util::LinesWithEndings,
 };
+use timing::{Rehearsal, SlideTimer, compare_runs};
+use typing::{TypedLine, typing_block};
+use zoom::Zoom;
 use unicode_width::UnicodeWidthStr;
 
 /// Command line arguments for the presentation tool.
 #[derive(Parser)]
 #[command(name = "presentation")]
 #[command(about = "A terminal-based markdown slideshow presentation tool")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Path to the markdown file to present
-    #[arg(help = "Path to the markdown file")]
-    file: String,
+    #[arg(help = "Path to the markdown file", required = true)]
+    file: Option<String>,
+
+    /// Log every navigation action with its timestamp to a session file
+    #[arg(long, value_name = "FILE")]
+    record_session: Option<String>,
+
+    /// Play the deck hands-free using per-slide durations from a timings file
+    #[arg(long, value_name = "FILE")]
+    autoplay: Option<String>,
+
+    /// Print each slide's number, title, lines, notes, and tags instead of
+    /// presenting, as tab-separated values (the default) or JSON
+    #[arg(long, value_name = "FORMAT", num_args = 0..=1, default_missing_value = "tsv")]
+    list_slides: Option<ListFormat>,
+
+    /// Print the names of the syntect themes --code-theme can pick
+    #[arg(long, exclusive = true)]
+    list_code_themes: bool,
+
+    /// Write warnings, exec output, and network events to FILE, since the
+    /// terminal is taken by the presentation
+    #[arg(long, value_name = "FILE", global = true)]
+    log_file: Option<PathBuf>,
+
+    /// What to log, as a level (e.g. debug) or RUST_LOG-style filter; read
+    /// from PRESENTRS_LOG or RUST_LOG by default, else info
+    #[arg(long, value_name = "FILTER", global = true, requires = "log_file")]
+    log_level: Option<String>,
+
+    #[command(flatten)]
+    present: PresentArgs,
+}
+
+/// How `--list-slides` prints the slides.
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum ListFormat {
+    /// A header row, then one tab-separated row per slide
+    Tsv,
+    /// A JSON array with an object per slide
+    Json,
+}
+
+/// A slide as `--list-slides` describes it.
+#[derive(Serialize)]
+struct SlideListing {
+    /// Number of the slide, from 1
+    slide: usize,
+    /// Title of the slide, if it has one
+    title: Option<String>,
+    /// First line of the slide's source in the markdown file, from 1
+    start_line: usize,
+    /// Last line of the slide's source in the markdown file
+    end_line: usize,
+    /// Whether the slide has speaker notes, in the deck or its notes file
+    notes: bool,
+    /// Tags set by `<!-- tags: ... -->` directives on the slide
+    tags: Vec<String>,
+}
+
+/// Options shared by every mode that presents a deck in the terminal.
+#[derive(clap::Args)]
+struct PresentArgs {
+    /// Record the session to an asciicast v2 file
+    #[arg(long, value_name = "FILE")]
+    record: Option<String>,
+
+    /// Write per-slide timings to a CSV or JSON file (by extension) on exit
+    #[arg(long, value_name = "FILE")]
+    export_timings: Option<String>,
+
+    /// Save this run's timings to the deck's rehearsal history
+    #[arg(long)]
+    rehearse: bool,
+
+    /// Start a web server on ADDR (e.g. 0.0.0.0:8080) where attendees can submit questions
+    #[arg(long, value_name = "ADDR")]
+    serve: Option<String>,
+
+    /// Stream the screen to read-only viewers who connect to ADDR with `nc`
+    #[arg(long, value_name = "ADDR")]
+    broadcast: Option<String>,
+
+    /// Also serve a presenter remote for a phone, opened by scanning a QR code
+    #[arg(long, requires = "serve")]
+    remote: bool,
+
+    /// Serve over HTTPS with this PEM certificate chain (needs --tls-key)
+    #[arg(long, value_name = "FILE", requires_all = ["serve", "tls_key"])]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM private key of the certificate given with --tls-cert
+    #[arg(long, value_name = "FILE", requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    /// Open a presenter console with notes, a timer, and the next slide in a
+    /// tmux split (the default) or window
+    #[arg(long, value_name = "LAYOUT", num_args = 0..=1, default_missing_value = "split")]
+    tmux: Option<TmuxLayout>,
+
+    /// Wait for a presenter console started in another terminal, printing
+    /// the command that starts it
+    #[arg(long, conflicts_with = "tmux")]
+    console: bool,
+
+    /// Advance to the next slide automatically after DURATION (e.g. 20s, 1m30s)
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration_arg)]
+    auto_advance: Option<Duration>,
+
+    /// Pause the talk's timer and auto-advance while the terminal does not
+    /// have the focus, such as while showing a demo in another window
+    #[arg(long)]
+    pause_on_blur: bool,
+
+    /// Reload the deck whenever the markdown file changes, staying on the
+    /// slide on screen
+    #[arg(long)]
+    watch: bool,
+
+    /// Never run exec blocks or terminal panes, whatever the config allows
+    #[arg(long)]
+    no_exec: bool,
+
+    /// Draw TEXT faintly across every slide (e.g. "CONFIDENTIAL")
+    #[arg(long, value_name = "TEXT")]
+    watermark: Option<String>,
+
+    /// Show the slide number in a corner of the slide (bottom-right by default)
+    #[arg(long, value_name = "CORNER", num_args = 0..=1, default_missing_value = "bottom-right")]
+    slide_numbers: Option<Corner>,
+
+    /// Hide the status bar, except to show messages
+    #[arg(long)]
+    no_status_bar: bool,
+
+    /// Show a bar mapping out the talk, each slide sized by its estimated length
+    #[arg(long)]
+    timeline: bool,
+
+    /// How to draw fragments after a `<!-- pause -->` before they are revealed
+    #[arg(long, value_name = "STYLE")]
+    fragments: Option<FragmentStyle>,
+
+    /// Leave less room between blocks so that dense slides fit short terminals
+    #[arg(long, value_name = "DENSITY")]
+    density: Option<Density>,
+
+    /// What to do with raw HTML: leave it out, show its text, or style it
+    #[arg(long, value_name = "POLICY")]
+    html: Option<HtmlPolicy>,
+
+    /// What separates slides: `heading` (H1 headings), `rule` (`---`), or a
+    /// line of TEXT of its own
+    #[arg(long, value_name = "TEXT")]
+    separator: Option<Separator>,
+
+    /// How to draw images: with the terminal's graphics protocol, or half blocks
+    #[arg(long, value_name = "PROTOCOL")]
+    images: Option<graphics::Protocol>,
+
+    /// Color theme to start in (dark, light, or high-contrast)
+    #[arg(long, value_name = "NAME")]
+    theme: Option<String>,
+
+    /// Highlight code blocks with this syntect theme, whatever the color theme
+    #[arg(long, value_name = "NAME")]
+    code_theme: Option<String>,
+
+    /// Highlight code blocks with the TextMate theme in FILE (a `.tmTheme`)
+    #[arg(long, value_name = "FILE", conflicts_with = "code_theme")]
+    code_theme_file: Option<PathBuf>,
+
+    /// Folder of `.sublime-syntax` files to highlight more languages with
+    #[arg(long, value_name = "DIR")]
+    syntaxes: Option<PathBuf>,
+
+    /// Draw the deck at WxH (e.g. 100x30) whatever the terminal's size
+    #[arg(long, value_name = "WxH")]
+    simulate: Option<Size>,
+
+    /// Split slides too tall for the screen into continuation slides
+    #[arg(long)]
+    paginate: bool,
+
+    /// Refuse to present a deck with content that cannot be shown, such as
+    /// raw HTML, unknown directives, or missing images, and list it instead
+    #[arg(long)]
+    strict: bool,
+
+    /// Present only the slides tagged with one of TAGS (comma-separated)
+    #[arg(long, value_name = "TAGS", value_delimiter = ',')]
+    tags: Vec<String>,
+
+    /// Leave out the slides tagged with one of TAGS (comma-separated)
+    #[arg(long, value_name = "TAGS", value_delimiter = ',')]
+    skip_tags: Vec<String>,
+
+    /// Start at the slide with this `<!-- id: ... -->`, or with this number
+    #[arg(long, value_name = "ID")]
+    start_at: Option<String>,
+
+    /// Present the content marked `<!-- lang: LANG -->` and the content shared
+    /// by every language
+    #[arg(long, value_name = "LANG")]
+    lang: Option<String>,
+}
+
+/// Subcommands other than presenting a deck directly.
+#[derive(Subcommand)]
+enum Command {
+    /// Start a presentation: a folder with a starter deck and a theme to edit
+    New {
+        /// Folder to create, which also names the talk
+        dir: PathBuf,
+        /// Theme the deck's theme file starts as a copy of
+        #[arg(long, value_name = "NAME", default_value = "dark")]
+        theme: String,
+    },
+    /// Replay a recorded navigation session with its original timing
+    Replay {
+        /// Path to the markdown file
+        file: String,
+        /// Session file written by --record-session
+        session: String,
+        #[command(flatten)]
+        present: PresentArgs,
+    },
+    /// Compare per-slide timings between runs saved with --rehearse
+    Compare {
+        /// Path to the markdown file
+        file: String,
+        /// Runs to compare, numbered from 1 (oldest); a single run is compared
+        /// with the latest, and the latest two are compared by default
+        #[arg(num_args = 0..=2)]
+        runs: Vec<usize>,
+        /// List the saved runs instead of comparing them
+        #[arg(long)]
+        list: bool,
+    },
+    /// Study the deck as flashcards: each H1 is a question, the rest of the slide its answer
+    Flashcards {
+        /// Path to the markdown file
+        file: String,
+        /// Include cards already marked as known
+        #[arg(long)]
+        all: bool,
+        /// Deal cards in deck order instead of shuffling them
+        #[arg(long)]
+        ordered: bool,
+        #[command(flatten)]
+        present: PresentArgs,
+    },
+    /// Write the slides to an HTML page, a PDF, a PowerPoint file, PNG images, or
+    /// ANSI text files
+    Export {
+        /// Path to the markdown file
+        file: String,
+        /// What to write
+        #[arg(long, value_name = "FORMAT", default_value = "html")]
+        format: export::Format,
+        /// File (or for ansi and png, folder) to write; named after the deck by default
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+        /// Write every format, the outline, a handout, and a manifest into DIR
+        #[arg(long, value_name = "DIR", conflicts_with_all = ["format", "output"])]
+        all: Option<PathBuf>,
+        /// Draw slides for a terminal of WxH (e.g. 100x30) instead of this one
+        #[arg(long, value_name = "WxH")]
+        simulate: Option<Size>,
+        /// Export again whenever the markdown file changes, until interrupted
+        #[arg(long)]
+        watch: bool,
+    },
+    /// Print a nested markdown outline of the deck's headings
+    Outline {
+        /// Path to the markdown file
+        file: String,
+    },
+    /// Write each slide to a numbered markdown file of its own, with the
+    /// deck's front matter and the slide's notes
+    Split {
+        /// Path to the markdown file
+        file: String,
+        /// Folder to write the slides into
+        dir: PathBuf,
+    },
+    /// Show the slide on screen, notes, timer, and next slide of a
+    /// presentation started with --tmux or --console
+    Console {
+        /// Address the presentation listens on for consoles
+        addr: String,
+        /// Secret the presentation made up for its consoles
+        #[arg(long)]
+        token: String,
+    },
+    /// Preview a sample slide in each built-in color theme
+    Themes,
+    /// Work with theme files
+    Theme {
+        #[command(subcommand)]
+        command: ThemeCommand,
+    },
+    /// Report the slides whose content does not fit on screen or that break
+    /// the rules in the config file
+    Check {
+        /// Path to the markdown file
+        file: String,
+        /// Lay slides out for a terminal of WxH (e.g. 100x30) instead of this one
+        #[arg(long, value_name = "WxH")]
+        simulate: Option<Size>,
+        /// Also check the spelling of the deck's prose
+        #[arg(long)]
+        spelling: bool,
+        /// Print the problems found as text or as JSON
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        format: Format,
+    },
+    /// Time parsing the deck, highlighting each slide's code, and drawing
+    /// each slide
+    Bench {
+        /// Path to the markdown file
+        file: String,
+        /// Lay out and draw slides for a terminal of WxH (e.g. 100x30) instead of this one
+        #[arg(long, value_name = "WxH")]
+        simulate: Option<Size>,
+        /// Number of runs each time is the median of
+        #[arg(long, value_name = "N", default_value_t = 5)]
+        runs: usize,
+        /// Print the times as a table or as JSON
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        format: Format,
+    },
+}
+
+/// Subcommands of `presentrs theme`.
+#[derive(Subcommand)]
+enum ThemeCommand {
+    /// Write a theme to a file that can be edited, shared, and loaded with --theme
+    Export {
+        /// Name of the theme
+        name: String,
+        /// Path of the theme file to write
+        file: String,
+    },
+}
+
+/// A navigation action, triggered by a key press or by a replayed session.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+    NextSlide,
+    PrevSlide,
+    ScrollDown,
+    ScrollUp,
+    FirstSlide,
+    LastSlide,
+    /// Jump to the slide with the given 0-based index
+    GotoSlide(usize),
+    /// Move to the top of the next column of vertical sub-slides
+    NextColumn,
+    /// Move to the top of the previous column of vertical sub-slides
+    PrevColumn,
+    /// Move down to the next sub-slide in the current column
+    NextInColumn,
+    /// Move up to the previous sub-slide in the current column
+    PrevInColumn,
+    /// Jump to the first slide of the next section
+    NextSection,
+    /// Jump back to the previous section boundary
+    PrevSection,
+}
+
+/// A single parsed slide.
+#[derive(Default)]
+struct Slide {
+    /// Formatted slide content
+    text: Text<'static>,
+    /// Text of the H1 (or, for a sub-slide, H2) heading that starts the
+    /// slide, if any
+    title: Option<String>,
+    /// Byte range of the slide's source within the markdown file
+    source: Range<usize>,
+    /// Index of the H1 column the slide belongs to
+    column: usize,
+    /// Position within its column: 0 for the H1 slide, then one per H2
+    /// sub-slide in decks with `vertical: true` front matter
+    row: usize,
+    /// Reveal schedule for lines that belong to `typing` demo blocks
+    typing: Vec<TypedLine>,
+    /// Blocks written as `animation`, played when the slide is shown
+    animations: Vec<Animation>,
+    /// Auto-advance interval set by an `<!-- advance: ... -->` directive
+    advance: Option<Duration>,
+    /// Region reserved by a `<!-- terminal: ... -->` directive
+    terminal: Option<TerminalRegion>,
+    /// Images a graphics protocol can draw instead of their half blocks
+    images: Vec<graphics::Image>,
+    /// Image set by a `<!-- background: ... -->` directive
+    background: Option<Background>,
+    /// Section the slide belongs to, started by a `<!-- section: ... -->`
+    /// directive on this or an earlier slide
+    section: Option<String>,
+    /// Name set by an `<!-- id: ... -->` directive, to jump to the slide by
+    id: Option<String>,
+    /// Code blocks marked `exec`, in slide order
+    exec: Vec<ExecBlock>,
+    /// Footnotes referenced on the slide, in order of first reference
+    footnotes: Vec<Footnote>,
+    /// Links on the slide, in order of appearance
+    links: Vec<Link>,
+    /// Headings on the slide, in order
+    headings: Vec<Heading>,
+    /// Speaker notes from `<!-- notes: ... -->` comments on the slide
+    notes: Vec<String>,
+    /// Content set apart from the main flow, such as callouts
+    regions: Vec<Region>,
+    /// Name of the layout set by a `<!-- layout: ... -->` directive
+    layout: Option<String>,
+    /// Name of the theme set by a `<!-- theme: ... -->` directive
+    theme: Option<String>,
+    /// Tags set by `<!-- tags: ... -->` directives
+    tags: Vec<String>,
+    /// Pauses and code walkthroughs that reveal the slide step by step
+    steps: Steps,
+    /// Problems found parsing the slide, such as unknown directives
+    warnings: Vec<Warning>,
+}
+
+/// Something on a slide that cannot be shown as written.
+#[derive(Clone)]
+struct Warning {
+    /// Kind of problem, named like the rules of `presentrs check`
+    rule: &'static str,
+    /// Byte offset of the problem within the markdown file
+    offset: usize,
+    /// What is wrong
+    message: String,
+}
+
+/// A heading within a slide.
+struct Heading {
+    /// Index of the slide line the heading is on
+    line: usize,
+    /// Level of the heading
+    level: HeadingLevel,
+    /// Text of the heading
+    text: String,
+}
+
+/// A link on a slide.
+#[derive(Clone)]
+struct Link {
+    /// Text the link is shown as
+    text: String,
+    /// Where the link points
+    url: String,
+}
+
+/// A footnote referenced from a slide.
+#[derive(Clone)]
+struct Footnote {
+    /// Label used in the markdown, e.g. `1` for `[^1]`
+    label: String,
+    /// Text of the footnote's definition, which may be on another slide
+    text: String,
+}
+
+impl Slide {
+    /// Short name for the slide in lists: its title, or else its first line
+    /// of text.
+    fn label(&self) -> String {
+        match &self.title {
+            Some(title) => title.trim().to_string(),
+            None => self
+                .plain_text()
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+
+    /// Records a problem of kind `rule` found at byte `offset` of the
+    /// markdown file.
+    fn warn(&mut self, rule: &'static str, offset: usize, message: String) {
+        self.warnings.push(Warning {
+            rule,
+            offset,
+            message,
+        });
+    }
+
+    /// First and last line of the slide's source within `markdown`, from 1.
+    fn source_lines(&self, markdown: &str) -> (usize, usize) {
+        // Blank lines around the slide are not part of it.
+        let source = &markdown[self.source.clone()];
+        let start = self.source.start + (source.len() - source.trim_start().len());
+        let end = self.source.start + source.trim_end().len();
+        (check::line_of(markdown, start), check::line_of(markdown, end.max(start)))
+    }
+
+    /// Returns the slide's content as unstyled text, without title centering.
+    fn plain_text(&self) -> String {
+        let mut out = String::new();
+        for line in &self.text.lines {
+            let text: String = line.spans.iter().map(|span| span.content.as_ref()).collect();
+            let text = match &self.title {
+                Some(title) if text.trim() == title.trim() => text.trim(),
+                _ => text.trim_end(),
+            };
+            out.push_str(text);
+            out.push('\n');
+        }
+        out.trim_end().to_string()
+    }
+
+    /// Splits the slide into pages of at most `room` rows of content
+    /// `width` columns wide, each after the first opening with the heading
+    /// marked "(cont.)". Pages break after a blank line where they can.
+    ///
+    /// Slides revealed step by step, with demos, regions, or a layout keep
+    /// their lines where they are and are never split.
+    fn paginate(self, width: u16, room: usize) -> Vec<Slide> {
+        let plain = self.steps.len() == 0
+            && self.typing.is_empty()
+            && self.animations.is_empty()
+            && self.exec.is_empty()
+            && self.terminal.is_none()
+            && self.regions.is_empty()
+            && self.layout.is_none();
+        let rows_of = |line: &Line<'static>| {
+            Paragraph::new(line.clone())
+                .wrap(Wrap { trim: false })
+                .line_count(width)
+        };
+        let rows: Vec<usize> = self.text.lines.iter().map(rows_of).collect();
+        if !plain || room == 0 || rows.iter().sum::<usize>() <= room {
+            return vec![self];
+        }
+
+        let lines = &self.text.lines;
+        let blank = |index: usize| lines[index].width() == 0;
+        // The heading and the blank line after it open every page.
+        let mut head = 0;
+        let mut continued_head = Vec::new();
+        if self.title.is_some() && self.headings.first().is_some_and(|h| h.line == 0) {
+            head = if lines.len() > 1 && blank(1) { 2 } else { 1 };
+            continued_head.push(continued(&lines[0]));
+            continued_head.extend(lines[1..head].iter().cloned());
+        }
+        let continued_rows: usize = continued_head.iter().map(rows_of).sum();
+
+        let mut pages: Vec<Range<usize>> = Vec::new();
+        let mut start = head;
+        let mut used: usize = rows[..head].iter().sum();
+        let mut index = head;
+        while index < lines.len() {
+            if index == start && !pages.is_empty() && blank(index) {
+                start += 1;
+            } else if used + rows[index] > room && index > start {
+                let end = (start + 1..index).rev().find(|&i| blank(i)).unwrap_or(index);
+                pages.push(start..end);
+                start = end;
+                used = continued_rows;
+                index = end;
+                continue;
+            } else {
+                used += rows[index];
+            }
+            index += 1;
+        }
+        pages.push(start..lines.len());
+        if pages.len() == 1 {
+            return vec![self];
+        }
+
+        pages
+            .into_iter()
+            .enumerate()
+            .map(|(page, range)| {
+                let mut text = if page == 0 {
+                    lines[..head].to_vec()
+                } else {
+                    continued_head.clone()
+                };
+                let offset = text.len();
+                text.extend(lines[range.clone()].iter().cloned());
+                let headings = self
+                    .headings
+                    .iter()
+                    .filter(|h| (h.line < head && page == 0) || range.contains(&h.line))
+                    .map(|h| Heading {
+                        line: if h.line < head { h.line } else { h.line - range.start + offset },
+                        level: h.level,
+                        text: h.text.clone(),
+                    });
+                // Images broken across pages stay half blocks.
+                let images = self
+                    .images
+                    .iter()
+                    .filter(|i| {
+                        range.contains(&i.line) && i.line + usize::from(i.rows) <= range.end
+                    })
+                    .map(|i| graphics::Image {
+                        line: i.line - range.start + offset,
+                        ..i.clone()
+                    });
+                let title = match (&self.title, page) {
+                    (Some(title), 1..) => Some(format!("{} (cont.)", title.trim())),
+                    (title, _) => title.clone(),
+                };
+                Slide {
+                    text: Text::from(text),
+                    title,
+                    source: self.source.clone(),
+                    column: self.column,
+                    row: self.row,
+                    advance: self.advance,
+                    background: self.background.clone(),
+                    section: self.section.clone(),
+                    id: self.id.clone(),
+                    footnotes: self.footnotes.clone(),
+                    links: self.links.clone(),
+                    headings: headings.collect(),
+                    images: images.collect(),
+                    notes: self.notes.clone(),
+                    theme: self.theme.clone(),
+                    tags: self.tags.clone(),
+                    warnings: self.warnings.clone(),
+                    ..Slide::default()
+                }
+            })
+            .collect()
+    }
+}
+
+/// The heading line `line` with "(cont.)" after it, still centered.
+fn continued(line: &Line<'static>) -> Line<'static> {
+    const SUFFIX: &str = " (cont.)";
+    let mut line = line.clone();
+    let style = line.spans.last().map_or(Style::default(), |span| span.style);
+    line.spans.push(Span::styled(SUFFIX, style));
+    if let Some(padding) = line.spans.first_mut().filter(|span| span.content.trim().is_empty()) {
+        let width = padding.content.width().saturating_sub(SUFFIX.len() / 2);
+        *padding = Span::raw(" ".repeat(width));
+    }
+    line
 }
 
+/// A question submitted by an attendee through the web server.
+struct Question {
+    /// The question as typed
+    text: String,
+    /// Whether the presenter has marked it as answered
+    answered: bool,
+}
+
+/// A panel drawn on top of the slide that takes over keyboard input.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Overlay {
+    /// Incoming audience questions
+    Questions,
+    /// Footnotes referenced on the current slide
+    Footnotes,
+    /// Every slide laid out in a grid
+    Overview,
+    /// Links on the current slide, with their targets
+    Links,
+    /// Asks the presenter before the first command of the session runs
+    ConfirmExec(ExecRequest),
+    /// Prompt for an annotation on the current slide
+    Annotate,
+    /// Prompt for text to search the deck for
+    Search,
+    /// Prompt for a command, such as `goto` a slide
+    Command,
+    /// Slides by title, narrowed down by the letters typed
+    Picker,
+    /// QR code to open the presenter remote on a phone
+    Remote,
+    /// Diagnostics about the current slide, to find out why it looks wrong
+    Inspect,
+}
+
+/// Code from the deck that the presenter asked to run.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ExecRequest {
+    /// An executable block, by slide and block index
+    Block { slide: usize, index: usize },
+    /// The terminal pane of a slide
+    Pane { slide: usize },
+}
+
+/// How long transient status bar messages stay visible.
+const STATUS_MESSAGE_DURATION: Duration = Duration::from_secs(3);
+
+/// How often the web server's event queue is checked while idle.
+const SERVER_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
 /// The main application state for the slideshow.
 ///
 /// Manages the collection of slides and tracks the current slide position.
 struct App {
+    /// Path of the markdown file being presented
+    deck_path: PathBuf,
     /// Raw markdown content, retained so slides can be re-parsed on resize.
     markdown_content: String,
-    /// Collection of slide content as formatted text
-    slides: Vec<Text<'static>>,
+    /// Whether the deck is read again when its file changes
+    watch: bool,
+    /// When the deck's file was last changed, as of reading it
+    deck_modified: Option<SystemTime>,
+    /// Width of the terminal, in columns
+    terminal_width: u16,
+    /// Height of the terminal, in rows
+    terminal_height: u16,
+    /// Whether slides too tall for the screen are split into pages
+    paginate: bool,
+    /// Collection of parsed slides
+    slides: Vec<Slide>,
     /// Index of the currently displayed slide (0-based)
     current_slide: usize,
     /// Vertical scroll offset for the current slide
     scroll_offset: usize,
+    /// Moment the current slide was shown, used to drive animations
+    slide_entered: Instant,
+    /// Number of steps taken through the current slide's fragments
+    fragment: usize,
+    /// Moment the current slide was shown or its latest step taken
+    fragment_entered: Instant,
+    /// How fragments not revealed yet are drawn
+    fragment_style: FragmentStyle,
+    /// How much room is left around the blocks of each slide
+    density: Density,
+    /// What becomes of raw HTML in the deck
+    html: HtmlPolicy,
+    /// What separates the deck's slides
+    separator: Separator,
+    /// Which slides are presented, by their tags
+    tag_filter: TagFilter,
+    /// Language whose content is presented along with the shared content,
+    /// or `None` to present every language
+    lang: Option<String>,
+    /// Slides whose spoilers the presenter has revealed
+    revealed_spoilers: HashSet<usize>,
+    /// Whether revealed spoilers stay revealed when a slide is shown again
+    keep_spoilers_revealed: bool,
+    /// Transient message shown in the status bar, with the moment it was set
+    status_message: Option<(String, Instant)>,
+    /// Time spent on each slide during this run
+    timer: SlideTimer,
+    /// Default interval after which slides advance on their own
+    auto_advance: Option<Duration>,
+    /// Whether timers pause while the terminal does not have the focus
+    pause_on_blur: bool,
+    /// Study session when presenting the deck as flashcards
+    flashcards: Option<Flashcards>,
+    /// Web server started with `--serve`
+    server: Option<Server>,
+    /// Read-only stream of the screen started with `--broadcast`
+    broadcast: Option<Broadcast>,
+    /// Presenter console opened with `--tmux`
+    console: Option<ConsoleLink>,
+    /// Font size of the terminal, as changed with the zoom keys
+    zoom: Zoom,
+    /// Audience questions received so far, oldest first
+    questions: Vec<Question>,
+    /// Index of the highlighted question in the questions panel
+    selected_question: usize,
+    /// Index of the highlighted link in the links panel
+    selected_link: usize,
+    /// Whether the slide list is shown down the left side
+    sidebar: bool,
+    /// Inner area of the slide list and the index of its first visible
+    /// slide, recorded while drawing so clicks can be mapped to slides
+    sidebar_view: Cell<(Rect, usize)>,
+    /// Whether thumbnails of nearby slides are shown below the slide
+    filmstrip: bool,
+    /// Whether the speaker notes are shown below the slide
+    notes_panel: bool,
+    /// Index of the slide highlighted in the overview
+    overview_selection: usize,
+    /// Number of slides per row of the overview of a deck without vertical
+    /// sub-slides, recorded while drawing
+    overview_width: Cell<usize>,
+    /// Panel currently drawn over the slide
+    overlay: Option<Overlay>,
+    /// Terminal panes started so far, keyed by slide index
+    panes: HashMap<usize, TerminalPane>,
+    /// Whether key presses go to the current slide's terminal pane
+    pane_focused: bool,
+    /// Inner area of the current slide's terminal pane, recorded while drawing
+    pane_area: Cell<Rect>,
+    /// Rows the slide's main content is drawn in, recorded while drawing
+    slide_rows: Cell<u16>,
+    /// Draws images with the terminal's graphics protocol, if it has one
+    painter: Painter,
+    /// Images on the current slide for the painter to draw, with the cells
+    /// they take up, recorded while drawing
+    image_areas: Cell<Vec<(PathBuf, Rect)>>,
+    /// Background image of the current slide, scaled when it was last drawn
+    backdrop: RefCell<Option<Backdrop>>,
+    /// Time the last frame took to draw
+    draw_time: Duration,
+    /// Logo shown in a corner of every slide
+    logo: Option<Logo>,
+    /// Faint text drawn across every slide
+    watermark: Option<Watermark>,
+    /// Corner of the slide that shows the slide number, if any
+    slide_numbers: Option<Corner>,
+    /// Whether the status bar is shown when there is no message
+    status_bar: bool,
+    /// Whether the timeline bar is shown above the status bar
+    timeline: bool,
+    /// Time each slide took in the latest rehearsal, if it was shown
+    rehearsed: Vec<Option<Duration>>,
+    /// Whether the deck keeps its size when the terminal is resized
+    fixed_size: bool,
+    /// What the status bar says when there is no message, from the config
+    status_template: Option<String>,
+    /// Metadata from the deck's front matter
+    front_matter: FrontMatter,
+    /// Speaker notes kept in a file next to the deck
+    companion_notes: CompanionNotes,
+    /// Notes the presenter made on slides while rehearsing
+    annotations: Annotations,
+    /// Text typed so far into the annotation prompt
+    annotation_draft: String,
+    /// The active search, if any
+    search: Option<Search>,
+    /// Text typed so far into the search prompt
+    search_draft: String,
+    /// Text of the last search, which an empty prompt searches for again
+    last_search: String,
+    /// Digits typed so far of a slide number to jump to with Enter
+    slide_number: String,
+    /// Letters typed into the slide picker and the slide picked
+    picker: Picker,
+    /// Text typed so far into the command prompt
+    command_draft: String,
+    /// Layouts slides can pick with a `<!-- layout: ... -->` directive
+    layouts: Layouts,
+    /// Words drawn in a color of their own wherever they appear
+    keywords: Keywords,
+    /// Keys bound to actions in the config file
+    keys: KeyMap,
+    /// Color themes the presenter can switch between
+    themes: Themes,
+    /// Index of the theme slides are drawn in
+    theme: usize,
+    /// Text shown along the bottom of every slide, built from the front matter
+    footer: Option<String>,
+    /// Commands started from executable blocks, keyed by slide and block index
+    runs: HashMap<(usize, usize), Execution>,
+    /// Rules for running code from the deck
+    exec_policy: ExecPolicy,
+    /// Whether running code is enabled at all (off with `--no-exec`)
+    exec_enabled: bool,
+    /// Whether the presenter already allowed running code this session
+    exec_confirmed: bool,
     /// Syntax highlighting theme set
     theme_set: ThemeSet,
-    /// Syntax definitions
-    syntax_set: SyntaxSet,
+    /// Highlighter for code blocks
+    highlighter: Highlighter,
 }
 
 impl App {
-    /// Creates a new App instance from markdown content.
-    fn new(markdown_content: String, terminal_width: u16) -> Self {
+    /// Creates a new App instance from markdown content read from `deck_path`.
+    fn new(deck_path: &Path, markdown_content: String, terminal_width: u16) -> Self {
         let theme_set = ThemeSet::load_defaults();
-        let syntax_set = SyntaxSet::load_defaults_newlines();
-        let slides =
-            parse_markdown_to_slides(&markdown_content, &theme_set, &syntax_set, terminal_width);
-        App {
+        let front_matter = FrontMatter::parse(&markdown_content);
+        let mut app = App {
+            deck_path: deck_path.to_path_buf(),
             markdown_content,
-            slides,
+            watch: false,
+            deck_modified: None,
+            terminal_width,
+            terminal_height: 0,
+            paginate: false,
+            slides: Vec::new(),
             current_slide: 0,
             scroll_offset: 0,
+            slide_entered: Instant::now(),
+            fragment: 0,
+            fragment_entered: Instant::now(),
+            fragment_style: FragmentStyle::default(),
+            density: Density::default(),
+            html: HtmlPolicy::default(),
+            separator: front_matter
+                .get("separator")
+                .and_then(Separator::named)
+                .unwrap_or_default(),
+            tag_filter: TagFilter::default(),
+            lang: front_matter.get("lang").map(str::to_string),
+            revealed_spoilers: HashSet::new(),
+            keep_spoilers_revealed: false,
+            status_message: None,
+            timer: SlideTimer::new(0),
+            auto_advance: None,
+            pause_on_blur: false,
+            flashcards: None,
+            server: None,
+            broadcast: None,
+            console: None,
+            zoom: Zoom::default(),
+            questions: Vec::new(),
+            selected_question: 0,
+            selected_link: 0,
+            sidebar: false,
+            sidebar_view: Cell::new((Rect::default(), 0)),
+            filmstrip: false,
+            notes_panel: false,
+            overview_selection: 0,
+            overview_width: Cell::new(1),
+            overlay: None,
+            panes: HashMap::new(),
+            pane_focused: false,
+            pane_area: Cell::new(Rect::default()),
+            slide_rows: Cell::new(0),
+            painter: Painter::default(),
+            image_areas: Cell::new(Vec::new()),
+            backdrop: RefCell::new(None),
+            draw_time: Duration::ZERO,
+            logo: None,
+            watermark: None,
+            slide_numbers: None,
+            status_bar: true,
+            timeline: false,
+            rehearsed: Vec::new(),
+            fixed_size: false,
+            status_template: None,
+            front_matter,
+            companion_notes: CompanionNotes::default(),
+            annotations: Annotations::default(),
+            annotation_draft: String::new(),
+            search: None,
+            search_draft: String::new(),
+            last_search: String::new(),
+            slide_number: String::new(),
+            picker: Picker::default(),
+            command_draft: String::new(),
+            layouts: Layouts::default(),
+            keywords: Keywords::default(),
+            keys: KeyMap::default(),
+            themes: Themes::default(),
+            theme: 0,
+            footer: None,
+            runs: HashMap::new(),
+            exec_policy: ExecPolicy::default(),
+            exec_enabled: true,
+            exec_confirmed: false,
             theme_set,
-            syntax_set,
-        }
+            highlighter: Highlighter::default(),
+        };
+        app.slides = app.parse_slides(terminal_width);
+        app
     }
 
-    /// Re-parses slides for the new terminal width, preserving the current slide index.
-    fn resize(&mut self, new_width: u16) {
-        let slides = parse_markdown_to_slides(
-            &self.markdown_content,
-            &self.theme_set,
-            &self.syntax_set,
-            new_width,
-        );
-        if !slides.is_empty() {
+    /// Re-parses slides for the new terminal size, preserving the current slide
+    /// and how far it is scrolled.
+    fn resize(&mut self, new_width: u16, new_height: u16) {
+        self.terminal_width = new_width;
+        self.terminal_height = new_height;
+        let scroll_offset = self.scroll_offset;
+        self.reflow();
+        // Rewrapped lines move, but the view stays near where it was rather
+        // than jumping back to the top of the slide.
+        let lines = self.slides.get(self.current_slide).map_or(0, |slide| slide.text.lines.len());
+        self.scroll_offset = scroll_offset.min(lines.saturating_sub(1));
+    }
+
+    /// Re-parses slides to fit the space left for them, preserving the
+    /// current slide, or its page if the deck is paginated.
+    fn reflow(&mut self) {
+        let slides = self.parse_slides(self.terminal_width - self.sidebar_width());
+        let source = self.slides.get(self.current_slide).map(|slide| slide.source.clone());
+        let first = |slides: &[Slide]| {
+            slides.iter().position(|slide| Some(&slide.source) == source.as_ref())
+        };
+        if let (Some(old), Some(new)) = (first(&self.slides), first(&slides)) {
+            let pages = slides[new..]
+                .iter()
+                .take_while(|slide| Some(&slide.source) == source.as_ref())
+                .count();
+            self.current_slide = new + (self.current_slide - old).min(pages - 1);
+        } else if !slides.is_empty() {
             self.current_slide = self.current_slide.min(slides.len() - 1);
         }
         self.scroll_offset = 0;
         self.slides = slides;
+        self.fragment = self.fragment.min(self.current_steps().map_or(0, Steps::len));
+    }
+
+    /// Time until the deck's file is next looked at for changes, with
+    /// `--watch`.
+    fn watch_timeout(&self) -> Option<Duration> {
+        self.watch.then_some(WATCH_INTERVAL)
+    }
+
+    /// Reads the deck and its notes again if the file changed since it was
+    /// last read, with `--watch`, staying on the slide on screen and where
+    /// it is scrolled to. A deck that fails to read is kept as it was.
+    fn reload_if_changed(&mut self) {
+        if !self.watch {
+            return;
+        }
+        let modified = modified_at(&self.deck_path);
+        if modified == self.deck_modified {
+            return;
+        }
+        self.deck_modified = modified;
+        let markdown = match fs::read_to_string(&self.deck_path) {
+            Ok(markdown) => markdown,
+            Err(e) => {
+                let path = self.deck_path.display();
+                log::error!("Failed to reload '{path}': {e}");
+                self.flash(format!("Failed to reload '{path}': {e}"));
+                return;
+            }
+        };
+        match CompanionNotes::load(&self.deck_path) {
+            Ok(notes) => self.companion_notes = notes,
+            Err(e) => log::warn!("{e}"),
+        }
+        self.front_matter = FrontMatter::parse(&markdown);
+        self.markdown_content = markdown;
+        let scroll_offset = self.scroll_offset;
+        self.reflow();
+        // The slide may have become shorter than it was scrolled.
+        let lines = self.slides.get(self.current_slide).map_or(0, |slide| slide.text.lines.len());
+        self.scroll_offset = scroll_offset.min(lines.saturating_sub(1));
+        log::info!("Reloaded '{}'", self.deck_path.display());
+        self.flash("Reloaded the deck".to_string());
+    }
+
+    /// The deck's markdown in the language it is presented in, with content
+    /// in other languages blanked out.
+    fn presented_markdown(&self) -> String {
+        match &self.lang {
+            Some(lang) => lang::select(&self.markdown_content, lang, &self.separator),
+            None => self.markdown_content.clone(),
+        }
+    }
+
+    /// Parses the deck into slides `width` columns wide, each in its theme.
+    fn parse_slides(&self, width: u16) -> Vec<Slide> {
+        let markdown = self.presented_markdown();
+        let parse = |theme: &Theme| {
+            parse_markdown_to_slides(
+                &markdown,
+                &self.front_matter,
+                &deck_dir(&self.deck_path),
+                theme,
+                &self.theme_set,
+                &self.highlighter,
+                width,
+                self.density,
+                self.html,
+                &self.separator,
+            )
+        };
+        let mut slides = parse(self.current_theme());
+        // Colors are settled while parsing, so a slide with a theme of its
+        // own is taken from the deck parsed again in that theme.
+        let names: HashSet<String> = slides
+            .iter()
+            .filter_map(|slide| slide.theme.clone())
+            .filter(|name| *name != self.current_theme().name)
+            .collect();
+        for name in names {
+            let Some(theme) = self.themes.resolve(&name, self.theme) else {
+                continue;
+            };
+            for (slide, themed) in slides.iter_mut().zip(parse(&theme)) {
+                if slide.theme.as_ref() == Some(&name) {
+                    *slide = themed;
+                }
+            }
+        }
+        slides.retain(|slide| self.tag_filter.keeps(&slide.tags));
+        if let Some(content) = self.page_area(width) {
+            slides = slides
+                .into_iter()
+                .flat_map(|slide| slide.paginate(content.width, usize::from(content.height)))
+                .collect();
+        }
+        for slide in &mut slides {
+            self.keywords.apply(&mut slide.text);
+            for region in &mut slide.regions {
+                self.keywords.apply(&mut region.text);
+            }
+        }
+        slides
+    }
+
+    /// Returns the theme the deck is drawn in.
+    fn current_theme(&self) -> &Theme {
+        self.themes.get(self.theme)
     }
 
-    /// Advances to the next slide if available.
+    /// Returns the theme the current slide picks for itself, if any.
+    fn slide_theme(&self) -> Option<Theme> {
+        let name = self.slides.get(self.current_slide)?.theme.as_deref()?;
+        self.themes.resolve(name, self.theme)
+    }
+
+    /// Switches to the next theme, or the previous one unless `forward` is
+    /// true, and redraws the slides in it.
+    fn cycle_theme(&mut self, forward: bool) {
+        self.theme = self.themes.step(self.theme, forward);
+        self.reflow();
+        self.flash(format!("Theme: {}", self.current_theme().name));
+    }
+
+    /// Takes the current slide's next step, or advances to the next
+    /// slide once every fragment is shown.
     ///
     /// Does nothing if already on the last slide or if no slides exist.
     fn next_slide(&mut self) {
-        if !self.slides.is_empty() && self.current_slide < self.slides.len() - 1 {
-            self.current_slide += 1;
-            self.scroll_offset = 0;
+        if self.fragment < self.current_steps().map_or(0, Steps::len) {
+            self.fragment += 1;
+            self.fragment_entered = Instant::now();
+        } else if !self.slides.is_empty() && self.current_slide < self.slides.len() - 1 {
+            self.show_slide(self.current_slide + 1);
         }
     }
 
-    /// Goes back to the previous slide if available.
+    /// Undoes the current slide's latest step, or goes back to the
+    /// previous slide, with all of its fragments shown, if available.
     ///
     /// Does nothing if already on the first slide.
     fn prev_slide(&mut self) {
-        if self.current_slide > 0 {
-            self.current_slide -= 1;
-            self.scroll_offset = 0;
+        if self.fragment > 0 {
+            self.fragment -= 1;
+            self.fragment_entered = Instant::now();
+        } else if self.current_slide > 0 {
+            self.show_slide(self.current_slide - 1);
+            self.fragment = self.current_steps().map_or(0, Steps::len);
         }
     }
 
+    /// Returns the steps that reveal the current slide.
+    ///
+    /// Flashcards always show the whole answer, so they have none.
+    fn current_steps(&self) -> Option<&Steps> {
+        self.slides
+            .get(self.current_slide)
+            .filter(|_| self.flashcards.is_none())
+            .map(|slide| &slide.steps)
+    }
+
     /// Jumps to the first slide.
     fn goto_first(&mut self) {
-        self.current_slide = 0;
-        self.scroll_offset = 0;
+        self.show_slide(0);
     }
 
     /// Jumps to the last slide.
     fn goto_last(&mut self) {
         if !self.slides.is_empty() {
-            self.current_slide = self.slides.len() - 1;
-            self.scroll_offset = 0;
+            self.show_slide(self.slides.len() - 1);
+        }
+    }
+
+    /// Jumps to the slide at `index`, clamped to the last slide.
+    fn goto_slide(&mut self, index: usize) {
+        if !self.slides.is_empty() {
+            self.show_slide(index.min(self.slides.len() - 1));
+        }
+    }
+
+    /// Index of the slide `target` names: the slide with that id, or else
+    /// the slide with that number, from 1.
+    fn find_slide(&self, target: &str) -> Option<usize> {
+        let target = target.trim();
+        self.slides
+            .iter()
+            .position(|slide| slide.id.as_deref() == Some(target))
+            .or_else(|| {
+                let number: usize = target.parse().ok()?;
+                (1..=self.slides.len()).contains(&number).then(|| number - 1)
+            })
+    }
+
+    /// Moves to the first slide of the column after (`forward`) or before
+    /// the current one.
+    fn change_column(&mut self, forward: bool) {
+        let Some(column) = self.slides.get(self.current_slide).map(|slide| slide.column) else {
+            return;
+        };
+        let target = if forward {
+            self.slides.iter().position(|slide| slide.column > column)
+        } else {
+            let Some(previous) = column.checked_sub(1) else {
+                return;
+            };
+            self.slides.iter().position(|slide| slide.column == previous)
+        };
+        if let Some(index) = target {
+            self.show_slide(index);
+        }
+    }
+
+    /// Moves to the next (`forward`) or previous sub-slide in the current
+    /// column, if there is one.
+    fn change_row(&mut self, forward: bool) {
+        let Some(column) = self.slides.get(self.current_slide).map(|slide| slide.column) else {
+            return;
+        };
+        let target = if forward {
+            self.current_slide + 1
+        } else {
+            let Some(index) = self.current_slide.checked_sub(1) else {
+                return;
+            };
+            index
+        };
+        if self.slides.get(target).is_some_and(|slide| slide.column == column) {
+            self.show_slide(target);
+        }
+    }
+
+    /// Fills in the placeholders of a status bar template from the config.
+    ///
+    /// `{slide}` and `{total}` are the slide number and count, `{title}` and
+    /// `{next}` the titles of this slide and the next one, `{section}` the
+    /// current section, `{questions}` the number of open audience questions,
+    /// `{driver}` the presenter driving from the remote, and `{keys}` the
+    /// usual key reminders.
+    fn expand_status_template(&self, template: &str) -> String {
+        let slide = self.slides.get(self.current_slide);
+        let next = self
+            .slides
+            .get(self.current_slide + 1)
+            .map_or("—".to_string(), Slide::label);
+        template
+            .replace("{slide}", &(self.current_slide + 1).to_string())
+            .replace("{total}", &self.slides.len().to_string())
+            .replace("{title}", &slide.map(Slide::label).unwrap_or_default())
+            .replace("{next}", &next)
+            .replace("{section}", self.current_section().unwrap_or_default())
+            .replace("{questions}", &self.unanswered_questions().to_string())
+            .replace(
+                "{driver}",
+                &self.server.as_ref().and_then(Server::driver).unwrap_or_default(),
+            )
+            .replace("{keys}", key_hints(self))
+    }
+
+    /// Name of the section the current slide belongs to, if any.
+    fn current_section(&self) -> Option<&str> {
+        self.slides
+            .get(self.current_slide)
+            .and_then(|slide| slide.section.as_deref())
+    }
+
+    /// Indices of the slides that start a section, in order.
+    fn section_starts(&self) -> impl Iterator<Item = usize> + '_ {
+        (1..self.slides.len())
+            .filter(|&index| self.slides[index].section != self.slides[index - 1].section)
+            .chain(self.slides.first().map(|_| 0))
+    }
+
+    /// Jumps to the first slide of the next section.
+    fn next_section(&mut self) {
+        match self.section_starts().filter(|&start| start > self.current_slide).min() {
+            Some(start) => self.show_slide(start),
+            None => self.flash("This is the last section".to_string()),
+        }
+    }
+
+    /// Jumps to the start of the current section, or to the start of the
+    /// previous one when already there.
+    fn prev_section(&mut self) {
+        if let Some(start) = self.section_starts().filter(|&start| start < self.current_slide).max() {
+            self.show_slide(start);
+        }
+    }
+
+    /// Where the content of a slide `width` columns wide goes, if the deck
+    /// is paginated to fit the screen.
+    fn page_area(&self, width: u16) -> Option<Rect> {
+        if !self.paginate || self.terminal_height == 0 {
+            return None;
+        }
+        let filmstrip = if self.filmstrip { FILMSTRIP_HEIGHT } else { 0 };
+        let status_bar = if self.status_bar { 3 } else { 0 };
+        let chrome = filmstrip + timeline_height(self) + status_bar;
+        let height = self.terminal_height.saturating_sub(chrome);
+        let area = Rect::new(0, 0, width, height);
+        Some(slide_block(self, area).inner(area))
+    }
+
+    /// Columns taken by the slide list, or 0 when it is hidden or the
+    /// terminal is too narrow for it.
+    fn sidebar_width(&self) -> u16 {
+        let width = (self.terminal_width / 4).min(SIDEBAR_MAX_WIDTH);
+        if self.sidebar && width >= SIDEBAR_MIN_WIDTH {
+            width
+        } else {
+            0
+        }
+    }
+
+    /// Shows or hides the slide list, reflowing the slides to the space left.
+    fn toggle_sidebar(&mut self) {
+        self.sidebar = !self.sidebar;
+        let scroll_offset = self.scroll_offset;
+        self.reflow();
+        self.scroll_offset = scroll_offset;
+    }
+
+    /// Index of the slide listed at column `x` and row `y` of the terminal,
+    /// if the slide list is shown there.
+    fn sidebar_slide_at(&self, x: u16, y: u16) -> Option<usize> {
+        let (area, first) = self.sidebar_view.get();
+        if self.sidebar_width() == 0 || !area.contains(Position { x, y }) {
+            return None;
+        }
+        let index = first + usize::from(y - area.y);
+        (index < self.slides.len()).then_some(index)
+    }
+
+    /// Returns true if the deck has vertical sub-slides.
+    fn is_vertical(&self) -> bool {
+        self.front_matter.flag("vertical")
+    }
+
+    /// Makes `index` the current slide, resetting scroll and restarting its animations.
+    fn show_slide(&mut self, index: usize) {
+        if index != self.current_slide {
+            self.timer.enter(index);
+            if !self.keep_spoilers_revealed {
+                self.revealed_spoilers.clear();
+            }
+        }
+        self.current_slide = index;
+        self.scroll_offset = 0;
+        self.slide_entered = Instant::now();
+        self.fragment = 0;
+        self.fragment_entered = self.slide_entered;
+        self.pane_focused = false;
+    }
+
+    /// Grows or shrinks the terminal's font by `steps` steps, or with
+    /// `None` puts it back to its size at the start.
+    fn zoom(&mut self, out: &mut impl Write, steps: Option<i32>) -> io::Result<()> {
+        let zoomed = match steps {
+            Some(steps) => self.zoom.step(out, steps)?,
+            None => self.zoom.reset(out)?,
+        };
+        if !zoomed {
+            self.flash("This terminal does not let presentrs change its font size".to_string());
+        } else if self.zoom.steps() == 0 {
+            self.flash("Font size reset".to_string());
+        } else {
+            self.flash(format!("Font size {:+}", self.zoom.steps()));
+        }
+        Ok(())
+    }
+
+    /// Reveals the current slide's spoilers, or hides them again.
+    fn toggle_spoilers(&mut self) {
+        if !self
+            .slides
+            .get(self.current_slide)
+            .is_some_and(|slide| spoilers::any(&slide.text))
+        {
+            self.flash("This slide has no spoilers".to_string());
+        } else if !self.revealed_spoilers.remove(&self.current_slide) {
+            self.revealed_spoilers.insert(self.current_slide);
         }
     }
 
@@ -156,7 +1541,13 @@ impl App {
     /// Increases the scroll offset to show content below the current view.
     fn scroll_down(&mut self) {
         if !self.slides.is_empty() {
-            let max_scroll = self.slides[self.current_slide].lines.len().saturating_sub(1);
+            let output_lines: usize = self
+                .exec_output()
+                .iter()
+                .map(|(_, lines)| lines.len())
+                .sum();
+            let max_scroll = (self.slides[self.current_slide].text.lines.len() + output_lines)
+                .saturating_sub(1);
             if self.scroll_offset < max_scroll {
                 self.scroll_offset += 1;
             }
@@ -172,6 +1563,86 @@ impl App {
         }
     }
 
+    /// Switches to flashcard mode and shows the first card.
+    fn start_flashcards(&mut self, mut cards: Flashcards) {
+        let titles: Vec<_> = self.slides.iter().map(|slide| slide.title.clone()).collect();
+        if let Some(first) = cards.deal(&titles) {
+            self.show_slide(first);
+        }
+        self.flashcards = Some(cards);
+    }
+
+    /// Returns true if a flashcard is on screen with its answer still hidden.
+    fn card_hidden(&self) -> bool {
+        self.flashcards
+            .as_ref()
+            .is_some_and(|cards| !cards.is_revealed())
+    }
+
+    /// Shows the answer of the current flashcard.
+    fn reveal_card(&mut self) {
+        if let Some(cards) = self.flashcards.as_mut() {
+            cards.reveal();
+        }
+    }
+
+    /// Toggles whether the current flashcard is known, confirming in the status bar.
+    fn toggle_card_known(&mut self) {
+        if let Some(cards) = self.flashcards.as_mut() {
+            let message = if cards.toggle_known() {
+                "Marked card as known"
+            } else {
+                "Marked card as not known"
+            };
+            self.flash(message.to_string());
+        }
+    }
+
+    /// Moves between flashcards the way `action` moves between slides.
+    fn turn_card(&mut self, action: Action) {
+        let Some(cards) = self.flashcards.as_mut() else {
+            return;
+        };
+        let position = match action {
+            Action::NextSlide | Action::NextColumn | Action::NextInColumn => cards.position() + 1,
+            Action::PrevSlide | Action::PrevColumn | Action::PrevInColumn => {
+                cards.position().saturating_sub(1)
+            }
+            Action::FirstSlide => 0,
+            Action::LastSlide => cards.len().saturating_sub(1),
+            Action::GotoSlide(position) => position,
+            Action::ScrollDown
+            | Action::ScrollUp
+            | Action::NextSection
+            | Action::PrevSection => return,
+        };
+        if let Some(slide) = cards.turn_to(position) {
+            self.show_slide(slide);
+        }
+    }
+
+    /// Performs a navigation action.
+    ///
+    /// In flashcard mode, slide navigation moves between cards instead.
+    fn apply(&mut self, action: Action) {
+        match action {
+            Action::ScrollDown => self.scroll_down(),
+            Action::ScrollUp => self.scroll_up(),
+            _ if self.flashcards.is_some() => self.turn_card(action),
+            Action::NextSlide => self.next_slide(),
+            Action::PrevSlide => self.prev_slide(),
+            Action::FirstSlide => self.goto_first(),
+            Action::LastSlide => self.goto_last(),
+            Action::GotoSlide(index) => self.goto_slide(index),
+            Action::NextColumn => self.change_column(true),
+            Action::PrevColumn => self.change_column(false),
+            Action::NextInColumn => self.change_row(true),
+            Action::PrevInColumn => self.change_row(false),
+            Action::NextSection => self.next_section(),
+            Action::PrevSection => self.prev_section(),
+        }
+    }
+
     /// Returns the content of the currently displayed slide.
     ///
     /// # Returns
@@ -185,37 +1656,1123 @@ impl App {
             });
             &EMPTY_SLIDE
         } else {
-            &self.slides[self.current_slide]
+            &self.slides[self.current_slide].text
         }
     }
 
-    /// Returns a formatted string showing current slide position.
+    /// Returns the typing demo schedule of the currently displayed slide.
+    fn current_typing(&self) -> &[TypedLine] {
+        self.slides
+            .get(self.current_slide)
+            .map_or(&[], |slide| slide.typing.as_slice())
+    }
+
+    /// Returns the regions of the currently displayed slide.
+    fn current_regions(&self) -> &[Region] {
+        self.slides
+            .get(self.current_slide)
+            .map_or(&[], |slide| slide.regions.as_slice())
+    }
+
+    /// Returns the animation blocks of the currently displayed slide.
+    fn current_animations(&self) -> &[Animation] {
+        self.slides
+            .get(self.current_slide)
+            .map_or(&[], |slide| slide.animations.as_slice())
+    }
+
+    /// Returns how long to wait before the next animation frame, or `None` if
+    /// nothing on the current slide is animating.
+    fn animation_timeout(&self) -> Option<Duration> {
+        let elapsed = self.slide_entered.elapsed();
+        let typing = self
+            .current_typing()
+            .iter()
+            .any(|line| line.finished_at() > elapsed)
+            .then_some(Duration::from_millis(30));
+        self.current_animations()
+            .iter()
+            .filter_map(|animation| animation.next_frame_in(elapsed))
+            .chain(typing)
+            .min()
+    }
+
+    /// Returns how long the current slide stays up before advancing on its
+    /// own, or `None` if it waits for the presenter.
     ///
-    /// # Returns
+    /// A slide's `advance` directive overrides the global `--auto-advance`
+    /// interval. The last slide never advances, and neither do flashcards.
+    fn advance_interval(&self) -> Option<Duration> {
+        if self.flashcards.is_some() || self.current_slide + 1 >= self.slides.len() {
+            return None;
+        }
+        self.slides[self.current_slide].advance.or(self.auto_advance)
+    }
+
+    /// Time remaining until the current slide advances on its own.
     ///
-    /// A string in the format "current/total" (e.g., "3/10")
-    fn slide_info(&self) -> String {
-        if self.slides.is_empty() {
-            "0/0".to_string()
+    /// Each fragment stays up for the whole interval, not counting the time
+    /// timers were paused.
+    fn advance_timeout(&self) -> Option<Duration> {
+        if self.timer.is_paused() {
+            return None;
+        }
+        self.advance_interval()
+            .map(|interval| interval.saturating_sub(self.fragment_entered.elapsed()))
+    }
+
+    /// Pauses the talk's timers when the terminal loses the focus, with
+    /// `--pause-on-blur`, and starts them again when it gets it back.
+    #[cfg(not(feature = "termwiz"))]
+    fn focus_changed(&mut self, focused: bool) {
+        if !self.pause_on_blur {
+            return;
+        }
+        if focused {
+            let paused = self.timer.resume();
+            self.fragment_entered += paused;
         } else {
-            format!("{}/{}", self.current_slide + 1, self.slides.len())
+            self.timer.pause();
         }
     }
-}
 
-/// Parses markdown content into individual slides.
-///
-/// Slides are separated by H1 headings (`# Title`). All content between
-/// H1 headings becomes part of a single slide.
-///
-/// # Arguments
-///
-/// * `markdown` - The raw markdown content to parse
-/// * `theme_set` - Syntax highlighting themes
-/// * `syntax_set` - Syntax definitions for highlighting
-/// * `terminal_width` - Width of the terminal for centering H1 headings
-///
-/// # Returns
+    /// Draws the current slide's background image, if it has one, behind the
+    /// text already rendered into `area`.
+    fn draw_background(&self, area: Rect, buf: &mut Buffer) {
+        let Some(background) = self
+            .slides
+            .get(self.current_slide)
+            .and_then(|slide| slide.background.as_ref())
+        else {
+            return;
+        };
+        let mut backdrop = self.backdrop.borrow_mut();
+        let backdrop = match backdrop.take() {
+            Some(cached) if cached.fits(background, area.width, area.height) => {
+                backdrop.insert(cached)
+            }
+            _ => backdrop.insert(Backdrop::load(background, area.width, area.height)),
+        };
+        backdrop.composite(area, buf);
+    }
+
+    /// Returns the terminal region of the current slide, if it has one.
+    fn current_terminal(&self) -> Option<&TerminalRegion> {
+        self.slides
+            .get(self.current_slide)
+            .and_then(|slide| slide.terminal.as_ref())
+    }
+
+    /// Gives the keyboard to the current slide's terminal pane, starting its
+    /// command if it is not running yet.
+    fn focus_pane(&mut self) {
+        if self.current_terminal().is_none() {
+            self.flash("This slide has no terminal".to_string());
+            return;
+        }
+        let running = self
+            .panes
+            .get(&self.current_slide)
+            .is_some_and(|pane| !pane.exited());
+        if running {
+            self.pane_focused = true;
+            self.flash("Terminal focused, Ctrl-] returns to the slides".to_string());
+        } else {
+            self.request_exec(ExecRequest::Pane {
+                slide: self.current_slide,
+            });
+        }
+    }
+
+    /// Starts the terminal pane of `slide` and focuses it.
+    fn start_pane(&mut self, slide: usize) {
+        let Some(region) = self.slides.get(slide).and_then(|s| s.terminal.as_ref()) else {
+            return;
+        };
+        let cwd = self.working_dir();
+        let argv = self
+            .exec_policy
+            .wrap(pane::command_argv(&region.command), &cwd);
+        let area = self.pane_area.get();
+        match TerminalPane::spawn(&argv, &cwd, area.height.max(1), area.width.max(1)) {
+            Ok(pane) => {
+                self.panes.insert(slide, pane);
+                self.pane_focused = true;
+                self.flash("Terminal focused, Ctrl-] returns to the slides".to_string());
+            }
+            Err(e) => {
+                log::warn!("{e}");
+                self.flash(e);
+            }
+        }
+    }
+
+    /// Directory commands from the deck run in: the deck's own directory.
+    fn working_dir(&self) -> PathBuf {
+        deck_dir(&self.deck_path)
+    }
+
+    /// Runs `request` if the exec policy allows it, asking the presenter
+    /// first when the policy wants confirmation.
+    fn request_exec(&mut self, request: ExecRequest) {
+        if !self.exec_enabled {
+            self.flash("Running code is disabled by --no-exec".to_string());
+            return;
+        }
+        let names: Vec<String> = match request {
+            ExecRequest::Block { slide, index } => {
+                let lang = self.slides[slide].exec[index].lang.clone();
+                let program = self
+                    .block_runner(slide, index)
+                    .map(|runner| runner.program().to_string());
+                lang.into_iter().chain(program).collect()
+            }
+            ExecRequest::Pane { slide } => self.slides[slide]
+                .terminal
+                .as_ref()
+                .and_then(|region| pane::command_argv(&region.command).into_iter().next())
+                .into_iter()
+                .collect(),
+        };
+        if !self.exec_policy.allows(&names) {
+            let name = names.first().map_or("plain", String::as_str);
+            self.flash(format!("'{name}' is not in the exec allowlist"));
+            return;
+        }
+        if self.exec_policy.confirm && !self.exec_confirmed {
+            self.overlay = Some(Overlay::ConfirmExec(request));
+            return;
+        }
+        match request {
+            ExecRequest::Block { slide, index } => self.start_block(slide, index),
+            ExecRequest::Pane { slide } => self.start_pane(slide),
+        }
+    }
+
+    /// Describes what `request` will run, for the confirmation prompt.
+    fn describe_exec(&self, request: ExecRequest) -> String {
+        let dir = self.working_dir();
+        match request {
+            ExecRequest::Block { slide, index } => {
+                let command = self
+                    .block_runner(slide, index)
+                    .map(|runner| self.exec_policy.wrap(runner.command, &dir).join(" "))
+                    .unwrap_or_default();
+                let code = self.slides[slide].exec[index].code.trim_end();
+                format!("{command}\n\n{code}")
+            }
+            ExecRequest::Pane { slide } => self.slides[slide]
+                .terminal
+                .as_ref()
+                .map(|region| {
+                    self.exec_policy
+                        .wrap(pane::command_argv(&region.command), &dir)
+                        .join(" ")
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The runner configured for executable block `index` of `slide`.
+    fn block_runner(&self, slide: usize, index: usize) -> Option<Runner> {
+        let lang = self.slides.get(slide)?.exec.get(index)?.lang.as_deref()?;
+        self.exec_policy.runner(lang)
+    }
+
+    /// Forwards a key press to the focused terminal pane.
+    fn send_to_pane(&mut self, key: KeyEvent) {
+        if let Some(pane) = self.panes.get_mut(&self.current_slide) {
+            pane.send_key(key);
+        }
+    }
+
+    /// Matches the current pane's terminal size to the area it was last drawn
+    /// in, and releases focus once its command has exited.
+    fn fit_pane(&mut self) {
+        let area = self.pane_area.get();
+        if let Some(pane) = self.panes.get_mut(&self.current_slide) {
+            pane.resize(area.height, area.width);
+            if pane.exited() {
+                self.pane_focused = false;
+            }
+        }
+    }
+
+    /// Returns how often to redraw while the current slide shows a live
+    /// terminal, or `None` if it has none.
+    fn pane_timeout(&self) -> Option<Duration> {
+        self.panes
+            .contains_key(&self.current_slide)
+            .then_some(Duration::from_millis(30))
+    }
+
+    /// Runs the next executable block on the current slide that has not run
+    /// yet. Once every block has run, starts over from the first one.
+    fn run_next_block(&mut self) {
+        let slide = self.current_slide;
+        let Some(blocks) = self
+            .slides
+            .get(slide)
+            .map(|s| &s.exec)
+            .filter(|blocks| !blocks.is_empty())
+        else {
+            self.flash("This slide has no exec blocks".to_string());
+            return;
+        };
+        let index = match (0..blocks.len()).find(|i| !self.runs.contains_key(&(slide, *i))) {
+            Some(index) => index,
+            None if self.runs.iter().any(|(key, run)| key.0 == slide && run.is_running()) => {
+                self.flash("Still running, Ctrl-C to interrupt".to_string());
+                return;
+            }
+            None => {
+                self.runs.retain(|key, _| key.0 != slide);
+                0
+            }
+        };
+
+        if self.block_runner(slide, index).is_none() {
+            let lang = blocks[index].lang.as_deref().unwrap_or("plain");
+            self.flash(format!(
+                "No runner for {lang} blocks, add one to the config"
+            ));
+            return;
+        }
+        self.request_exec(ExecRequest::Block { slide, index });
+    }
+
+    /// Starts executable block `index` of `slide`.
+    fn start_block(&mut self, slide: usize, index: usize) {
+        let Some(runner) = self.block_runner(slide, index) else {
+            return;
+        };
+        let code = &self.slides[slide].exec[index].code;
+        match self.exec_policy.start(&runner, code, &self.working_dir()) {
+            Ok(run) => {
+                self.runs.insert((slide, index), run);
+            }
+            Err(e) => {
+                let message = format!("Failed to run {}: {}", runner.program(), e);
+                log::warn!("{message}");
+                self.flash(message);
+            }
+        }
+    }
+
+    /// Interrupts every command still running on the current slide.
+    fn interrupt_runs(&mut self) {
+        for ((slide, _), run) in &mut self.runs {
+            if *slide == self.current_slide {
+                run.interrupt();
+            }
+        }
+    }
+
+    /// Checks which running commands have finished.
+    fn poll_runs(&mut self) {
+        self.runs.values_mut().for_each(Execution::poll);
+    }
+
+    /// Returns how often to redraw while a command on the current slide runs.
+    fn exec_timeout(&self) -> Option<Duration> {
+        self.runs
+            .iter()
+            .any(|((slide, _), run)| *slide == self.current_slide && run.is_running())
+            .then_some(Duration::from_millis(80))
+    }
+
+    /// Rendered output of the current slide's executed blocks, as pairs of
+    /// the slide line the output follows and the output lines, in slide order.
+    fn exec_output(&self) -> Vec<(usize, Vec<Line<'static>>)> {
+        let Some(slide) = self.slides.get(self.current_slide) else {
+            return Vec::new();
+        };
+        slide
+            .exec
+            .iter()
+            .enumerate()
+            .filter_map(|(i, block)| {
+                let run = self.runs.get(&(self.current_slide, i))?;
+                Some((block.end, run.render()))
+            })
+            .collect()
+    }
+
+    /// Returns the headings that the first line in view falls under, from
+    /// the outermost in (e.g. `Ownership › Moves › Partial moves`), or
+    /// `None` if the slide is not scrolled.
+    fn breadcrumb(&self) -> Option<String> {
+        let slide = self.slides.get(self.current_slide)?;
+        if self.scroll_offset == 0 || self.card_hidden() {
+            return None;
+        }
+        let exec_output = self.exec_output();
+        let mut trail: Vec<&Heading> = Vec::new();
+        for heading in &slide.headings {
+            // Output of executed blocks above the heading pushes it down.
+            let output_above: usize = exec_output
+                .iter()
+                .filter(|(end, _)| *end <= heading.line)
+                .map(|(_, output)| output.len())
+                .sum();
+            if heading.line + output_above > self.scroll_offset {
+                break;
+            }
+            while trail.last().is_some_and(|outer| outer.level >= heading.level) {
+                trail.pop();
+            }
+            trail.push(heading);
+        }
+        if trail.is_empty() {
+            return None;
+        }
+        let names: Vec<&str> = trail.iter().map(|heading| heading.text.trim()).collect();
+        Some(names.join(" › "))
+    }
+
+    /// Returns the current slide as unstyled text.
+    fn current_slide_plain_text(&self) -> String {
+        self.slides
+            .get(self.current_slide)
+            .map(Slide::plain_text)
+            .unwrap_or_default()
+    }
+
+    /// Returns the raw markdown source of the current slide.
+    fn current_slide_source(&self) -> &str {
+        self.slides
+            .get(self.current_slide)
+            .and_then(|slide| self.markdown_content.get(slide.source.clone()))
+            .unwrap_or("")
+            .trim()
+    }
+
+    /// Ends the run and returns how long each slide was shown.
+    fn into_rehearsal(self) -> Rehearsal {
+        let titles: Vec<_> = self.slides.iter().map(|slide| slide.title.clone()).collect();
+        self.timer.finish(&titles)
+    }
+
+    /// Shows `message` in the status bar for a few seconds.
+    fn flash(&mut self, message: String) {
+        self.status_message = Some((message, Instant::now()));
+    }
+
+    /// Returns the status bar message if it has not expired yet.
+    fn active_status_message(&self) -> Option<&str> {
+        self.status_message
+            .as_ref()
+            .filter(|(_, set)| set.elapsed() < STATUS_MESSAGE_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// Time until the status bar message expires and the bar must be redrawn.
+    fn status_message_timeout(&self) -> Option<Duration> {
+        self.status_message
+            .as_ref()
+            .and_then(|(_, set)| STATUS_MESSAGE_DURATION.checked_sub(set.elapsed()))
+    }
+
+    /// Attaches a running web server and announces its address. With a
+    /// remote, shows the QR code to open it.
+    fn start_server(&mut self, server: Server) {
+        self.flash(format!("Taking questions at {}", server.url()));
+        if server.remote_url().is_some() {
+            self.overlay = Some(Overlay::Remote);
+        }
+        self.server = Some(server);
+    }
+
+    /// Handles events queued by the web server since the last frame, and
+    /// returns the moves requested from the remote.
+    fn poll_server(&mut self) -> Vec<Action> {
+        let Some(server) = &self.server else {
+            return Vec::new();
+        };
+        let mut received = 0;
+        let mut actions = Vec::new();
+        for event in server.drain() {
+            match event {
+                ServerEvent::Question(text) => {
+                    log::info!("Question received: {text}");
+                    self.questions.push(Question {
+                        text,
+                        answered: false,
+                    });
+                    received += 1;
+                }
+                ServerEvent::Next => actions.push(Action::NextSlide),
+                ServerEvent::Previous => actions.push(Action::PrevSlide),
+                ServerEvent::Handoff(name) => {
+                    log::info!("{name} took control of the remote");
+                    self.flash(format!("{name} took control"));
+                }
+            }
+        }
+        if received > 0 {
+            self.flash(format!(
+                "{} new question{} (a to view)",
+                received,
+                if received == 1 { "" } else { "s" }
+            ));
+        }
+        actions
+    }
+
+    /// Shows the remote the slide on screen, its notes, and the time spent.
+    fn publish_remote(&self) {
+        let Some(server) = &self.server else {
+            return;
+        };
+        let slide = self.slides.get(self.current_slide);
+        server.publish(RemoteState {
+            slide: self.current_slide + 1,
+            slides: self.slides.len(),
+            title: slide.map(Slide::label).unwrap_or_default(),
+            notes: self.current_notes().unwrap_or_default(),
+            elapsed: self.timer.elapsed().as_secs(),
+        });
+    }
+
+    /// Returns the moves requested from the presenter console since the
+    /// last frame.
+    fn poll_console(&self) -> Vec<Action> {
+        let Some(console) = &self.console else {
+            return Vec::new();
+        };
+        console
+            .drain()
+            .into_iter()
+            .map(|event| match event {
+                ConsoleEvent::Next => Action::NextSlide,
+                ConsoleEvent::Previous => Action::PrevSlide,
+            })
+            .collect()
+    }
+
+    /// Shows the presenter console the slide on screen, its notes, the next
+    /// slide, and the time spent.
+    fn publish_console(&mut self) {
+        if self.console.is_none() {
+            return;
+        }
+        let slide = self.slides.get(self.current_slide);
+        let next = self.slides.get(self.current_slide + 1);
+        // Titles are already on the console's frame.
+        let body = |slide: &Slide| match &slide.title {
+            Some(title) => {
+                let text = slide.plain_text();
+                text.strip_prefix(title.trim()).unwrap_or(&text).trim_start().to_string()
+            }
+            None => slide.plain_text(),
+        };
+        let state = ConsoleState {
+            slide: self.current_slide + 1,
+            slides: self.slides.len(),
+            title: slide.map(Slide::label).unwrap_or_default(),
+            text: slide.map(body).unwrap_or_default(),
+            notes: self.current_notes().unwrap_or_default(),
+            next_title: next.and_then(|slide| slide.title.clone()),
+            next_text: next.map(body).unwrap_or_default(),
+            elapsed: self.timer.elapsed().as_secs(),
+        };
+        if let Some(console) = self.console.as_mut() {
+            console.publish(state);
+        }
+    }
+
+    /// Opens or closes the QR code for the presenter remote.
+    fn toggle_remote(&mut self) {
+        if self.server.as_ref().and_then(Server::remote_url).is_none() {
+            self.flash("Start with --serve <ADDR> --remote to use a phone as a remote".to_string());
+        } else if self.overlay == Some(Overlay::Remote) {
+            self.overlay = None;
+        } else {
+            self.overlay = Some(Overlay::Remote);
+        }
+    }
+
+    /// Time until the web server's queue should be checked again.
+    fn server_timeout(&self) -> Option<Duration> {
+        self.server.as_ref().map(|_| SERVER_POLL_INTERVAL)
+    }
+
+    /// Time until the presenter console's queue should be checked again.
+    fn console_timeout(&self) -> Option<Duration> {
+        self.console.as_ref().map(|_| SERVER_POLL_INTERVAL)
+    }
+
+    /// Time until the broadcast should be checked for new viewers, who are
+    /// sent their first frame on the next redraw.
+    fn broadcast_timeout(&self) -> Option<Duration> {
+        self.broadcast.as_ref().map(|_| SERVER_POLL_INTERVAL)
+    }
+
+    /// Number of audience questions not yet marked as answered.
+    fn unanswered_questions(&self) -> usize {
+        self.questions.iter().filter(|q| !q.answered).count()
+    }
+
+    /// Opens or closes the audience questions panel.
+    fn toggle_questions(&mut self) {
+        if self.server.is_none() {
+            self.flash("Start with --serve <ADDR> to take audience questions".to_string());
+        } else if self.overlay == Some(Overlay::Questions) {
+            self.overlay = None;
+        } else {
+            self.overlay = Some(Overlay::Questions);
+        }
+    }
+
+    /// Speaker notes for the current slide, from the deck and its companion
+    /// notes file.
+    fn current_notes(&self) -> Option<String> {
+        let slide = self.slides.get(self.current_slide)?;
+        self.companion_notes
+            .merge(&slide.notes, self.current_slide, slide.title.as_deref())
+    }
+
+    /// Returns the presenter's annotations on the current slide.
+    fn current_annotations(&self) -> &[String] {
+        self.slides
+            .get(self.current_slide)
+            .map_or(&[], |slide| self.annotations.get(&slide.label()))
+    }
+
+    /// Opens the prompt for an annotation on the current slide.
+    fn start_annotation(&mut self) {
+        if !self.slides.is_empty() {
+            self.annotation_draft.clear();
+            self.overlay = Some(Overlay::Annotate);
+        }
+    }
+
+    /// Saves the annotation typed into the prompt, unless it is blank.
+    fn save_annotation(&mut self) {
+        let note = std::mem::take(&mut self.annotation_draft);
+        let note = note.trim();
+        let Some(slide) = self.slides.get(self.current_slide) else {
+            return;
+        };
+        if note.is_empty() {
+            return;
+        }
+        let message = match self.annotations.add(&slide.label(), note.to_string()) {
+            Ok(()) => "Saved annotation".to_string(),
+            Err(e) => format!("Failed to save annotation: {e}"),
+        };
+        self.flash(message);
+    }
+
+    /// Opens the prompt for a command such as `goto benchmarks`.
+    fn start_command(&mut self) {
+        self.command_draft.clear();
+        self.overlay = Some(Overlay::Command);
+    }
+
+    /// Runs the command typed into the prompt, returning the navigation it
+    /// triggers, if any.
+    fn submit_command(&mut self) -> Option<Action> {
+        let command = std::mem::take(&mut self.command_draft);
+        let command = command.trim();
+        // A bare number is short for going to that slide.
+        let (name, argument) = match command.parse::<usize>() {
+            Ok(_) => ("goto", command),
+            Err(_) => command.split_once(' ').unwrap_or((command, "")),
+        };
+        match name {
+            "" => None,
+            "goto" | "g" => match self.find_slide(argument) {
+                Some(index) => Some(Action::GotoSlide(index)),
+                None => {
+                    self.flash(format!("No slide '{}'", argument.trim()));
+                    None
+                }
+            },
+            _ => {
+                self.flash(format!("Unknown command '{name}'"));
+                None
+            }
+        }
+    }
+
+    /// Opens the prompt for text to search the deck for.
+    fn start_search(&mut self) {
+        if !self.slides.is_empty() {
+            self.search_draft.clear();
+            self.overlay = Some(Overlay::Search);
+        }
+    }
+
+    /// Starts a search for the text typed into the prompt, or for the last
+    /// search's if nothing was typed, and jumps to its first match from the
+    /// current slide on.
+    fn submit_search(&mut self) -> Option<Action> {
+        let mut query = std::mem::take(&mut self.search_draft);
+        if query.is_empty() {
+            query = self.last_search.clone();
+        }
+        if query.is_empty() {
+            return None;
+        }
+        self.last_search = query.clone();
+        self.search = Some(Search::new(query));
+        self.step_search(true)
+    }
+
+    /// Jumps to the next match of the active search, or the previous one if
+    /// `forward` is false, scrolling it into view.
+    ///
+    /// Returns the move to another slide, if the match is on one.
+    fn step_search(&mut self, forward: bool) -> Option<Action> {
+        let search = self.search.as_mut()?;
+        let matches = search.matches(self.slides.iter().map(|slide| &slide.text));
+        let origin = Match {
+            slide: self.current_slide,
+            line: self.scroll_offset,
+            start: 0,
+        };
+        let Some((found, wrapped)) = search.advance(&matches, origin, forward) else {
+            let message = format!("No matches for '{}'", search.query());
+            self.search = None;
+            self.flash(message);
+            return None;
+        };
+        let position = matches.iter().position(|m| *m == found).unwrap_or(0) + 1;
+        let mut message = format!(
+            "Match {} of {} for '{}'",
+            position,
+            matches.len(),
+            search.query()
+        );
+        if wrapped {
+            message.push_str(" (wrapped)");
+        }
+        let action = (found.slide != self.current_slide).then_some(Action::GotoSlide(found.slide));
+        if let Some(action) = action {
+            self.apply(action);
+        }
+        let rows = usize::from(self.slide_rows.get()).max(1);
+        if found.line < self.scroll_offset || found.line >= self.scroll_offset + rows {
+            self.scroll_offset = found.line.saturating_sub(rows / 2);
+        }
+        self.flash(message);
+        action
+    }
+
+    /// Opens the footnotes panel for the current slide.
+    fn show_footnotes(&mut self) {
+        if self.current_footnotes().is_empty() {
+            self.flash("This slide has no footnotes".to_string());
+        } else {
+            self.overlay = Some(Overlay::Footnotes);
+        }
+    }
+
+    /// Problems with slide `index` that keep it from being shown as written:
+    /// what the parser could not show, and a layout or theme that does not
+    /// exist.
+    fn slide_problems(&self, index: usize) -> Vec<Finding> {
+        let Some(slide) = self.slides.get(index) else {
+            return Vec::new();
+        };
+        let mut problems: Vec<Finding> = slide
+            .warnings
+            .iter()
+            .map(|warning| Finding {
+                rule: warning.rule,
+                slide: index + 1,
+                line: Some(check::line_of(&self.markdown_content, warning.offset)),
+                message: warning.message.clone(),
+            })
+            .collect();
+        if let Some(name) = &slide.layout
+            && self.layouts.get(name).is_none()
+        {
+            problems.push(Finding {
+                rule: "layout",
+                slide: index + 1,
+                line: None,
+                message: format!("Unknown layout '{name}'"),
+            });
+        }
+        if let Some(name) = &slide.theme
+            && self.themes.resolve(name, self.theme).is_none()
+        {
+            problems.push(Finding {
+                rule: "theme",
+                slide: index + 1,
+                line: None,
+                message: format!("Unknown theme '{name}'"),
+            });
+        }
+        // Only the first slide with an id can be jumped to by it.
+        if let Some(id) = &slide.id
+            && let Some(first) = self.find_slide(id)
+            && self.slides[first].source != slide.source
+        {
+            problems.push(Finding {
+                rule: "id",
+                slide: index + 1,
+                line: None,
+                message: format!("Id '{id}' is already used by slide {}", first + 1),
+            });
+        }
+        problems
+    }
+
+    /// Problems with every slide of the deck, as `--strict` and
+    /// `presentrs check` report them.
+    fn deck_problems(&self) -> Vec<Finding> {
+        (0..self.slides.len())
+            // The pages of a paginated slide share its problems.
+            .filter(|&index| {
+                index == 0 || self.slides[index].source != self.slides[index - 1].source
+            })
+            .flat_map(|index| self.slide_problems(index))
+            .collect()
+    }
+
+    /// Footnotes referenced on the current slide.
+    fn current_footnotes(&self) -> &[Footnote] {
+        self.slides
+            .get(self.current_slide)
+            .map_or(&[], |slide| slide.footnotes.as_slice())
+    }
+
+    /// Opens the links panel for the current slide.
+    fn show_links(&mut self) {
+        if self.current_links().is_empty() {
+            self.flash("This slide has no links".to_string());
+        } else {
+            self.selected_link = 0;
+            self.overlay = Some(Overlay::Links);
+        }
+    }
+
+    /// Links on the current slide.
+    fn current_links(&self) -> &[Link] {
+        self.slides
+            .get(self.current_slide)
+            .map_or(&[], |slide| slide.links.as_slice())
+    }
+
+    /// Opens the link highlighted in the links panel with the system's
+    /// default handler, e.g. the web browser.
+    fn open_selected_link(&mut self) {
+        let Some(url) = self
+            .current_links()
+            .get(self.selected_link)
+            .map(|link| link.url.clone())
+        else {
+            return;
+        };
+        let message = match open_link(&url, &self.working_dir()) {
+            Ok(()) => format!("Opened {url}"),
+            Err(e) => format!("Failed to open {url}: {e}"),
+        };
+        self.flash(message);
+    }
+
+    /// Opens the overview of every slide, with the current one selected.
+    fn show_overview(&mut self) {
+        if self.flashcards.is_some() {
+            self.flash("The overview is not available for flashcards".to_string());
+        } else {
+            self.overview_selection = self.current_slide;
+            self.overlay = Some(Overlay::Overview);
+        }
+    }
+
+    /// Drops the slide number being typed, if any, along with its prompt in
+    /// the status bar, and returns its digits.
+    fn take_slide_number(&mut self) -> String {
+        let slide_number = std::mem::take(&mut self.slide_number);
+        if !slide_number.is_empty() {
+            self.status_message = None;
+        }
+        slide_number
+    }
+
+    /// Opens the slide picker with every slide listed.
+    fn show_picker(&mut self) {
+        if self.flashcards.is_some() {
+            self.flash("The slide picker is not available for flashcards".to_string());
+        } else if !self.slides.is_empty() {
+            self.picker = Picker::default();
+            self.overlay = Some(Overlay::Picker);
+        }
+    }
+
+    /// Slides listed by the slide picker for the letters typed, best first.
+    /// The pages of a paginated slide are listed once.
+    fn picker_candidates(&self) -> Vec<Candidate> {
+        let mut titles = Vec::new();
+        for (index, slide) in self.slides.iter().enumerate() {
+            if index == 0 || slide.source != self.slides[index - 1].source {
+                titles.push((index, slide.label()));
+            }
+        }
+        self.picker
+            .filter(titles.iter().map(|(index, title)| (*index, title.as_str())))
+    }
+
+    /// Column and row of the slide at `index` in the overview.
+    ///
+    /// Decks with vertical sub-slides show each H1 column as a column of the
+    /// grid; other decks fill rows left to right.
+    fn overview_position(&self, index: usize) -> (usize, usize) {
+        if self.is_vertical() {
+            let slide = &self.slides[index];
+            (slide.column, slide.row)
+        } else {
+            let width = self.overview_width.get().max(1);
+            (index % width, index / width)
+        }
+    }
+
+    /// Moves the overview's selection `dx` columns right and `dy` rows down.
+    ///
+    /// Moving into a shorter column selects its last slide.
+    fn move_overview_selection(&mut self, dx: isize, dy: isize) {
+        if self.slides.is_empty() {
+            return;
+        }
+        let (x, y) = self.overview_position(self.overview_selection);
+        let (Some(x), Some(y)) = (x.checked_add_signed(dx), y.checked_add_signed(dy)) else {
+            return;
+        };
+        let target = (0..self.slides.len())
+            .map(|index| (index, self.overview_position(index)))
+            .filter(|&(_, (column, row))| column == x && row <= y)
+            .max_by_key(|&(_, (_, row))| row);
+        if let Some((index, _)) = target {
+            self.overview_selection = index;
+        }
+    }
+
+    /// Handles a key press while an overlay is open, returning the
+    /// navigation it triggers, if any.
+    fn overlay_key(&mut self, code: KeyCode) -> Option<Action> {
+        if self.overlay == Some(Overlay::Search) {
+            match code {
+                KeyCode::Enter => {
+                    self.overlay = None;
+                    return self.submit_search();
+                }
+                KeyCode::Esc => self.overlay = None,
+                KeyCode::Backspace => {
+                    self.search_draft.pop();
+                }
+                KeyCode::Char(c) => self.search_draft.push(c),
+                _ => {}
+            }
+            return None;
+        }
+        if self.overlay == Some(Overlay::Command) {
+            match code {
+                KeyCode::Enter => {
+                    self.overlay = None;
+                    return self.submit_command();
+                }
+                KeyCode::Esc => self.overlay = None,
+                KeyCode::Backspace => {
+                    self.command_draft.pop();
+                }
+                KeyCode::Char(c) => self.command_draft.push(c),
+                _ => {}
+            }
+            return None;
+        }
+        if self.overlay == Some(Overlay::Picker) {
+            match code {
+                KeyCode::Esc => self.overlay = None,
+                KeyCode::Up => self.picker.step(-1, self.picker_candidates().len()),
+                KeyCode::Down => self.picker.step(1, self.picker_candidates().len()),
+                KeyCode::Enter => {
+                    let candidates = self.picker_candidates();
+                    if let Some(candidate) = candidates.get(self.picker.selected()) {
+                        self.overlay = None;
+                        return Some(Action::GotoSlide(candidate.slide));
+                    }
+                }
+                KeyCode::Backspace => self.picker.pop(),
+                KeyCode::Char(c) => self.picker.push(c),
+                _ => {}
+            }
+            return None;
+        }
+        if self.overlay == Some(Overlay::Annotate) {
+            match code {
+                KeyCode::Enter => {
+                    self.overlay = None;
+                    self.save_annotation();
+                }
+                KeyCode::Esc => self.overlay = None,
+                KeyCode::Backspace => {
+                    self.annotation_draft.pop();
+                }
+                KeyCode::Char(c) => self.annotation_draft.push(c),
+                _ => {}
+            }
+            return None;
+        }
+        if let Some(Overlay::ConfirmExec(request)) = self.overlay {
+            match code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    self.overlay = None;
+                    self.exec_confirmed = true;
+                    self.request_exec(request);
+                }
+                KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => self.overlay = None,
+                _ => {}
+            }
+            return None;
+        }
+        if self.overlay == Some(Overlay::Links) {
+            match code {
+                KeyCode::Esc | KeyCode::Char('o') | KeyCode::Char('q') => self.overlay = None,
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.selected_link = self.selected_link.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.selected_link =
+                        (self.selected_link + 1).min(self.current_links().len().saturating_sub(1));
+                }
+                KeyCode::Enter => {
+                    self.overlay = None;
+                    self.open_selected_link();
+                }
+                _ => {}
+            }
+            return None;
+        }
+        if self.overlay == Some(Overlay::Overview) {
+            match code {
+                KeyCode::Esc | KeyCode::Char('g') | KeyCode::Char('q') => self.overlay = None,
+                KeyCode::Left | KeyCode::Char('h') => self.move_overview_selection(-1, 0),
+                KeyCode::Right | KeyCode::Char('l') => self.move_overview_selection(1, 0),
+                KeyCode::Up | KeyCode::Char('k') => self.move_overview_selection(0, -1),
+                KeyCode::Down | KeyCode::Char('j') => self.move_overview_selection(0, 1),
+                KeyCode::Enter => {
+                    self.overlay = None;
+                    return Some(Action::GotoSlide(self.overview_selection));
+                }
+                _ => {}
+            }
+            return None;
+        }
+        if self.overlay == Some(Overlay::Footnotes) {
+            if matches!(code, KeyCode::Esc | KeyCode::Char('f') | KeyCode::Char('q')) {
+                self.overlay = None;
+            }
+            return None;
+        }
+        if self.overlay == Some(Overlay::Inspect) {
+            if matches!(code, KeyCode::Esc | KeyCode::Char('i') | KeyCode::Char('q')) {
+                self.overlay = None;
+            }
+            return None;
+        }
+        if self.overlay == Some(Overlay::Remote) {
+            // Any key dismisses the QR code shown when the presentation starts.
+            self.overlay = None;
+            return None;
+        }
+        match code {
+            KeyCode::Esc | KeyCode::Char('a') | KeyCode::Char('q') => self.overlay = None,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected_question = self.selected_question.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected_question =
+                    (self.selected_question + 1).min(self.questions.len().saturating_sub(1));
+            }
+            KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Char('x') => {
+                if let Some(question) = self.questions.get_mut(self.selected_question) {
+                    question.answered = !question.answered;
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Saves `buffer` (the last rendered frame) in the working directory as
+    /// `<deck>-slide-<n>.ans`, never overwriting an earlier screenshot.
+    fn save_screenshot(&mut self, buffer: &Buffer) {
+        let stem = self
+            .deck_path
+            .file_stem()
+            .map_or("slides".into(), |stem| stem.to_string_lossy());
+        let base = format!("{}-slide-{}", stem, self.current_slide + 1);
+        let mut path = PathBuf::from(format!("{base}.ans"));
+        let mut n = 2;
+        while path.exists() {
+            path = PathBuf::from(format!("{base}-{n}.ans"));
+            n += 1;
+        }
+        let message = match record::save_screenshot(buffer, &path) {
+            Ok(()) => format!("Saved {}", path.display()),
+            Err(e) => format!("Screenshot failed: {e}"),
+        };
+        self.flash(message);
+    }
+
+    /// Returns a formatted string showing current slide position.
+    ///
+    /// # Returns
+    ///
+    /// A string in the format "current/total" (e.g., "3/10")
+    fn slide_info(&self) -> String {
+        if self.slides.is_empty() {
+            "0/0".to_string()
+        } else {
+            format!("{}/{}", self.current_slide + 1, self.slides.len())
+        }
+    }
+
+    /// Estimated time to present each slide, for the timeline bar.
+    fn slide_durations(&self) -> Vec<Duration> {
+        // Rehearsed times only fit a deck that still has the same slides.
+        let rehearsed = (self.rehearsed.len() == self.slides.len()).then_some(&self.rehearsed);
+        self.slides
+            .iter()
+            .enumerate()
+            .map(|(index, slide)| {
+                slide
+                    .advance
+                    .or_else(|| rehearsed.and_then(|times| times[index]))
+                    .unwrap_or_else(|| {
+                        timeline::estimate(slide.plain_text().split_whitespace().count())
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Parses markdown content into individual slides.
+///
+/// Slides are separated by H1 headings (`# Title`), or by `separator` if it
+/// is something else. All content between separators becomes part of a
+/// single slide.
+///
+/// # Arguments
+///
+/// * `markdown` - The raw markdown content to parse
+/// * `front_matter` - The deck's front matter; `vertical: true` makes H2
+///   headings start sub-slides
+/// * `base_dir` - Directory that relative image paths are resolved against
+/// * `theme` - Colors to draw the slides in
+/// * `theme_set` - Syntax highlighting themes
+/// * `highlighter` - Highlighter for code blocks
+/// * `terminal_width` - Width of the terminal for centering H1 headings
+/// * `density` - How many blank lines follow each block, and how table
+///   cells are padded
+/// * `html` - Whether raw HTML is left out, shown as text, or styled
+/// * `separator` - What separates slides
+///
+/// # Returns
 ///
 /// A vector of formatted text, each representing the content of one slide
 ///
@@ -227,25 +2784,56 @@ impl App {
 /// - Emphasis (*italic*, **bold**) with proper styling
 /// - Inline code (`code`) with styling
 /// - Code blocks with syntax highlighting (```rust```, ```python```)
+#[allow(clippy::too_many_arguments, clippy::collapsible_match)]
 fn parse_markdown_to_slides(
     markdown: &str,
+    front_matter: &FrontMatter,
+    base_dir: &Path,
+    theme: &Theme,
     theme_set: &ThemeSet,
-    syntax_set: &SyntaxSet,
+    highlighter: &Highlighter,
     terminal_width: u16,
-) -> Vec<Text<'static>> {
+    density: Density,
+    html: HtmlPolicy,
+    separator: &Separator,
+) -> Vec<Slide> {
+    // Notes in `::: notes` blocks are added to their slides once parsed.
+    let (markdown, note_blocks) = notes::take_blocks(markdown);
+    // Separator lines are taken out, and slides split where they were.
+    let (markdown, separator_lines) = separator.take_lines(&markdown);
+    let mut separator_lines = separator_lines.into_iter().peekable();
+    let markdown = markdown.as_str();
     let mut options = Options::empty();
     options.insert(Options::ENABLE_TABLES);
-    let parser = MarkdownParser::new_ext(markdown, options);
+    options.insert(Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    let mut parser = MarkdownParser::new_ext(markdown, options)
+        .into_offset_iter()
+        .peekable();
     let mut slides = Vec::new();
     let mut current_slide_lines: Vec<Line<'static>> = Vec::new();
     let mut current_line_spans: Vec<Span<'static>> = Vec::new();
+    let vertical = front_matter.flag("vertical");
     let mut in_heading = false;
+    // Whether the heading being parsed gives the slide its title
+    let mut in_title = false;
+    // Text of the heading being parsed
+    let mut heading_text = String::new();
     let mut heading_level = HeadingLevel::H1;
     let mut in_strong = false;
     let mut in_emphasis = false;
+    // Whether the text being parsed is between `||` spoiler markers
+    let mut in_spoiler = false;
     let mut in_code_block = false;
     let mut code_block_lang: Option<String> = None;
+    let mut code_block_modifiers: Vec<String> = Vec::new();
     let mut code_block_content = String::new();
+    // Destination and alt text of the image being parsed
+    let mut image: Option<(String, String, usize)> = None;
+    // Metadata of the slide being built; its text is filled in by `finish_slide`.
+    let mut pending = Slide::default();
+    // Offset from slide entry at which the last typing demo on the slide ends
+    let mut typing_clock = Duration::ZERO;
     let mut in_table = false;
     // Stack of list contexts: None = unordered, Some(n) = next number for ordered list
     let mut list_stack: Vec<Option<u64>> = Vec::new();
@@ -253,8 +2841,16 @@ fn parse_markdown_to_slides(
     let mut table_header_rows: usize = 0;
     let mut current_table_row: Vec<String> = Vec::new();
     let mut current_cell_content = String::new();
+    // Label and text of the footnote definition being parsed
+    let mut footnote: Option<(String, String)> = None;
+    // Marker shown before the first line of a footnote definition
+    let mut footnote_marker: Option<Span<'static>> = None;
+    // Footnote texts by label, from anywhere in the deck
+    let mut footnote_texts: HashMap<String, String> = HashMap::new();
+    // Link being parsed
+    let mut link: Option<Link> = None;
 
-    let theme = &theme_set.themes["base16-ocean.dark"];
+    let code_theme = &theme_set.themes[theme.code_theme.as_str()];
 
     // Inner width of the bordered Paragraph (terminal - 2 for left/right border columns).
     let effective_width: usize = (terminal_width as usize).saturating_sub(2);
@@ -278,62 +2874,217 @@ fn parse_markdown_to_slides(
             }
         };
 
-    let add_spacing = |lines: &mut Vec<Line<'static>>| {
+    let add_spacing = |lines: &mut Vec<Line<'static>>, element: Element| {
         if !lines.is_empty() {
-            lines.push(Line::from(""));
+            lines.extend((0..density.gap_after(element)).map(|_| Line::from("")));
+        }
+    };
+    // Blank columns on each side of a table cell's text
+    let pad = density.cell_padding();
+    let cell_padding = " ".repeat(pad);
+
+    // Moves the lines of the region being parsed out of the main content.
+    let close_region = |lines: &mut Vec<Line<'static>>,
+                        pending: &mut Slide,
+                        open_region: &mut Option<(regions::Kind, usize)>| {
+        if let Some((kind, start)) = open_region.take() {
+            let mut text: Vec<_> = lines.drain(start.min(lines.len())..).collect();
+            while text.last().is_some_and(|line| line.width() == 0) {
+                text.pop();
+            }
+            pending.regions.push(Region {
+                kind,
+                text: Text::from(text),
+            });
         }
     };
 
-    let finish_slide = |slides: &mut Vec<Text<'static>>, lines: &mut Vec<Line<'static>>| {
+    let finish_slide = |slides: &mut Vec<Slide>,
+                        lines: &mut Vec<Line<'static>>,
+                        pending: &mut Slide,
+                        open_region: &mut Option<(regions::Kind, usize)>,
+                        source_end: usize| {
+        close_region(lines, pending, open_region);
+        if (!pending.regions.is_empty() || pending.layout.is_some()) && pending.title.is_some() {
+            regions::center_title(lines);
+        }
         if !lines.is_empty() {
-            slides.push(Text::from(std::mem::take(lines)));
+            pending.text = Text::from(std::mem::take(lines));
+            pending.source.end = source_end;
+            slides.push(std::mem::take(pending));
+            pending.source.start = source_end;
         }
     };
 
-    for event in parser {
+    // Ends the slide at a separator, which belongs to neither slide.
+    let separate = |slides: &mut Vec<Slide>,
+                    lines: &mut Vec<Line<'static>>,
+                    pending: &mut Slide,
+                    open_region: &mut Option<(regions::Kind, usize)>,
+                    at: Range<usize>| {
+        finish_slide(slides, lines, pending, open_region, at.start);
+        if markdown[pending.source.start..at.start].trim().is_empty() {
+            pending.source.start = at.end;
+        }
+        pending.column = slides.last().map_or(0, |slide| slide.column + 1);
+        pending.row = 0;
+    };
+
+    // Text of the HTML block being parsed
+    let mut html_block: Option<String> = None;
+    // Kind of the region being parsed and the line it starts on
+    let mut open_region: Option<(regions::Kind, usize)> = None;
+    // Grid whose cells are being parsed
+    let mut grid: Option<regions::Grid> = None;
+    // Events that raw HTML was read as, to be handled before the parser's next
+    let mut queued: VecDeque<(MarkdownEvent, Range<usize>)> = VecDeque::new();
+
+    while let Some((event, range)) = queued.pop_front().or_else(|| parser.next()) {
+        let mut passed = None;
+        while let Some(line) = separator_lines.next_if(|line| line.start <= range.start) {
+            passed = Some(line);
+        }
+        if let Some(line) = passed {
+            push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+            separate(
+                &mut slides,
+                &mut current_slide_lines,
+                &mut pending,
+                &mut open_region,
+                line,
+            );
+            typing_clock = Duration::ZERO;
+        }
+        // HTML blocks arrive a line at a time, but a comment may span several.
+        let event = match event {
+            MarkdownEvent::Start(Tag::HtmlBlock) => {
+                html_block = Some(String::new());
+                continue;
+            }
+            MarkdownEvent::Html(html) if html_block.is_some() => {
+                html_block.get_or_insert_with(String::new).push_str(&html);
+                continue;
+            }
+            MarkdownEvent::End(TagEnd::HtmlBlock) => match html_block.take() {
+                Some(html) => MarkdownEvent::Html(html.into()),
+                None => continue,
+            },
+            event => event,
+        };
+        // HTML that is shown is handled as the markdown it stands for.
+        if let MarkdownEvent::Html(markup) | MarkdownEvent::InlineHtml(markup) = &event
+            && let Some(events) =
+                html.translate(markup, matches!(event, MarkdownEvent::Html(_)))
+        {
+            queued.extend(events.into_iter().map(|event| (event, range.clone())));
+            continue;
+        }
         match event {
+            MarkdownEvent::Start(Tag::MetadataBlock(_)) => {
+                // Front matter is read separately and never shown.
+                for (event, range) in parser.by_ref() {
+                    if let MarkdownEvent::End(TagEnd::MetadataBlock(_)) = event {
+                        pending.source.start = range.end;
+                        break;
+                    }
+                }
+            }
             MarkdownEvent::Start(Tag::Heading {
                 level: HeadingLevel::H1,
                 ..
-            }) => {
+            }) if *separator == Separator::Heading => {
                 push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
                 if !current_slide_lines.is_empty() {
-                    finish_slide(&mut slides, &mut current_slide_lines);
+                    finish_slide(
+                        &mut slides,
+                        &mut current_slide_lines,
+                        &mut pending,
+                        &mut open_region,
+                        range.start,
+                    );
+                    typing_clock = Duration::ZERO;
                 }
+                pending.column = slides.last().map_or(0, |slide| slide.column + 1);
+                pending.row = 0;
                 in_heading = true;
+                in_title = true;
                 heading_level = HeadingLevel::H1;
             }
+            MarkdownEvent::Start(Tag::Heading {
+                level: HeadingLevel::H2,
+                ..
+            }) if vertical => {
+                push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                if !current_slide_lines.is_empty() {
+                    finish_slide(
+                        &mut slides,
+                        &mut current_slide_lines,
+                        &mut pending,
+                        &mut open_region,
+                        range.start,
+                    );
+                    typing_clock = Duration::ZERO;
+                    if let Some(above) = slides.last() {
+                        pending.column = above.column;
+                        pending.row = above.row + 1;
+                    }
+                }
+                in_heading = true;
+                in_title = true;
+                heading_level = HeadingLevel::H2;
+            }
             MarkdownEvent::Start(Tag::Heading { level, .. }) => {
                 push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                // Between separators, the slide's first heading is its title.
+                in_title = *separator != Separator::Heading && pending.headings.is_empty();
                 in_heading = true;
                 heading_level = level;
             }
             MarkdownEvent::End(TagEnd::Heading(_)) => {
+                pending.headings.push(Heading {
+                    line: current_slide_lines.len(),
+                    level: heading_level,
+                    text: std::mem::take(&mut heading_text),
+                });
                 push_current_line(&mut current_slide_lines, &mut current_line_spans, heading_level == HeadingLevel::H1);
-                add_spacing(&mut current_slide_lines);
+                let element = if in_title { Element::Title } else { Element::Heading };
+                add_spacing(&mut current_slide_lines, element);
                 in_heading = false;
+                in_title = false;
             }
             MarkdownEvent::Text(text) => {
+                // `~sub~` and `^sup^` outside code become sub- and superscripts.
+                let text: CowStr = if in_code_block {
+                    text
+                } else {
+                    scripts::render(&text).into()
+                };
                 if in_code_block {
                     code_block_content.push_str(&text);
+                } else if let Some((_, alt, _)) = &mut image {
+                    alt.push_str(&text);
                 } else if in_table {
                     current_cell_content.push_str(&text);
                 } else {
-                    let mut style = Style::default().fg(Color::White);
+                    let mut style = Style::default().fg(theme.text);
 
+                    if in_title {
+                        pending.title.get_or_insert_with(String::new).push_str(&text);
+                    }
                     if in_heading {
+                        heading_text.push_str(&text);
                         style = match heading_level {
                             HeadingLevel::H1 => Style::default()
-                                .fg(Color::Cyan)
+                                .fg(theme.primary)
                                 .add_modifier(Modifier::BOLD),
                             HeadingLevel::H2 => Style::default()
-                                .fg(Color::Blue)
+                                .fg(theme.secondary)
                                 .add_modifier(Modifier::BOLD),
                             HeadingLevel::H3 => Style::default()
-                                .fg(Color::Green)
+                                .fg(theme.tertiary)
                                 .add_modifier(Modifier::BOLD),
                             _ => Style::default()
-                                .fg(Color::Yellow)
+                                .fg(theme.accent)
                                 .add_modifier(Modifier::BOLD),
                         };
                     } else if in_strong {
@@ -342,8 +3093,35 @@ fn parse_markdown_to_slides(
                         style = style.add_modifier(Modifier::ITALIC);
                     }
 
-                    current_line_spans.push(Span::styled(text.to_string(), style));
+                    if let Some(link) = &mut link {
+                        link.text.push_str(&text);
+                        style = style.fg(theme.secondary).add_modifier(Modifier::UNDERLINED);
+                    }
+                    if let Some((_, note)) = &mut footnote {
+                        note.push_str(&text);
+                    }
+                    current_line_spans.extend(footnote_marker.take());
+                    for (i, part) in text.split(spoilers::MARKER).enumerate() {
+                        if i > 0 {
+                            in_spoiler = !in_spoiler;
+                        }
+                        if !part.is_empty() {
+                            let style = if in_spoiler { spoilers::mark(style) } else { style };
+                            current_line_spans.push(Span::styled(part.to_string(), style));
+                        }
+                    }
+                }
+            }
+            MarkdownEvent::Start(Tag::Paragraph)
+                if !in_table && markdown[range.clone()].trim() == fragments::PAUSE_DOTS =>
+            {
+                for (event, _) in parser.by_ref() {
+                    if matches!(event, MarkdownEvent::End(TagEnd::Paragraph)) {
+                        break;
+                    }
                 }
+                push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                pending.steps.pause(current_slide_lines.len());
             }
             MarkdownEvent::Start(Tag::Paragraph) => {
                 if !in_table {
@@ -353,7 +3131,9 @@ fn parse_markdown_to_slides(
             MarkdownEvent::End(TagEnd::Paragraph) => {
                 if !in_table {
                     push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
-                    add_spacing(&mut current_slide_lines);
+                    add_spacing(&mut current_slide_lines, Element::Paragraph);
+                    // An unmatched marker hides no more than its own paragraph.
+                    in_spoiler = false;
                 }
             }
             MarkdownEvent::Start(Tag::List(start)) => {
@@ -372,18 +3152,19 @@ fn parse_markdown_to_slides(
                         *n += 1;
                         marker
                     }
-                    _ => "• ".to_string(),
+                    _ => format!("{} ", theme.bullet(depth)),
                 };
                 current_line_spans
-                    .push(Span::styled(marker, Style::default().fg(Color::Yellow)));
+                    .push(Span::styled(marker, Style::default().fg(theme.accent)));
             }
             MarkdownEvent::End(TagEnd::Item) => {
                 push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                in_spoiler = false;
             }
             MarkdownEvent::End(TagEnd::List(_)) => {
                 list_stack.pop();
                 if list_stack.is_empty() {
-                    add_spacing(&mut current_slide_lines);
+                    add_spacing(&mut current_slide_lines, Element::List);
                 }
             }
             MarkdownEvent::Start(Tag::Strong) => {
@@ -402,172 +3183,209 @@ fn parse_markdown_to_slides(
                 if in_table {
                     current_cell_content.push_str(&format!("`{}`", code));
                 } else {
-                    current_line_spans.push(Span::styled(
-                        format!("`{}`", code),
-                        Style::default().fg(Color::Green).bg(Color::Rgb(40, 40, 40)),
-                    ));
+                    // Code that starts with a language is highlighted in it.
+                    let highlighted =
+                        highlight_inline_code(&code, theme, code_theme, highlighter);
+                    let text = highlighted.as_ref().map_or(&*code, |(source, _)| source);
+                    if in_title {
+                        pending.title.get_or_insert_with(String::new).push_str(text);
+                    }
+                    if in_heading {
+                        heading_text.push_str(text);
+                    }
+                    if let Some(link) = &mut link {
+                        link.text.push_str(text);
+                    }
+                    if let Some((_, note)) = &mut footnote {
+                        note.push_str(text);
+                    }
+                    current_line_spans.extend(footnote_marker.take());
+                    let style = Style::default().fg(theme.code).bg(theme.code_background);
+                    let mark = |span: Span<'static>| {
+                        if in_spoiler {
+                            let style = spoilers::mark(span.style);
+                            span.style(style)
+                        } else {
+                            span
+                        }
+                    };
+                    match highlighted {
+                        Some((_, spans)) => {
+                            current_line_spans.push(mark(Span::styled("`", style)));
+                            current_line_spans.extend(spans.into_iter().map(mark));
+                            current_line_spans.push(mark(Span::styled("`", style)));
+                        }
+                        None => {
+                            let span = Span::styled(format!("`{}`", code), style);
+                            current_line_spans.push(mark(span));
+                        }
+                    }
+                }
+            }
+            MarkdownEvent::Start(Tag::Link { dest_url, .. }) => {
+                link = Some(Link {
+                    text: String::new(),
+                    url: dest_url.to_string(),
+                });
+            }
+            MarkdownEvent::End(TagEnd::Link) => {
+                if let Some(link) = link.take()
+                    && !pending
+                        .links
+                        .iter()
+                        .any(|seen| seen.url == link.url && seen.text == link.text)
+                {
+                    pending.links.push(link);
+                }
+            }
+            MarkdownEvent::FootnoteReference(label) => {
+                current_line_spans.push(Span::styled(
+                    format!("[{label}]"),
+                    Style::default().fg(theme.primary),
+                ));
+                if !pending.footnotes.iter().any(|note| *note.label == *label) {
+                    pending.footnotes.push(Footnote {
+                        label: label.to_string(),
+                        text: String::new(),
+                    });
+                }
+            }
+            MarkdownEvent::Start(Tag::FootnoteDefinition(label)) => {
+                push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                footnote_marker = Some(Span::styled(
+                    format!("[{label}]: "),
+                    Style::default().fg(theme.muted),
+                ));
+                footnote = Some((label.to_string(), String::new()));
+            }
+            MarkdownEvent::End(TagEnd::FootnoteDefinition) => {
+                push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                footnote_marker = None;
+                if let Some((label, text)) = footnote.take() {
+                    footnote_texts.insert(label, text.trim().to_string());
+                }
+            }
+            MarkdownEvent::Start(Tag::Image { dest_url, .. }) => {
+                image = Some((dest_url.to_string(), String::new(), range.start));
+            }
+            MarkdownEvent::End(TagEnd::Image) => {
+                if let Some((src, alt, at)) = image.take() {
+                    // An attribute list such as `{width=60%}` may follow the image
+                    let mut placement = Placement::default();
+                    if queued.is_empty()
+                        && let Some((MarkdownEvent::Text(text), _)) = parser.peek_mut()
+                        && let Some((parsed, rest)) = Placement::parse_prefix(text)
+                    {
+                        placement = parsed;
+                        let rest = rest.trim_start().to_string();
+                        if rest.is_empty() {
+                            parser.next();
+                        } else {
+                            *text = rest.into();
+                        }
+                    }
+                    push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                    // Images in a region are sized to fit it.
+                    let width = open_region
+                        .as_ref()
+                        .map_or(effective_width, |(kind, _)| kind.width(effective_width));
+                    let (lines, problem) = images::render(&src, &alt, base_dir, width, &placement);
+                    // Images in regions are moved out of the slide's lines.
+                    if problem.is_none() && open_region.is_none() {
+                        pending.images.push(graphics::Image {
+                            path: base_dir.join(&src),
+                            line: current_slide_lines.len(),
+                            cols: lines.iter().map(Line::width).max().unwrap_or(0) as u16,
+                            rows: lines.len() as u16,
+                            align: placement.align,
+                        });
+                    }
+                    current_slide_lines.extend(lines);
+                    if let Some(problem) = problem {
+                        pending.warn("image", at, problem);
+                    }
                 }
             }
             MarkdownEvent::Start(Tag::CodeBlock(info)) => {
                 push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
                 in_code_block = true;
+                // The first word of the info string is the language; any further
+                // words are modifiers such as `typing`.
+                code_block_modifiers.clear();
                 code_block_lang = match info {
                     pulldown_cmark::CodeBlockKind::Indented => None,
-                    pulldown_cmark::CodeBlockKind::Fenced(lang) => {
-                        if lang.is_empty() {
-                            None
-                        } else {
-                            Some(lang.to_string())
-                        }
+                    pulldown_cmark::CodeBlockKind::Fenced(info) => {
+                        let mut words = info.split_whitespace();
+                        let lang = words.next().map(str::to_string);
+                        code_block_modifiers.extend(words.map(str::to_string));
+                        lang
                     }
                 };
                 code_block_content.clear();
+                if let Some(lang) = &code_block_lang
+                    && lang != "animation"
+                    && !PLAIN_LANGS.contains(&code_lang(lang).as_str())
+                    && !is_highlighted(lang, highlighter)
+                {
+                    let message = format!("No syntax definition for '{lang}' (see --syntaxes)");
+                    pending.warn("language", range.start, message);
+                }
             }
             MarkdownEvent::End(TagEnd::CodeBlock) => {
                 in_code_block = false;
 
-                let lang_lower = code_block_lang.as_deref().map(|s| s.to_lowercase());
-                let is_lean = matches!(lang_lower.as_deref(), Some("lean") | Some("lean4"));
-
-                if is_lean {
-                    for line in highlight_lean4_code(&code_block_content) {
-                        current_slide_lines.push(line);
+                if code_block_lang.as_deref() == Some("animation") {
+                    let (lines, animation) = Animation::parse(
+                        &code_block_content,
+                        &code_block_modifiers,
+                        current_slide_lines.len(),
+                        theme.text,
+                    );
+                    current_slide_lines.extend(lines);
+                    pending.animations.push(animation);
+                } else if code_block_modifiers.iter().any(|m| m == "typing") {
+                    let (lines, schedule, end) = typing_block(
+                        &code_block_content,
+                        code_block_lang.as_deref(),
+                        theme,
+                        code_theme,
+                        highlighter,
+                        current_slide_lines.len(),
+                        typing_clock,
+                    );
+                    current_slide_lines.extend(lines);
+                    pending.typing.extend(schedule);
+                    typing_clock = end;
+                } else {
+                    let start = current_slide_lines.len();
+                    current_slide_lines.extend(highlight_code_block(
+                        &code_block_content,
+                        code_block_lang.as_deref(),
+                        theme,
+                        code_theme,
+                        highlighter,
+                    ));
+                    let block = start..current_slide_lines.len();
+                    if code_block_modifiers.iter().any(|m| m == "step") {
+                        pending.steps.code_lines(block.clone());
                     }
-                } else if let Some(lang) = &code_block_lang {
-                    // Try to find syntax by the language name first, then by common extensions
-                    let syntax = syntax_set.find_syntax_by_token(lang)
-                        .or_else(|| {
-                            // Map common language names to their file extensions
-                            let extension = match lang.as_str() {
-                                "rust" | "rs" => "rs",
-                                "python" | "py" => "py",
-                                "javascript" | "js" => "js",
-                                "typescript" | "ts" => "ts",
-                                "java" => "java",
-                                "c" => "c",
-                                "cpp" | "c++" | "cxx" => "cpp",
-                                "csharp" | "c#" | "cs" => "cs",
-                                "go" | "golang" => "go",
-                                "html" => "html",
-                                "css" => "css",
-                                "json" => "json",
-                                "xml" => "xml",
-                                "yaml" | "yml" => "yaml",
-                                "toml" => "toml",
-                                "markdown" | "md" => "md",
-                                "dockerfile" | "docker" => "Dockerfile",
-                                "sql" => "sql",
-                                "shell" | "bash" | "sh" => "sh",
-                                "php" => "php",
-                                "ruby" | "rb" => "rb",
-                                "perl" | "pl" => "pl",
-                                "swift" => "swift",
-                                "kotlin" | "kt" => "kt",
-                                "scala" => "scala",
-                                "haskell" | "hs" => "hs",
-                                "elixir" | "ex" => "ex",
-                                "erlang" | "erl" => "erl",
-                                "clojure" | "clj" => "clj",
-                                "lua" => "lua",
-                                "r" => "r",
-                                "matlab" => "m",
-                                "powershell" | "ps1" => "ps1",
-                                "vim" => "vim",
-                                "tex" | "latex" => "tex",
-                                "makefile" | "make" => "Makefile",
-                                "nginx" => "conf",
-                                "apache" => "conf",
-                                "ini" => "ini",
-                                "properties" => "properties",
-                                "groovy" => "groovy",
-                                "dart" => "dart",
-                                "assembly" | "asm" => "asm",
-                                "lisp" => "lisp",
-                                "scheme" => "scm",
-                                "ocaml" => "ml",
-                                "fsharp" | "f#" => "fs",
-                                "pascal" => "pas",
-                                "fortran" => "f90",
-                                "cobol" => "cob",
-                                "ada" => "ada",
-                                "verilog" => "v",
-                                "vhdl" => "vhd",
-                                _ => lang, // Fall back to using the language name as extension
-                            };
-                            syntax_set.find_syntax_by_extension(extension)
-                        });
-                    
-                    if let Some(syntax) = syntax {
-                        let mut highlighter = HighlightLines::new(syntax, theme);
-
-                        for line in LinesWithEndings::from(&code_block_content) {
-                            // syntect uses the trailing \n for context, but the \n must not
-                            // leak into ratatui spans (it would be rendered as a control char).
-                            let ranges = highlighter
-                                .highlight_line(line, syntax_set)
-                                .unwrap_or_default();
-                            let mut line_spans = Vec::new();
-
-                            if ranges.is_empty() {
-                                let clean = line.trim_end_matches(['\n', '\r']).to_string();
-                                line_spans.push(Span::styled(
-                                    clean,
-                                    Style::default().fg(Color::Green),
-                                ));
-                            } else {
-                                for (style, text) in ranges {
-                                    let clean = text.trim_end_matches(['\n', '\r']);
-                                    if clean.is_empty() {
-                                        continue;
-                                    }
-                                    let fg_color = Color::Rgb(
-                                        style.foreground.r,
-                                        style.foreground.g,
-                                        style.foreground.b,
-                                    );
-                                    let mut ratatui_style = Style::default().fg(fg_color);
-
-                                    if style
-                                        .font_style
-                                        .contains(syntect::highlighting::FontStyle::BOLD)
-                                    {
-                                        ratatui_style = ratatui_style.add_modifier(Modifier::BOLD);
-                                    }
-                                    if style
-                                        .font_style
-                                        .contains(syntect::highlighting::FontStyle::ITALIC)
-                                    {
-                                        ratatui_style =
-                                            ratatui_style.add_modifier(Modifier::ITALIC);
-                                    }
-
-                                    line_spans.push(Span::styled(clean.to_string(), ratatui_style));
-                                }
-                            }
-
-                            current_slide_lines.push(Line::from(line_spans));
-                        }
-                    } else {
-                        // Fallback to unstyled code if no syntax is found
-                        for line in code_block_content.lines() {
-                            current_slide_lines.push(Line::from(Span::styled(
-                                line.to_string(),
-                                Style::default().fg(Color::Green),
-                            )));
+                    for modifier in &code_block_modifiers {
+                        if pending.steps.code_focus(block.clone(), modifier) {
+                            break;
                         }
                     }
-                } else {
-                    for line in code_block_content.lines() {
-                        current_slide_lines.push(Line::from(Span::styled(
-                            line.to_string(),
-                            Style::default().fg(Color::Green),
-                        )));
+                    if code_block_modifiers.iter().any(|m| m == "exec") {
+                        pending.exec.push(ExecBlock {
+                            lang: code_block_lang.clone(),
+                            code: code_block_content.clone(),
+                            end: current_slide_lines.len(),
+                        });
                     }
                 }
 
                 code_block_content.clear();
                 code_block_lang = None;
-                add_spacing(&mut current_slide_lines);
+                add_spacing(&mut current_slide_lines, Element::CodeBlock);
             }
             MarkdownEvent::Start(Tag::Table(_)) => {
                 push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
@@ -592,14 +3410,14 @@ fn parse_markdown_to_slides(
                     
                     // Add top border
                     let mut top_border_spans = Vec::new();
-                    top_border_spans.push(Span::styled("┌", Style::default().fg(Color::Gray)));
+                    top_border_spans.push(Span::styled("┌", Style::default().fg(theme.border)));
                     for (i, width) in col_widths.iter().enumerate() {
-                        top_border_spans.push(Span::styled("─".repeat(width + 2), Style::default().fg(Color::Gray)));
+                        top_border_spans.push(Span::styled("─".repeat(width + 2 * pad), Style::default().fg(theme.border)));
                         if i < col_widths.len() - 1 {
-                            top_border_spans.push(Span::styled("┬", Style::default().fg(Color::Gray)));
+                            top_border_spans.push(Span::styled("┬", Style::default().fg(theme.border)));
                         }
                     }
-                    top_border_spans.push(Span::styled("┐", Style::default().fg(Color::Gray)));
+                    top_border_spans.push(Span::styled("┐", Style::default().fg(theme.border)));
                     current_slide_lines.push(Line::from(top_border_spans));
                     
                     // Render table rows
@@ -607,14 +3425,17 @@ fn parse_markdown_to_slides(
                         let is_header_row = row_idx < table_header_rows;
                         let cell_style = if is_header_row {
                             Style::default()
-                                .fg(Color::White)
+                                .fg(theme.text)
                                 .add_modifier(Modifier::BOLD)
                         } else {
-                            Style::default().fg(Color::White)
+                            Style::default().fg(theme.text)
                         };
 
                         let mut line_spans = Vec::new();
-                        line_spans.push(Span::styled("│ ", Style::default().fg(Color::Gray)));
+                        line_spans.push(Span::styled(
+                            format!("│{cell_padding}"),
+                            Style::default().fg(theme.border),
+                        ));
 
                         for (col_idx, cell) in row.iter().enumerate() {
                             let width = col_widths.get(col_idx).unwrap_or(&10);
@@ -623,7 +3444,10 @@ fn parse_markdown_to_slides(
                             let padded_cell = format!("{}{}", cell, " ".repeat(padding_needed));
 
                             line_spans.push(Span::styled(padded_cell, cell_style));
-                            line_spans.push(Span::styled(" │ ", Style::default().fg(Color::Gray)));
+                            line_spans.push(Span::styled(
+                                format!("{cell_padding}│{cell_padding}"),
+                                Style::default().fg(theme.border),
+                            ));
                         }
 
                         current_slide_lines.push(Line::from(line_spans));
@@ -634,38 +3458,38 @@ fn parse_markdown_to_slides(
                             let mut sep_spans = Vec::new();
                             if is_header_boundary {
                                 sep_spans
-                                    .push(Span::styled("╞", Style::default().fg(Color::Gray)));
+                                    .push(Span::styled("╞", Style::default().fg(theme.border)));
                                 for (i, width) in col_widths.iter().enumerate() {
                                     sep_spans.push(Span::styled(
-                                        "═".repeat(width + 2),
-                                        Style::default().fg(Color::Gray),
+                                        "═".repeat(width + 2 * pad),
+                                        Style::default().fg(theme.border),
                                     ));
                                     if i < col_widths.len() - 1 {
                                         sep_spans.push(Span::styled(
                                             "╪",
-                                            Style::default().fg(Color::Gray),
+                                            Style::default().fg(theme.border),
                                         ));
                                     }
                                 }
                                 sep_spans
-                                    .push(Span::styled("╡", Style::default().fg(Color::Gray)));
+                                    .push(Span::styled("╡", Style::default().fg(theme.border)));
                             } else {
                                 sep_spans
-                                    .push(Span::styled("├", Style::default().fg(Color::Gray)));
+                                    .push(Span::styled("├", Style::default().fg(theme.border)));
                                 for (i, width) in col_widths.iter().enumerate() {
                                     sep_spans.push(Span::styled(
-                                        "─".repeat(width + 2),
-                                        Style::default().fg(Color::Gray),
+                                        "─".repeat(width + 2 * pad),
+                                        Style::default().fg(theme.border),
                                     ));
                                     if i < col_widths.len() - 1 {
                                         sep_spans.push(Span::styled(
                                             "┼",
-                                            Style::default().fg(Color::Gray),
+                                            Style::default().fg(theme.border),
                                         ));
                                     }
                                 }
                                 sep_spans
-                                    .push(Span::styled("┤", Style::default().fg(Color::Gray)));
+                                    .push(Span::styled("┤", Style::default().fg(theme.border)));
                             }
                             current_slide_lines.push(Line::from(sep_spans));
                         }
@@ -673,65 +3497,478 @@ fn parse_markdown_to_slides(
                     
                     // Add bottom border
                     let mut bottom_border_spans = Vec::new();
-                    bottom_border_spans.push(Span::styled("└", Style::default().fg(Color::Gray)));
+                    bottom_border_spans.push(Span::styled("└", Style::default().fg(theme.border)));
                     for (i, width) in col_widths.iter().enumerate() {
-                        bottom_border_spans.push(Span::styled("─".repeat(width + 2), Style::default().fg(Color::Gray)));
+                        bottom_border_spans.push(Span::styled("─".repeat(width + 2 * pad), Style::default().fg(theme.border)));
                         if i < col_widths.len() - 1 {
-                            bottom_border_spans.push(Span::styled("┴", Style::default().fg(Color::Gray)));
+                            bottom_border_spans.push(Span::styled("┴", Style::default().fg(theme.border)));
+                        }
+                    }
+                    bottom_border_spans.push(Span::styled("┘", Style::default().fg(theme.border)));
+                    current_slide_lines.push(Line::from(bottom_border_spans));
+                }
+                
+                add_spacing(&mut current_slide_lines, Element::Table);
+                in_table = false;
+            }
+            MarkdownEvent::Start(Tag::TableHead) => {}
+            MarkdownEvent::End(TagEnd::TableHead) => {
+                table_header_rows = table_rows.len();
+            }
+            MarkdownEvent::Start(Tag::TableRow) => {
+                current_table_row.clear();
+            }
+            MarkdownEvent::End(TagEnd::TableRow) => {
+                table_rows.push(current_table_row.clone());
+                current_table_row.clear();
+            }
+            MarkdownEvent::Start(Tag::TableCell) => {
+                current_cell_content.clear();
+            }
+            MarkdownEvent::End(TagEnd::TableCell) => {
+                current_table_row.push(current_cell_content.trim().to_string());
+                current_cell_content.clear();
+            }
+            MarkdownEvent::SoftBreak | MarkdownEvent::HardBreak => {
+                if !in_table {
+                    push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                    if let Some((_, note)) = &mut footnote {
+                        note.push(' ');
+                    }
+                }
+            }
+            MarkdownEvent::Html(html) | MarkdownEvent::InlineHtml(html) => {
+                let at = range.start;
+                let comments = html_comments(&html);
+                // Anything but comments is markup the terminal cannot show.
+                let markup = comments
+                    .iter()
+                    .fold(html.to_string(), |rest, comment| rest.replacen(comment, "", 1));
+                if let Some(first) = markup.lines().map(str::trim).find(|l| !l.is_empty()) {
+                    pending.warn("raw-html", at, format!("Raw HTML is not shown: {first}"));
+                }
+                for comment in comments {
+                    match parse_directive(comment) {
+                        // A value that is malformed or too long to wait for
+                        // is reported, leaving the slide's advance as it was.
+                        Some(("advance", value)) => match parse_duration(value) {
+                            Some(advance) => pending.advance = Some(advance),
+                            None => {
+                                let message =
+                                    format!("Invalid advance '{value}' (try 8s, 1m30s or 500ms)");
+                                pending.warn("directive", at, message);
+                            }
+                        },
+                        Some(("note" | "notes", value)) => pending.notes.push(value.to_string()),
+                        Some(("background", value)) => {
+                            pending.background = Background::parse(value, base_dir);
+                            match &pending.background {
+                                None => {
+                                    let message = format!("Invalid background '{value}'");
+                                    pending.warn("directive", at, message);
+                                }
+                                Some(background) if !background.path.is_file() => {
+                                    let message = format!("{}: no such file", value);
+                                    pending.warn("image", at, message);
+                                }
+                                Some(_) => {}
+                            }
+                        }
+                        Some(("section", value)) => pending.section = Some(value.to_string()),
+                        // Content in other languages was blanked out before parsing.
+                        Some(("lang", _)) => {}
+                        Some(("id", value)) => {
+                            if value.is_empty() || value.contains(char::is_whitespace) {
+                                pending.warn("directive", at, format!("Invalid id '{value}'"));
+                            } else {
+                                pending.id = Some(value.to_string());
+                            }
+                        }
+                        Some(("layout", value)) => pending.layout = Some(value.to_string()),
+                        Some(("theme", value)) => pending.theme = Some(value.to_string()),
+                        Some(("tags", value)) => pending.tags.extend(
+                            value
+                                .split(',')
+                                .map(str::trim)
+                                .filter(|tag| !tag.is_empty())
+                                .map(str::to_string),
+                        ),
+                        Some(("pause", _)) => {
+                            push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                            pending.steps.pause(current_slide_lines.len());
+                        }
+                        Some(("callout", value)) => {
+                            push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                            close_region(&mut current_slide_lines, &mut pending, &mut open_region);
+                            let title = (!value.is_empty()).then(|| value.to_string());
+                            open_region =
+                                Some((regions::Kind::Callout(title), current_slide_lines.len()));
+                        }
+                        Some(("grid", value)) => {
+                            push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                            close_region(&mut current_slide_lines, &mut pending, &mut open_region);
+                            grid = regions::Grid::parse(value);
+                            if grid.is_none() {
+                                pending.warn("directive", at, format!("Invalid grid '{value}'"));
+                            }
+                            open_region = grid
+                                .map(|grid| (regions::Kind::Cell(grid), current_slide_lines.len()));
+                        }
+                        Some(("cell", _)) => {
+                            push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                            // The grid's first cell starts at the grid itself,
+                            // so a cell right after it adds nothing.
+                            let empty = open_region
+                                .as_ref()
+                                .is_some_and(|(_, start)| *start == current_slide_lines.len());
+                            if let Some(grid) = grid
+                                && !empty
+                            {
+                                close_region(&mut current_slide_lines, &mut pending, &mut open_region);
+                                open_region =
+                                    Some((regions::Kind::Cell(grid), current_slide_lines.len()));
+                            }
+                        }
+                        Some((key @ ("image-left" | "image-right"), "")) => {
+                            pending.warn("directive", at, format!("Invalid {key} ''"));
+                        }
+                        Some((key @ ("image-left" | "image-right"), value)) => {
+                            let side = if key == "image-left" {
+                                regions::Side::Left
+                            } else {
+                                regions::Side::Right
+                            };
+                            let kind = regions::Kind::Image(side);
+                            let placement = Placement {
+                                align: Alignment::Center,
+                                ..Placement::default()
+                            };
+                            let (lines, problem) = images::render(
+                                value,
+                                value,
+                                base_dir,
+                                kind.width(effective_width),
+                                &placement,
+                            );
+                            if let Some(problem) = problem {
+                                pending.warn("image", at, problem);
+                            }
+                            pending.regions.push(Region {
+                                kind,
+                                text: Text::from(lines),
+                            });
+                        }
+                        Some(("end", _)) => {
+                            push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                            close_region(&mut current_slide_lines, &mut pending, &mut open_region);
+                            grid = None;
+                        }
+                        Some(("terminal", value)) => {
+                            push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                            let line = current_slide_lines.len();
+                            if let Some(region) = TerminalRegion::parse(value, line) {
+                                current_slide_lines
+                                    .extend((0..region.rows).map(|_| Line::from("")));
+                                add_spacing(&mut current_slide_lines, Element::Terminal);
+                                pending.terminal = Some(region);
+                            } else {
+                                let message = format!("Invalid terminal '{value}'");
+                                pending.warn("directive", at, message);
+                            }
+                        }
+                        // Any other comment of a single lowercase word
+                        // looks like a misspelled directive.
+                        Some((key, _)) if is_directive_name(key) => {
+                            pending.warn("directive", at, format!("Unknown directive '{key}'"));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            MarkdownEvent::Rule if *separator == Separator::Rule => {
+                push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                separate(
+                    &mut slides,
+                    &mut current_slide_lines,
+                    &mut pending,
+                    &mut open_region,
+                    range,
+                );
+                typing_clock = Duration::ZERO;
+            }
+            MarkdownEvent::Rule => {
+                push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+                // Render a horizontal rule as a line of dashes spanning the inner width.
+                let rule_width = effective_width.max(4);
+                current_slide_lines.push(Line::from(Span::styled(
+                    "─".repeat(rule_width),
+                    Style::default().fg(theme.muted),
+                )));
+                add_spacing(&mut current_slide_lines, Element::Rule);
+            }
+            _ => {}
+        }
+    }
+
+    push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
+    finish_slide(
+        &mut slides,
+        &mut current_slide_lines,
+        &mut pending,
+        &mut open_region,
+        markdown.len(),
+    );
+
+    // Slides stay in the section started before them. Front matter can name
+    // the section the deck opens with.
+    let mut section = front_matter.get("section").map(str::to_string);
+    for slide in &mut slides {
+        match &slide.section {
+            Some(name) => section = Some(name.clone()),
+            None => slide.section.clone_from(&section),
+        }
+    }
+
+    for (offset, text) in note_blocks {
+        if let Some(slide) = slides.iter_mut().find(|slide| slide.source.contains(&offset)) {
+            slide.notes.push(text);
+        }
+    }
+
+    // Definitions may come after the slides that reference them.
+    for note in slides.iter_mut().flat_map(|slide| &mut slide.footnotes) {
+        if let Some(text) = footnote_texts.get(&note.label) {
+            note.text.clone_from(text);
+        }
+    }
+
+    if slides.is_empty() {
+        slides.push(Slide {
+            text: Text::from("No slides found in markdown file"),
+            ..Slide::default()
+        });
+    }
+
+    slides
+}
+
+/// The language of a code block as it is looked up, so that ```` ```Golang ````
+/// is highlighted like ```` ```golang ````.
+fn code_lang(lang: &str) -> String {
+    lang.trim().to_lowercase()
+}
+
+/// Looks up the syntax definition for a code block's language, by name and
+/// then by common file extension.
+fn find_syntax<'a>(syntax_set: &'a SyntaxSet, lang: &str) -> Option<&'a SyntaxReference> {
+    // Try to find syntax by the language name first, then by common extensions
+    syntax_set.find_syntax_by_token(lang)
+        .or_else(|| {
+            // Map common language names to their file extensions
+            let extension = match lang {
+                "rust" | "rs" => "rs",
+                "python" | "py" => "py",
+                "javascript" | "js" => "js",
+                "typescript" | "ts" => "ts",
+                "java" => "java",
+                "c" => "c",
+                "cpp" | "c++" | "cxx" => "cpp",
+                "csharp" | "c#" | "cs" => "cs",
+                "go" | "golang" => "go",
+                "html" => "html",
+                "css" => "css",
+                "json" => "json",
+                "xml" => "xml",
+                "yaml" | "yml" => "yaml",
+                "toml" => "toml",
+                "markdown" | "md" => "md",
+                "dockerfile" | "docker" => "Dockerfile",
+                "sql" => "sql",
+                "shell" | "bash" | "sh" => "sh",
+                "php" => "php",
+                "ruby" | "rb" => "rb",
+                "perl" | "pl" => "pl",
+                "swift" => "swift",
+                "kotlin" | "kt" => "kt",
+                "scala" => "scala",
+                "haskell" | "hs" => "hs",
+                "elixir" | "ex" => "ex",
+                "erlang" | "erl" => "erl",
+                "clojure" | "clj" => "clj",
+                "lua" => "lua",
+                "r" => "r",
+                "matlab" => "m",
+                "powershell" | "ps1" => "ps1",
+                "vim" => "vim",
+                "tex" | "latex" => "tex",
+                "makefile" | "make" => "Makefile",
+                "nginx" => "conf",
+                "apache" => "conf",
+                "ini" => "ini",
+                "properties" => "properties",
+                "groovy" => "groovy",
+                "dart" => "dart",
+                "assembly" | "asm" => "asm",
+                "lisp" => "lisp",
+                "scheme" => "scm",
+                "ocaml" => "ml",
+                "fsharp" | "f#" => "fs",
+                "pascal" => "pas",
+                "fortran" => "f90",
+                "cobol" => "cob",
+                "ada" => "ada",
+                "verilog" => "v",
+                "vhdl" => "vhd",
+                _ => lang, // Fall back to using the language name as extension
+            };
+            syntax_set.find_syntax_by_extension(extension)
+        })
+}
+
+/// Info strings that mark a code block as plain text, left unhighlighted on
+/// purpose.
+const PLAIN_LANGS: &[&str] = &["text", "txt", "plain", "plaintext", "console", "output"];
+
+/// Whether code blocks in `lang` are highlighted, rather than drawn plainly
+/// for want of a syntax definition.
+fn is_highlighted(lang: &str, highlighter: &Highlighter) -> bool {
+    let lang = code_lang(lang);
+    let is_lean = matches!(lang.as_str(), "lean" | "lean4");
+    if is_lean || find_syntax(&highlighter.syntaxes, &lang).is_some() {
+        return true;
+    }
+    #[cfg(feature = "tree-sitter")]
+    if highlighter.has_grammar(&lang) {
+        return true;
+    }
+    false
+}
+
+/// Highlights inline code that starts with a language, as in
+/// `` `rust:Option<T>` ``, on the code background of `theme`.
+///
+/// Returns the code without its language together with its spans, or `None`
+/// if the code does not start with the name of a known language.
+fn highlight_inline_code<'a>(
+    code: &'a str,
+    theme: &Theme,
+    code_theme: &CodeTheme,
+    highlighter: &Highlighter,
+) -> Option<(&'a str, Vec<Span<'static>>)> {
+    let (lang, source) = code.split_once(':')?;
+    // Paths such as `std::mem` and URLs are not code with a language.
+    if lang.is_empty()
+        || lang.contains(char::is_whitespace)
+        || source.is_empty()
+        || source.starts_with([':', '/'])
+    {
+        return None;
+    }
+    let name = code_lang(lang);
+    let is_lean = matches!(name.as_str(), "lean" | "lean4");
+    if !is_lean && find_syntax(&highlighter.syntaxes, &name).is_none() {
+        return None;
+    }
+    let line = highlight_code_block(
+        &format!("{source}\n"),
+        Some(lang),
+        theme,
+        code_theme,
+        highlighter,
+    )
+    .into_iter()
+    .next()?;
+    let spans = line
+        .spans
+        .into_iter()
+        .map(|span| {
+            let style = span.style.bg(theme.code_background);
+            span.style(style)
+        })
+        .collect();
+    Some((source, spans))
+}
+
+/// Renders the contents of a fenced code block into styled lines.
+///
+/// Lean 4 blocks use the built-in Lean highlighter, and languages with a
+/// grammar go through tree-sitter when that backend is chosen; other
+/// languages are looked up in the highlighter's syntaxes with [`find_syntax`]
+/// and highlighted with `code_theme`. Blocks with no language or an unknown
+/// one are rendered plainly in the code color of `theme`.
+fn highlight_code_block(
+    content: &str,
+    lang: Option<&str>,
+    theme: &Theme,
+    code_theme: &CodeTheme,
+    highlighter: &Highlighter,
+) -> Vec<Line<'static>> {
+    let lang = lang.map(code_lang);
+    let lang = lang.as_deref();
+    let is_lean = matches!(lang, Some("lean") | Some("lean4"));
+
+    if is_lean {
+        return highlight_lean4_code(content, theme);
+    }
+
+    #[cfg(feature = "tree-sitter")]
+    if let Some(lines) = lang.and_then(|lang| highlighter.tree_sitter(content, lang, code_theme)) {
+        return lines;
+    }
+    let syntax_set = &highlighter.syntaxes;
+
+    let mut lines = Vec::new();
+    if let Some(lang) = lang {
+        let syntax = find_syntax(syntax_set, lang);
+
+        if let Some(syntax) = syntax {
+            let mut highlighter = HighlightLines::new(syntax, code_theme);
+
+            for line in LinesWithEndings::from(content) {
+                // syntect uses the trailing \n for context, but the \n must not
+                // leak into ratatui spans (it would be rendered as a control char).
+                let ranges = highlighter
+                    .highlight_line(line, syntax_set)
+                    .unwrap_or_default();
+                let mut line_spans = Vec::new();
+
+                if ranges.is_empty() {
+                    let clean = line.trim_end_matches(['\n', '\r']).to_string();
+                    line_spans.push(Span::styled(
+                        clean,
+                        Style::default().fg(theme.code),
+                    ));
+                } else {
+                    for (style, text) in ranges {
+                        let clean = text.trim_end_matches(['\n', '\r']);
+                        if clean.is_empty() {
+                            continue;
                         }
+                        line_spans.push(Span::styled(clean.to_string(), span_style(style)));
                     }
-                    bottom_border_spans.push(Span::styled("┘", Style::default().fg(Color::Gray)));
-                    current_slide_lines.push(Line::from(bottom_border_spans));
-                }
-                
-                add_spacing(&mut current_slide_lines);
-                in_table = false;
-            }
-            MarkdownEvent::Start(Tag::TableHead) => {}
-            MarkdownEvent::End(TagEnd::TableHead) => {
-                table_header_rows = table_rows.len();
-            }
-            MarkdownEvent::Start(Tag::TableRow) => {
-                current_table_row.clear();
-            }
-            MarkdownEvent::End(TagEnd::TableRow) => {
-                table_rows.push(current_table_row.clone());
-                current_table_row.clear();
-            }
-            MarkdownEvent::Start(Tag::TableCell) => {
-                current_cell_content.clear();
-            }
-            MarkdownEvent::End(TagEnd::TableCell) => {
-                current_table_row.push(current_cell_content.trim().to_string());
-                current_cell_content.clear();
-            }
-            MarkdownEvent::SoftBreak | MarkdownEvent::HardBreak => {
-                if !in_table {
-                    push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
                 }
+
+                lines.push(Line::from(line_spans));
             }
-            MarkdownEvent::Rule => {
-                push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
-                // Render a horizontal rule as a line of dashes spanning the inner width.
-                let rule_width = effective_width.max(4);
-                current_slide_lines.push(Line::from(Span::styled(
-                    "─".repeat(rule_width),
-                    Style::default().fg(Color::DarkGray),
+        } else {
+            // Fallback to unstyled code if no syntax is found
+            for line in content.lines() {
+                lines.push(Line::from(Span::styled(
+                    line.to_string(),
+                    Style::default().fg(theme.code),
                 )));
-                add_spacing(&mut current_slide_lines);
             }
-            _ => {}
+        }
+    } else {
+        for line in content.lines() {
+            lines.push(Line::from(Span::styled(
+                line.to_string(),
+                Style::default().fg(theme.code),
+            )));
         }
     }
 
-    push_current_line(&mut current_slide_lines, &mut current_line_spans, false);
-    finish_slide(&mut slides, &mut current_slide_lines);
-
-    if slides.is_empty() {
-        slides.push(Text::from("No slides found in markdown file"));
-    }
-
-    slides
+    lines
 }
 
 /// Lean 4 keywords — declarations, modifiers, and structural forms.
@@ -799,8 +4036,8 @@ fn lean4_style(word: &str) -> Option<Style> {
 /// Handles line comments (`-- ...`), nested block comments (`/- ... -/`),
 /// string literals, numeric literals, attribute forms like `@[simp]`, common
 /// unicode operators (∀, ∃, λ, →, ↔, ∧, ∨, etc.), keywords, tactics, and
-/// built-in types. Anything else is emitted with the default foreground.
-fn highlight_lean4_code(content: &str) -> Vec<Line<'static>> {
+/// built-in types. Anything else is emitted in the text color of `theme`.
+fn highlight_lean4_code(content: &str, theme: &Theme) -> Vec<Line<'static>> {
     let comment_style = Style::default()
         .fg(Color::DarkGray)
         .add_modifier(Modifier::ITALIC);
@@ -810,7 +4047,7 @@ fn highlight_lean4_code(content: &str) -> Vec<Line<'static>> {
     let operator_style = Style::default()
         .fg(Color::Magenta)
         .add_modifier(Modifier::BOLD);
-    let default_style = Style::default().fg(Color::White);
+    let default_style = Style::default().fg(theme.text);
 
     let mut lines: Vec<Line<'static>> = Vec::new();
     let mut block_comment_depth: u32 = 0;
@@ -1025,43 +4262,1185 @@ fn highlight_lean4_code(content: &str) -> Vec<Line<'static>> {
 /// * `f` - The frame to render into
 /// * `app` - The application state containing slide data
 fn ui(f: &mut Frame, app: &App) {
+    if let Some(background) = app.current_theme().background {
+        f.render_widget(Block::default().style(Style::default().bg(background)), f.area());
+    }
+    let filmstrip_height = if app.filmstrip { FILMSTRIP_HEIGHT } else { 0 };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(filmstrip_height),
+            Constraint::Length(timeline_height(app)),
+            Constraint::Length(status_bar_height(app)),
+        ])
         .split(f.area());
+    let [sidebar_area, main_area] = Layout::horizontal([
+        Constraint::Length(app.sidebar_width()),
+        Constraint::Min(0),
+    ])
+    .areas(chunks[0]);
+    if !sidebar_area.is_empty() {
+        render_sidebar(f, app, sidebar_area);
+    }
+    let notes = app.notes_panel.then(|| notes_panel(app));
+    let notes_height = notes.as_ref().map_or(0, |notes| {
+        // The count includes the panel's border.
+        let rows = notes.line_count(main_area.width);
+        u16::try_from(rows)
+            .unwrap_or(u16::MAX)
+            .clamp(NOTES_MIN_HEIGHT, (main_area.height / 2).max(NOTES_MIN_HEIGHT))
+    });
+    let [slide_area, notes_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(notes_height)]).areas(main_area);
+    if let Some(theme) = app.slide_theme() {
+        let background = theme.background.unwrap_or(Color::Reset);
+        f.render_widget(Block::default().style(Style::default().bg(background)), slide_area);
+    }
+    if let Some(notes) = notes {
+        f.render_widget(notes, notes_area);
+    }
+    if !chunks[1].is_empty() {
+        render_filmstrip(f, app, chunks[1]);
+    }
 
     let slide_content = app.current_slide_content();
-    
-    // Apply scroll offset to the content
-    let visible_lines: Vec<_> = slide_content
+    let typing = app.current_typing();
+    let elapsed = app.slide_entered.elapsed();
+    // Fragments not revealed yet neither play nor show output
+    let steps = app.current_steps();
+    let hidden_from = steps
+        .and_then(|steps| steps.hidden_from(app.fragment))
+        .unwrap_or(usize::MAX);
+
+    // Play back any typing demos and animations, and show the output of
+    // executed blocks
+    let mut lines: Vec<_> = slide_content
         .lines
         .iter()
-        .skip(app.scroll_offset)
-        .cloned()
+        .enumerate()
+        .map(|(i, line)| {
+            let visibility = steps.map_or(Visibility::Shown, |s| s.visibility(app.fragment, i));
+            let line = match visibility {
+                Visibility::Concealed => return app.fragment_style.conceal(line),
+                Visibility::Highlighted => fragments::highlight(line),
+                Visibility::Dimmed => fragments::dim(line),
+                Visibility::Shown => match typing.iter().find(|t| t.line == i) {
+                    Some(typed) => typed.render(line, elapsed),
+                    None => line.clone(),
+                },
+            };
+            match &app.search {
+                Some(search) => search.highlight(app.current_slide, i, &line),
+                None => line,
+            }
+        })
         .collect();
+    let revealed = app.revealed_spoilers.contains(&app.current_slide);
+    for line in &mut lines {
+        spoilers::render(line, revealed);
+    }
+    for animation in app.current_animations() {
+        if animation.line >= hidden_from {
+            continue;
+        }
+        let end = (animation.line + animation.height()).min(lines.len());
+        let start = animation.line.min(end);
+        lines.splice(start..end, animation.frame(elapsed).iter().cloned());
+    }
+    let mut exec_output = app.exec_output();
+    exec_output.retain(|(end, _)| *end <= hidden_from);
+    for (end, output) in exec_output.iter().rev() {
+        lines.splice(end..end, output.iter().cloned());
+    }
+
+    // Apply scroll offset to the content
+    let mut visible_lines: Vec<_> = lines.into_iter().skip(app.scroll_offset).collect();
+
+    // A flashcard shows only its title until the answer is revealed
+    if app.card_hidden() {
+        visible_lines = slide_content.lines.iter().take(1).cloned().collect();
+        visible_lines.push(Line::from(""));
+        visible_lines.push(
+            Line::styled(
+                "Press Space to reveal the answer",
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            )
+            .alignment(Alignment::Center),
+        );
+    }
     
+    // Rows of wrapped slide text above the terminal pane, if it is in view
+    let pane_row = app
+        .current_terminal()
+        .filter(|region| !app.card_hidden() && region.line < hidden_from)
+        .and_then(|region| {
+            let output_above: usize = exec_output
+                .iter()
+                .filter(|(end, _)| *end <= region.line)
+                .map(|(_, output)| output.len())
+                .sum();
+            let line = region.line + output_above;
+            let above = visible_lines.get(..line.checked_sub(app.scroll_offset)?)?;
+            let rows = Paragraph::new(Text::from(above.to_vec()))
+                .wrap(Wrap { trim: false })
+                .line_count(slide_area.width.saturating_sub(2));
+            Some((region, rows))
+        });
+
     let scrolled_content = Text::from(visible_lines);
-    
-    let paragraph = Paragraph::new(scrolled_content)
+
+    // Regions such as callouts take their share of the slide first, and the
+    // main content flows into what is left
+    let block = slide_block(app, slide_area);
+    let content_area = block.inner(slide_area);
+    f.render_widget(block, slide_area);
+    let regions = if app.card_hidden() {
+        &[]
+    } else {
+        app.current_regions()
+    };
+    let main_area = regions::render(regions, &scrolled_content, content_area, f.buffer_mut());
+    app.slide_rows.set(main_area.height);
+    app.image_areas.set(image_areas(app, &scrolled_content, &exec_output, hidden_from, main_area));
+    let layout = app
+        .slides
+        .get(app.current_slide)
+        .filter(|_| !app.card_hidden())
+        .and_then(|slide| Some((slide, app.layouts.get(slide.layout.as_deref()?)?)));
+    match layout {
+        Some((slide, layout)) => {
+            let title = slide.title.is_some() && app.scroll_offset == 0;
+            layout.render(scrolled_content, title, main_area, f.buffer_mut());
+        }
+        None => {
+            let paragraph = Paragraph::new(scrolled_content).wrap(Wrap { trim: false });
+            f.render_widget(paragraph, main_area);
+        }
+    }
+    let inner = Block::default().borders(Borders::ALL).inner(slide_area);
+    if let Some(watermark) = &app.watermark {
+        watermark.render(inner, f.buffer_mut());
+    }
+    if !app.card_hidden() {
+        app.draw_background(inner, f.buffer_mut());
+    }
+    if let Some(logo) = &app.logo {
+        logo.render(inner, f.buffer_mut());
+    }
+    let number = app
+        .slide_numbers
+        .filter(|_| !app.slides.is_empty())
+        .map(|corner| (format!("{} / {}", app.current_slide + 1, app.slides.len()), corner));
+    if let Some((number, corner)) = &number {
+        chrome::render_slide_number(number, *corner, app.logo.as_ref(), inner, f.buffer_mut());
+    }
+    if let Some(footer) = &app.footer {
+        chrome::render_footer(
+            footer,
+            app.logo.as_ref(),
+            number.as_ref().map(|(number, corner)| (number.as_str(), *corner)),
+            inner,
+            f.buffer_mut(),
+        );
+    }
+
+    if let Some((region, row)) = pane_row {
+        render_pane(f, app, region, slide_area, row);
+    }
+
+    let questions = match app.unanswered_questions() {
+        0 => String::new(),
+        n => format!(" | {n} open question{}", if n == 1 { "" } else { "s" }),
+    };
+    let section = app
+        .current_section()
+        .map_or(String::new(), |name| format!(" · {name}"));
+    let driver = app
+        .server
+        .as_ref()
+        .and_then(Server::driver)
+        .map_or(String::new(), |name| format!(" | ▶ {name} driving"));
+    let viewers = match app.broadcast.as_ref().map_or(0, Broadcast::viewers) {
+        0 => String::new(),
+        n => format!(" | {n} watching"),
+    };
+    let paused = if app.timer.is_paused() { " | ⏸ Timers paused" } else { "" };
+    let position = format!(
+        "Slide {}{}{}{}{}{}",
+        app.slide_info(),
+        section,
+        questions,
+        driver,
+        viewers,
+        paused
+    );
+    let info_text = match (app.active_status_message(), &app.flashcards) {
+        (Some(message), None) => format!(" {position} | {message} "),
+        (message, Some(cards)) => format!(
+            " Card {}/{}{} | {} known | {} ",
+            cards.position() + 1,
+            cards.len(),
+            if cards.is_known() { " ✓" } else { "" },
+            cards.known_count(),
+            message.unwrap_or("Space Reveal/Next | ← → Cards | m Mark known | q Quit")
+        ),
+        (None, None) => match &app.status_template {
+            Some(template) => format!(" {}{paused} ", app.expand_status_template(template)),
+            None => format!(" {position} | {} ", key_hints(app)),
+        },
+    };
+    let info = Paragraph::new(info_text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(app.current_theme().borders.border_type()),
+        )
+        .style(Style::default().fg(app.current_theme().accent));
+
+    f.render_widget(info, chunks[3]);
+    if !chunks[2].is_empty() {
+        let theme = app.current_theme();
+        let durations = app.slide_durations();
+        timeline::render(&durations, app.current_slide, theme, chunks[2], f.buffer_mut());
+    }
+
+    match app.overlay {
+        Some(Overlay::Questions) => render_questions(f, app),
+        Some(Overlay::Footnotes) => render_footnotes(f, app),
+        Some(Overlay::Overview) => render_overview(f, app),
+        Some(Overlay::Links) => render_links(f, app),
+        Some(Overlay::ConfirmExec(request)) => render_confirm_exec(f, app, request),
+        Some(Overlay::Annotate) => render_annotation_prompt(f, app),
+        Some(Overlay::Search) => render_search_prompt(f, app),
+        Some(Overlay::Command) => render_command_prompt(f, app),
+        Some(Overlay::Picker) => render_picker(f, app),
+        Some(Overlay::Remote) => render_remote(f, app),
+        Some(Overlay::Inspect) => render_inspector(f, app),
+        None => {}
+    }
+}
+
+/// Fewest rows the notes panel takes, including its border.
+const NOTES_MIN_HEIGHT: u16 = 3;
+
+/// The panel showing the current slide's speaker notes below the slide.
+fn notes_panel(app: &App) -> Paragraph<'static> {
+    let annotations = app.current_annotations();
+    let mut text = match app.current_notes() {
+        Some(notes) => Text::styled(notes, Style::default().fg(Color::White)),
+        None if annotations.is_empty() => Text::styled(
+            "No notes for this slide",
+            Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+        ),
+        None => Text::default(),
+    };
+    // Annotations from rehearsals follow the authored notes.
+    if !annotations.is_empty() && !text.lines.is_empty() {
+        text.lines.push(Line::default());
+    }
+    text.lines.extend(
+        annotations
+            .iter()
+            .map(|note| Line::styled(format!("✎ {note}"), Style::default().fg(Color::Yellow))),
+    );
+    Paragraph::new(text)
         .block(
             Block::default()
-                .title("Markdown Slideshow")
+                .title("Notes")
                 .borders(Borders::ALL)
-                .style(Style::default().fg(Color::White)),
+                .border_style(Style::default().fg(Color::Magenta)),
         )
-        .wrap(Wrap { trim: false });
+        .wrap(Wrap { trim: false })
+}
+
+/// Narrowest the slide list may be; below this it is not shown.
+const SIDEBAR_MIN_WIDTH: u16 = 16;
+/// Widest the slide list may be.
+const SIDEBAR_MAX_WIDTH: u16 = 32;
+
+/// Draws the list of slide titles in `area`, highlighting the current slide.
+fn render_sidebar(f: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default()
+        .title("Slides")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::White));
+    let inner = block.inner(area);
+    let items: Vec<ListItem> = app
+        .slides
+        .iter()
+        .enumerate()
+        .map(|(index, slide)| {
+            // Sub-slides are indented under the top of their column.
+            let indent = if slide.row > 0 { "  " } else { "" };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:>3} ", index + 1), Style::default().fg(Color::DarkGray)),
+                Span::raw(format!("{indent}{}", slide.label())),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(app.current_slide));
+    f.render_stateful_widget(list, area, &mut state);
+    app.sidebar_view.set((inner, state.offset()));
+}
+
+/// Height of the filmstrip, including its border.
+const FILMSTRIP_HEIGHT: u16 = 7;
+/// Narrowest a thumbnail in the filmstrip may be.
+const THUMBNAIL_MIN_WIDTH: u16 = 20;
+/// Most thumbnails shown in the filmstrip at once.
+const THUMBNAIL_MAX_COUNT: usize = 7;
+
+/// Draws miniature versions of the previous, current, and next few slides
+/// side by side in `area`.
+///
+/// Each thumbnail keeps only the first few non-blank lines of its slide,
+/// with their styles.
+fn render_filmstrip(f: &mut Frame, app: &App, area: Rect) {
+    let count = usize::from(area.width / THUMBNAIL_MIN_WIDTH)
+        .clamp(1, THUMBNAIL_MAX_COUNT)
+        .min(app.slides.len());
+    if count == 0 {
+        return;
+    }
+    // One slide of context before the current one, unless near the end.
+    let first = app
+        .current_slide
+        .saturating_sub(1)
+        .min(app.slides.len() - count);
+    let cells = Layout::horizontal(vec![Constraint::Ratio(1, count as u32); count]).split(area);
+    for (cell, index) in cells.iter().zip(first..) {
+        let slide = &app.slides[index];
+        let border = if index == app.current_slide {
+            Style::default().fg(Color::Cyan)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        let lines: Vec<Line> = slide
+            .text
+            .lines
+            .iter()
+            .filter(|line| line.spans.iter().any(|span| !span.content.trim().is_empty()))
+            .take(usize::from(FILMSTRIP_HEIGHT - 2))
+            .map(|line| {
+                // Drop the indentation used to center titles.
+                let mut spans = line.spans.clone();
+                if let Some(first) = spans.first_mut() {
+                    first.content = first.content.trim_start().to_string().into();
+                }
+                Line::from(spans)
+            })
+            .collect();
+        f.render_widget(
+            Paragraph::new(lines).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border)
+                    .title(format!(" {} ", index + 1)),
+            ),
+            *cell,
+        );
+    }
+}
+
+/// Keys worth reminding the presenter of in the status bar.
+fn key_hints(app: &App) -> &'static str {
+    if app.is_vertical() {
+        "← → Columns | ↑ ↓ Sub-slides | j k Scroll | g Overview | q Quit"
+    } else {
+        "← → Navigate | ↑ ↓ Scroll | Home/End First/Last | s Screenshot | y Copy | q Quit"
+    }
+}
+
+/// Height of the status bar: hidden with `--no-status-bar` unless there is a
+/// message to show.
+fn status_bar_height(app: &App) -> u16 {
+    if app.status_bar || app.active_status_message().is_some() {
+        3
+    } else {
+        0
+    }
+}
+
+/// Rows taken by the timeline bar.
+fn timeline_height(app: &App) -> u16 {
+    if app.timeline && !app.slides.is_empty() {
+        1
+    } else {
+        0
+    }
+}
+
+/// The bordered block around the slide drawn in `area`, padded to keep room
+/// for the logo and footer.
+///
+/// Once the slide is scrolled, its title gives way to the breadcrumb of the
+/// headings in view.
+fn slide_block(app: &App, area: Rect) -> Block<'static> {
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    let mut padding = app.logo.as_ref().map_or(Padding::ZERO, |logo| logo.padding(inner));
+    if app.footer.is_some() {
+        padding.bottom = padding.bottom.max(1);
+    }
+    let slide_theme = app.slide_theme();
+    let theme = slide_theme.as_ref().unwrap_or_else(|| app.current_theme());
+    let title = match app.breadcrumb() {
+        Some(trail) => Line::styled(format!(" {trail} "), Style::default().fg(theme.primary)),
+        None => Line::from("Markdown Slideshow"),
+    };
+    Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(theme.borders.border_type())
+        .padding(padding)
+        .style(Style::default().fg(theme.text))
+}
+
+/// Cells each image on the current slide takes up in `area`, where `content`
+/// is drawn, for the painter to draw it over. Only images revealed and whole
+/// in view are listed, and none while an overlay covers the slide.
+fn image_areas(
+    app: &App,
+    content: &Text<'static>,
+    exec_output: &[(usize, Vec<Line<'static>>)],
+    hidden_from: usize,
+    area: Rect,
+) -> Vec<(PathBuf, Rect)> {
+    let Some(slide) = app.slides.get(app.current_slide) else {
+        return Vec::new();
+    };
+    // Layouts move the slide's lines about.
+    if !app.painter.is_active()
+        || app.overlay.is_some()
+        || app.card_hidden()
+        || slide.layout.is_some()
+    {
+        return Vec::new();
+    }
+    slide
+        .images
+        .iter()
+        .filter(|image| image.line < hidden_from)
+        .filter_map(|image| {
+            let output_above: usize = exec_output
+                .iter()
+                .filter(|(end, _)| *end <= image.line)
+                .map(|(_, output)| output.len())
+                .sum();
+            let line = image.line + output_above;
+            let above = content.lines.get(..line.checked_sub(app.scroll_offset)?)?;
+            let row = Paragraph::new(Text::from(above.to_vec()))
+                .wrap(Wrap { trim: false })
+                .line_count(area.width);
+            Some((image.path.clone(), image.area(area, row)?))
+        })
+        .collect()
+}
+
+/// Draws the current slide's terminal pane `row` wrapped lines below the top
+/// of the slide area.
+fn render_pane(f: &mut Frame, app: &App, region: &TerminalRegion, slide_area: Rect, row: usize) {
+    let content = slide_block(app, slide_area).inner(slide_area);
+    let top = content.y.saturating_add(u16::try_from(row).unwrap_or(u16::MAX));
+    if top >= content.bottom() {
+        return;
+    }
+    let area = Rect {
+        height: region.rows.min(content.bottom() - top),
+        y: top,
+        ..content
+    };
+    // The terminal keeps its full size when the pane is cut off at the bottom.
+    app.pane_area.set(Rect {
+        height: region.rows - 2,
+        ..Block::default().borders(Borders::ALL).inner(area)
+    });
+
+    let pane = app.panes.get(&app.current_slide);
+    let (hint, color) = match pane {
+        None => (" t to start ", Color::DarkGray),
+        Some(pane) if pane.exited() => (" exited, t to restart ", Color::DarkGray),
+        Some(_) if app.pane_focused => (" Ctrl-] to release ", Color::Green),
+        Some(_) => (" t to focus ", Color::DarkGray),
+    };
+    let block = Block::default()
+        .title(format!(" $ {} ", region.command))
+        .title_bottom(Line::from(hint).alignment(Alignment::Right))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color));
+    let inner = block.inner(area);
+
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    match pane {
+        Some(pane) => pane.render(inner, f.buffer_mut(), app.pane_focused),
+        None => f.render_widget(
+            Paragraph::new(Line::styled(
+                "Press t to start",
+                Style::default().fg(Color::DarkGray),
+            ))
+            .alignment(Alignment::Center),
+            inner,
+        ),
+    }
+}
+
+/// Draws the audience questions panel over the slide.
+fn render_questions(f: &mut Frame, app: &App) {
+    let area = centered_rect(70, 60, f.area());
+    let block = Block::default()
+        .title(" Audience questions ")
+        .title_bottom(" ↑ ↓ Select | Enter Mark answered | a Close ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::White));
+    let inner_width = usize::from(area.width.saturating_sub(4)).max(1);
+
+    f.render_widget(Clear, area);
+    if app.questions.is_empty() {
+        let waiting = Paragraph::new(Line::styled(
+            "No questions yet",
+            Style::default().fg(Color::DarkGray),
+        ))
+        .alignment(Alignment::Center)
+        .block(block);
+        f.render_widget(waiting, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = app
+        .questions
+        .iter()
+        .map(|question| {
+            let (marker, style) = if question.answered {
+                ("✓ ", Style::default().fg(Color::DarkGray))
+            } else {
+                ("• ", Style::default().fg(Color::White))
+            };
+            let lines: Vec<Line> = wrap_words(&question.text, inner_width)
+                .into_iter()
+                .enumerate()
+                .map(|(i, text)| {
+                    let prefix = if i == 0 { marker } else { "  " };
+                    Line::styled(format!("{prefix}{text}"), style)
+                })
+                .collect();
+            ListItem::new(lines)
+        })
+        .collect();
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(app.selected_question));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+/// Draws the footnotes referenced on the current slide.
+fn render_footnotes(f: &mut Frame, app: &App) {
+    let area = centered_rect(70, 50, f.area());
+    let block = Block::default()
+        .title(" Footnotes ")
+        .title_bottom(" f Close ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::White));
+    let mut lines = Vec::new();
+    for note in app.current_footnotes() {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        let text = if note.text.is_empty() {
+            Span::styled("(not defined)", Style::default().fg(Color::DarkGray))
+        } else {
+            Span::raw(note.text.clone())
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("[{}] ", note.label), Style::default().fg(Color::Cyan)),
+            text,
+        ]));
+    }
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+/// Draws what is known about the current slide: where it comes from in the
+/// deck, how far it is revealed, what was wrong with it, and how long the
+/// last frame took to draw.
+fn render_inspector(f: &mut Frame, app: &App) {
+    let Some(slide) = app.slides.get(app.current_slide) else {
+        return;
+    };
+    let area = centered_rect(70, 60, f.area());
+    let block = Block::default()
+        .title(" Inspect ")
+        .title_bottom(" i Close ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::White));
+    let row = |name: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{name:<12}"), Style::default().fg(Color::Cyan)),
+            Span::raw(value),
+        ])
+    };
+    let none = || "none".to_string();
+
+    let (start, end) = slide.source_lines(&app.markdown_content);
+    let fragments = match slide.steps.len() {
+        0 => none(),
+        steps => format!("{} of {} revealed", app.fragment.min(steps), steps),
+    };
+    let background = match (&slide.background, app.backdrop.borrow().as_ref()) {
+        (None, _) => none(),
+        (Some(background), Some(backdrop)) => match backdrop.covers(background) {
+            Some(_) if backdrop.is_empty() => {
+                format!("{} could not be loaded", background.path.display())
+            }
+            Some((cols, rows)) => format!("cached at {cols}x{rows}"),
+            None => "not loaded yet".to_string(),
+        },
+        (Some(_), None) => "not loaded yet".to_string(),
+    };
+    let mut lines = vec![
+        row(
+            "Slide",
+            format!("{} of {}", app.current_slide + 1, app.slides.len()),
+        ),
+        row("Title", slide.title.as_deref().map_or_else(none, str::to_string)),
+        row(
+            "Source",
+            format!("lines {start}–{end} of {}", app.deck_path.display()),
+        ),
+        row(
+            "Lines",
+            format!("{}, scrolled by {}", slide.text.lines.len(), app.scroll_offset),
+        ),
+        row("Fragments", fragments),
+        row("Layout", slide.layout.clone().unwrap_or_else(none)),
+        row("Theme", slide.theme.clone().unwrap_or_else(none)),
+        row("Background", background),
+        row("Drawn in", format!("{:.2} ms", bench::ms(app.draw_time))),
+        Line::from(""),
+    ];
+
+    let problems = app.slide_problems(app.current_slide);
+    if problems.is_empty() {
+        lines.push(Line::styled("No problems found", Style::default().fg(Color::Green)));
+    }
+    for problem in problems {
+        let text = match problem.line {
+            Some(line) => format!("⚠ Line {line}: {}", problem.message),
+            None => format!("⚠ {}", problem.message),
+        };
+        lines.push(Line::styled(text, Style::default().fg(Color::Yellow)));
+    }
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+/// Draws the QR code that opens the presenter remote, with its address
+/// below for phones that cannot scan it.
+fn render_remote(f: &mut Frame, app: &App) {
+    let Some(url) = app.server.as_ref().and_then(Server::remote_url) else {
+        return;
+    };
+    // The lowest error correction keeps the code small enough for the
+    // screen; it is scanned off a screen, not a crumpled print.
+    let Ok(code) = QrCode::with_error_correction_level(&url, EcLevel::L) else {
+        return;
+    };
+    // Scanners need a margin of light modules around the code.
+    const QUIET_ZONE: usize = 2;
+    let width = code.width();
+    let dark = |x: usize, y: usize| {
+        x >= QUIET_ZONE
+            && y >= QUIET_ZONE
+            && x < width + QUIET_ZONE
+            && y < width + QUIET_ZONE
+            && code[(x - QUIET_ZONE, y - QUIET_ZONE)] == qrcode::Color::Dark
+    };
+    let size = width + 2 * QUIET_ZONE;
+    let shade = |dark: bool| if dark { Color::Black } else { Color::White };
+    // Each row of cells shows two rows of modules, the upper as the
+    // foreground of a half block and the lower as its background.
+    let mut lines: Vec<Line> = (0..size.div_ceil(2))
+        .map(|row| {
+            let spans: Vec<Span> = (0..size)
+                .map(|x| {
+                    let style = Style::default()
+                        .fg(shade(dark(x, 2 * row)))
+                        .bg(shade(dark(x, 2 * row + 1)));
+                    Span::styled("▀", style)
+                })
+                .collect();
+            Line::from(spans).alignment(Alignment::Center)
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(url.clone()).alignment(Alignment::Center));
+
+    let outer = f.area();
+    let area_width = (size.max(url.width()) as u16 + 4).min(outer.width);
+    let area_height = (lines.len() as u16 + 2).min(outer.height);
+    let area = Rect::new(
+        outer.x + (outer.width - area_width) / 2,
+        outer.y + (outer.height - area_height) / 2,
+        area_width,
+        area_height,
+    );
+    let block = Block::default()
+        .title(" Scan to open the remote ")
+        .title_bottom(" Any key Close ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::White));
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Narrowest a slide's card in the overview may be.
+const OVERVIEW_CARD_WIDTH: u16 = 24;
+/// Height of a slide's card in the overview.
+const OVERVIEW_CARD_HEIGHT: u16 = 4;
+
+/// Draws every slide as a card in a grid, scrolled to keep the selection in
+/// view.
+fn render_overview(f: &mut Frame, app: &App) {
+    let area = centered_rect(90, 90, f.area());
+    let block = Block::default()
+        .title(" Overview ")
+        .title_bottom(" ← → ↑ ↓ Select | Enter Go to slide | g Close ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::White));
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    let columns = usize::from(inner.width / OVERVIEW_CARD_WIDTH).max(1);
+    let rows = usize::from(inner.height / OVERVIEW_CARD_HEIGHT).max(1);
+    // Cards share out the full width.
+    let card_width = (inner.width / columns as u16).max(OVERVIEW_CARD_WIDTH);
+    app.overview_width.set(columns);
+    let (selected_x, selected_y) = app.overview_position(app.overview_selection);
+    let first_x = (selected_x + 1).saturating_sub(columns);
+    let first_y = (selected_y + 1).saturating_sub(rows);
+
+    for (index, slide) in app.slides.iter().enumerate() {
+        let (x, y) = app.overview_position(index);
+        if !(first_x..first_x + columns).contains(&x) || !(first_y..first_y + rows).contains(&y) {
+            continue;
+        }
+        let card = Rect {
+            x: inner.x + (x - first_x) as u16 * card_width,
+            y: inner.y + (y - first_y) as u16 * OVERVIEW_CARD_HEIGHT,
+            width: card_width,
+            height: OVERVIEW_CARD_HEIGHT,
+        }
+        .intersection(inner);
+        let border = if index == app.overview_selection {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        let number = if index == app.current_slide {
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        f.render_widget(
+            Paragraph::new(slide.label())
+                .wrap(Wrap { trim: true })
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(border)
+                        .title(Span::styled(format!(" {} ", index + 1), number)),
+                ),
+            card,
+        );
+    }
+}
+
+/// Draws the links on the current slide with their full targets.
+fn render_links(f: &mut Frame, app: &App) {
+    let area = centered_rect(70, 50, f.area());
+    let block = Block::default()
+        .title(" Links ")
+        .title_bottom(" ↑ ↓ Select | Enter Open | o Close ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::White));
+    let items: Vec<ListItem> = app
+        .current_links()
+        .iter()
+        .map(|link| {
+            let mut lines = Vec::new();
+            if link.text != link.url {
+                lines.push(Line::from(link.text.clone()));
+            }
+            lines.push(Line::styled(
+                link.url.clone(),
+                Style::default().fg(Color::Blue),
+            ));
+            ListItem::new(lines)
+        })
+        .collect();
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(app.selected_link));
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+/// Draws the prompt asking whether code from the deck may run.
+fn render_confirm_exec(f: &mut Frame, app: &App, request: ExecRequest) {
+    let area = centered_rect(70, 50, f.area());
+    let block = Block::default()
+        .title(" Run code from this deck? ")
+        .title_bottom(" y Allow for this session | n Cancel ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let mut lines = vec![Line::from("This will run on your machine:"), Line::from("")];
+    lines.extend(
+        app.describe_exec(request)
+            .lines()
+            .map(|line| Line::styled(line.to_string(), Style::default().fg(Color::Cyan))),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+/// Draws the prompt for an annotation on the current slide.
+fn render_annotation_prompt(f: &mut Frame, app: &App) {
+    let area = centered_rect(70, 20, f.area());
+    let block = Block::default()
+        .title(format!(" Annotate slide {} ", app.current_slide + 1))
+        .title_bottom(" Enter Save | Esc Cancel ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let line = Line::from(vec![
+        Span::styled(app.annotation_draft.clone(), Style::default().fg(Color::White)),
+        Span::styled("▌", Style::default().fg(Color::Yellow)),
+    ]);
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(line)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+/// Draws the prompt for text to search the deck for.
+fn render_search_prompt(f: &mut Frame, app: &App) {
+    let area = centered_rect(70, 20, f.area());
+    let block = Block::default()
+        .title(" Search ")
+        .title_bottom(" Enter Search | Esc Cancel ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let mut spans = vec![
+        Span::styled("/", Style::default().fg(Color::Yellow)),
+        Span::styled(app.search_draft.clone(), Style::default().fg(Color::White)),
+        Span::styled("▌", Style::default().fg(Color::Yellow)),
+    ];
+    // Until something is typed, Enter searches for the last text again.
+    if app.search_draft.is_empty() {
+        spans.push(Span::styled(app.last_search.clone(), Style::default().fg(Color::DarkGray)));
+    }
+    let line = Line::from(spans);
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(line)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+/// Draws the slide picker: the letters typed, and below them the slides
+/// whose titles match, with the matched letters picked out.
+fn render_picker(f: &mut Frame, app: &App) {
+    let area = centered_rect(70, 50, f.area());
+    let block = Block::default()
+        .title(" Go to Slide ")
+        .title_bottom(" ↑ ↓ Select | Enter Jump | Esc Close ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    let [prompt, list] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+    let query = Line::from(vec![
+        Span::styled("> ", Style::default().fg(Color::Yellow)),
+        Span::styled(app.picker.query().to_string(), Style::default().fg(Color::White)),
+        Span::styled("▌", Style::default().fg(Color::Yellow)),
+    ]);
+    f.render_widget(Paragraph::new(query), prompt);
+
+    let candidates = app.picker_candidates();
+    if candidates.is_empty() {
+        let none = Line::styled("No matching slides", Style::default().fg(Color::DarkGray));
+        f.render_widget(Paragraph::new(none), list);
+        return;
+    }
+    let width = app.slides.len().to_string().len();
+    let items: Vec<ListItem> = candidates
+        .iter()
+        .map(|candidate| {
+            let mut spans = vec![Span::styled(
+                format!("{:>width$}  ", candidate.slide + 1),
+                Style::default().fg(Color::DarkGray),
+            )];
+            let title = app.slides[candidate.slide].label();
+            for (i, c) in title.chars().enumerate() {
+                let style = if candidate.positions.contains(&i) {
+                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                spans.push(Span::styled(c.to_string(), style));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let list_widget =
+        List::new(items).highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(app.picker.selected()));
+    f.render_stateful_widget(list_widget, list, &mut state);
+}
+
+/// Draws the prompt for a command, such as `goto benchmarks`.
+fn render_command_prompt(f: &mut Frame, app: &App) {
+    let area = centered_rect(70, 20, f.area());
+    let block = Block::default()
+        .title(" Command ")
+        .title_bottom(" Enter Run | Esc Cancel ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let line = Line::from(vec![
+        Span::styled(":", Style::default().fg(Color::Yellow)),
+        Span::styled(app.command_draft.clone(), Style::default().fg(Color::White)),
+        Span::styled("▌", Style::default().fg(Color::Yellow)),
+    ]);
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(line)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+/// Returns the directory containing the deck at `path`, which relative paths
+/// in the deck are resolved against.
+fn deck_dir(path: &Path) -> PathBuf {
+    path.parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .to_path_buf()
+}
+
+/// Returns a rectangle of the given percentage size centered within `area`.
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
+/// Breaks `text` into lines of at most `width` columns at word boundaries.
+///
+/// Words longer than `width` are split across lines.
+fn wrap_words(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let needed = if line.is_empty() { 0 } else { line.width() + 1 };
+        if !line.is_empty() && needed + word.width() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        for c in word.chars() {
+            if line.width() + c.to_string().width() > width && !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            line.push(c);
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Parses a human-friendly duration such as `8s`, `1m30s`, `2m`, `500ms` or a
+/// bare number of seconds, or returns `None` if it is malformed, negative, or
+/// too long to represent.
+fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    if let Ok(secs) = text.parse::<f64>() {
+        return Duration::try_from_secs_f64(secs).ok();
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let value: f64 = rest[..split].parse().ok()?;
+        rest = &rest[split..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let seconds = match &rest[..unit_len] {
+            "ms" => value / 1000.0,
+            "s" => value,
+            "m" => value * 60.0,
+            "h" => value * 3600.0,
+            _ => return None,
+        };
+        total = total.checked_add(Duration::try_from_secs_f64(seconds).ok()?)?;
+        rest = &rest[unit_len..];
+    }
+    Some(total)
+}
+
+/// Parses a duration given on the command line; see [`parse_duration`].
+fn parse_duration_arg(text: &str) -> Result<Duration, String> {
+    parse_duration(text)
+        .ok_or_else(|| format!("invalid duration '{text}' (try 8s, 1m30s or 500ms)"))
+}
+
+/// Splits `html` into the HTML comments it contains, so that several
+/// directives on consecutive lines are each seen.
+fn html_comments(html: &str) -> Vec<&str> {
+    let mut comments = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find("<!--") {
+        let Some(len) = rest[start..].find("-->") else {
+            break;
+        };
+        let end = start + len + "-->".len();
+        comments.push(&rest[start..end]);
+        rest = &rest[end..];
+    }
+    comments
+}
+
+/// Parses a slide directive written as an HTML comment, `<!-- key: value -->`,
+/// into its key and value. A bare `<!-- key -->` has an empty value.
+fn parse_directive(html: &str) -> Option<(&str, &str)> {
+    let inner = html.trim().strip_prefix("<!--")?.strip_suffix("-->")?;
+    let (key, value) = inner.split_once(':').unwrap_or((inner, ""));
+    Some((key.trim(), value.trim()))
+}
 
-    f.render_widget(paragraph, chunks[0]);
+/// Returns true if `key` could name a directive: a word of lowercase
+/// letters, digits, and dashes.
+fn is_directive_name(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
 
-    let info_text = format!(
-        " Slide {} | ← → Navigate | ↑ ↓ Scroll | Home/End First/Last | q Quit ",
-        app.slide_info()
-    );
-    let info = Paragraph::new(info_text)
-        .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::Yellow));
+/// Opens `url` with the system's default handler, without waiting for it.
+///
+/// Links without a scheme are taken as paths relative to `dir`.
+fn open_link(url: &str, dir: &Path) -> io::Result<()> {
+    let target = if url.contains("://") || url.starts_with("mailto:") {
+        url.into()
+    } else {
+        dir.join(url).into_os_string()
+    };
+    let mut command = if cfg!(target_os = "macos") {
+        process::Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        process::Command::new("xdg-open")
+    };
+    command
+        .arg(target)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+/// Copies `text` to the system clipboard using the OSC 52 escape sequence.
+///
+/// The terminal emulator performs the copy, so this also works over SSH.
+fn copy_to_clipboard(out: &mut impl Write, text: &str) -> io::Result<()> {
+    write!(out, "\x1b]52;c;{}\x07", BASE64.encode(text))?;
+    out.flush()
+}
 
-    f.render_widget(info, chunks[1]);
+/// Maps a key press to the navigation action it triggers, if any.
+///
+/// In decks with vertical sub-slides, `←`/`→` move between columns and
+/// `↑`/`↓` within one, while `Space` still visits every slide in order.
+fn key_action(code: KeyCode, vertical: bool) -> Option<Action> {
+    match code {
+        KeyCode::Right | KeyCode::Char('l') if vertical => Some(Action::NextColumn),
+        KeyCode::Left | KeyCode::Char('h') if vertical => Some(Action::PrevColumn),
+        KeyCode::Down if vertical => Some(Action::NextInColumn),
+        KeyCode::Up if vertical => Some(Action::PrevInColumn),
+        KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => Some(Action::NextSlide),
+        KeyCode::Left | KeyCode::Char('h') => Some(Action::PrevSlide),
+        KeyCode::Down | KeyCode::Char('j') => Some(Action::ScrollDown),
+        KeyCode::Up | KeyCode::Char('k') => Some(Action::ScrollUp),
+        KeyCode::PageDown => Some(Action::NextSlide),
+        KeyCode::PageUp => Some(Action::PrevSlide),
+        KeyCode::Home => Some(Action::FirstSlide),
+        KeyCode::End => Some(Action::LastSlide),
+        KeyCode::Char(']') => Some(Action::NextSection),
+        KeyCode::Char('[') => Some(Action::PrevSection),
+        _ => None,
+    }
 }
 
 /// Main application loop that handles user input and rendering.
@@ -1073,6 +5452,9 @@ fn ui(f: &mut Frame, app: &App) {
 ///
 /// * `terminal` - The terminal instance to draw to
 /// * `app` - The application state to manage
+/// * `recorder` - Optional asciicast recorder that receives every drawn frame
+/// * `session_log` - Optional log that records every navigation action
+/// * `script` - Optional timed actions to play back, e.g. a replayed session
 ///
 /// # Returns
 ///
@@ -1081,38 +5463,263 @@ fn ui(f: &mut Frame, app: &App) {
 /// # Keyboard Controls
 ///
 /// - `q`, `Esc`: Quit the application
-/// - `→`, `l`, `Space`: Next slide
-/// - `←`, `h`: Previous slide
+/// - `→`, `l`, `Space`: Next fragment or slide
+/// - `←`, `h`: Previous fragment or slide
 /// - `↑`, `k`: Scroll up within slide
 /// - `↓`, `j`: Scroll down within slide
-fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, mut app: App) -> io::Result<()> {
+/// - `s`: Save a screenshot of the current frame
+/// - `y` / `Y`: Copy the slide's text / markdown source to the clipboard
+/// - `Space`, `Enter`: Reveal the answer (flashcard mode)
+/// - `m`: Mark the card as known or not known (flashcard mode)
+/// - `a`: Open or close the audience questions panel (with `--serve`)
+/// - `R`: Show the QR code for the presenter remote (with `--remote`)
+/// - `f`: Show the footnotes referenced on the current slide
+/// - `n`: Show or hide the speaker notes below the slide
+/// - `A`: Annotate the slide; annotations are shown with the notes next time
+/// - `/`: Search the deck; `n` / `N` then jump to the next / previous match
+/// - `:`: Run a command, such as `goto benchmarks` to jump to a slide by id
+/// - Digits, then `Enter`: Jump to the slide with that number
+/// - `Ctrl-P`: Pick a slide to jump to by a few letters of its title
+/// - `r`: Reveal or hide the slide's spoilers
+/// - `o`: List the links on the current slide and open one
+/// - `g`: Show an overview of every slide and jump to one
+/// - `i`: Inspect the slide: its source lines, fragments, warnings, and draw time
+/// - `b`: Show or hide the list of slides; click a slide to jump to it
+/// - `v`: Show or hide thumbnails of the surrounding slides
+/// - `c` / `C`: Switch to the next / previous color theme
+/// - `]` / `[`: Jump to the next / previous section
+/// - `t`: Focus the slide's terminal pane; `Ctrl-]` hands the keyboard back
+/// - `e`: Run the slide's next `exec` block; `Ctrl-C` interrupts it
+fn run_app(
+    terminal: &mut Terminal<term::Backend>,
+    app: &mut App,
+    mut recorder: Option<Recorder>,
+    mut session_log: Option<&mut SessionLog>,
+    mut script: Option<Script>,
+) -> io::Result<()> {
+    let mut last_frame = Buffer::default();
     loop {
-        terminal.draw(|f| ui(f, &app))?;
-
-        match event::read()? {
-            Event::Key(key) => match key.code {
-                KeyCode::Char('q') | KeyCode::Esc => break,
-                KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => app.next_slide(),
-                KeyCode::Left | KeyCode::Char('h') => app.prev_slide(),
-                KeyCode::Down | KeyCode::Char('j') => app.scroll_down(),
-                KeyCode::Up | KeyCode::Char('k') => app.scroll_up(),
-                KeyCode::PageDown => app.next_slide(),
-                KeyCode::PageUp => app.prev_slide(),
-                KeyCode::Home => app.goto_first(),
-                KeyCode::End => app.goto_last(),
+        let drawing = Instant::now();
+        let frame = terminal.draw(|f| ui(f, app))?;
+        app.draw_time = drawing.elapsed();
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.frame(frame.buffer)?;
+        }
+        if let Some(broadcast) = app.broadcast.as_mut() {
+            broadcast.frame(frame.buffer);
+        }
+        last_frame.clone_from(frame.buffer);
+        app.fit_pane();
+        let images = app.image_areas.take();
+        if app.painter.changed(&images) {
+            if app.painter.needs_redraw() {
+                terminal.clear()?;
+                terminal.draw(|f| ui(f, app))?;
+            }
+            app.painter.paint(&mut io::stdout(), &images)?;
+        }
+
+        // Block on input unless a scripted action or animation frame is due first.
+        let timeout = [
+            script.as_ref().and_then(Script::time_until_next),
+            app.animation_timeout(),
+            app.status_message_timeout(),
+            app.server_timeout(),
+            app.broadcast_timeout(),
+            app.console_timeout(),
+            app.advance_timeout(),
+            app.pane_timeout(),
+            app.exec_timeout(),
+            app.watch_timeout(),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        if let Some(event) = term::next_event(terminal, timeout)? {
+            match event {
+                Event::Key(key) if app.pane_focused => {
+                    if pane::is_release_key(key) {
+                        app.pane_focused = false;
+                    } else {
+                        app.send_to_pane(key);
+                    }
+                }
+                Event::Key(key) if app.overlay.is_some() => {
+                    if let Some(action) = app.overlay_key(key.code) {
+                        app.apply(action);
+                        if let Some(log) = session_log.as_mut() {
+                            log.push(action);
+                        }
+                    }
+                }
+                Event::Key(key)
+                    if key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    app.interrupt_runs();
+                }
+                Event::Key(key)
+                    if key.code == KeyCode::Char('p')
+                        && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    app.take_slide_number();
+                    app.show_picker();
+                }
+                Event::Key(pressed) => {
+                    let key = app.keys.translate(pressed);
+                    // Digits add up to a slide number that Enter jumps to;
+                    // any other key drops it. They are read as pressed, so
+                    // that Enter and Esc keep their meaning while a number is
+                    // typed even if they are bound to actions, and a digit
+                    // bound to an action only starts a number when unbound.
+                    let slide_number = app.take_slide_number();
+                    let typing = !slide_number.is_empty();
+                    if let KeyCode::Char(digit @ '0'..='9') = pressed.code
+                        && (typing || (digit != '0' && key.code == pressed.code))
+                    {
+                        app.slide_number = format!("{slide_number}{digit}");
+                        app.flash(format!("Go to slide {} (Enter)", app.slide_number));
+                    } else if pressed.code == KeyCode::Enter && typing {
+                        match app.find_slide(&slide_number) {
+                            Some(index) => {
+                                let action = Action::GotoSlide(index);
+                                app.apply(action);
+                                if let Some(log) = session_log.as_mut() {
+                                    log.push(action);
+                                }
+                            }
+                            None => app.flash(format!("No slide {slide_number}")),
+                        }
+                    } else if pressed.code == KeyCode::Esc && typing {
+                        // Esc drops a slide number before it ends the
+                        // presentation.
+                    } else if key.code == KeyCode::Esc && app.search.is_some() {
+                        // Esc ends a search before it ends the presentation.
+                        app.search = None;
+                    } else if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                        break;
+                    } else if key.code == KeyCode::Char('/') {
+                        app.start_search();
+                    } else if key.code == KeyCode::Char(':') {
+                        app.start_command();
+                    } else if matches!(key.code, KeyCode::Char('n') | KeyCode::Char('N'))
+                        && app.search.is_some()
+                    {
+                        if let Some(action) = app.step_search(key.code == KeyCode::Char('n'))
+                            && let Some(log) = session_log.as_mut()
+                        {
+                            log.push(action);
+                        }
+                    } else if key.code == KeyCode::Char('a') {
+                        app.toggle_questions();
+                    } else if key.code == KeyCode::Char('R') {
+                        app.toggle_remote();
+                    } else if key.code == KeyCode::Char('A') {
+                        app.start_annotation();
+                    } else if key.code == KeyCode::Char('f') {
+                        app.show_footnotes();
+                    } else if key.code == KeyCode::Char('n') {
+                        app.notes_panel = !app.notes_panel;
+                    } else if key.code == KeyCode::Char('o') {
+                        app.show_links();
+                    } else if key.code == KeyCode::Char('t') {
+                        app.focus_pane();
+                    } else if key.code == KeyCode::Char('e') {
+                        app.run_next_block();
+                    } else if matches!(key.code, KeyCode::Char(' ') | KeyCode::Enter)
+                        && app.card_hidden()
+                    {
+                        app.reveal_card();
+                    } else if key.code == KeyCode::Char('r') {
+                        app.toggle_spoilers();
+                    } else if key.code == KeyCode::Char('m') {
+                        app.toggle_card_known();
+                    } else if key.code == KeyCode::Char('s') {
+                        app.save_screenshot(&last_frame);
+                    } else if key.code == KeyCode::Char('y') {
+                        copy_to_clipboard(&mut io::stdout(), &app.current_slide_plain_text())?;
+                        app.flash("Copied slide text to clipboard".to_string());
+                    } else if key.code == KeyCode::Char('Y') {
+                        copy_to_clipboard(&mut io::stdout(), app.current_slide_source())?;
+                        app.flash("Copied slide markdown to clipboard".to_string());
+                    } else if matches!(key.code, KeyCode::Char('+') | KeyCode::Char('=')) {
+                        app.zoom(&mut io::stdout(), Some(1))?;
+                    } else if key.code == KeyCode::Char('-') {
+                        app.zoom(&mut io::stdout(), Some(-1))?;
+                    } else if key.code == KeyCode::Char('0') {
+                        app.zoom(&mut io::stdout(), None)?;
+                    } else if key.code == KeyCode::Char('b') {
+                        app.toggle_sidebar();
+                    } else if key.code == KeyCode::Char('v') {
+                        app.filmstrip = !app.filmstrip;
+                        if app.paginate {
+                            app.reflow();
+                        }
+                    } else if key.code == KeyCode::Char('g') {
+                        app.show_overview();
+                    } else if key.code == KeyCode::Char('i') {
+                        app.overlay = Some(Overlay::Inspect);
+                    } else if matches!(key.code, KeyCode::Char('c') | KeyCode::Char('C')) {
+                        app.cycle_theme(key.code == KeyCode::Char('c'));
+                    } else if let Some(action) = key_action(key.code, app.is_vertical()) {
+                        app.apply(action);
+                        if let Some(log) = session_log.as_mut() {
+                            log.push(action);
+                        }
+                    }
+                }
+                Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                    if let Some(index) = app.sidebar_slide_at(mouse.column, mouse.row) {
+                        let action = Action::GotoSlide(index);
+                        app.apply(action);
+                        if let Some(log) = session_log.as_mut() {
+                            log.push(action);
+                        }
+                    }
+                }
+                #[cfg(not(feature = "termwiz"))]
+                Event::Focus(focused) => app.focus_changed(focused),
+                Event::Resize(w, h) if !app.fixed_size => {
+                    if let Some(recorder) = recorder.as_mut() {
+                        recorder.resize(w, h)?;
+                    }
+                    app.resize(w, h);
+                    // Resizing clears the screen, images and all.
+                    app.painter.forget();
+                }
                 _ => {}
-            },
-            Event::Resize(w, _) => app.resize(w),
-            _ => {}
+            }
+        }
+
+        if let Some(script) = script.as_mut() {
+            while let Some(action) = script.next_due() {
+                app.apply(action);
+            }
+        }
+        if app.advance_timeout() == Some(Duration::ZERO) {
+            app.apply(Action::NextSlide);
+        }
+        for action in [app.poll_server(), app.poll_console()].concat() {
+            app.apply(action);
+            if let Some(log) = session_log.as_mut() {
+                log.push(action);
+            }
         }
+        app.publish_remote();
+        app.publish_console();
+        app.poll_runs();
+        app.reload_if_changed();
+    }
+    app.painter.paint(&mut io::stdout(), &[])?;
+    if let Some(recorder) = recorder {
+        recorder.finish()?;
     }
     Ok(())
 }
 
 /// Main entry point for the presentation application.
 ///
-/// Parses command line arguments, sets up the terminal, runs the slideshow,
-/// and cleans up the terminal state before exiting.
+/// Parses command line arguments and dispatches to the requested mode.
 ///
 /// # Returns
 ///
@@ -1120,36 +5727,1037 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, mut app: App) -> i
 ///
 /// # Errors
 ///
-/// - File I/O errors when reading the markdown file
+/// - File I/O errors when reading the markdown or session file
 /// - Terminal setup/cleanup errors
 /// - Application runtime errors
 fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::parse();
+    if let Some(path) = &args.log_file {
+        logging::init(path, args.log_level.as_deref())?;
+        log::info!("presentrs {} started", env!("CARGO_PKG_VERSION"));
+    }
+
+    match args.command {
+        Some(Command::Replay {
+            file,
+            session,
+            present: options,
+        }) => {
+            let script = Script::load_session(&session)?;
+            present(&file, &options, None, Some(script), None)
+        }
+        Some(Command::Flashcards {
+            file,
+            all,
+            ordered,
+            present: options,
+        }) => {
+            let cards = Flashcards::load(Path::new(&file), all, !ordered)?;
+            present(&file, &options, None, None, Some(cards))
+        }
+        Some(Command::Compare { file, runs, list }) => compare(Path::new(&file), &runs, list),
+        Some(Command::Export {
+            file,
+            format,
+            output,
+            all,
+            simulate,
+            watch,
+        }) => {
+            let size = match simulate {
+                Some(size) => size,
+                None => term::size()
+                    .map_or(EXPORT_SIZE, |(width, height)| Size { width, height }),
+            };
+            match all {
+                Some(dir) => export(&file, None, &dir, size, watch),
+                None => {
+                    let output =
+                        output.unwrap_or_else(|| format.default_output(Path::new(&file)));
+                    export(&file, Some(format), &output, size, watch)
+                }
+            }
+        }
+        Some(Command::Outline { file }) => outline(&file),
+        Some(Command::Split { file, dir }) => split(&file, &dir),
+        Some(Command::Console { addr, token }) => console::run(&addr, &token),
+        Some(Command::Themes) => show_themes(),
+        Some(Command::New { dir, theme }) => {
+            let mut themes = Themes::load()?;
+            let index = themes.select(&theme)?;
+            let files = scaffold::create(&dir, themes.get(index))?;
+            for file in &files {
+                println!("Wrote {}", file.display());
+            }
+            let deck = dir.join(scaffold::DECK_FILE);
+            let theme = dir.join(scaffold::THEME_FILE);
+            println!("Present it with: presentrs {} --theme {}", deck.display(), theme.display());
+            Ok(())
+        }
+        Some(Command::Check {
+            file,
+            simulate,
+            spelling,
+            format,
+        }) => check(&file, simulate, spelling, format),
+        Some(Command::Bench {
+            file,
+            simulate,
+            runs,
+            format,
+        }) => bench(&file, simulate, runs, format),
+        Some(Command::Theme {
+            command: ThemeCommand::Export { name, file },
+        }) => {
+            let mut themes = Themes::load()?;
+            let index = themes.select(&name)?;
+            themes.get(index).export(Path::new(&file))?;
+            println!("Wrote theme '{}' to {}", themes.get(index).name, file);
+            Ok(())
+        }
+        None if args.list_code_themes => {
+            for name in themes::code_theme_names(&ThemeSet::load_defaults()) {
+                println!("{name}");
+            }
+            Ok(())
+        }
+        None => {
+            let file = args.file.expect("clap requires the file argument");
+            if let Some(format) = args.list_slides {
+                return list_slides(&file, format, &args.present);
+            }
+            let script = match &args.autoplay {
+                Some(path) => Some(Script::load_timings(path)?),
+                None => None,
+            };
+            let mut session_log = args
+                .record_session
+                .as_deref()
+                .map(|path| SessionLog::new(path, &file));
+            present(&file, &args.present, session_log.as_mut(), script, None)?;
+            if let Some(log) = session_log {
+                log.save()?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Presents a markdown deck in the terminal.
+///
+/// Sets up the terminal, runs the slideshow, and cleans up the terminal
+/// state before returning.
+///
+/// # Arguments
+///
+/// * `file` - Path to the markdown file
+/// * `options` - Presentation options shared by all presenting modes
+/// * `session_log` - Optional log that records navigation actions
+/// * `script` - Optional timed actions to play back
+/// * `flashcards` - Study session to run instead of a regular presentation
+fn present(
+    file: &str,
+    options: &PresentArgs,
+    session_log: Option<&mut SessionLog>,
+    script: Option<Script>,
+    flashcards: Option<Flashcards>,
+) -> Result<(), Box<dyn Error>> {
+    let markdown_content = fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read file '{}': {}", file, e))?;
+    log::info!("Presenting '{file}'");
 
-    let markdown_content = fs::read_to_string(&args.file)
-        .map_err(|e| format!("Failed to read file '{}': {}", args.file, e))?;
+    let recorder = match &options.record {
+        Some(path) => {
+            let (width, height) = match options.simulate {
+                Some(size) => (size.width, size.height),
+                None => term::size()?,
+            };
+            Some(
+                Recorder::create(path, width, height)
+                    .map_err(|e| format!("Failed to create recording '{}': {}", path, e))?,
+            )
+        }
+        None => None,
+    };
+    let config = Config::load()?;
+    let companion_notes = CompanionNotes::load(Path::new(file))?;
+    let annotations = Annotations::load(Path::new(file))?;
+    let logo = config.logo.as_ref().map(Logo::load).transpose()?;
+    // The command line's theme takes precedence over the config's.
+    let mut themes = Themes::load()?;
+    let theme = match options.theme.as_deref().or(config.theme.as_deref()) {
+        Some(name) => themes.select(name)?,
+        None => 0,
+    };
+    // Likewise for the code theme, where a file wins over a name.
+    let mut code_themes = ThemeSet::load_defaults();
+    let code_theme = if let Some(path) = &options.code_theme_file {
+        Some(themes::load_code_theme(path, &mut code_themes)?)
+    } else if let Some(name) = &options.code_theme {
+        Some(name.clone())
+    } else if let Some(path) = &config.code_theme_file {
+        Some(themes::load_code_theme(path, &mut code_themes)?)
+    } else {
+        config.code_theme.clone()
+    };
+    if let Some(name) = &code_theme {
+        themes.set_code_theme(name, &code_themes)?;
+    }
+    // syntect's own definitions come with the app, so they are only loaded
+    // again to add to them or to pair them with another backend.
+    let syntaxes = options.syntaxes.as_deref().or(config.syntaxes.as_deref());
+    let highlighter = match (syntaxes, config.highlighter) {
+        (None, Backend::Syntect) => None,
+        (syntaxes, backend) => {
+            let syntax_set = match syntaxes {
+                Some(dir) => load_syntaxes(dir)?,
+                None => SyntaxSet::load_defaults_newlines(),
+            };
+            Some(Highlighter::new(syntax_set, backend)?)
+        }
+    };
+    let tag_filter = TagFilter::new(options.tags.clone(), options.skip_tags.clone());
+    // Checked before the terminal is taken over, so the problems stay on
+    // screen.
+    if let Some(lang) = &options.lang {
+        let languages = lang::languages(&markdown_content);
+        if !languages.iter().any(|l| l.eq_ignore_ascii_case(lang)) {
+            return Err(format!(
+                "No content in '{}' is marked 'lang: {}' (marked: {})",
+                file,
+                lang,
+                if languages.is_empty() { "none".to_string() } else { languages.join(", ") }
+            )
+            .into());
+        }
+    }
+    if !tag_filter.is_empty() || options.start_at.is_some() {
+        let mut deck = App::new(Path::new(file), markdown_content.clone(), EXPORT_SIZE.width);
+        deck.tag_filter = tag_filter.clone();
+        deck.lang = options.lang.clone().or(deck.lang);
+        deck.reflow();
+        if deck.slides.is_empty() {
+            let message = format!("No slides in '{}' are left by --tags and --skip-tags", file);
+            return Err(message.into());
+        }
+        if let Some(target) = &options.start_at
+            && deck.find_slide(target).is_none()
+        {
+            return Err(format!("No slide in '{}' has the id or number '{}'", file, target).into());
+        }
+    }
+    if options.strict || config.strict {
+        let mut deck = App::new(Path::new(file), markdown_content.clone(), EXPORT_SIZE.width);
+        deck.layouts = Layouts::new(config.layouts.clone());
+        deck.themes = std::mem::take(&mut themes);
+        deck.theme = theme;
+        deck.theme_set = std::mem::take(&mut code_themes);
+        deck.html = options
+            .html
+            .or(deck.front_matter.get("html").and_then(HtmlPolicy::named))
+            .or(config.html)
+            .unwrap_or_default();
+        deck.tag_filter = tag_filter.clone();
+        deck.lang = options.lang.clone().or(deck.lang);
+        deck.reflow();
+        let problems = deck.deck_problems();
+        themes = deck.themes;
+        code_themes = deck.theme_set;
+        if !problems.is_empty() {
+            for problem in &problems {
+                eprintln!("{problem}");
+            }
+            return Err(format!(
+                "{} problems in '{}', which --strict does not present",
+                problems.len(),
+                file
+            )
+            .into());
+        }
+    }
+    let tls = match (&options.tls_cert, &options.tls_key) {
+        (Some(cert), Some(key)) => Some(server::tls_config(cert, key)?),
+        _ => None,
+    };
+    let broadcast = match &options.broadcast {
+        Some(addr) => Some(
+            Broadcast::start(addr)
+                .map_err(|e| format!("Failed to listen on '{}': {}", addr, e))?,
+        ),
+        None => None,
+    };
+    let server = match &options.serve {
+        Some(addr) => Some(
+            Server::start(addr, options.remote, tls)
+                .map_err(|e| format!("Failed to listen on '{}': {}", addr, e))?,
+        ),
+        None => None,
+    };
+    let console = if options.tmux.is_some() || options.console {
+        let console = ConsoleLink::start()
+            .map_err(|e| format!("Failed to listen for the presenter console: {}", e))?;
+        match options.tmux {
+            Some(layout) => console.open_in_tmux(layout)?,
+            None => {
+                // The slides take over this terminal, so the command is
+                // shown until the presenter is ready.
+                println!("Run the presenter console in another terminal with:\n");
+                println!("    {}\n", console.command()?);
+                println!("Then press Enter here to start the presentation.");
+                io::stdin().read_line(&mut String::new())?;
+            }
+        }
+        Some(console)
+    } else {
+        None
+    };
 
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    let backend = term::enter(true)?;
+    // A simulated size confines the deck to that much of the terminal.
+    let mut terminal = match options.simulate {
+        Some(size) => Terminal::with_options(
+            backend,
+            TerminalOptions {
+                viewport: Viewport::Fixed(Rect::new(0, 0, size.width, size.height)),
+            },
+        )?,
+        None => Terminal::new(backend)?,
+    };
 
-    let terminal_size = terminal.size()?;
-    let app = App::new(markdown_content, terminal_size.width);
-    let res = run_app(&mut terminal, app);
+    let terminal_size = terminal.get_frame().area();
+    let mut app = App::new(Path::new(file), markdown_content, terminal_size.width);
+    app.terminal_height = terminal_size.height;
+    app.paginate = options.paginate || app.front_matter.flag("paginate");
+    app.tag_filter = tag_filter;
+    // The command line's language takes precedence over the front matter's.
+    if let Some(lang) = &options.lang {
+        app.lang = Some(lang.clone());
+    }
+    app.fixed_size = options.simulate.is_some();
+    app.auto_advance = options.auto_advance;
+    app.pause_on_blur = options.pause_on_blur || config.pause_on_blur;
+    app.watch = options.watch;
+    app.deck_modified = modified_at(Path::new(file));
+    app.exec_policy = config.exec;
+    app.companion_notes = companion_notes;
+    app.annotations = annotations;
+    app.logo = logo;
+    // The command line's text takes precedence over the config's.
+    let position = config.watermark.as_ref().map(|w| w.position).unwrap_or_default();
+    app.watermark = options
+        .watermark
+        .as_deref()
+        .or(config.watermark.as_ref().map(|w| w.text.as_str()))
+        .map(|text| Watermark::new(text, position));
+    app.slide_numbers = options.slide_numbers.or(config.slide_numbers);
+    app.status_bar = !options.no_status_bar && config.status_bar.unwrap_or(true);
+    app.timeline = options.timeline || config.timeline;
+    if app.timeline
+        && let Some(run) = Rehearsal::load_runs(Path::new(file))?.pop()
+    {
+        app.rehearsed = run
+            .slides
+            .iter()
+            // A time that is not a duration, as in a hand-edited run, counts as
+            // not rehearsed.
+            .map(|slide| {
+                Duration::try_from_secs_f64(slide.seconds)
+                    .ok()
+                    .filter(|_| slide.visits > 0)
+            })
+            .collect();
+    }
+    app.status_template = config.status_template;
+    app.fragment_style = options.fragments.or(config.fragments).unwrap_or_default();
+    app.painter = Painter::new(options.images.or(config.images).unwrap_or_default());
+    // The command line takes precedence over the front matter, and both over the config.
+    app.density = options
+        .density
+        .or(app.front_matter.get("density").and_then(Density::named))
+        .or(config.density)
+        .unwrap_or_default();
+    app.html = options
+        .html
+        .or(app.front_matter.get("html").and_then(HtmlPolicy::named))
+        .or(config.html)
+        .unwrap_or_default();
+    app.separator = options
+        .separator
+        .clone()
+        .or(app.front_matter.get("separator").and_then(Separator::named))
+        .or(config.separator)
+        .unwrap_or_default();
+    app.keys = config.keys;
+    app.keep_spoilers_revealed = config.keep_spoilers_revealed;
+    app.layouts = Layouts::new(config.layouts);
+    app.keywords = Keywords::new(config.keywords);
+    app.themes = themes;
+    app.theme = theme;
+    app.theme_set = code_themes;
+    if let Some(highlighter) = highlighter {
+        app.highlighter = highlighter;
+    }
+    // A template in the deck's front matter takes precedence over the config's.
+    let template = app.front_matter.get("footer").or(config.footer.as_deref());
+    app.footer = app.front_matter.footer(template);
+    app.reflow();
+    if let Some(index) = options.start_at.as_deref().and_then(|target| app.find_slide(target)) {
+        app.goto_slide(index);
+    }
+    if let Some(name) = app
+        .slides
+        .iter()
+        .filter_map(|slide| slide.layout.as_deref())
+        .find(|name| app.layouts.get(name).is_none())
+    {
+        log::warn!("Unknown layout '{name}'");
+        app.flash(format!("Unknown layout '{name}'"));
+    }
+    if let Some(name) = app
+        .slides
+        .iter()
+        .filter_map(|slide| slide.theme.as_deref())
+        .find(|name| app.themes.resolve(name, app.theme).is_none())
+    {
+        log::warn!("Unknown theme '{name}'");
+        app.flash(format!("Unknown theme '{name}'"));
+    }
+    app.exec_enabled = !options.no_exec;
+    if let Some(cards) = flashcards {
+        app.start_flashcards(cards);
+    }
+    if let Some(broadcast) = broadcast {
+        app.flash(format!("Broadcasting at {} (nc to follow along)", broadcast.addr()));
+        app.broadcast = Some(broadcast);
+    }
+    if let Some(server) = server {
+        app.start_server(server);
+    }
+    app.console = console;
+    app.zoom = Zoom::detect();
+    let res = run_app(&mut terminal, &mut app, recorder, session_log, script);
 
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    app.zoom.reset(&mut io::stdout())?;
+    term::leave(&mut terminal)?;
 
     if let Err(err) = res {
+        log::error!("The presentation stopped: {err}");
         println!("{err:?}");
     }
 
+    if let Some(cards) = &app.flashcards {
+        cards.save()?;
+        println!("{} of {} cards known", cards.known_count(), cards.len());
+    }
+
+    if options.export_timings.is_some() || options.rehearse {
+        let rehearsal = app.into_rehearsal();
+        if let Some(path) = &options.export_timings {
+            rehearsal.export(Path::new(path))?;
+        }
+        if options.rehearse {
+            let saved = rehearsal.save_run(Path::new(file))?;
+            println!("Saved rehearsal run to {}", saved.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns syntect's syntax definitions together with the `.sublime-syntax`
+/// files in `dir` and its subfolders.
+fn load_syntaxes(dir: &Path) -> Result<SyntaxSet, Box<dyn Error>> {
+    let mut builder = SyntaxSet::load_defaults_newlines().into_builder();
+    builder.add_from_folder(dir, true).map_err(|e| {
+        format!("Failed to load syntax definitions from '{}': {}", dir.display(), e)
+    })?;
+    Ok(builder.build())
+}
+
+/// Loads the deck in `file` to be drawn at `size` somewhere other than the
+/// terminal, with the logo, footer, status bar, and styles of `config`.
+///
+/// Decks paginated in their front matter are paginated at that size.
+fn offscreen_app(file: &str, size: Size, config: Config) -> Result<App, Box<dyn Error>> {
+    let markdown_content = fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read file '{}': {}", file, e))?;
+    let mut app = App::new(Path::new(file), markdown_content, size.width);
+    app.terminal_height = size.height;
+    app.fixed_size = true;
+    // The logo, footer, and status bar all take room from the slides.
+    app.logo = config.logo.as_ref().map(Logo::load).transpose()?;
+    app.status_bar = config.status_bar.unwrap_or(true);
+    app.timeline = config.timeline;
+    let template = app.front_matter.get("footer").or(config.footer.as_deref());
+    app.footer = app.front_matter.footer(template);
+    app.paginate = app.front_matter.flag("paginate");
+    app.density = app
+        .front_matter
+        .get("density")
+        .and_then(Density::named)
+        .or(config.density)
+        .unwrap_or_default();
+    app.html = app
+        .front_matter
+        .get("html")
+        .and_then(HtmlPolicy::named)
+        .or(config.html)
+        .unwrap_or_default();
+    app.separator = app
+        .front_matter
+        .get("separator")
+        .and_then(Separator::named)
+        .or(config.separator)
+        .unwrap_or_default();
+    let position = config.watermark.as_ref().map(|w| w.position).unwrap_or_default();
+    app.watermark = config
+        .watermark
+        .as_ref()
+        .map(|w| Watermark::new(&w.text, position));
+    app.slide_numbers = config.slide_numbers;
+    app.layouts = Layouts::new(config.layouts);
+    app.keywords = Keywords::new(config.keywords);
+    app.themes = Themes::load()?;
+    if let Some(name) = &config.theme {
+        app.theme = app.themes.select(name)?;
+    }
+    let code_theme = match &config.code_theme_file {
+        Some(path) => Some(themes::load_code_theme(path, &mut app.theme_set)?),
+        None => config.code_theme,
+    };
+    if let Some(name) = &code_theme {
+        app.themes.set_code_theme(name, &app.theme_set)?;
+    }
+    if let Some(dir) = &config.syntaxes {
+        app.highlighter = Highlighter::new(load_syntaxes(dir)?, config.highlighter)?;
+    } else if !matches!(config.highlighter, Backend::Syntect) {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        app.highlighter = Highlighter::new(syntax_set, config.highlighter)?;
+    }
+    app.reflow();
+    Ok(app)
+}
+
+/// Lays out every slide of a deck at `size`, or at the terminal's size if
+/// it is not given, and reports the slides whose content does not fit, the
+/// slides that break the rules of the config file, and with `spelling`, the
+/// words of the deck that are not in the dictionary.
+///
+/// Fails if any problem is found, so that the check can gate a deck in CI.
+fn check(
+    file: &str,
+    size: Option<Size>,
+    spelling: bool,
+    format: Format,
+) -> Result<(), Box<dyn Error>> {
+    let size = match size {
+        Some(size) => size,
+        None => {
+            let (width, height) = term::size()?;
+            Size { width, height }
+        }
+    };
+    let mut config = Config::load()?;
+    let rules = std::mem::take(&mut config.check);
+    let spelling_config = std::mem::take(&mut config.spelling);
+    let mut app = offscreen_app(file, size, config)?;
+    let chrome = timeline_height(&app) + status_bar_height(&app);
+    let area = Rect::new(0, 0, size.width, size.height.saturating_sub(chrome));
+    let mut findings = Vec::new();
+    for slide in 0..app.slides.len() {
+        app.current_slide = slide;
+        let content = slide_block(&app, area).inner(area);
+        let text = &app.slides[slide].text;
+        // Nothing wraps into no columns at all, so every line overflows.
+        let rows = match content.width {
+            0 => text.lines.len(),
+            width => Paragraph::new(text.clone()).wrap(Wrap { trim: false }).line_count(width),
+        };
+        let room = if content.width == 0 { 0 } else { usize::from(content.height) };
+        if rows > room {
+            findings.push(Finding {
+                rule: "overflow",
+                slide: slide + 1,
+                line: None,
+                message: format!(
+                    "{rows} rows of content at {size}, room for {room} ({} over)",
+                    rows - room
+                ),
+            });
+        }
+    }
+    let slides: Vec<SlideInfo> = app
+        .slides
+        .iter()
+        .map(|slide| SlideInfo {
+            source: slide.source.clone(),
+            title: slide.title.as_deref(),
+            layout: slide.layout.as_deref(),
+        })
+        .collect();
+    // Notes are not on the slide, so they count against no rule.
+    let (markdown, _) = notes::take_blocks(&app.markdown_content);
+    findings.extend(rules.check(&markdown, &slides));
+    findings.extend(app.deck_problems());
+    if spelling {
+        let dictionary = Dictionary::load(&spelling_config, Path::new(file))?;
+        let sources: Vec<Range<usize>> =
+            app.slides.iter().map(|slide| slide.source.clone()).collect();
+        findings.extend(spelling::check(&app.markdown_content, &sources, &dictionary));
+    }
+
+    match format {
+        Format::Text => {
+            for finding in &findings {
+                println!("{finding}");
+            }
+        }
+        Format::Json => {
+            let report = Report {
+                file,
+                slides: app.slides.len(),
+                findings: &findings,
+            };
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+    }
+    if !findings.is_empty() {
+        return Err(format!("{} problems in {} slides", findings.len(), app.slides.len()).into());
+    }
+    if let Format::Text = format {
+        println!("All {} slides fit in {} and follow the rules", app.slides.len(), size);
+        if spelling {
+            println!("No unknown words");
+        }
+    }
+    Ok(())
+}
+
+/// Times the work done to show a deck at `size`, or at the terminal's size
+/// if it is not given: parsing it, highlighting each slide's code blocks,
+/// and drawing each slide. Each time is the median of `runs` runs.
+fn bench(
+    file: &str,
+    size: Option<Size>,
+    runs: usize,
+    format: Format,
+) -> Result<(), Box<dyn Error>> {
+    let size = match size {
+        Some(size) => size,
+        None => {
+            let (width, height) = term::size()?;
+            Size { width, height }
+        }
+    };
+    let mut app = offscreen_app(file, size, Config::load()?)?;
+    let parse = bench::median(runs, || app.reflow());
+    let theme = app.current_theme().clone();
+    let code_theme = app.theme_set.themes[theme.code_theme.as_str()].clone();
+    let mut terminal = Terminal::new(TestBackend::new(size.width, size.height))?;
+    let mut slides = Vec::new();
+    for index in 0..app.slides.len() {
+        let blocks = code_blocks(&app.markdown_content[app.slides[index].source.clone()]);
+        let highlight = bench::median(runs, || {
+            for (lang, code) in &blocks {
+                highlight_code_block(code, lang.as_deref(), &theme, &code_theme, &app.highlighter);
+            }
+        });
+        app.show_slide(index);
+        let mut drawn = Ok(());
+        let render = bench::median(runs, || {
+            if drawn.is_ok() {
+                drawn = terminal.draw(|f| ui(f, &app)).map(|_| ());
+            }
+        });
+        drawn?;
+        slides.push(SlideTimes {
+            slide: index + 1,
+            title: app.slides[index].title.clone(),
+            code_blocks: blocks.len(),
+            highlight_ms: bench::ms(highlight),
+            render_ms: bench::ms(render),
+        });
+    }
+    let report = bench::Report {
+        file: file.to_string(),
+        size: size.to_string(),
+        runs: runs.max(1),
+        parse_ms: bench::ms(parse),
+        slides,
+    };
+    match format {
+        Format::Text => println!("{report}"),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+    Ok(())
+}
+
+/// Returns the language and text of every code block in `markdown`.
+fn code_blocks(markdown: &str) -> Vec<(Option<String>, String)> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(Option<String>, String)> = None;
+    for event in MarkdownParser::new(markdown) {
+        match event {
+            MarkdownEvent::Start(Tag::CodeBlock(kind)) => {
+                let lang = match kind {
+                    pulldown_cmark::CodeBlockKind::Indented => None,
+                    pulldown_cmark::CodeBlockKind::Fenced(info) => {
+                        info.split_whitespace().next().map(str::to_string)
+                    }
+                };
+                current = Some((lang, String::new()));
+            }
+            MarkdownEvent::Text(text) => {
+                if let Some((_, code)) = &mut current {
+                    code.push_str(&text);
+                }
+            }
+            MarkdownEvent::End(TagEnd::CodeBlock) => blocks.extend(current.take()),
+            _ => {}
+        }
+    }
+    blocks
+}
+
+/// Prints a line of tab-separated values, or an object in a JSON array,
+/// for every slide of a deck presented with `options`, for scripts such as
+/// one adding chapter markers to a recording.
+fn list_slides(
+    file: &str,
+    format: ListFormat,
+    options: &PresentArgs,
+) -> Result<(), Box<dyn Error>> {
+    let markdown_content = fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read file '{}': {}", file, e))?;
+    let companion_notes = CompanionNotes::load(Path::new(file))?;
+    let mut app = App::new(Path::new(file), markdown_content, THEME_SAMPLE_WIDTH);
+    app.tag_filter = TagFilter::new(options.tags.clone(), options.skip_tags.clone());
+    if let Some(lang) = &options.lang {
+        app.lang = Some(lang.clone());
+    }
+    app.separator = options
+        .separator
+        .clone()
+        .or(app.front_matter.get("separator").and_then(Separator::named))
+        .or(Config::load()?.separator)
+        .unwrap_or_default();
+    app.reflow();
+    let markdown = &app.markdown_content;
+    let listings: Vec<SlideListing> = app
+        .slides
+        .iter()
+        .enumerate()
+        .map(|(index, slide)| {
+            let (start_line, end_line) = slide.source_lines(markdown);
+            SlideListing {
+                slide: index + 1,
+                title: slide.title.as_ref().map(|title| title.trim().to_string()),
+                start_line,
+                end_line,
+                notes: companion_notes
+                    .merge(&slide.notes, index, slide.title.as_deref())
+                    .is_some(),
+                tags: slide.tags.clone(),
+            }
+        })
+        .collect();
+    match write_listings(&mut io::stdout().lock(), &listings, format) {
+        // A reader that stops early, such as `head`, is not an error.
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Writes `listings` to `out` in `format`, for [`list_slides`].
+fn write_listings(
+    out: &mut impl Write,
+    listings: &[SlideListing],
+    format: ListFormat,
+) -> io::Result<()> {
+    match format {
+        ListFormat::Tsv => {
+            writeln!(out, "slide\ttitle\tstart_line\tend_line\tnotes\ttags")?;
+            for listing in listings {
+                // Tabs and newlines in a title would break the row apart.
+                let title = listing.title.as_deref().unwrap_or_default().replace(['\t', '\n'], " ");
+                writeln!(
+                    out,
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    listing.slide,
+                    title,
+                    listing.start_line,
+                    listing.end_line,
+                    if listing.notes { "yes" } else { "no" },
+                    listing.tags.join(",")
+                )?;
+            }
+        }
+        ListFormat::Json => {
+            let json = serde_json::to_string_pretty(listings).map_err(io::Error::other)?;
+            writeln!(out, "{json}")?;
+        }
+    }
+    out.flush()
+}
+
+/// Size slides are exported at when neither `--simulate` nor a terminal
+/// gives one.
+const EXPORT_SIZE: Size = Size {
+    width: 100,
+    height: 30,
+};
+
+/// How often `--watch` looks for changes to the deck.
+const WATCH_INTERVAL: Duration = Duration::from_millis(300);
+
+/// When the file at `path` was last changed, if that can be told.
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Exports the deck in `file` to `output`, with its slides drawn at `size`.
+/// Without a `format`, `output` is a folder to write every format into.
+///
+/// With `watch`, exports it again whenever the file changes, until the
+/// process is interrupted. Errors in the deck are then reported and the
+/// next change is waited for.
+fn export(
+    file: &str,
+    format: Option<export::Format>,
+    output: &Path,
+    size: Size,
+    watch: bool,
+) -> Result<(), Box<dyn Error>> {
+    let modified = || modified_at(Path::new(file));
+    loop {
+        let seen = modified();
+        match export_once(file, format, output, size, watch) {
+            Ok(slides) => {
+                log::info!("Wrote {slides} slides to {}", output.display());
+                println!("Wrote {slides} slides to {}", output.display());
+            }
+            Err(e) if watch => {
+                log::error!("Export failed: {e}");
+                eprintln!("Export failed: {e}");
+            }
+            Err(e) => return Err(e),
+        }
+        if !watch {
+            return Ok(());
+        }
+        log::info!("Watching '{file}' for changes");
+        while modified() == seen {
+            thread::sleep(WATCH_INTERVAL);
+        }
+        log::info!("'{file}' changed, exporting again");
+    }
+}
+
+/// Draws every slide of the deck in `file`, fully revealed, and writes
+/// them to `output` in `format`, or without one, writes every format with
+/// the outline, a handout, and a manifest into the folder `output`.
+/// Returns the number of slides.
+fn export_once(
+    file: &str,
+    format: Option<export::Format>,
+    output: &Path,
+    size: Size,
+    live: bool,
+) -> Result<usize, Box<dyn Error>> {
+    let mut config = Config::load()?;
+    let font = config.export.font.take();
+    let mut app = offscreen_app(file, size, config)?;
+    // Exported slides have nothing to navigate, so no status bar.
+    app.status_bar = false;
+    app.timeline = false;
+    app.reflow();
+    let mut frames = Vec::new();
+    for index in 0..app.slides.len() {
+        app.show_slide(index);
+        app.fragment = app.current_steps().map_or(0, Steps::len);
+        // Typing demos are shown once they have finished.
+        let typed = app.slides[index].typing.iter().map(TypedLine::finished_at).max();
+        if let Some(now) = Instant::now().checked_sub(typed.unwrap_or_default()) {
+            app.slide_entered = now;
+        }
+        let mut terminal = Terminal::new(TestBackend::new(size.width, size.height))?;
+        frames.push(terminal.draw(|f| ui(f, &app))?.buffer.clone());
+    }
+    let title = app.front_matter.get("title").map_or_else(
+        || Path::new(file).file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+        str::to_string,
+    );
+    let (markdown, _) = notes::take_blocks(&app.presented_markdown());
+    let deck = app
+        .slides
+        .iter()
+        .enumerate()
+        // The pages of a paginated slide share its source.
+        .filter(|&(index, slide)| index == 0 || slide.source != app.slides[index - 1].source)
+        .map(|(_, slide)| pptx::read(&markdown[slide.source.clone()], slide.title.as_deref()))
+        .collect();
+    let slides = frames.len();
+    let rendered = Rendered {
+        title,
+        frames,
+        labels: app.slides.iter().map(Slide::label).collect(),
+        deck,
+        font,
+    };
+    match format {
+        Some(format) => {
+            rendered.write(format, output, live)?;
+        }
+        None => {
+            let companion_notes = CompanionNotes::load(Path::new(file))?;
+            let handout = handout(&app, &rendered.title, &companion_notes);
+            rendered.write_all(file, size, output, &outline_text(&app), &handout)?;
+        }
+    }
+    Ok(slides)
+}
+
+/// Writes a markdown handout of the deck in `app` titled `title`: the text
+/// of every slide followed by its speaker notes.
+fn handout(app: &App, title: &str, companion_notes: &CompanionNotes) -> String {
+    let mut out = format!("# {title}\n");
+    for (index, slide) in app.slides.iter().enumerate() {
+        out.push_str(&format!("\n## {}. {}\n\n", index + 1, slide.label()));
+        let text = slide.plain_text();
+        if !text.trim().is_empty() {
+            out.push_str(&format!("```text\n{text}\n```\n"));
+        }
+        if let Some(notes) = companion_notes.merge(&slide.notes, index, slide.title.as_deref()) {
+            out.push_str(&format!("\n{notes}\n"));
+        }
+    }
+    out
+}
+
+/// Deepest heading level listed by `presentrs outline`.
+const OUTLINE_DEPTH: usize = 3;
+
+/// Prints the H1, H2, and H3 headings of every slide as a nested markdown
+/// list, under the deck's title from the front matter, for a talk abstract
+/// or a handout.
+fn outline(file: &str) -> Result<(), Box<dyn Error>> {
+    let markdown_content = fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read file '{}': {}", file, e))?;
+    let app = App::new(Path::new(file), markdown_content, THEME_SAMPLE_WIDTH);
+    print!("{}", outline_text(&app));
+    Ok(())
+}
+
+/// Writes each slide of the deck in `file` to a file of its own in `dir`.
+fn split(file: &str, dir: &Path) -> Result<(), Box<dyn Error>> {
+    let markdown_content = fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read file '{}': {}", file, e))?;
+    let companion_notes = CompanionNotes::load(Path::new(file))?;
+    let app = App::new(Path::new(file), markdown_content, EXPORT_SIZE.width);
+    // The front matter, if any, ends where the first slide starts.
+    let front_matter = app
+        .slides
+        .first()
+        .map_or("", |slide| &app.markdown_content[..slide.source.start]);
+    let parts: Vec<split::Part> = app
+        .slides
+        .iter()
+        .enumerate()
+        // The pages of a paginated slide share its source.
+        .filter(|&(index, slide)| index == 0 || slide.source != app.slides[index - 1].source)
+        .map(|(index, slide)| split::Part {
+            title: slide.title.clone(),
+            source: app.markdown_content[slide.source.clone()].to_string(),
+            notes: companion_notes.merge(&[], index, slide.title.as_deref()),
+        })
+        .collect();
+    let paths = split::write(dir, front_matter, &parts)?;
+    println!("Wrote {} slides to {}", paths.len(), dir.display());
+    Ok(())
+}
+
+/// Writes the outline `presentrs outline` prints for the deck in `app`.
+fn outline_text(app: &App) -> String {
+    let mut out = String::new();
+    if let Some(title) = app.front_matter.get("title") {
+        out.push_str(&format!("# {title}\n\n"));
+    }
+    for heading in app.slides.iter().flat_map(|slide| &slide.headings) {
+        let depth = heading.level as usize;
+        let text = heading.text.trim();
+        if depth <= OUTLINE_DEPTH && !text.is_empty() {
+            out.push_str(&format!("{}- {}\n", "  ".repeat(depth - 1), text));
+        }
+    }
+    out
+}
+
+/// Widest the sample slides of `presentrs themes` are drawn.
+const THEME_SAMPLE_WIDTH: u16 = 80;
+/// Height of the sample slides of `presentrs themes`, including the status bar.
+const THEME_SAMPLE_HEIGHT: u16 = 22;
+
+/// Prints a sample slide drawn in each built-in theme, one after another.
+fn show_themes() -> Result<(), Box<dyn Error>> {
+    let width = term::size().map_or(THEME_SAMPLE_WIDTH, |(width, _)| {
+        width.min(THEME_SAMPLE_WIDTH)
+    });
+    let mut app = App::new(Path::new("sample.md"), themes::SAMPLE.to_string(), width);
+    app.themes = Themes::load()?;
+    app.reflow();
+    let mut stdout = io::stdout().lock();
+    loop {
+        let name = app.current_theme().name.clone();
+        app.status_template = Some(format!("{name} (--theme {name})"));
+        let mut terminal = Terminal::new(TestBackend::new(width, THEME_SAMPLE_HEIGHT))?;
+        let frame = terminal.draw(|f| ui(f, &app))?;
+        write!(stdout, "{}", record::buffer_to_text(frame.buffer))?;
+        app.theme = app.themes.step(app.theme, true);
+        if app.theme == 0 {
+            break;
+        }
+        writeln!(stdout)?;
+        app.reflow();
+    }
+    Ok(())
+}
+
+/// Prints the rehearsal history of a deck or a drift report between two runs.
+///
+/// # Arguments
+///
+/// * `deck` - Path to the markdown file
+/// * `runs` - 1-based run numbers to compare; the latest two when empty
+/// * `list` - List the saved runs instead of comparing
+fn compare(deck: &Path, runs: &[usize], list: bool) -> Result<(), Box<dyn Error>> {
+    let history = Rehearsal::load_runs(deck)?;
+    if list {
+        if history.is_empty() {
+            println!("No rehearsal runs saved for {}", deck.display());
+        }
+        for (i, run) in history.iter().enumerate() {
+            println!("{:>3}  {}", i + 1, run.summary());
+        }
+        return Ok(());
+    }
+
+    let (before, after) = match *runs {
+        [] if history.len() >= 2 => (history.len() - 1, history.len()),
+        [] => {
+            return Err(format!(
+                "Need at least two runs saved with --rehearse to compare, found {}",
+                history.len()
+            )
+            .into());
+        }
+        [only] => (only, history.len()),
+        [first, second, ..] => (first, second),
+    };
+    let get = |n: usize| {
+        n.checked_sub(1)
+            .and_then(|i| history.get(i))
+            .ok_or_else(|| format!("No run {} (saved runs: 1-{})", n, history.len()))
+    };
+    print!("{}", compare_runs((get(before)?, before), (get(after)?, after)));
     Ok(())
 }
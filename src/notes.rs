@@ -0,0 +1,178 @@
+//! Speaker notes.
+//!
+//! Notes can be written on the slide itself, in a comment that the audience
+//! never sees:
+//!
+//! ```markdown
+//! # Ownership
+//! <!-- notes: Ask who has fought the borrow checker this week. -->
+//! ```
+//!
+//! or in a `::: notes` block, the way Pandoc and Quarto decks write them:
+//!
+//! ```markdown
+//! ::: notes
+//! Ask who has fought the borrow checker this week.
+//! :::
+//! ```
+//!
+//! or kept out of the deck in a companion file next to it, so `talk.md` takes
+//! notes from `talk.notes.md`. Each H1 there names the slide the notes below
+//! it belong to, by title or by number:
+//!
+//! ```markdown
+//! # Ownership
+//! Ask who has fought the borrow checker this week.
+//!
+//! # 7
+//! Skip the second example if running late.
+//! ```
+//!
+//! A slide with notes in both places shows the inline notes first.
+
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+/// Which slide a section of the companion file is for.
+enum Target {
+    /// A slide number, counting from 1
+    Number(usize),
+    /// A slide title, compared without regard to case
+    Title(String),
+}
+
+/// Notes read from a deck's companion file.
+#[derive(Default)]
+pub struct CompanionNotes {
+    /// Sections of the file, in order
+    sections: Vec<(Target, String)>,
+}
+
+impl CompanionNotes {
+    /// Reads the companion file of the deck at `deck`, if it has one.
+    pub fn load(deck: &Path) -> Result<Self, String> {
+        let path = companion_path(deck);
+        match fs::read_to_string(&path) {
+            Ok(text) => Ok(CompanionNotes::parse(&text)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(CompanionNotes::default()),
+            Err(e) => Err(format!("Failed to read notes '{}': {}", path.display(), e)),
+        }
+    }
+
+    /// Splits `text` into sections at its H1 headings. Text before the first
+    /// heading belongs to no slide and is ignored.
+    fn parse(text: &str) -> Self {
+        let mut sections: Vec<(Target, String)> = Vec::new();
+        let mut in_fence = false;
+        for line in text.lines() {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+            }
+            match line.strip_prefix("# ") {
+                Some(heading) if !in_fence => {
+                    let heading = heading.trim();
+                    let target = match heading.parse() {
+                        Ok(number) => Target::Number(number),
+                        Err(_) => Target::Title(heading.to_lowercase()),
+                    };
+                    sections.push((target, String::new()));
+                }
+                _ => {
+                    if let Some((_, notes)) = sections.last_mut() {
+                        notes.push_str(line);
+                        notes.push('\n');
+                    }
+                }
+            }
+        }
+        CompanionNotes { sections }
+    }
+
+    /// Notes for the slide at `index` (counting from 0) titled `title`.
+    fn for_slide<'a>(&'a self, index: usize, title: Option<&str>) -> impl Iterator<Item = &'a str> {
+        let title = title.map(|title| title.trim().to_lowercase());
+        self.sections
+            .iter()
+            .filter(move |(target, _)| match target {
+                Target::Number(number) => *number == index + 1,
+                Target::Title(name) => title.as_ref() == Some(name),
+            })
+            .map(|(_, notes)| notes.trim())
+    }
+
+    /// All notes for the slide at `index` titled `title`: its `inline` notes
+    /// followed by those from the companion file, separated by blank lines.
+    pub fn merge(&self, inline: &[String], index: usize, title: Option<&str>) -> Option<String> {
+        let notes: Vec<&str> = inline
+            .iter()
+            .map(|notes| notes.trim())
+            .chain(self.for_slide(index, title))
+            .filter(|notes| !notes.is_empty())
+            .collect();
+        (!notes.is_empty()).then(|| notes.join("\n\n"))
+    }
+}
+
+/// Takes the `::: notes` blocks out of `markdown`. Returns the markdown with
+/// each block blanked out, so that every offset stays where it was, and the
+/// text of each block with the offset it starts at. Blocks that are never
+/// closed are left as written.
+pub fn take_blocks(markdown: &str) -> (String, Vec<(usize, String)>) {
+    let mut blanked = String::with_capacity(markdown.len());
+    let mut blocks = Vec::new();
+    // Offset and lines of the block being read
+    let mut open: Option<(usize, Vec<&str>)> = None;
+    let mut in_fence = false;
+    let mut offset = 0;
+    for line in markdown.split_inclusive('\n') {
+        let trimmed = line.trim();
+        let closing = trimmed.len() >= 3 && trimmed.chars().all(|c| c == ':');
+        match open.take() {
+            Some((start, lines)) if closing => {
+                blocks.push((start, lines[1..].concat().trim().to_string()));
+                for line in lines.into_iter().chain([line]) {
+                    let content = line.trim_end_matches(['\n', '\r']);
+                    blanked.extend(std::iter::repeat_n(' ', content.len()));
+                    blanked.push_str(&line[content.len()..]);
+                }
+            }
+            Some((start, mut lines)) => {
+                lines.push(line);
+                open = Some((start, lines));
+            }
+            None => {
+                if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                    in_fence = !in_fence;
+                }
+                if !in_fence && is_notes_block(trimmed) {
+                    open = Some((offset, vec![line]));
+                } else {
+                    blanked.push_str(line);
+                }
+            }
+        }
+        offset += line.len();
+    }
+    if let Some((_, lines)) = open {
+        blanked.extend(lines);
+    }
+    (blanked, blocks)
+}
+
+/// Whether `line` opens a `::: notes` block, also written `::: {.notes}`.
+fn is_notes_block(line: &str) -> bool {
+    let Some(rest) = line.strip_prefix(":::") else {
+        return false;
+    };
+    let class = rest.trim_start_matches(':').trim();
+    matches!(class, "notes" | ".notes" | "{.notes}")
+}
+
+/// Path of the companion notes file for the deck at `deck`, e.g.
+/// `talk.notes.md` for `talk.md`.
+fn companion_path(deck: &Path) -> PathBuf {
+    deck.with_extension("notes.md")
+}
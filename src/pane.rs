@@ -0,0 +1,312 @@
+//! Live terminal panes embedded in slides.
+//!
+//! A `<!-- terminal: <command> -->` directive reserves a region of the slide
+//! for an interactive pseudo-terminal. The command is started the first time
+//! the pane is focused and keeps running for the rest of the presentation, so
+//! a REPL keeps its state when the presenter moves away and comes back.
+//!
+//! Output is read on a background thread and fed through a `vt100` terminal
+//! emulator; the UI draws the emulator's screen into the slide each frame.
+
+use crate::term::{KeyCode, KeyEvent, KeyModifiers};
+use portable_pty::{Child, CommandBuilder, MasterPty, PtySize, native_pty_system};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+};
+use std::{
+    io::{Read, Write},
+    path::Path,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+};
+
+/// Number of rows a terminal pane reserves when the directive does not say.
+pub const DEFAULT_ROWS: u16 = 12;
+
+/// Where a terminal pane sits within a slide and what it runs.
+#[derive(Clone)]
+pub struct TerminalRegion {
+    /// Shell command to run in the pane
+    pub command: String,
+    /// Index of the first slide line reserved for the pane
+    pub line: usize,
+    /// Number of lines reserved, including the pane's border
+    pub rows: u16,
+}
+
+impl TerminalRegion {
+    /// Parses the value of a `terminal` directive: an optional `rows=<n>`
+    /// followed by the command, e.g. `rows=8 python3 -q`.
+    pub fn parse(value: &str, line: usize) -> Option<Self> {
+        let (rows, command) = match value.split_once(char::is_whitespace) {
+            Some((first, rest)) if first.starts_with("rows=") => {
+                (first["rows=".len()..].parse().ok()?, rest.trim())
+            }
+            _ => (DEFAULT_ROWS, value.trim()),
+        };
+        (!command.is_empty()).then(|| TerminalRegion {
+            command: command.to_string(),
+            line,
+            rows: rows.max(3),
+        })
+    }
+}
+
+/// A running command attached to a pseudo-terminal.
+pub struct TerminalPane {
+    /// Terminal emulator state, updated by the reader thread
+    parser: Arc<Mutex<vt100::Parser>>,
+    /// Controlling side of the pseudo-terminal, used for resizing
+    master: Box<dyn MasterPty + Send>,
+    /// Input stream of the pseudo-terminal
+    writer: Box<dyn Write + Send>,
+    /// The running command
+    child: Box<dyn Child + Send + Sync>,
+    /// Cleared by the reader thread once the command's output ends
+    running: Arc<AtomicBool>,
+    /// Current size as `(rows, cols)`
+    size: (u16, u16),
+}
+
+impl TerminalPane {
+    /// Starts the program and arguments in `argv` in a pseudo-terminal of the
+    /// given size, with `cwd` as its working directory.
+    pub fn spawn(argv: &[String], cwd: &Path, rows: u16, cols: u16) -> Result<Self, String> {
+        let size = PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        };
+        let pair = native_pty_system()
+            .openpty(size)
+            .map_err(|e| format!("Failed to open a terminal: {e}"))?;
+
+        let mut builder = CommandBuilder::from_argv(argv.iter().map(Into::into).collect());
+        builder.cwd(cwd);
+        builder.env("TERM", "xterm-256color");
+
+        let child = pair
+            .slave
+            .spawn_command(builder)
+            .map_err(|e| format!("Failed to start '{}': {}", argv.join(" "), e))?;
+        log::info!("Started terminal pane running {:?}", argv);
+        let mut reader = pair.master.try_clone_reader().map_err(|e| e.to_string())?;
+        let writer = pair.master.take_writer().map_err(|e| e.to_string())?;
+
+        let parser = Arc::new(Mutex::new(vt100::Parser::new(rows, cols, 0)));
+        let running = Arc::new(AtomicBool::new(true));
+        let output = Arc::clone(&parser);
+        let alive = Arc::clone(&running);
+        thread::spawn(move || {
+            let mut buf = [0; 4096];
+            while let Ok(n) = reader.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+                if let Ok(mut parser) = output.lock() {
+                    parser.process(&buf[..n]);
+                }
+            }
+            alive.store(false, Ordering::Relaxed);
+            log::info!("Terminal pane exited");
+        });
+
+        Ok(TerminalPane {
+            parser,
+            master: pair.master,
+            writer,
+            child,
+            running,
+            size: (rows, cols),
+        })
+    }
+
+    /// Returns true once the command has exited and its output is drained.
+    pub fn exited(&self) -> bool {
+        !self.running.load(Ordering::Relaxed)
+    }
+
+    /// Resizes the pseudo-terminal if the pane's area changed.
+    pub fn resize(&mut self, rows: u16, cols: u16) {
+        if self.size == (rows, cols) || rows == 0 || cols == 0 {
+            return;
+        }
+        self.size = (rows, cols);
+        let _ = self.master.resize(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        });
+        if let Ok(mut parser) = self.parser.lock() {
+            parser.screen_mut().set_size(rows, cols);
+        }
+    }
+
+    /// Forwards a key press to the command.
+    pub fn send_key(&mut self, key: KeyEvent) {
+        if let Some(bytes) = key_bytes(key) {
+            let _ = self.writer.write_all(&bytes);
+            let _ = self.writer.flush();
+        }
+    }
+
+    /// Draws the emulated screen into `area` of `buf`.
+    ///
+    /// The cursor is drawn as a reversed cell when `show_cursor` is set.
+    pub fn render(&self, area: Rect, buf: &mut Buffer, show_cursor: bool) {
+        let Ok(parser) = self.parser.lock() else {
+            return;
+        };
+        let screen = parser.screen();
+        let (cursor_row, cursor_col) = screen.cursor_position();
+
+        for row in 0..area.height {
+            for col in 0..area.width {
+                let Some(cell) = screen.cell(row, col) else {
+                    continue;
+                };
+                let Some(target) = buf.cell_mut((area.x + col, area.y + row)) else {
+                    continue;
+                };
+                if cell.is_wide_continuation() {
+                    continue;
+                }
+                let mut style = Style::default()
+                    .fg(color(cell.fgcolor()))
+                    .bg(color(cell.bgcolor()));
+                for (on, modifier) in [
+                    (cell.bold(), Modifier::BOLD),
+                    (cell.dim(), Modifier::DIM),
+                    (cell.italic(), Modifier::ITALIC),
+                    (cell.underline(), Modifier::UNDERLINED),
+                    (cell.inverse(), Modifier::REVERSED),
+                ] {
+                    if on {
+                        style = style.add_modifier(modifier);
+                    }
+                }
+                if show_cursor && !screen.hide_cursor() && (row, col) == (cursor_row, cursor_col) {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                let contents = cell.contents();
+                target.set_symbol(if contents.is_empty() { " " } else { contents });
+                target.set_style(style);
+            }
+        }
+    }
+}
+
+impl Drop for TerminalPane {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Characters that give a command line meaning beyond a program and its
+/// arguments, such as chaining, redirection, or substitution.
+const SHELL_SYNTAX: &[char] = &[
+    ';', '|', '&', '<', '>', '(', ')', '$', '`', '\\', '*', '?', '[', ']', '{', '}', '~', '#',
+    '!', '%', '^', '\n',
+];
+
+/// Returns the command line that runs `command`.
+///
+/// A program with arguments, quoted with `'` or `"` where they hold spaces,
+/// is started directly, so its first word is what runs. Anything else goes
+/// through the platform's shell, so that the shell is what the exec
+/// allowlist sees rather than whatever the command line chains after its
+/// first word.
+pub fn command_argv(command: &str) -> Vec<String> {
+    // A leading `NAME=value` sets a variable for the shell to pass on.
+    if !command.contains(SHELL_SYNTAX)
+        && let Some(words) = split_words(command)
+        && !words[0].contains('=')
+    {
+        return words;
+    }
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    vec![shell.to_string(), flag.to_string(), command.to_string()]
+}
+
+/// Splits `command` into its words at unquoted whitespace, or returns `None`
+/// if a quote is left open or there are no words.
+fn split_words(command: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), c) => word.get_or_insert_default().push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                word.get_or_insert_default();
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_default().push(c),
+        }
+    }
+    words.extend(word);
+    (quote.is_none() && !words.is_empty()).then_some(words)
+}
+
+/// Returns true for the key that hands the keyboard back to the presentation.
+///
+/// That is `Ctrl-]`, which terminals report either as `]` or as `5` with Control held.
+pub fn is_release_key(key: KeyEvent) -> bool {
+    key.modifiers.contains(KeyModifiers::CONTROL)
+        && matches!(key.code, KeyCode::Char(']') | KeyCode::Char('5'))
+}
+
+/// Translates a key press into the bytes a terminal would send for it.
+fn key_bytes(key: KeyEvent) -> Option<Vec<u8>> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+    let mut bytes = match key.code {
+        KeyCode::Char(c) if ctrl && c.is_ascii_alphabetic() => {
+            vec![c.to_ascii_lowercase() as u8 - b'a' + 1]
+        }
+        KeyCode::Char(c) => c.to_string().into_bytes(),
+        KeyCode::Enter => vec![b'\r'],
+        KeyCode::Backspace => vec![0x7f],
+        KeyCode::Tab => vec![b'\t'],
+        KeyCode::BackTab => b"\x1b[Z".to_vec(),
+        KeyCode::Esc => vec![0x1b],
+        KeyCode::Up => b"\x1b[A".to_vec(),
+        KeyCode::Down => b"\x1b[B".to_vec(),
+        KeyCode::Right => b"\x1b[C".to_vec(),
+        KeyCode::Left => b"\x1b[D".to_vec(),
+        KeyCode::Home => b"\x1b[H".to_vec(),
+        KeyCode::End => b"\x1b[F".to_vec(),
+        KeyCode::Insert => b"\x1b[2~".to_vec(),
+        KeyCode::Delete => b"\x1b[3~".to_vec(),
+        KeyCode::PageUp => b"\x1b[5~".to_vec(),
+        KeyCode::PageDown => b"\x1b[6~".to_vec(),
+        _ => return None,
+    };
+    if alt {
+        bytes.insert(0, 0x1b);
+    }
+    Some(bytes)
+}
+
+/// Maps a `vt100` color to a ratatui color.
+fn color(color: vt100::Color) -> Color {
+    match color {
+        vt100::Color::Default => Color::Reset,
+        vt100::Color::Idx(i) => Color::Indexed(i),
+        vt100::Color::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
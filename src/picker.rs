@@ -0,0 +1,125 @@
+//! Jumping to a slide by a few letters of its title.
+//!
+//! `Ctrl-P` opens a list of the deck's slides by title, narrowed down as you
+//! type. A slide stays on the list if the letters typed appear in its title
+//! in order, though not necessarily next to each other, so `rtry` finds
+//! "Retry logic". The closest matches come first: those whose letters run
+//! together, start words, or come early in the title. `↑` and `↓` pick a
+//! slide and `Enter` jumps to it.
+//!
+//! Like a search, text in lowercase ignores case and text with any capital
+//! letter does not.
+
+/// A slide on the picker's list.
+pub struct Candidate {
+    /// Index of the slide
+    pub slide: usize,
+    /// Characters of the title that the typed text matched, in order
+    pub positions: Vec<usize>,
+}
+
+/// Text typed into the slide picker and the slide picked on its list.
+#[derive(Default)]
+pub struct Picker {
+    /// Letters typed so far
+    query: String,
+    /// Index of the picked slide within the filtered list
+    selected: usize,
+}
+
+impl Picker {
+    /// Returns the letters typed so far.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Index of the picked slide within the filtered list.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Adds `c` to the text, picking the best match again.
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.selected = 0;
+    }
+
+    /// Removes the last character of the text, picking the best match again.
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    /// Picks the slide `delta` places down the list of `len`, or up it if
+    /// `delta` is negative, stopping at either end.
+    pub fn step(&mut self, delta: isize, len: usize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(len.saturating_sub(1));
+    }
+
+    /// The slides whose titles match the text, best first, from each slide's
+    /// index and title. Every slide matches while nothing is typed.
+    pub fn filter<'a>(&self, titles: impl IntoIterator<Item = (usize, &'a str)>) -> Vec<Candidate> {
+        let sensitive = self.query.chars().any(char::is_uppercase);
+        let fold = |c: char| {
+            if sensitive {
+                c
+            } else {
+                c.to_lowercase().next().unwrap_or(c)
+            }
+        };
+        let query: Vec<char> = self.query.chars().map(fold).collect();
+        let mut scored: Vec<(i64, Candidate)> = titles
+            .into_iter()
+            .filter_map(|(slide, title)| {
+                let title: Vec<char> = title.chars().map(fold).collect();
+                let (score, positions) = score(&query, &title)?;
+                Some((score, Candidate { slide, positions }))
+            })
+            .collect();
+        // Stable, so that equally good matches stay in deck order.
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, candidate)| candidate).collect()
+    }
+}
+
+/// How well `query` matches `title`, higher being better, and the characters
+/// of `title` it matched, or `None` if it does not match.
+fn score(query: &[char], title: &[char]) -> Option<(i64, Vec<usize>)> {
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    // The first place the whole query fits, found from the left...
+    let mut positions = Vec::with_capacity(query.len());
+    let mut from = 0;
+    for &c in query {
+        let at = (from..title.len()).find(|&i| title[i] == c)?;
+        positions.push(at);
+        from = at + 1;
+    }
+    // ...then tightened from its end, so that `ab` in `a-xab` matches the
+    // letters that run together.
+    let mut until = positions[positions.len() - 1];
+    for (k, &c) in query.iter().enumerate().rev() {
+        let at = (0..=until).rev().find(|&i| title[i] == c)?;
+        positions[k] = at;
+        if k > 0 {
+            until = at.checked_sub(1)?;
+        }
+    }
+    let mut score = 0;
+    for (k, &at) in positions.iter().enumerate() {
+        if k > 0 && at == positions[k - 1] + 1 {
+            score += 8;
+        }
+        if at == 0 || !title[at - 1].is_alphanumeric() {
+            score += 6;
+        }
+    }
+    let span = positions[positions.len() - 1] - positions[0] + 1;
+    score -= (span - query.len()) as i64;
+    score -= positions[0].min(10) as i64;
+    Some((score, positions))
+}
@@ -0,0 +1,703 @@
+//! Exporting a deck to PowerPoint.
+//!
+//! Some events want every talk "in PowerPoint format". `presentrs export
+//! --format pptx` writes a basic `.pptx` with a slide per slide of the deck:
+//! its title at the top, then its headings, bullets, and paragraphs as text,
+//! and each code block in a monospaced text box of its own. Slides are read
+//! from the deck's markdown rather than drawn the way they are presented, so
+//! their text stays editable; colors, images, and layouts are left behind.
+//!
+//! A `.pptx` file is a zip archive of XML parts, both written here by hand.
+
+use crate::fragments;
+use miniz_oxide::deflate::compress_to_vec;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use std::{fmt::Write as _, fs, path::Path};
+
+/// What a slide says, as written to the PowerPoint file.
+pub struct Slide {
+    /// Title of the slide, if it has one
+    pub title: Option<String>,
+    /// Everything below the title, in order
+    pub blocks: Vec<Block>,
+}
+
+/// A piece of a slide below its title.
+pub enum Block {
+    /// A heading other than the slide's title
+    Heading(String),
+    /// A list item, nested `depth` lists deep, with its number in an
+    /// ordered list
+    Item {
+        /// Number of lists the item's list is nested in
+        depth: usize,
+        /// Number of the item, if its list is ordered
+        number: Option<u64>,
+        /// Text of the item, without the lists nested in it
+        text: String,
+    },
+    /// A paragraph, or a row of a table
+    Paragraph(String),
+    /// A code block, as written
+    Code(String),
+}
+
+/// English Metric Units per point, the unit of positions in the file.
+const EMU_PER_POINT: u64 = 12_700;
+
+/// Size of a slide: 13.33 by 7.5 inches, PowerPoint's widescreen default.
+const SLIDE_WIDTH: u64 = 12_192_000;
+const SLIDE_HEIGHT: u64 = 6_858_000;
+
+/// Space around the slide's content.
+const MARGIN: u64 = 457_200;
+
+/// Height of the box holding the title.
+const TITLE_HEIGHT: u64 = 914_400;
+
+/// Space between text boxes, and between a box's border and its text.
+const GAP: u64 = 91_440;
+
+/// Indentation of each level of list.
+const INDENT: u64 = 342_900;
+
+/// Font sizes, in hundredths of a point.
+const TITLE_SIZE: u64 = 3600;
+const HEADING_SIZE: u64 = 2400;
+const TEXT_SIZE: u64 = 2000;
+const CODE_SIZE: u64 = 1400;
+
+/// Font code blocks are set in.
+const CODE_FONT: &str = "Courier New";
+
+/// Background of code blocks.
+const CODE_FILL: &str = "F2F2F2";
+
+/// Namespaces of the XML parts of a presentation.
+const NAMESPACES: &str = concat!(
+    r#"xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" "#,
+    r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" "#,
+    r#"xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main""#
+);
+
+/// Start of every XML part.
+const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#;
+
+/// Relationship types linking the parts together.
+const RELATIONSHIPS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+/// Properties of the shape tree every slide, layout, and master starts with.
+const GROUP: &str = concat!(
+    r#"<p:nvGrpSpPr><p:cNvPr id="1" name=""/><p:cNvGrpSpPr/><p:nvPr/></p:nvGrpSpPr>"#,
+    r#"<p:grpSpPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="0" cy="0"/>"#,
+    r#"<a:chOff x="0" y="0"/><a:chExt cx="0" cy="0"/></a:xfrm></p:grpSpPr>"#
+);
+
+/// The presentation's theme: Office's colors and fonts, with no effects.
+const THEME: &str = concat!(
+    r#"<a:theme xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" name="presentrs">"#,
+    r#"<a:themeElements><a:clrScheme name="presentrs">"#,
+    r#"<a:dk1><a:srgbClr val="000000"/></a:dk1><a:lt1><a:srgbClr val="FFFFFF"/></a:lt1>"#,
+    r#"<a:dk2><a:srgbClr val="44546A"/></a:dk2><a:lt2><a:srgbClr val="E7E6E6"/></a:lt2>"#,
+    r#"<a:accent1><a:srgbClr val="4472C4"/></a:accent1>"#,
+    r#"<a:accent2><a:srgbClr val="ED7D31"/></a:accent2>"#,
+    r#"<a:accent3><a:srgbClr val="A5A5A5"/></a:accent3>"#,
+    r#"<a:accent4><a:srgbClr val="FFC000"/></a:accent4>"#,
+    r#"<a:accent5><a:srgbClr val="5B9BD5"/></a:accent5>"#,
+    r#"<a:accent6><a:srgbClr val="70AD47"/></a:accent6>"#,
+    r#"<a:hlink><a:srgbClr val="0563C1"/></a:hlink>"#,
+    r#"<a:folHlink><a:srgbClr val="954F72"/></a:folHlink></a:clrScheme>"#,
+    r#"<a:fontScheme name="presentrs">"#,
+    r#"<a:majorFont><a:latin typeface="Calibri Light"/><a:ea typeface=""/><a:cs typeface=""/></a:majorFont>"#,
+    r#"<a:minorFont><a:latin typeface="Calibri"/><a:ea typeface=""/><a:cs typeface=""/></a:minorFont>"#,
+    r#"</a:fontScheme><a:fmtScheme name="presentrs"><a:fillStyleLst>"#,
+    r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill>"#,
+    r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill>"#,
+    r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:fillStyleLst><a:lnStyleLst>"#,
+    r#"<a:ln w="6350"><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:ln>"#,
+    r#"<a:ln w="12700"><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:ln>"#,
+    r#"<a:ln w="19050"><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:ln>"#,
+    r#"</a:lnStyleLst><a:effectStyleLst>"#,
+    r#"<a:effectStyle><a:effectLst/></a:effectStyle>"#,
+    r#"<a:effectStyle><a:effectLst/></a:effectStyle>"#,
+    r#"<a:effectStyle><a:effectLst/></a:effectStyle></a:effectStyleLst><a:bgFillStyleLst>"#,
+    r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill>"#,
+    r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill>"#,
+    r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:bgFillStyleLst>"#,
+    r#"</a:fmtScheme></a:themeElements></a:theme>"#
+);
+
+/// Reads the markdown of a slide titled `title`. The heading the title comes
+/// from is not repeated below it, and directives and other raw HTML are left
+/// out.
+pub fn read(markdown: &str, title: Option<&str>) -> Slide {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    let mut reader = Reader {
+        title: title.map(|title| title.trim().to_string()),
+        title_seen: false,
+        blocks: Vec::new(),
+        text: String::new(),
+        lists: Vec::new(),
+        number: None,
+    };
+    let mut code: Option<String> = None;
+    for event in Parser::new_ext(markdown, options) {
+        if let Some(code) = &mut code {
+            match &event {
+                Event::End(TagEnd::CodeBlock) => {}
+                Event::Text(text) => {
+                    code.push_str(text);
+                    continue;
+                }
+                _ => continue,
+            }
+        }
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => {
+                reader.flush();
+                code = Some(String::new());
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some(code) = code.take() {
+                    reader.blocks.push(Block::Code(code.trim_end().to_string()));
+                }
+            }
+            Event::Start(Tag::List(start)) => {
+                reader.flush();
+                reader.lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => {
+                reader.flush();
+                reader.lists.pop();
+            }
+            Event::Start(Tag::Item) => {
+                reader.flush();
+                reader.number = reader.lists.last_mut().and_then(|next| {
+                    let number = *next;
+                    *next = next.map(|n| n + 1);
+                    number
+                });
+            }
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                reader.flush();
+                reader.text.push_str(&format!("[{label}] "));
+            }
+            Event::Start(Tag::Heading { .. } | Tag::TableHead | Tag::TableRow) => reader.flush(),
+            Event::End(TagEnd::Heading(_)) => reader.heading(),
+            Event::End(TagEnd::TableCell) => reader.text.push_str(" | "),
+            Event::End(TagEnd::TableHead | TagEnd::TableRow) => {
+                let row = reader.text.trim_end().trim_end_matches('|').trim_end();
+                reader.text.truncate(row.len());
+                reader.flush();
+            }
+            Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Item
+                | TagEnd::BlockQuote(_)
+                | TagEnd::FootnoteDefinition,
+            )
+            | Event::Rule => reader.flush(),
+            Event::Text(text) | Event::Code(text) => reader.text.push_str(&text),
+            Event::FootnoteReference(label) => reader.text.push_str(&format!("[{label}]")),
+            Event::TaskListMarker(done) => reader.text.push_str(if done { "☑ " } else { "☐ " }),
+            Event::SoftBreak => reader.text.push(' '),
+            Event::HardBreak => reader.text.push('\n'),
+            _ => {}
+        }
+    }
+    reader.flush();
+    Slide {
+        title: reader.title,
+        blocks: reader.blocks,
+    }
+}
+
+/// What [`read`] keeps track of while reading a slide.
+struct Reader {
+    /// Title of the slide
+    title: Option<String>,
+    /// Whether the heading the title comes from has been read
+    title_seen: bool,
+    /// Blocks read so far
+    blocks: Vec<Block>,
+    /// Text of the block being read
+    text: String,
+    /// Number of the next item of each open list, `None` for bullets
+    lists: Vec<Option<u64>>,
+    /// Number of the list item being read, if it is numbered
+    number: Option<u64>,
+}
+
+impl Reader {
+    /// Ends the heading being read, which is the slide's title the first
+    /// time it matches it.
+    fn heading(&mut self) {
+        let text = std::mem::take(&mut self.text);
+        let text = text.trim();
+        if !self.title_seen && self.title.as_deref() == Some(text) {
+            self.title_seen = true;
+        } else if !text.is_empty() {
+            self.blocks.push(Block::Heading(text.to_string()));
+        }
+    }
+
+    /// Ends the block being read, if it has any text.
+    fn flush(&mut self) {
+        let text = std::mem::take(&mut self.text);
+        let text = text.trim();
+        // Pauses mean nothing on a PowerPoint slide.
+        if text.is_empty() || text == fragments::PAUSE_DOTS {
+            return;
+        }
+        let text = text.to_string();
+        self.blocks.push(match self.lists.len() {
+            0 => Block::Paragraph(text),
+            depth => Block::Item {
+                depth: depth - 1,
+                number: self.number.take(),
+                text,
+            },
+        });
+    }
+}
+
+/// Writes `slides` to the PowerPoint file `path`, titled `title`.
+pub fn write(path: &Path, title: &str, slides: &[Slide]) -> Result<(), String> {
+    let mut parts: Vec<(String, String)> = Vec::new();
+    let mut part = |name: &str, xml: String| {
+        parts.push((name.to_string(), format!("{XML_DECLARATION}\n{xml}")));
+    };
+    part("[Content_Types].xml", content_types(slides.len()));
+    part(
+        "_rels/.rels",
+        relationships(&[
+            ("officeDocument", "ppt/presentation.xml".to_string()),
+            (
+                "http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties",
+                "docProps/core.xml".to_string(),
+            ),
+        ]),
+    );
+    part(
+        "docProps/core.xml",
+        format!(
+            concat!(
+                r#"<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" "#,
+                r#"xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>{}</dc:title></cp:coreProperties>"#
+            ),
+            escape(title)
+        ),
+    );
+    part("ppt/presentation.xml", presentation(slides.len()));
+    let mut links = vec![
+        ("slideMaster", "slideMasters/slideMaster1.xml".to_string()),
+        ("theme", "theme/theme1.xml".to_string()),
+    ];
+    links.extend((1..=slides.len()).map(|n| ("slide", format!("slides/slide{n}.xml"))));
+    part("ppt/_rels/presentation.xml.rels", relationships(&links));
+    part("ppt/theme/theme1.xml", THEME.to_string());
+    part(
+        "ppt/slideMasters/slideMaster1.xml",
+        format!(
+            concat!(
+                r#"<p:sldMaster {}><p:cSld><p:spTree>{}</p:spTree></p:cSld>"#,
+                r#"<p:clrMap bg1="lt1" tx1="dk1" bg2="lt2" tx2="dk2" accent1="accent1" "#,
+                r#"accent2="accent2" accent3="accent3" accent4="accent4" accent5="accent5" "#,
+                r#"accent6="accent6" hlink="hlink" folHlink="folHlink"/>"#,
+                r#"<p:sldLayoutIdLst><p:sldLayoutId id="2147483649" r:id="rId1"/></p:sldLayoutIdLst>"#,
+                r#"</p:sldMaster>"#
+            ),
+            NAMESPACES, GROUP
+        ),
+    );
+    part(
+        "ppt/slideMasters/_rels/slideMaster1.xml.rels",
+        relationships(&[
+            (
+                "slideLayout",
+                "../slideLayouts/slideLayout1.xml".to_string(),
+            ),
+            ("theme", "../theme/theme1.xml".to_string()),
+        ]),
+    );
+    part(
+        "ppt/slideLayouts/slideLayout1.xml",
+        format!(
+            concat!(
+                r#"<p:sldLayout {} type="blank" preserve="1"><p:cSld name="Blank">"#,
+                r#"<p:spTree>{}</p:spTree></p:cSld>"#,
+                r#"<p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sldLayout>"#
+            ),
+            NAMESPACES, GROUP
+        ),
+    );
+    part(
+        "ppt/slideLayouts/_rels/slideLayout1.xml.rels",
+        relationships(&[(
+            "slideMaster",
+            "../slideMasters/slideMaster1.xml".to_string(),
+        )]),
+    );
+    for (index, slide) in slides.iter().enumerate() {
+        let n = index + 1;
+        part(&format!("ppt/slides/slide{n}.xml"), slide_xml(slide));
+        part(
+            &format!("ppt/slides/_rels/slide{n}.xml.rels"),
+            relationships(&[(
+                "slideLayout",
+                "../slideLayouts/slideLayout1.xml".to_string(),
+            )]),
+        );
+    }
+    fs::write(path, zip(&parts)).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+/// Lists the type of every part of a presentation with `slides` slides.
+fn content_types(slides: usize) -> String {
+    let mut xml = String::from(concat!(
+        r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+        r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+        r#"<Default Extension="xml" ContentType="application/xml"/>"#
+    ));
+    let presentationml = "application/vnd.openxmlformats-officedocument.presentationml";
+    let mut part = |name: &str, content_type: &str| {
+        let _ = write!(
+            xml,
+            r#"<Override PartName="/{name}" ContentType="{content_type}"/>"#
+        );
+    };
+    part(
+        "ppt/presentation.xml",
+        &format!("{presentationml}.presentation.main+xml"),
+    );
+    part(
+        "ppt/slideMasters/slideMaster1.xml",
+        &format!("{presentationml}.slideMaster+xml"),
+    );
+    part(
+        "ppt/slideLayouts/slideLayout1.xml",
+        &format!("{presentationml}.slideLayout+xml"),
+    );
+    part(
+        "ppt/theme/theme1.xml",
+        "application/vnd.openxmlformats-officedocument.theme+xml",
+    );
+    part(
+        "docProps/core.xml",
+        "application/vnd.openxmlformats-package.core-properties+xml",
+    );
+    for n in 1..=slides {
+        part(
+            &format!("ppt/slides/slide{n}.xml"),
+            &format!("{presentationml}.slide+xml"),
+        );
+    }
+    xml.push_str("</Types>");
+    xml
+}
+
+/// Links a part to `targets`, each with the type of relationship it is,
+/// numbered `rId1` on in order.
+fn relationships(targets: &[(&str, String)]) -> String {
+    let mut xml = String::from(
+        r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    );
+    for (index, (kind, target)) in targets.iter().enumerate() {
+        let kind = if kind.starts_with("http") {
+            kind.to_string()
+        } else {
+            format!("{RELATIONSHIPS}/{kind}")
+        };
+        let _ = write!(
+            xml,
+            r#"<Relationship Id="rId{}" Type="{kind}" Target="{target}"/>"#,
+            index + 1
+        );
+    }
+    xml.push_str("</Relationships>");
+    xml
+}
+
+/// The presentation part, which lists the slides in order.
+fn presentation(slides: usize) -> String {
+    let mut xml = format!(
+        concat!(
+            r#"<p:presentation {}><p:sldMasterIdLst>"#,
+            r#"<p:sldMasterId id="2147483648" r:id="rId1"/></p:sldMasterIdLst>"#
+        ),
+        NAMESPACES
+    );
+    if slides > 0 {
+        xml.push_str("<p:sldIdLst>");
+        for index in 0..slides {
+            // The master and theme come first among the relationships.
+            let _ = write!(
+                xml,
+                r#"<p:sldId id="{}" r:id="rId{}"/>"#,
+                256 + index,
+                index + 3
+            );
+        }
+        xml.push_str("</p:sldIdLst>");
+    }
+    let _ = write!(
+        xml,
+        concat!(
+            r#"<p:sldSz cx="{width}" cy="{height}"/>"#,
+            r#"<p:notesSz cx="{height}" cy="{width}"/></p:presentation>"#
+        ),
+        width = SLIDE_WIDTH,
+        height = SLIDE_HEIGHT
+    );
+    xml
+}
+
+/// A paragraph of a text box, as XML, with its estimated height.
+struct Paragraph {
+    xml: String,
+    height: u64,
+}
+
+/// Lays `slide` out as its title, then a text box for each run of text
+/// between code blocks and one for each code block, top to bottom.
+fn slide_xml(slide: &Slide) -> String {
+    let width = SLIDE_WIDTH - 2 * MARGIN;
+    let mut shapes = String::new();
+    let mut id = 1;
+    let mut shape = |name: &str, y: u64, paragraphs: &[Paragraph], code: bool| {
+        id += 1;
+        let height = paragraphs.iter().map(|p| p.height).sum::<u64>() + 2 * GAP;
+        let fill = if code {
+            format!(r#"<a:solidFill><a:srgbClr val="{CODE_FILL}"/></a:solidFill>"#)
+        } else {
+            String::new()
+        };
+        let _ = write!(
+            shapes,
+            concat!(
+                r#"<p:sp><p:nvSpPr><p:cNvPr id="{id}" name="{name} {id}"/>"#,
+                r#"<p:cNvSpPr txBox="1"/><p:nvPr/></p:nvSpPr>"#,
+                r#"<p:spPr><a:xfrm><a:off x="{x}" y="{y}"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm>"#,
+                r#"<a:prstGeom prst="rect"><a:avLst/></a:prstGeom>{fill}</p:spPr>"#,
+                r#"<p:txBody><a:bodyPr wrap="square" lIns="{gap}" tIns="{gap}" rIns="{gap}" "#,
+                r#"bIns="{gap}"><a:normAutofit/></a:bodyPr><a:lstStyle/>"#
+            ),
+            id = id,
+            name = name,
+            x = MARGIN,
+            y = y,
+            cx = width,
+            cy = height,
+            fill = fill,
+            gap = GAP
+        );
+        for paragraph in paragraphs {
+            shapes.push_str(&paragraph.xml);
+        }
+        shapes.push_str("</p:txBody></p:sp>");
+        height
+    };
+    let mut y = MARGIN;
+    if let Some(title) = &slide.title {
+        let title = paragraph(title, TITLE_SIZE, width, Style::Title);
+        shape("Title", y, &[title], false);
+    }
+    y += TITLE_HEIGHT + GAP;
+    let mut text = Vec::new();
+    for block in &slide.blocks {
+        let (content, size, style) = match block {
+            Block::Heading(heading) => (heading.clone(), HEADING_SIZE, Style::Heading),
+            Block::Item {
+                depth,
+                number,
+                text,
+            } => match number {
+                Some(number) => (
+                    format!("{number}. {text}"),
+                    TEXT_SIZE,
+                    Style::Number(*depth),
+                ),
+                None => (text.clone(), TEXT_SIZE, Style::Bullet(*depth)),
+            },
+            Block::Paragraph(paragraph) => (paragraph.clone(), TEXT_SIZE, Style::Plain),
+            Block::Code(code) => {
+                if !text.is_empty() {
+                    y += shape("Text", y, &text, false) + GAP;
+                    text.clear();
+                }
+                let lines: Vec<Paragraph> = code
+                    .lines()
+                    .map(|line| paragraph(line, CODE_SIZE, width, Style::Code))
+                    .collect();
+                y += shape("Code", y, &lines, true) + GAP;
+                continue;
+            }
+        };
+        text.push(paragraph(&content, size, width, style));
+    }
+    if !text.is_empty() {
+        shape("Text", y, &text, false);
+    }
+    format!(
+        concat!(
+            r#"<p:sld {}><p:cSld><p:spTree>{}{}</p:spTree></p:cSld>"#,
+            r#"<p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sld>"#
+        ),
+        NAMESPACES, GROUP, shapes
+    )
+}
+
+/// How a paragraph is set.
+#[derive(Clone, Copy)]
+enum Style {
+    /// The slide's title
+    Title,
+    /// A heading below the title, in bold
+    Heading,
+    /// A paragraph
+    Plain,
+    /// A bulleted item of a list nested this deep
+    Bullet(usize),
+    /// A numbered item of a list nested this deep
+    Number(usize),
+    /// A line of code, in a monospaced font
+    Code,
+}
+
+/// Sets `text` in a paragraph of a text box `width` wide, in `size`
+/// hundredths of a point. Line breaks in `text` are kept.
+fn paragraph(text: &str, size: u64, width: u64, style: Style) -> Paragraph {
+    let indent = |depth: usize| INDENT * (depth as u64 + 1);
+    let properties = match style {
+        Style::Bullet(depth) => format!(
+            concat!(
+                r#"<a:pPr marL="{}" indent="-{}"><a:buFont typeface="Arial"/>"#,
+                r#"<a:buChar char="•"/></a:pPr>"#
+            ),
+            indent(depth),
+            INDENT
+        ),
+        // The number hangs to the left, where the bullet would be.
+        Style::Number(depth) => format!(
+            r#"<a:pPr marL="{}" indent="-{}"><a:buNone/></a:pPr>"#,
+            indent(depth),
+            INDENT
+        ),
+        _ => "<a:pPr><a:buNone/></a:pPr>".to_string(),
+    };
+    let font = match style {
+        Style::Code => {
+            format!(r#"<a:latin typeface="{CODE_FONT}"/><a:cs typeface="{CODE_FONT}"/>"#)
+        }
+        _ => String::new(),
+    };
+    let bold = if matches!(style, Style::Heading) {
+        r#" b="1""#
+    } else {
+        ""
+    };
+    let run_properties =
+        format!(r#"<a:rPr lang="en-US" sz="{size}"{bold} dirty="0">{font}</a:rPr>"#);
+    let mut xml = format!("<a:p>{properties}");
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            let _ = write!(xml, "<a:br>{run_properties}</a:br>");
+        }
+        if !line.is_empty() {
+            let _ = write!(
+                xml,
+                "<a:r>{run_properties}<a:t>{}</a:t></a:r>",
+                escape(line)
+            );
+        }
+    }
+    let _ = write!(
+        xml,
+        r#"<a:endParaRPr lang="en-US" sz="{size}" dirty="0"/></a:p>"#
+    );
+    // Lines are about 1.2 times the font size high, and characters about
+    // half of it wide, or 0.6 of it in the monospaced font of code.
+    let line_height = size * 12 * EMU_PER_POINT / 1000;
+    let char_width = size * if matches!(style, Style::Code) { 6 } else { 5 } * EMU_PER_POINT / 1000;
+    let per_line = ((width - 2 * GAP) / char_width).max(1) as usize;
+    let lines: usize = text
+        .split('\n')
+        .map(|line| line.chars().count().div_ceil(per_line).max(1))
+        .sum();
+    Paragraph {
+        xml,
+        height: lines as u64 * line_height,
+    }
+}
+
+/// Escapes `text` for XML, leaving out control characters XML cannot hold.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' => escaped.push_str("    "),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Packs `files`, named by their paths, into a zip archive, compressed.
+fn zip(files: &[(String, String)]) -> Vec<u8> {
+    // Every file is dated 1980-01-01, the earliest date a zip archive can
+    // hold, so that the same deck always gives the same file.
+    const DATE: u16 = (1 << 5) | 1;
+    const VERSION: u16 = 20;
+    const DEFLATE: u16 = 8;
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for (name, contents) in files {
+        let compressed = compress_to_vec(contents.as_bytes(), 6);
+        let crc = crc32fast::hash(contents.as_bytes());
+        let offset = archive.len() as u32;
+        let header = |out: &mut Vec<u8>, central: bool| {
+            if central {
+                out.extend_from_slice(&0x0201_4b50_u32.to_le_bytes());
+                out.extend_from_slice(&VERSION.to_le_bytes());
+            } else {
+                out.extend_from_slice(&0x0403_4b50_u32.to_le_bytes());
+            }
+            for field in [VERSION, 0, DEFLATE, 0, DATE] {
+                out.extend_from_slice(&field.to_le_bytes());
+            }
+            out.extend_from_slice(&crc.to_le_bytes());
+            out.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            out.extend_from_slice(&0_u16.to_le_bytes());
+            if central {
+                // No comment, on the first disk, with no attributes.
+                out.extend_from_slice(&[0; 4]);
+                out.extend_from_slice(&[0; 6]);
+                out.extend_from_slice(&offset.to_le_bytes());
+            }
+            out.extend_from_slice(name.as_bytes());
+        };
+        header(&mut archive, false);
+        archive.extend_from_slice(&compressed);
+        header(&mut directory, true);
+    }
+    let directory_offset = archive.len() as u32;
+    archive.extend_from_slice(&directory);
+    archive.extend_from_slice(&0x0605_4b50_u32.to_le_bytes());
+    archive.extend_from_slice(&[0; 4]);
+    for _ in 0..2 {
+        archive.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    }
+    archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&directory_offset.to_le_bytes());
+    archive.extend_from_slice(&0_u16.to_le_bytes());
+    archive
+}
@@ -0,0 +1,244 @@
+//! Capturing rendered frames: session recording in the [asciicast v2] format
+//! and single-frame screenshots.
+//!
+//! Every frame drawn by the render loop is serialized back into ANSI escape
+//! sequences and appended to the cast file together with its timestamp, so the
+//! talk can be replayed with `asciinema play` or embedded with the asciinema
+//! web player. Screenshots use the same serialization, written as plain lines
+//! that can be viewed with `cat`, and a broadcast streams the cells that
+//! changed from one frame to the next.
+//!
+//! [asciicast v2]: https://docs.asciinema.org/manual/asciicast/v2/
+
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+use serde_json::json;
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+use unicode_width::UnicodeWidthStr;
+
+/// Writes rendered frames to an asciicast v2 file as they are drawn.
+pub struct Recorder {
+    /// Destination cast file
+    out: BufWriter<File>,
+    /// Moment the recording started; event times are relative to it
+    start: Instant,
+    /// Last frame written, used to skip redraws that changed nothing
+    last_frame: String,
+}
+
+impl Recorder {
+    /// Creates the cast file at `path` and writes the asciicast header.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Destination of the `.cast` file
+    /// * `width` - Initial terminal width in columns
+    /// * `height` - Initial terminal height in rows
+    pub fn create(path: &str, width: u16, height: u16) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let header = json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": timestamp,
+            "env": { "TERM": std::env::var("TERM").unwrap_or_default() },
+        });
+        writeln!(out, "{header}")?;
+        Ok(Recorder {
+            out,
+            start: Instant::now(),
+            last_frame: String::new(),
+        })
+    }
+
+    /// Appends a full redraw of `buffer` as an output event.
+    ///
+    /// Frames identical to the previously recorded one are skipped.
+    pub fn frame(&mut self, buffer: &Buffer) -> io::Result<()> {
+        let frame = buffer_to_ansi(buffer);
+        if frame == self.last_frame {
+            return Ok(());
+        }
+        self.event("o", &format!("\x1b[H\x1b[2J{frame}"))?;
+        self.last_frame = frame;
+        Ok(())
+    }
+
+    /// Appends a terminal resize event.
+    pub fn resize(&mut self, width: u16, height: u16) -> io::Result<()> {
+        self.last_frame.clear();
+        self.event("r", &format!("{width}x{height}"))
+    }
+
+    /// Flushes buffered events to disk.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    fn event(&mut self, kind: &str, data: &str) -> io::Result<()> {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        writeln!(self.out, "{}", json!([elapsed, kind, data]))
+    }
+}
+
+/// Writes `buffer` to `path` as ANSI-styled text, one line per terminal row.
+pub fn save_screenshot(buffer: &Buffer, path: &Path) -> io::Result<()> {
+    fs::write(path, buffer_to_text(buffer))
+}
+
+/// Serializes a rendered buffer into ANSI-styled text, one line per row,
+/// that reproduces it when printed.
+pub fn buffer_to_text(buffer: &Buffer) -> String {
+    let mut text = ansi_rows(buffer).join("\n");
+    text.push('\n');
+    text
+}
+
+/// Serializes a rendered buffer into ANSI escape sequences.
+///
+/// Each row is addressed with an absolute cursor move.
+pub fn buffer_to_ansi(buffer: &Buffer) -> String {
+    ansi_rows(buffer)
+        .iter()
+        .enumerate()
+        .map(|(y, row)| format!("\x1b[{};1H{}", y + 1, row))
+        .collect()
+}
+
+/// Serializes the cells of `next` that differ from `previous` into ANSI
+/// escape sequences that turn a terminal showing `previous` into `next`.
+///
+/// The cursor is only moved where the changed cells are not contiguous, and
+/// SGR attributes are only emitted when they change. Buffers of different
+/// sizes are redrawn in full.
+pub fn buffer_diff_to_ansi(previous: &Buffer, next: &Buffer) -> String {
+    if previous.area != next.area {
+        return format!("\x1b[H\x1b[2J{}", buffer_to_ansi(next));
+    }
+    let area = next.area;
+    let mut out = String::new();
+    let mut cursor = None;
+    let mut current: Option<(Color, Color, Modifier)> = None;
+    for (x, y, cell) in previous.diff(next) {
+        let (x, y) = (x - area.x, y - area.y);
+        if cursor != Some((x, y)) {
+            out.push_str(&format!("\x1b[{};{}H", y + 1, x + 1));
+        }
+        let attrs = (cell.fg, cell.bg, cell.modifier);
+        if current != Some(attrs) {
+            out.push_str(&sgr(cell.fg, cell.bg, cell.modifier));
+            current = Some(attrs);
+        }
+        let symbol = cell.symbol();
+        out.push_str(symbol);
+        cursor = Some((x + symbol.width().max(1) as u16, y));
+    }
+    if current.is_some() {
+        out.push_str("\x1b[0m");
+    }
+    out
+}
+
+/// Serializes each row of a rendered buffer into ANSI escape sequences.
+///
+/// SGR attributes are only emitted when they change between adjacent cells,
+/// and every row ends with a reset.
+fn ansi_rows(buffer: &Buffer) -> Vec<String> {
+    let area = buffer.area;
+    let mut rows = Vec::new();
+
+    for y in 0..area.height {
+        let mut out = String::new();
+        let mut current: Option<(Color, Color, Modifier)> = None;
+        let mut skip = 0;
+
+        for x in 0..area.width {
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let Some(cell) = buffer.cell((area.x + x, area.y + y)) else {
+                continue;
+            };
+            let attrs = (cell.fg, cell.bg, cell.modifier);
+            if current != Some(attrs) {
+                out.push_str(&sgr(cell.fg, cell.bg, cell.modifier));
+                current = Some(attrs);
+            }
+            let symbol = cell.symbol();
+            out.push_str(symbol);
+            skip = symbol.width().saturating_sub(1);
+        }
+        out.push_str("\x1b[0m");
+        rows.push(out);
+    }
+
+    rows
+}
+
+/// Builds a complete SGR sequence (reset followed by attributes) for a cell.
+fn sgr(fg: Color, bg: Color, modifier: Modifier) -> String {
+    let mut codes = vec!["0".to_string()];
+
+    for (flag, code) in [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::RAPID_BLINK, "6"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::HIDDEN, "8"),
+        (Modifier::CROSSED_OUT, "9"),
+    ] {
+        if modifier.contains(flag) {
+            codes.push(code.to_string());
+        }
+    }
+    if let Some(code) = color_code(fg, false) {
+        codes.push(code);
+    }
+    if let Some(code) = color_code(bg, true) {
+        codes.push(code);
+    }
+
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+/// Maps a ratatui color to its SGR parameter, or `None` for the terminal default.
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let offset = if background { 10 } else { 0 };
+    let base = match color {
+        Color::Reset => return None,
+        Color::Black => 30,
+        Color::Red => 31,
+        Color::Green => 32,
+        Color::Yellow => 33,
+        Color::Blue => 34,
+        Color::Magenta => 35,
+        Color::Cyan => 36,
+        Color::Gray => 37,
+        Color::DarkGray => 90,
+        Color::LightRed => 91,
+        Color::LightGreen => 92,
+        Color::LightYellow => 93,
+        Color::LightBlue => 94,
+        Color::LightMagenta => 95,
+        Color::LightCyan => 96,
+        Color::White => 97,
+        Color::Indexed(i) => return Some(format!("{};5;{}", 38 + offset, i)),
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{};{};{}", 38 + offset, r, g, b)),
+    };
+    Some((base + offset).to_string())
+}
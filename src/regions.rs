@@ -0,0 +1,260 @@
+//! Content set apart from the main flow of a slide.
+//!
+//! A slide's content normally fills the whole slide. Directives can move
+//! part of it into regions of its own, which are laid out next to the main
+//! content when the slide is drawn. A region starts at its directive and
+//! runs to the next `<!-- end -->`.
+//!
+//! `<!-- callout: Definition -->` boxes its content against the right margin
+//! of the slide, with the main content flowing to its left:
+//!
+//! ```markdown
+//! # Ownership
+//!
+//! Every value has a single owner.
+//!
+//! <!-- callout: Definition -->
+//! **Drop**: freeing a value when its owner goes out of scope.
+//! <!-- end -->
+//! ```
+//!
+//! `<!-- grid: 2x2 -->` arranges the blocks that follow, each started by a
+//! `<!-- cell -->`, in equal cells below the rest of the slide. Without a row
+//! count (`<!-- grid: 3 -->`), there are as many rows as the cells need.
+//!
+//! `<!-- image-left: diagram.png -->` (or `image-right`) gives an image one
+//! half of the slide, with the rest of the slide's content in the other half.
+//! It takes no `<!-- end -->`.
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Text},
+    widgets::{Block, Borders, Paragraph, Widget, Wrap},
+};
+
+/// Share of the slide's width taken by callouts, in percent.
+const CALLOUT_PERCENT: u16 = 35;
+/// Narrowest a callout is drawn, including its border.
+const CALLOUT_MIN_WIDTH: u16 = 20;
+/// Columns left blank between neighbouring grid cells.
+const CELL_GAP: u16 = 2;
+/// Columns left blank between a half-slide image and the content beside it.
+const HALF_GAP: u16 = 2;
+
+/// How a region is placed on the slide.
+#[derive(Clone)]
+pub enum Kind {
+    /// A box against the right margin, with an optional title
+    Callout(Option<String>),
+    /// A cell of a grid with the given number of columns and, if set, rows
+    Cell(Grid),
+    /// An image filling one half of the slide
+    Image(Side),
+}
+
+/// Half of the slide an image sits in.
+#[derive(Clone, Copy)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// Dimensions of a grid, as written in a `<!-- grid: 2x3 -->` directive.
+#[derive(Clone, Copy)]
+pub struct Grid {
+    /// Number of columns
+    columns: u16,
+    /// Number of rows, or `None` for as many as the cells need
+    rows: Option<u16>,
+}
+
+impl Grid {
+    /// Parses `3` (columns) or `3x2` (columns by rows).
+    pub fn parse(value: &str) -> Option<Self> {
+        let (columns, rows) = match value.split_once(['x', '×']) {
+            Some((columns, rows)) => (columns, Some(rows.trim().parse().ok()?)),
+            None => (value, None),
+        };
+        let columns: u16 = columns.trim().parse().ok()?;
+        (columns > 0 && rows != Some(0)).then_some(Grid { columns, rows })
+    }
+}
+
+impl Kind {
+    /// Width, in columns, that content in a region of this kind is laid out
+    /// for on a slide `width` columns wide.
+    pub fn width(&self, width: usize) -> usize {
+        match self {
+            Kind::Callout(_) => {
+                let outer = (width * usize::from(CALLOUT_PERCENT) / 100)
+                    .max(usize::from(CALLOUT_MIN_WIDTH));
+                outer.saturating_sub(2)
+            }
+            Kind::Cell(grid) => {
+                let columns = usize::from(grid.columns);
+                width.saturating_sub(usize::from(CELL_GAP) * (columns - 1)) / columns
+            }
+            Kind::Image(_) => width.saturating_sub(usize::from(HALF_GAP)) / 2,
+        }
+    }
+}
+
+/// Part of a slide's content laid out apart from the rest.
+pub struct Region {
+    /// How the region is placed
+    pub kind: Kind,
+    /// Formatted content of the region
+    pub text: Text<'static>,
+}
+
+impl Region {
+    /// Draws the region into `area`.
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let mut paragraph = Paragraph::new(self.text.clone()).wrap(Wrap { trim: false });
+        if let Kind::Callout(title) = &self.kind {
+            let mut block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow));
+            if let Some(title) = title {
+                block = block.title(format!(" {title} "));
+            }
+            paragraph = paragraph.block(block);
+        }
+        paragraph.render(area, buf);
+    }
+}
+
+/// Splits `area` between the main content, `main`, and `regions`, draws the
+/// regions, and returns the area left for the main content.
+pub fn render(regions: &[Region], main: &Text<'static>, area: Rect, buf: &mut Buffer) -> Rect {
+    let area = render_image(regions, area, buf);
+    let area = render_callouts(regions, area, buf);
+    let cells: Vec<&Region> = regions
+        .iter()
+        .filter(|region| matches!(region.kind, Kind::Cell(_)))
+        .collect();
+    let Some(Kind::Cell(grid)) = cells.first().map(|cell| &cell.kind) else {
+        return area;
+    };
+    let main_height = Paragraph::new(main.clone())
+        .wrap(Wrap { trim: false })
+        .line_count(area.width);
+    let main_height = u16::try_from(main_height)
+        .unwrap_or(u16::MAX)
+        .min(area.height);
+    let [main_area, grid_area] =
+        Layout::vertical([Constraint::Length(main_height), Constraint::Min(0)]).areas(area);
+    render_grid(*grid, &cells, grid_area, buf);
+    main_area
+}
+
+/// Draws the first half-slide image among `regions`, centered in its half
+/// of `area`, and returns the other half.
+fn render_image(regions: &[Region], area: Rect, buf: &mut Buffer) -> Rect {
+    let Some((side, image)) = regions.iter().find_map(|region| match region.kind {
+        Kind::Image(side) => Some((side, region)),
+        _ => None,
+    }) else {
+        return area;
+    };
+    let [left, _, right] = Layout::horizontal([
+        Constraint::Fill(1),
+        Constraint::Length(HALF_GAP),
+        Constraint::Fill(1),
+    ])
+    .areas(area);
+    let (half, rest) = match side {
+        Side::Left => (left, right),
+        Side::Right => (right, left),
+    };
+    let height = u16::try_from(image.text.lines.len())
+        .unwrap_or(u16::MAX)
+        .min(half.height);
+    image.render(
+        Rect {
+            y: half.y + (half.height - height) / 2,
+            height,
+            ..half
+        },
+        buf,
+    );
+    rest
+}
+
+/// Draws the callouts among `regions` down the right side of `area`, and
+/// returns the part of `area` to their left.
+fn render_callouts(regions: &[Region], area: Rect, buf: &mut Buffer) -> Rect {
+    let callouts: Vec<&Region> = regions
+        .iter()
+        .filter(|region| matches!(region.kind, Kind::Callout(_)))
+        .collect();
+    if callouts.is_empty() {
+        return area;
+    }
+    let width = (area.width * CALLOUT_PERCENT / 100).max(CALLOUT_MIN_WIDTH);
+    if width >= area.width {
+        return area;
+    }
+    let [main, _, column] = Layout::horizontal([
+        Constraint::Min(0),
+        Constraint::Length(1),
+        Constraint::Length(width),
+    ])
+    .areas(area);
+    // Callouts are stacked from the top, each as tall as its content.
+    let mut top = column.y;
+    for region in callouts {
+        let height = Paragraph::new(region.text.clone())
+            .block(Block::default().borders(Borders::ALL))
+            .wrap(Wrap { trim: false })
+            .line_count(column.width);
+        let height = u16::try_from(height)
+            .unwrap_or(u16::MAX)
+            .min(column.bottom().saturating_sub(top));
+        if height == 0 {
+            break;
+        }
+        region.render(
+            Rect {
+                y: top,
+                height,
+                ..column
+            },
+            buf,
+        );
+        top += height;
+    }
+    main
+}
+
+/// Draws `cells` in equal cells of `grid` filling `area`, row by row.
+fn render_grid(grid: Grid, cells: &[&Region], area: Rect, buf: &mut Buffer) {
+    let columns = usize::from(grid.columns);
+    let rows = grid.rows.map_or(cells.len().div_ceil(columns), usize::from);
+    let row_areas = Layout::vertical(vec![Constraint::Fill(1); rows]).split(area);
+    for (row_area, row) in row_areas.iter().zip(cells.chunks(columns)) {
+        let cell_areas = Layout::horizontal(vec![Constraint::Fill(1); columns])
+            .spacing(CELL_GAP)
+            .split(*row_area);
+        for (cell_area, cell) in cell_areas.iter().zip(row) {
+            cell.render(*cell_area, buf);
+        }
+    }
+}
+
+/// Centers the slide's title, which `lines` starts with, in whatever width
+/// is left for the main content rather than in the whole slide.
+pub fn center_title(lines: &mut [Line<'static>]) {
+    if let Some(title) = lines.first_mut() {
+        if title
+            .spans
+            .first()
+            .is_some_and(|span| span.content.trim().is_empty())
+        {
+            title.spans.remove(0);
+        }
+        title.alignment = Some(Alignment::Center);
+    }
+}
@@ -0,0 +1,107 @@
+//! Starting a new presentation.
+//!
+//! `presentrs new my-talk` creates the folder `my-talk` with a starter deck,
+//! `slides.md`, that shows off what a deck can do: front matter, a title
+//! slide, a code slide, and speaker notes. Next to it goes `theme.toml`, a
+//! copy of a built-in theme to make the deck's own, which the deck is
+//! presented in with `--theme my-talk/theme.toml`.
+
+use crate::themes::Theme;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Name of the starter deck in the new folder.
+pub const DECK_FILE: &str = "slides.md";
+
+/// Name of the theme file in the new folder.
+pub const THEME_FILE: &str = "theme.toml";
+
+/// The starter deck, with `{title}` standing for the talk's title.
+const DECK: &str = "\
+---
+title: {title}
+author: Your Name
+event: Where You Are Speaking
+---
+
+# {title}
+
+A subtitle that says what the talk is about
+
+<!-- notes: Introduce yourself. Notes like these are for you alone:
+press n to show them while presenting. -->
+
+# Why This Matters
+
+- One idea per bullet
+- Three or four bullets per slide
+- Every H1 heading starts a new slide
+
+<!-- pause -->
+
+Text after a `pause` comment is revealed on the next key press.
+
+# Some Code
+
+```rust
+fn main() {
+    let audience = [\"colleagues\", \"friends\"];
+    for person in audience {
+        println!(\"Hello, {person}!\");
+    }
+}
+```
+
+<!-- notes: Walk through the loop line by line. -->
+
+# Thank You
+
+Questions?
+";
+
+/// Creates the folder `dir` with a starter deck and `theme` to edit, and
+/// returns the paths of the files written.
+pub fn create(dir: &Path, theme: &Theme) -> Result<Vec<PathBuf>, String> {
+    if dir.exists() {
+        return Err(format!(
+            "Failed to create '{}': it already exists",
+            dir.display()
+        ));
+    }
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    let deck = dir.join(DECK_FILE);
+    fs::write(&deck, DECK.replace("{title}", &title(dir)))
+        .map_err(|e| format!("Failed to write '{}': {}", deck.display(), e))?;
+    // Named after the talk, so that it replaces no built-in theme.
+    let mut theme = theme.clone();
+    theme.name = slug(dir);
+    let theme_path = dir.join(THEME_FILE);
+    theme.export(&theme_path)?;
+    Ok(vec![deck, theme_path])
+}
+
+/// Name of the folder `dir`, which names the talk.
+fn slug(dir: &Path) -> String {
+    dir.file_name().map_or_else(
+        || "talk".to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+/// Title of the talk in `dir`: its name, such as `my-talk`, in words, such as
+/// `My Talk`.
+fn title(dir: &Path) -> String {
+    slug(dir)
+        .split(['-', '_', ' '])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars).collect()
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
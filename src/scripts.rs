@@ -0,0 +1,134 @@
+//! Subscripts and superscripts, for units and exponents on technical slides.
+//!
+//! `H~2~O` and `x^2^` are drawn as `H₂O` and `x²` with Unicode's subscript
+//! and superscript characters. Unicode only has them for digits, a few
+//! symbols, and some letters, so text that cannot be written entirely with
+//! them falls back to an inline notation instead: `e^i·π^` becomes `e^(i·π)`
+//! and `x~max~` becomes `x_(max)`. Like in Pandoc, the text between the
+//! markers may not contain spaces, so a lone `~` or `^` is left alone.
+
+/// Digits, symbols, and letters that have a superscript form.
+const SUPERSCRIPTS: &[(char, char)] = &[
+    ('0', '⁰'),
+    ('1', '¹'),
+    ('2', '²'),
+    ('3', '³'),
+    ('4', '⁴'),
+    ('5', '⁵'),
+    ('6', '⁶'),
+    ('7', '⁷'),
+    ('8', '⁸'),
+    ('9', '⁹'),
+    ('+', '⁺'),
+    ('-', '⁻'),
+    ('=', '⁼'),
+    ('(', '⁽'),
+    (')', '⁾'),
+    ('a', 'ᵃ'),
+    ('b', 'ᵇ'),
+    ('c', 'ᶜ'),
+    ('d', 'ᵈ'),
+    ('e', 'ᵉ'),
+    ('f', 'ᶠ'),
+    ('g', 'ᵍ'),
+    ('h', 'ʰ'),
+    ('i', 'ⁱ'),
+    ('j', 'ʲ'),
+    ('k', 'ᵏ'),
+    ('l', 'ˡ'),
+    ('m', 'ᵐ'),
+    ('n', 'ⁿ'),
+    ('o', 'ᵒ'),
+    ('p', 'ᵖ'),
+    ('r', 'ʳ'),
+    ('s', 'ˢ'),
+    ('t', 'ᵗ'),
+    ('u', 'ᵘ'),
+    ('v', 'ᵛ'),
+    ('w', 'ʷ'),
+    ('x', 'ˣ'),
+    ('y', 'ʸ'),
+    ('z', 'ᶻ'),
+];
+
+/// Digits, symbols, and letters that have a subscript form.
+const SUBSCRIPTS: &[(char, char)] = &[
+    ('0', '₀'),
+    ('1', '₁'),
+    ('2', '₂'),
+    ('3', '₃'),
+    ('4', '₄'),
+    ('5', '₅'),
+    ('6', '₆'),
+    ('7', '₇'),
+    ('8', '₈'),
+    ('9', '₉'),
+    ('+', '₊'),
+    ('-', '₋'),
+    ('=', '₌'),
+    ('(', '₍'),
+    (')', '₎'),
+    ('a', 'ₐ'),
+    ('e', 'ₑ'),
+    ('h', 'ₕ'),
+    ('i', 'ᵢ'),
+    ('j', 'ⱼ'),
+    ('k', 'ₖ'),
+    ('l', 'ₗ'),
+    ('m', 'ₘ'),
+    ('n', 'ₙ'),
+    ('o', 'ₒ'),
+    ('p', 'ₚ'),
+    ('r', 'ᵣ'),
+    ('s', 'ₛ'),
+    ('t', 'ₜ'),
+    ('u', 'ᵤ'),
+    ('v', 'ᵥ'),
+    ('x', 'ₓ'),
+];
+
+/// Rewrites the `~sub~` and `^sup^` spans in `text` as subscripts and
+/// superscripts.
+pub fn render(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(['~', '^']) {
+        let marker = rest[start..].chars().next().unwrap_or('^');
+        let after = &rest[start + 1..];
+        out.push_str(&rest[..start]);
+        let inner = after.find(marker).map(|len| &after[..len]);
+        let Some(inner) =
+            inner.filter(|inner| !inner.is_empty() && !inner.contains(char::is_whitespace))
+        else {
+            // Not a script; the marker stays and the search goes on after it.
+            out.push(marker);
+            rest = after;
+            continue;
+        };
+        let (table, fallback) = if marker == '^' {
+            (SUPERSCRIPTS, '^')
+        } else {
+            (SUBSCRIPTS, '_')
+        };
+        match convert(inner, table) {
+            Some(script) => out.push_str(&script),
+            None => {
+                out.push(fallback);
+                out.push('(');
+                out.push_str(inner);
+                out.push(')');
+            }
+        }
+        rest = &after[inner.len() + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Writes `text` in the characters `table` maps it to, if it has a mapping
+/// for every character.
+fn convert(text: &str, table: &[(char, char)]) -> Option<String> {
+    text.chars()
+        .map(|c| table.iter().find(|(from, _)| *from == c).map(|(_, to)| *to))
+        .collect()
+}
@@ -0,0 +1,189 @@
+//! Searching the deck for text.
+//!
+//! `/` opens a prompt for the text to look for, and `Enter` jumps to its
+//! first match from the current slide on. While a search is active, every
+//! match on the slide is drawn in reverse video, with the one jumped to
+//! picked out, and `n` and `N` move to the next and previous match, on the
+//! same slide or another one. `Esc` ends the search, and `Enter` at an
+//! empty prompt starts the last one again.
+//!
+//! Like in vim's smartcase, a search for text in lowercase ignores case and
+//! a search with any capital letter does not.
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+};
+use std::ops::Range;
+
+/// Where a match of the search was found.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Match {
+    /// Index of the slide
+    pub slide: usize,
+    /// Index of the line within the slide's content
+    pub line: usize,
+    /// Offset of the match within the line, in characters
+    pub start: usize,
+}
+
+/// A search of the deck and the match last jumped to.
+pub struct Search {
+    /// Text being looked for
+    query: String,
+    /// Match last jumped to, if any
+    current: Option<Match>,
+}
+
+impl Search {
+    /// Starts a search for `query`.
+    pub fn new(query: String) -> Self {
+        Search {
+            query,
+            current: None,
+        }
+    }
+
+    /// Returns the text being looked for.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Finds every match in the content of `slides`, in deck order.
+    pub fn matches<'a>(&self, slides: impl IntoIterator<Item = &'a Text<'static>>) -> Vec<Match> {
+        let mut matches = Vec::new();
+        for (slide, text) in slides.into_iter().enumerate() {
+            for (line, content) in text.lines.iter().enumerate() {
+                for range in self.find(content) {
+                    matches.push(Match {
+                        slide,
+                        line,
+                        start: range.start,
+                    });
+                }
+            }
+        }
+        matches
+    }
+
+    /// Picks the match after the one last jumped to, or before it if
+    /// `forward` is false, wrapping around the ends of the deck.
+    ///
+    /// Once the presenter has moved to another slide, or before the first
+    /// jump, the search goes on from `origin` instead. Returns the match and
+    /// whether the search wrapped around, or `None` if there are no matches.
+    pub fn advance(
+        &mut self,
+        matches: &[Match],
+        origin: Match,
+        forward: bool,
+    ) -> Option<(Match, bool)> {
+        let current = self.current.filter(|current| current.slide == origin.slide);
+        let found = match (current, forward) {
+            (None, true) => matches.iter().find(|m| **m >= origin),
+            (None, false) => matches.iter().rev().find(|m| **m < origin),
+            (Some(current), true) => matches.iter().find(|m| **m > current),
+            (Some(current), false) => matches.iter().rev().find(|m| **m < current),
+        };
+        let wrapped = found.is_none();
+        let found = *found.or(if forward {
+            matches.first()
+        } else {
+            matches.last()
+        })?;
+        self.current = Some(found);
+        Some((found, wrapped))
+    }
+
+    /// Draws the matches on line `line` of slide `slide` in reverse video,
+    /// and the one last jumped to in black on yellow.
+    pub fn highlight(&self, slide: usize, line: usize, content: &Line<'static>) -> Line<'static> {
+        let ranges: Vec<(Range<usize>, Style)> = self
+            .find(content)
+            .into_iter()
+            .map(|range| {
+                let here = Match {
+                    slide,
+                    line,
+                    start: range.start,
+                };
+                let style = if self.current == Some(here) {
+                    Style::default().fg(Color::Black).bg(Color::Yellow)
+                } else {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                };
+                (range, style)
+            })
+            .collect();
+        if ranges.is_empty() {
+            return content.clone();
+        }
+        restyle(content, &ranges)
+    }
+
+    /// Finds the matches in `line`, as ranges of characters.
+    fn find(&self, line: &Line) -> Vec<Range<usize>> {
+        let sensitive = self.query.chars().any(char::is_uppercase);
+        let fold = |c: char| {
+            if sensitive {
+                c
+            } else {
+                c.to_lowercase().next().unwrap_or(c)
+            }
+        };
+        let query: Vec<char> = self.query.chars().map(fold).collect();
+        let text: Vec<char> = line
+            .spans
+            .iter()
+            .flat_map(|span| span.content.chars())
+            .map(fold)
+            .collect();
+        let mut ranges = Vec::new();
+        if query.is_empty() {
+            return ranges;
+        }
+        let mut start = 0;
+        while start + query.len() <= text.len() {
+            if text[start..start + query.len()] == query[..] {
+                ranges.push(start..start + query.len());
+                start += query.len();
+            } else {
+                start += 1;
+            }
+        }
+        ranges
+    }
+}
+
+/// Splits the spans of `line` where `ranges` of characters start and end,
+/// and patches each range with its style.
+fn restyle(line: &Line<'static>, ranges: &[(Range<usize>, Style)]) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    for span in &line.spans {
+        let chars: Vec<char> = span.content.chars().collect();
+        let end = pos + chars.len();
+        let mut cuts: Vec<usize> = ranges
+            .iter()
+            .flat_map(|(range, _)| [range.start, range.end])
+            .filter(|&cut| cut > pos && cut < end)
+            .collect();
+        cuts.push(end);
+        cuts.sort_unstable();
+        cuts.dedup();
+        let mut from = pos;
+        for cut in cuts {
+            let style = ranges
+                .iter()
+                .find(|(range, _)| range.contains(&from))
+                .map_or(span.style, |(_, style)| span.style.patch(*style));
+            let text: String = chars[from - pos..cut - pos].iter().collect();
+            spans.push(Span::styled(text, style));
+            from = cut;
+        }
+        pos = end;
+    }
+    let mut restyled = line.clone();
+    restyled.spans = spans;
+    restyled
+}
@@ -0,0 +1,109 @@
+//! Where one slide ends and the next begins.
+//!
+//! Every H1 heading starts a new slide by default. Decks written for Marp or
+//! reveal.js separate their slides with thematic breaks (`---`) instead, and
+//! `--separator rule` (or `separator: rule` in the front matter, or
+//! `separator = "rule"` in the config file) splits them there. Any other
+//! value is a line of its own that separates slides, such as
+//! `separator: +++`. With a separator, headings no longer start slides, and
+//! the first heading on a slide, whatever its level, is its title.
+
+use serde::Deserialize;
+use std::{convert::Infallible, ops::Range, str::FromStr};
+
+/// Value of `--separator` that splits slides at H1 headings.
+const HEADING: &str = "heading";
+/// Values of `--separator` that split slides at thematic breaks.
+const RULE: [&str; 2] = ["rule", "---"];
+
+/// What separates the slides of a deck.
+#[derive(Clone, Default, PartialEq, Deserialize)]
+#[serde(from = "String")]
+pub enum Separator {
+    /// An H1 heading starts every slide
+    #[default]
+    Heading,
+    /// Thematic breaks such as `---` separate slides
+    Rule,
+    /// Lines holding just this text separate slides
+    Line(String),
+}
+
+impl From<String> for Separator {
+    fn from(text: String) -> Self {
+        let text = text.trim();
+        if text.eq_ignore_ascii_case(HEADING) {
+            Separator::Heading
+        } else if RULE.iter().any(|rule| text.eq_ignore_ascii_case(rule)) {
+            Separator::Rule
+        } else {
+            Separator::Line(text.to_string())
+        }
+    }
+}
+
+impl FromStr for Separator {
+    type Err = Infallible;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Ok(text.to_string().into())
+    }
+}
+
+impl Separator {
+    /// Looks up a separator as written in a deck's front matter.
+    pub fn named(name: &str) -> Option<Self> {
+        (!name.trim().is_empty()).then(|| name.to_string().into())
+    }
+
+    /// Whether `line`, outside code blocks, separates two slides rather than
+    /// belonging to one, given whether the line before it was blank. Headings
+    /// belong to the slides they start, so they never do.
+    pub fn is_break(&self, line: &str, after_blank: bool) -> bool {
+        match self {
+            Separator::Heading => false,
+            Separator::Rule => is_thematic_break(line, after_blank),
+            Separator::Line(separator) => line.trim() == separator,
+        }
+    }
+
+    /// Returns `markdown` with the separator lines outside code blocks
+    /// replaced by spaces, and where each of them was. Lines are blanked byte
+    /// for byte, so that offsets stay the same.
+    pub fn take_lines(&self, markdown: &str) -> (String, Vec<Range<usize>>) {
+        let Separator::Line(separator) = self else {
+            return (markdown.to_string(), Vec::new());
+        };
+        let mut taken = String::with_capacity(markdown.len());
+        let mut lines = Vec::new();
+        let mut in_fence = false;
+        for line in markdown.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+            }
+            let content = line.trim_end_matches(['\n', '\r']);
+            if !in_fence && content.trim() == separator {
+                lines.push(taken.len()..taken.len() + line.len());
+                taken.extend(std::iter::repeat_n(' ', content.len()));
+                taken.push_str(&line[content.len()..]);
+            } else {
+                taken.push_str(line);
+            }
+        }
+        (taken, lines)
+    }
+}
+
+/// Whether `line` is a thematic break: three or more `-`, `*` or `_`, with
+/// nothing else but spaces. Dashes right under text underline a heading.
+fn is_thematic_break(line: &str, after_blank: bool) -> bool {
+    if line.starts_with("    ") {
+        return false;
+    }
+    let mut marks = line.chars().filter(|c| !c.is_whitespace());
+    let Some(mark) = marks.next().filter(|c| matches!(c, '-' | '*' | '_')) else {
+        return false;
+    };
+    marks.clone().count() >= 2 && marks.all(|c| c == mark) && (mark != '-' || after_blank)
+}
@@ -0,0 +1,631 @@
+//! Built-in web server for audience interaction.
+//!
+//! `--serve <ADDR>` starts a small HTTP server next to the presentation.
+//! Requests are handled on background threads and turned into
+//! [`ServerEvent`]s, which are queued on a channel that the UI drains between
+//! frames, so networking never blocks rendering.
+//!
+//! With `--remote`, the server also turns a phone into the presenter's
+//! clicker. The remote's pages only answer requests carrying the token
+//! generated for the session, which is part of the URL shown as a QR code
+//! when the presentation starts, so only whoever scans it can change slides.
+//!
+//! Several presenters can have the remote open at once, each under their
+//! own name. One of them drives: only their buttons move through the deck.
+//! The first to press a button when nobody drives takes control, and anyone
+//! else can take it over with the remote's Take control button. A driver
+//! whose remote stops asking for the state is taken to have left, which
+//! frees control for the next presenter.
+//!
+//! Routes:
+//!
+//! - `GET /` serves a page where attendees can submit questions
+//! - `POST /questions` accepts a form with a `question` field
+//! - `GET /remote?token=...` serves the remote, with big Next and Previous
+//!   buttons, the notes of the current slide, and the time elapsed
+//! - `GET /remote/state?token=...` returns what the remote shows, as JSON
+//! - `POST /remote/next?token=...` and `POST /remote/previous?token=...`
+//!   move through the deck
+//! - `POST /remote/take?token=...` gives control to the presenter asking
+//!
+//! On networks shared with strangers, such as conference Wi-Fi, the token
+//! could be read off the air. `--tls-cert` and `--tls-key` make the server
+//! speak HTTPS instead, with a certificate in PEM, for instance one made with
+//! `mkcert`, so both the token and the questions travel encrypted.
+//!
+//! Requests to the remote also carry `client`, an id the page makes up for
+//! itself, and `name`, the presenter's name.
+
+use rustls::{
+    ServerConfig, ServerConnection, StreamOwned,
+    pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, hash_map::RandomState},
+    hash::{BuildHasher, Hasher},
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, UdpSocket},
+    path::Path,
+    sync::{
+        Arc, Mutex,
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Largest request body accepted, in bytes.
+const MAX_BODY: usize = 8 * 1024;
+/// Longest question accepted, in characters.
+const MAX_QUESTION: usize = 500;
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a presenter's remote may go without asking for the state before
+/// they are taken to have left.
+const PRESENTER_TIMEOUT: Duration = Duration::from_secs(5);
+/// Longest presenter name accepted, in characters.
+const MAX_NAME: usize = 40;
+
+/// Something that happened on the server that the UI should react to.
+pub enum ServerEvent {
+    /// An attendee submitted a question
+    Question(String),
+    /// The presenter pressed Next on the remote
+    Next,
+    /// The presenter pressed Previous on the remote
+    Previous,
+    /// The presenter with this name took control of the remote
+    Handoff(String),
+}
+
+/// What the remote shows about the presentation.
+#[derive(Clone, Default, PartialEq, Serialize)]
+pub struct RemoteState {
+    /// Number of the slide on screen, starting at 1
+    pub slide: usize,
+    /// Number of slides in the deck
+    pub slides: usize,
+    /// Title of the slide on screen
+    pub title: String,
+    /// Speaker notes of the slide on screen
+    pub notes: String,
+    /// Seconds since the presentation started
+    pub elapsed: u64,
+}
+
+/// The presenter remote of a session.
+struct Remote {
+    /// Secret that requests to the remote must carry
+    token: String,
+    /// What the remote shows, as last published by the UI
+    state: Mutex<RemoteState>,
+    /// Who has the remote open and who drives
+    control: Mutex<Control>,
+}
+
+/// The presenters who have the remote open, and which of them drives.
+#[derive(Default)]
+struct Control {
+    /// Client id of the presenter whose buttons move through the deck
+    driver: Option<String>,
+    /// Name of each presenter by client id, and when they were last heard from
+    presenters: HashMap<String, (String, Instant)>,
+}
+
+impl Control {
+    /// Notes that the presenter `name` has the remote open as `client`, and
+    /// forgets the presenters not heard from in a while.
+    fn heard(&mut self, client: &str, name: &str) {
+        let now = Instant::now();
+        self.presenters
+            .insert(client.to_string(), (name.to_string(), now));
+        self.presenters
+            .retain(|_, (_, seen)| now.duration_since(*seen) < PRESENTER_TIMEOUT);
+        if let Some(driver) = &self.driver
+            && !self.presenters.contains_key(driver)
+        {
+            self.driver = None;
+        }
+    }
+
+    /// Name of the presenter who drives, if anyone does.
+    fn driver_name(&self) -> Option<&str> {
+        let driver = self.driver.as_ref()?;
+        self.presenters.get(driver).map(|(name, _)| name.as_str())
+    }
+
+    /// Gives control to `client`. Returns whether it changed hands.
+    fn take(&mut self, client: &str) -> bool {
+        if self.driver.as_deref() == Some(client) {
+            return false;
+        }
+        self.driver = Some(client.to_string());
+        true
+    }
+}
+
+/// What the remote's page is sent when it asks for the state.
+#[derive(Serialize)]
+struct StateResponse<'a> {
+    /// The presentation, as last published by the UI
+    #[serde(flatten)]
+    state: &'a RemoteState,
+    /// Name of the presenter who drives, if anyone does
+    driver: Option<&'a str>,
+    /// Whether the presenter asking drives
+    driving: bool,
+    /// Names of the presenters who have the remote open
+    presenters: Vec<&'a str>,
+}
+
+/// A running web server and the queue of events it produced.
+pub struct Server {
+    /// Address the server is listening on
+    addr: SocketAddr,
+    /// Events received from connection handlers
+    events: Receiver<ServerEvent>,
+    /// The presenter remote, if the server serves one
+    remote: Option<Arc<Remote>>,
+    /// Whether the server speaks HTTPS
+    tls: bool,
+}
+
+/// Loads the certificate chain in `cert` and the private key in `key`, both
+/// PEM files, for the server to speak HTTPS with.
+pub fn tls_config(cert: &Path, key: &Path) -> Result<Arc<ServerConfig>, String> {
+    let certs = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Failed to read certificate '{}': {}", cert.display(), e))?;
+    if certs.is_empty() {
+        return Err(format!("No certificate found in '{}'", cert.display()));
+    }
+    let private_key = PrivateKeyDer::from_pem_file(key)
+        .map_err(|e| format!("Failed to read private key '{}': {}", key.display(), e))?;
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .with_no_client_auth()
+        .with_single_cert(certs, private_key)
+        .map_err(|e| format!("Invalid certificate '{}': {}", cert.display(), e))?;
+    Ok(Arc::new(config))
+}
+
+impl Server {
+    /// Binds `addr` (e.g. `0.0.0.0:8080`) and starts accepting connections in
+    /// the background, over HTTPS if given a `tls` config. With `remote`,
+    /// also serves a presenter remote under a new token.
+    pub fn start(addr: &str, remote: bool, tls: Option<Arc<ServerConfig>>) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let (sender, events) = mpsc::channel();
+        let remote = remote.then(|| {
+            Arc::new(Remote {
+                token: new_token(),
+                state: Mutex::new(RemoteState::default()),
+                control: Mutex::new(Control::default()),
+            })
+        });
+        let shared = remote.clone();
+        let secure = tls.is_some();
+        log::info!("Serving questions on {addr}");
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let sender = sender.clone();
+                let remote = shared.clone();
+                let tls = tls.clone();
+                thread::spawn(move || {
+                    let peer = stream
+                        .peer_addr()
+                        .map_or_else(|_| "a client".to_string(), |addr| addr.to_string());
+                    let serve = || -> io::Result<()> {
+                        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+                        match tls {
+                            Some(config) => {
+                                let connection =
+                                    ServerConnection::new(config).map_err(io::Error::other)?;
+                                let mut stream = StreamOwned::new(connection, stream);
+                                handle(&mut stream, &sender, remote.as_deref())?;
+                                stream.conn.send_close_notify();
+                                stream.flush()
+                            }
+                            None => handle(stream, &sender, remote.as_deref()),
+                        }
+                    };
+                    // A failed connection only affects that client.
+                    if let Err(e) = serve() {
+                        log::debug!("Connection from {peer} failed: {e}");
+                    }
+                });
+            }
+        });
+        Ok(Server {
+            addr,
+            events,
+            remote,
+            tls: secure,
+        })
+    }
+
+    /// Address the questions page is served at.
+    pub fn url(&self) -> String {
+        format!("{}://{}/", self.scheme(), self.addr)
+    }
+
+    /// `https` if the server speaks HTTPS, or else `http`.
+    fn scheme(&self) -> &'static str {
+        if self.tls { "https" } else { "http" }
+    }
+
+    /// Address of the presenter remote, token included, if the server
+    /// serves one. A server listening on every interface is reached at
+    /// this machine's address on the local network.
+    pub fn remote_url(&self) -> Option<String> {
+        let remote = self.remote.as_ref()?;
+        let ip = if self.addr.ip().is_unspecified() {
+            local_ip().unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        } else {
+            self.addr.ip()
+        };
+        let host = SocketAddr::new(ip, self.addr.port());
+        Some(format!(
+            "{}://{host}/remote?token={}",
+            self.scheme(),
+            remote.token
+        ))
+    }
+
+    /// Name of the presenter who drives from the remote, if anyone does.
+    pub fn driver(&self) -> Option<String> {
+        let control = self.remote.as_ref()?.control.lock().ok()?;
+        control.driver_name().map(str::to_string)
+    }
+
+    /// Updates what the remote shows.
+    pub fn publish(&self, state: RemoteState) {
+        if let Some(remote) = &self.remote
+            && let Ok(mut current) = remote.state.lock()
+        {
+            *current = state;
+        }
+    }
+
+    /// Returns every event received since the last call, without blocking.
+    pub fn drain(&self) -> Vec<ServerEvent> {
+        self.events.try_iter().collect()
+    }
+}
+
+/// Makes a token for a session's remote: 128 bits from the randomly keyed
+/// hashers of the standard library, which are seeded by the OS.
+fn new_token() -> String {
+    let half = || RandomState::new().build_hasher().finish();
+    format!("{:016x}{:016x}", half(), half())
+}
+
+/// Address of this machine on the network it reaches others through.
+fn local_ip() -> Option<IpAddr> {
+    // Connecting a UDP socket only picks a route; nothing is sent.
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:80").ok()?;
+    Some(socket.local_addr().ok()?.ip())
+}
+
+/// Whether the query string of a request carries the remote's token.
+fn authorized(remote: &Remote, query: &str) -> bool {
+    let token = form_value(query.as_bytes(), "token").unwrap_or_default();
+    // Compared in constant time so the token cannot be guessed byte by byte.
+    token.len() == remote.token.len()
+        && token
+            .bytes()
+            .zip(remote.token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Serves a single HTTP request on `stream`.
+fn handle(
+    stream: impl Read + Write,
+    events: &Sender<ServerEvent>,
+    remote: Option<&Remote>,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+    log::debug!("{method} {}", path.split('?').next().unwrap_or(""));
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+    }
+
+    let (route, query) = path.split_once('?').unwrap_or((path, ""));
+    if route == "/remote" || route.starts_with("/remote/") {
+        return match remote {
+            Some(remote) if authorized(remote, query) => {
+                handle_remote(reader.get_mut(), method, route, query, remote, events)
+            }
+            Some(_) => respond(reader.get_mut(), "403 Forbidden", "Wrong or missing token"),
+            None => respond(reader.get_mut(), "404 Not Found", "Not found"),
+        };
+    }
+    match (method, route) {
+        ("GET", "/") => respond(reader.get_mut(), "200 OK", &page(None)),
+        ("POST", "/questions") => {
+            if content_length > MAX_BODY {
+                return respond(
+                    reader.get_mut(),
+                    "413 Payload Too Large",
+                    "Question too long",
+                );
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body)?;
+            let question = form_value(&body, "question")
+                .map(|q| q.trim().chars().take(MAX_QUESTION).collect::<String>())
+                .unwrap_or_default();
+            let notice = if question.is_empty() {
+                "Please type a question first."
+            } else {
+                let _ = events.send(ServerEvent::Question(question));
+                "Thanks! Your question was sent to the speaker."
+            };
+            respond(reader.get_mut(), "200 OK", &page(Some(notice)))
+        }
+        _ => respond(reader.get_mut(), "404 Not Found", "Not found"),
+    }
+}
+
+/// Serves a request to the presenter remote, already authorized, from the
+/// presenter named in its `query`.
+fn handle_remote(
+    out: &mut impl Write,
+    method: &str,
+    route: &str,
+    query: &str,
+    remote: &Remote,
+    events: &Sender<ServerEvent>,
+) -> io::Result<()> {
+    if (method, route) == ("GET", "/remote") {
+        return respond(out, "200 OK", REMOTE_PAGE);
+    }
+    let Some(client) = form_value(query.as_bytes(), "client").filter(|c| !c.is_empty()) else {
+        return respond(out, "400 Bad Request", "Missing client");
+    };
+    let name = form_value(query.as_bytes(), "name")
+        .map(|name| name.trim().chars().take(MAX_NAME).collect::<String>())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "Presenter".to_string());
+    let Ok(mut control) = remote.control.lock() else {
+        return respond(out, "500 Internal Server Error", "Remote unavailable");
+    };
+    control.heard(&client, &name);
+    let take = |control: &mut Control| {
+        if control.take(&client) {
+            let _ = events.send(ServerEvent::Handoff(name.clone()));
+        }
+    };
+    match (method, route) {
+        ("GET", "/remote/state") => {
+            let state = remote.state.lock().map(|s| s.clone()).unwrap_or_default();
+            let mut presenters: Vec<&str> = control
+                .presenters
+                .values()
+                .map(|(name, _)| name.as_str())
+                .collect();
+            presenters.sort_unstable();
+            let response = StateResponse {
+                state: &state,
+                driver: control.driver_name(),
+                driving: control.driver.as_deref() == Some(client.as_str()),
+                presenters,
+            };
+            let body = serde_json::to_string(&response).unwrap_or_default();
+            respond_with(out, "200 OK", "application/json", &body)
+        }
+        ("POST", "/remote/next" | "/remote/previous") => {
+            match control.driver_name() {
+                None => take(&mut control),
+                Some(driver) if control.driver.as_deref() != Some(client.as_str()) => {
+                    let message = format!("{driver} is driving");
+                    return respond_with(out, "409 Conflict", "text/plain", &message);
+                }
+                Some(_) => {}
+            }
+            let event = if route == "/remote/next" {
+                ServerEvent::Next
+            } else {
+                ServerEvent::Previous
+            };
+            let _ = events.send(event);
+            respond_with(out, "200 OK", "text/plain", "ok")
+        }
+        ("POST", "/remote/take") => {
+            take(&mut control);
+            respond_with(out, "200 OK", "text/plain", "ok")
+        }
+        _ => respond(out, "404 Not Found", "Not found"),
+    }
+}
+
+/// Writes a complete HTML response and closes the connection.
+fn respond(out: &mut impl Write, status: &str, body: &str) -> io::Result<()> {
+    respond_with(out, status, "text/html; charset=utf-8", body)
+}
+
+/// Writes a complete response of `content_type` and closes the connection.
+fn respond_with(
+    out: &mut impl Write,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    write!(
+        out,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    out.flush()
+}
+
+/// The question submission page, optionally with a notice above the form.
+fn page(notice: Option<&str>) -> String {
+    let notice = notice
+        .map(|text| format!("<p class=\"notice\">{text}</p>"))
+        .unwrap_or_default();
+    format!(
+        r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Ask a question</title>
+<style>
+body {{ font-family: sans-serif; max-width: 32em; margin: 2em auto; padding: 0 1em; }}
+textarea {{ width: 100%; box-sizing: border-box; font-size: 1.1em; }}
+button {{ font-size: 1.1em; padding: 0.4em 1.2em; margin-top: 0.5em; }}
+.notice {{ background: #e8f4e8; padding: 0.6em; border-radius: 4px; }}
+</style>
+</head>
+<body>
+<h1>Ask a question</h1>
+{notice}
+<form method="post" action="/questions">
+<textarea name="question" rows="4" maxlength="{MAX_QUESTION}" required></textarea>
+<button type="submit">Send</button>
+</form>
+</body>
+</html>
+"#
+    )
+}
+
+/// The presenter remote. It asks for the state of the presentation every
+/// second, and sends the token it was opened with along, together with the
+/// presenter's name and an id it keeps for the browser.
+const REMOTE_PAGE: &str = r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1, user-scalable=no">
+<title>Remote</title>
+<style>
+body { font-family: sans-serif; margin: 0; padding: 1em; background: #111; color: #eee;
+       display: flex; flex-direction: column; height: 100vh; box-sizing: border-box; }
+header { display: flex; justify-content: space-between; font-size: 1.2em; }
+#control { display: flex; justify-content: space-between; align-items: center;
+           margin-top: 0.5em; color: #8c8; }
+#control.other { color: #e96; }
+#control button { flex: none; font-size: 1em; padding: 0.4em 0.8em; background: #a63; }
+#timer { font-variant-numeric: tabular-nums; }
+h1 { font-size: 1.3em; margin: 0.5em 0; }
+#notes { flex: 1; overflow-y: auto; white-space: pre-wrap; color: #bbb; margin-bottom: 1em; }
+.buttons { display: flex; gap: 1em; }
+button { flex: 1; font-size: 2em; padding: 1.2em 0; border: none; border-radius: 12px;
+         color: #fff; background: #2a6; touch-action: manipulation; }
+button.previous { flex: 0.6; background: #444; }
+.watching button { opacity: 0.4; }
+</style>
+</head>
+<body>
+<header><span id="position"></span><span id="timer"></span></header>
+<div id="control"><span id="driver"></span><button id="take" onclick="take()">Take control</button></div>
+<h1 id="title"></h1>
+<div id="notes"></div>
+<div class="buttons" id="buttons">
+<button class="previous" onclick="send('previous')">Prev</button>
+<button onclick="send('next')">Next</button>
+</div>
+<script>
+function remembered(key, make) {
+  let value = localStorage.getItem(key);
+  if (!value) {
+    value = make();
+    localStorage.setItem(key, value);
+  }
+  return value;
+}
+const client = remembered("presentrs-client", () => Math.random().toString(36).slice(2));
+const name = remembered("presentrs-name", () => prompt("Your name", "") || "Presenter");
+const token = location.search + "&client=" + encodeURIComponent(client) +
+  "&name=" + encodeURIComponent(name);
+function show(state) {
+  const others = state.driver && !state.driving;
+  document.getElementById("driver").textContent = state.driving ? "You are driving"
+    : state.driver ? state.driver + " is driving" : "Nobody is driving";
+  document.getElementById("control").className = others ? "other" : "";
+  document.getElementById("take").style.display = state.driving ? "none" : "";
+  document.getElementById("buttons").className = others ? "buttons watching" : "buttons";
+  document.getElementById("position").textContent = state.slide + " / " + state.slides;
+  document.getElementById("title").textContent = state.title;
+  document.getElementById("notes").textContent = state.notes;
+  const minutes = Math.floor(state.elapsed / 60);
+  const seconds = String(state.elapsed % 60).padStart(2, "0");
+  document.getElementById("timer").textContent = minutes + ":" + seconds;
+}
+function refresh() {
+  fetch("/remote/state" + token).then(r => r.json()).then(show).catch(() => {});
+}
+function send(action) {
+  fetch("/remote/" + action + token, { method: "POST" }).then(() => setTimeout(refresh, 300));
+}
+function take() {
+  fetch("/remote/take" + token, { method: "POST" }).then(refresh);
+}
+refresh();
+setInterval(refresh, 1000);
+</script>
+</body>
+</html>
+"#;
+
+/// Extracts and decodes the field `name` from an `application/x-www-form-urlencoded` body.
+fn form_value(body: &[u8], name: &str) -> Option<String> {
+    body.split(|b| *b == b'&').find_map(|pair| {
+        let mut parts = pair.splitn(2, |b| *b == b'=');
+        let key = parts.next()?;
+        (key == name.as_bytes()).then(|| percent_decode(parts.next().unwrap_or(&[])))
+    })
+}
+
+/// Decodes `%XX` escapes and `+` as space.
+fn percent_decode(bytes: &[u8]) -> String {
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match std::str::from_utf8(&bytes[i + 1..i + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
@@ -0,0 +1,197 @@
+//! Recording and replaying navigation sessions.
+//!
+//! A session file is a small JSON document listing every navigation action
+//! taken during a presentation together with the number of seconds since the
+//! presentation started:
+//!
+//! ```json
+//! { "deck": "talk.md", "events": [ { "time": 4.2, "action": "next_slide" } ] }
+//! ```
+//!
+//! `presentrs replay deck.md session.json` feeds the events back through the
+//! same [`Action`] handling as the keyboard, reproducing the original timing.
+//!
+//! Hand-written timings files drive `--autoplay` instead. Each line maps a
+//! 1-based slide number to how long it stays on screen, optionally spreading
+//! a number of scroll steps evenly across that time:
+//!
+//! ```text
+//! # slide  duration  [scroll <n>]
+//! 1        8s
+//! 2        1m30s     scroll 4
+//! ```
+
+use crate::{Action, parse_duration};
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    fs,
+    time::{Duration, Instant},
+};
+
+/// A single timestamped navigation action.
+#[derive(Serialize, Deserialize)]
+struct SessionEvent {
+    /// Seconds since the start of the presentation
+    time: f64,
+    /// The action that was taken
+    action: Action,
+}
+
+/// On-disk representation of a recorded session.
+#[derive(Serialize, Deserialize)]
+struct SessionFile {
+    /// Path of the deck the session was recorded against
+    deck: String,
+    /// Navigation actions in the order they happened
+    events: Vec<SessionEvent>,
+}
+
+/// Collects navigation actions while presenting and writes them on exit.
+pub struct SessionLog {
+    /// Destination of the session file
+    path: String,
+    /// Moment the presentation started
+    start: Instant,
+    /// Data accumulated so far
+    file: SessionFile,
+}
+
+impl SessionLog {
+    /// Starts a new log for `deck` that will be saved to `path`.
+    pub fn new(path: &str, deck: &str) -> Self {
+        SessionLog {
+            path: path.to_string(),
+            start: Instant::now(),
+            file: SessionFile {
+                deck: deck.to_string(),
+                events: Vec::new(),
+            },
+        }
+    }
+
+    /// Records `action` at the current time.
+    pub fn push(&mut self, action: Action) {
+        self.file.events.push(SessionEvent {
+            time: self.start.elapsed().as_secs_f64(),
+            action,
+        });
+    }
+
+    /// Writes the session file to disk.
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let json = serde_json::to_string_pretty(&self.file)?;
+        fs::write(&self.path, json)
+            .map_err(|e| format!("Failed to write session '{}': {}", self.path, e))?;
+        Ok(())
+    }
+}
+
+/// A queue of actions to perform at fixed offsets from the start of playback.
+pub struct Script {
+    /// Pending actions, sorted by offset
+    steps: Vec<(Duration, Action)>,
+    /// Index of the next pending action
+    next: usize,
+    /// Moment playback started
+    start: Instant,
+}
+
+impl Script {
+    /// Creates a script from `(offset, action)` pairs and starts its clock.
+    pub fn new(mut steps: Vec<(Duration, Action)>) -> Self {
+        steps.sort_by_key(|(offset, _)| *offset);
+        Script {
+            steps,
+            next: 0,
+            start: Instant::now(),
+        }
+    }
+
+    /// Loads a session file written by [`SessionLog::save`].
+    pub fn load_session(path: &str) -> Result<Self, Box<dyn Error>> {
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read session '{}': {}", path, e))?;
+        let file: SessionFile = serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid session file '{}': {}", path, e))?;
+        let steps = file
+            .events
+            .into_iter()
+            .map(|event| {
+                let time = Duration::try_from_secs_f64(event.time.max(0.0))
+                    .map_err(|_| format!("Invalid session file '{}': time {}", path, event.time))?;
+                Ok((time, event.action))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Script::new(steps))
+    }
+
+    /// Loads a timings file for `--autoplay`.
+    ///
+    /// Slides are visited in ascending order; slides missing from the file are
+    /// skipped. Scroll steps are distributed evenly within a slide's duration.
+    pub fn load_timings(path: &str) -> Result<Self, Box<dyn Error>> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read timings '{}': {}", path, e))?;
+
+        let mut entries = Vec::new();
+        for (line_no, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || {
+                format!(
+                    "{}:{}: expected `<slide> <duration> [scroll <n>]`",
+                    path,
+                    line_no + 1
+                )
+            };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let slide = match fields.first().and_then(|f| f.parse::<usize>().ok()) {
+                Some(slide) if slide > 0 => slide - 1,
+                _ => return Err(invalid().into()),
+            };
+            let duration = fields
+                .get(1)
+                .and_then(|f| parse_duration(f))
+                .ok_or_else(invalid)?;
+            let scrolls = match fields.get(2..) {
+                Some([]) | None => 0,
+                Some(["scroll", n]) => n.parse::<u32>().map_err(|_| invalid())?,
+                Some(_) => return Err(invalid().into()),
+            };
+            entries.push((slide, duration, scrolls));
+        }
+        entries.sort_by_key(|(slide, _, _)| *slide);
+
+        let mut steps = Vec::new();
+        let mut offset = Duration::ZERO;
+        for (slide, duration, scrolls) in entries {
+            steps.push((offset, Action::GotoSlide(slide)));
+            for step in 1..=scrolls {
+                steps.push((offset + duration * step / (scrolls + 1), Action::ScrollDown));
+            }
+            offset += duration;
+        }
+        Ok(Script::new(steps))
+    }
+
+    /// Time remaining until the next action is due, or `None` once the script is exhausted.
+    pub fn time_until_next(&self) -> Option<Duration> {
+        self.steps
+            .get(self.next)
+            .map(|(offset, _)| offset.saturating_sub(self.start.elapsed()))
+    }
+
+    /// Returns the next action if its time has come.
+    pub fn next_due(&mut self) -> Option<Action> {
+        let (offset, action) = *self.steps.get(self.next)?;
+        if self.start.elapsed() >= offset {
+            self.next += 1;
+            Some(action)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Spell-checking the prose of a deck, for `presentrs check --spelling`.
+//!
+//! Words are looked up in a dictionary, a plain list of words one per line
+//! such as `/usr/share/dict/words` or the words of a hunspell `.dic` file.
+//! The config file can point at another dictionary and list words to accept
+//! in every deck:
+//!
+//! ```toml
+//! [spelling]
+//! dictionary = "/usr/share/dict/british-english"
+//! words = ["ratatui", "syntect"]
+//! ```
+//!
+//! Words only one deck uses go in a file next to it, one per line, so
+//! `talk.md` accepts the words in `talk.words`. Code blocks, inline code,
+//! HTML comments, and link targets are not checked, and neither are words
+//! with digits or capitals after their first letter, such as `HTTP2` or
+//! `GitHub`, which are rarely in a dictionary.
+
+use crate::check::{self, Finding};
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use serde::Deserialize;
+use std::{
+    collections::HashSet,
+    fs,
+    io::ErrorKind,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// Dictionary used when the config file does not name one.
+const DEFAULT_DICTIONARY: &str = "/usr/share/dict/words";
+
+/// The `[spelling]` table of the config file.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpellingConfig {
+    /// Word list to check against, instead of the system's
+    pub dictionary: Option<PathBuf>,
+    /// Words accepted in every deck
+    pub words: Vec<String>,
+}
+
+/// Words known to be spelled correctly.
+pub struct Dictionary(HashSet<String>);
+
+impl Dictionary {
+    /// Loads the dictionary named in `config`, together with its accepted
+    /// words and the allowlist of `deck`.
+    pub fn load(config: &SpellingConfig, deck: &Path) -> Result<Self, String> {
+        let path = config
+            .dictionary
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_DICTIONARY));
+        let text = fs::read_to_string(&path).map_err(|e| {
+            format!(
+                "Failed to read dictionary '{}': {} (set `dictionary` under [spelling] in the config file)",
+                path.display(),
+                e
+            )
+        })?;
+        let mut words: HashSet<String> = text
+            .lines()
+            // Hunspell dictionaries add flags after a slash.
+            .map(|line| line.split('/').next().unwrap_or(line).trim())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        words.extend(config.words.iter().map(|word| word.to_lowercase()));
+        let allowlist = allowlist_path(deck);
+        match fs::read_to_string(&allowlist) {
+            Ok(text) => words.extend(
+                text.lines()
+                    .map(str::trim)
+                    .filter(|word| !word.is_empty())
+                    .map(str::to_lowercase),
+            ),
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(format!(
+                    "Failed to read allowlist '{}': {}",
+                    allowlist.display(),
+                    e
+                ));
+            }
+        }
+        Ok(Dictionary(words))
+    }
+
+    /// Whether `word` is spelled correctly, ignoring case and a possessive
+    /// `'s`.
+    fn knows(&self, word: &str) -> bool {
+        let word = word.to_lowercase().replace('’', "'");
+        self.0.contains(&word)
+            || word
+                .strip_suffix("'s")
+                .is_some_and(|stem| self.0.contains(stem))
+    }
+}
+
+/// Finds the words of `markdown` that are not in `dictionary`. `slides`
+/// holds the byte range of each slide within `markdown`.
+pub fn check(markdown: &str, slides: &[Range<usize>], dictionary: &Dictionary) -> Vec<Finding> {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    let mut misspellings = Vec::new();
+    let mut skipping = 0;
+    for (event, range) in Parser::new_ext(markdown, options).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_) | Tag::MetadataBlock(_)) => skipping += 1,
+            Event::End(TagEnd::CodeBlock | TagEnd::MetadataBlock(_)) => skipping -= 1,
+            Event::Text(text) if skipping == 0 => {
+                let Some(slide) = slides.iter().position(|slide| slide.contains(&range.start))
+                else {
+                    continue;
+                };
+                let line = check::line_of(markdown, range.start);
+                for word in words(&text) {
+                    if is_checked(word) && !dictionary.knows(word) {
+                        misspellings.push(Finding {
+                            rule: "spelling",
+                            slide: slide + 1,
+                            line: Some(line),
+                            message: format!("unknown word '{word}'"),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    misspellings
+}
+
+/// Location of the allowlist of `deck`: `talk.md` has `talk.words`.
+fn allowlist_path(deck: &Path) -> PathBuf {
+    deck.with_extension("words")
+}
+
+/// Splits `text` into words: runs of letters, with apostrophes inside them.
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'' && c != '’')
+        .map(|word| word.trim_matches(['\'', '’']))
+        .filter(|word| !word.is_empty())
+}
+
+/// Whether `word` is one a dictionary could be expected to have.
+fn is_checked(word: &str) -> bool {
+    !word.chars().any(|c| c.is_numeric()) && !word.chars().skip(1).any(char::is_uppercase)
+}
@@ -0,0 +1,83 @@
+//! Splitting a deck into a file per slide.
+//!
+//! `presentrs split talk.md parts/` writes each slide of `talk.md` to a
+//! numbered file of its own, such as `parts/03-ownership.md`, for taking a
+//! long deck apart to reorganize it. Every file starts with the deck's front
+//! matter, so it presents the way it did in the deck, and notes for the slide
+//! from the companion file `talk.notes.md` are moved into the slide as a
+//! `notes` comment, so none are lost.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// One slide of the deck, as written to its own file.
+pub struct Part {
+    /// Title of the slide, which names the file
+    pub title: Option<String>,
+    /// Markdown of the slide, as written in the deck
+    pub source: String,
+    /// Notes for the slide from the companion file
+    pub notes: Option<String>,
+}
+
+/// Name of the file of an untitled slide.
+const UNTITLED: &str = "slide";
+
+/// Most words of a slide's title used in its file's name.
+const SLUG_WORDS: usize = 6;
+
+/// Writes each of `parts` to a numbered file in `dir`, after `front_matter`,
+/// and returns the paths of the files written. Nothing is written if any of
+/// them already exists.
+pub fn write(dir: &Path, front_matter: &str, parts: &[Part]) -> Result<Vec<PathBuf>, String> {
+    // Numbers are padded so that the files sort in deck order.
+    let digits = parts.len().to_string().len().max(2);
+    let paths: Vec<PathBuf> = parts
+        .iter()
+        .enumerate()
+        .map(|(index, part)| {
+            let name = part.title.as_deref().map(slug).filter(|s| !s.is_empty());
+            let name = name.as_deref().unwrap_or(UNTITLED);
+            dir.join(format!("{:0digits$}-{name}.md", index + 1))
+        })
+        .collect();
+    if let Some(path) = paths.iter().find(|path| path.exists()) {
+        return Err(format!(
+            "Failed to split into '{}': '{}' already exists",
+            dir.display(),
+            path.display()
+        ));
+    }
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    for (part, path) in parts.iter().zip(&paths) {
+        let mut text = String::new();
+        if !front_matter.trim().is_empty() {
+            text.push_str(front_matter.trim_end());
+            text.push_str("\n\n");
+        }
+        text.push_str(part.source.trim());
+        text.push('\n');
+        if let Some(notes) = &part.notes {
+            text.push_str(&format!(
+                "\n<!-- notes: {} -->\n",
+                notes.replace("-->", "-- >")
+            ));
+        }
+        fs::write(path, text)
+            .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+    }
+    Ok(paths)
+}
+
+/// Lowercase words of `title` joined by dashes, as in `why-rust`.
+fn slug(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .take(SLUG_WORDS)
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
@@ -0,0 +1,51 @@
+//! Answers hidden on quiz and training slides.
+//!
+//! Text between `||` markers, as in `The answer is ||42||.`, is drawn as a
+//! redacted block (`▓▓`) until the presenter presses `r`. The answer is kept
+//! in the slide's lines, marked with the `HIDDEN` modifier, and only
+//! redacted when the slide is drawn.
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+};
+use unicode_width::UnicodeWidthStr;
+
+/// Marker written before and after a spoiler.
+pub const MARKER: &str = "||";
+
+/// Character a redacted spoiler is drawn with.
+const REDACTED: &str = "▓";
+
+/// Returns `style` marked as part of a spoiler.
+pub fn mark(style: Style) -> Style {
+    style.add_modifier(Modifier::HIDDEN)
+}
+
+/// Returns true if `span` is part of a spoiler.
+fn is_spoiler(span: &Span) -> bool {
+    span.style.add_modifier.contains(Modifier::HIDDEN)
+}
+
+/// Returns true if `text` has any spoilers.
+pub fn any(text: &Text) -> bool {
+    text.lines
+        .iter()
+        .flat_map(|line| &line.spans)
+        .any(is_spoiler)
+}
+
+/// Draws the spoilers in `line`, redacted unless `revealed` is true.
+pub fn render(line: &mut Line<'static>, revealed: bool) {
+    for span in line.spans.iter_mut().filter(|span| is_spoiler(span)) {
+        if revealed {
+            span.style = span
+                .style
+                .remove_modifier(Modifier::HIDDEN)
+                .add_modifier(Modifier::UNDERLINED);
+        } else {
+            span.content = REDACTED.repeat(span.content.width()).into();
+            span.style = Style::default().fg(Color::DarkGray);
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Persistent per-deck state kept between presentations.
+//!
+//! Each deck gets its own directory under `$XDG_STATE_HOME/presentrs` (or
+//! `~/.local/state/presentrs`), named after the file and a hash of its
+//! absolute path so decks with the same name in different folders stay apart.
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+/// Returns the state directory for `deck`. The directory is not created.
+pub fn deck_dir(deck: &Path) -> PathBuf {
+    let base = env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))
+        .unwrap_or_else(env::temp_dir)
+        .join("presentrs");
+
+    let absolute = deck.canonicalize().unwrap_or_else(|_| deck.to_path_buf());
+    let stem = deck
+        .file_stem()
+        .map_or("deck".into(), |stem| stem.to_string_lossy());
+    base.join(format!(
+        "{}-{:016x}",
+        stem,
+        fnv1a(absolute.to_string_lossy().as_bytes())
+    ))
+}
+
+/// 64-bit FNV-1a, used because it is stable across Rust versions (unlike
+/// `DefaultHasher`), so the same deck always maps to the same directory.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
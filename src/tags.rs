@@ -0,0 +1,38 @@
+//! Presenting part of a deck, picked by the tags on its slides.
+//!
+//! Slides are tagged with `<!-- tags: advanced, demo -->`. `--tags advanced`
+//! presents only the slides with one of the given tags, and `--skip-tags demo`
+//! leaves out those with one of the given tags, so one deck can hold both the
+//! short and the long version of a talk. Slides left out are gone as far as
+//! the presentation is concerned: they are not counted in slide numbers or
+//! shown in the overview.
+
+/// Which slides to present, by their tags.
+#[derive(Clone, Default)]
+pub struct TagFilter {
+    /// Tags a slide must have one of, or empty to keep every slide
+    only: Vec<String>,
+    /// Tags of slides to leave out
+    skip: Vec<String>,
+}
+
+impl TagFilter {
+    pub fn new(only: Vec<String>, skip: Vec<String>) -> Self {
+        TagFilter { only, skip }
+    }
+
+    /// Whether the filter leaves out any slide at all.
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.skip.is_empty()
+    }
+
+    /// Whether a slide tagged with `tags` is presented. Tags are compared
+    /// without regard to case.
+    pub fn keeps(&self, tags: &[String]) -> bool {
+        let tagged = |wanted: &[String]| {
+            tags.iter()
+                .any(|tag| wanted.iter().any(|w| w.eq_ignore_ascii_case(tag)))
+        };
+        (self.only.is_empty() || tagged(&self.only)) && !tagged(&self.skip)
+    }
+}
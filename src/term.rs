@@ -0,0 +1,561 @@
+//! The terminal presentrs runs in, over whichever library the build talks to
+//! it with.
+//!
+//! Everything else draws through ratatui and reads input as the [`Event`]s
+//! defined here, so this module is the only one that knows the library. It
+//! is crossterm by default. Builds for terminals or platforms where crossterm
+//! misbehaves can use termion (Unix only) or termwiz instead:
+//!
+//! ```bash
+//! cargo build --release --no-default-features --features termion
+//! ```
+//!
+//! Exactly one of the `crossterm`, `termion`, and `termwiz` features must be
+//! enabled.
+
+#[cfg(any(
+    all(feature = "crossterm", feature = "termion"),
+    all(feature = "crossterm", feature = "termwiz"),
+    all(feature = "termion", feature = "termwiz"),
+    not(any(feature = "crossterm", feature = "termion", feature = "termwiz")),
+))]
+compile_error!("enable exactly one of the crossterm, termion, and termwiz features");
+
+#[cfg(feature = "crossterm")]
+pub use self::crossterm_backend::{Backend, enter, leave, next_event, size};
+#[cfg(feature = "termion")]
+pub use self::termion_backend::{Backend, enter, leave, next_event, size};
+#[cfg(feature = "termwiz")]
+pub use self::termwiz_backend::{Backend, enter, leave, next_event, size};
+
+/// Something that happened at the terminal.
+pub enum Event {
+    /// A key was pressed
+    Key(KeyEvent),
+    /// The mouse was used
+    Mouse(MouseEvent),
+    /// The terminal now has this many columns and rows
+    Resize(u16, u16),
+    /// The terminal gained (`true`) or lost (`false`) the keyboard focus.
+    /// termwiz does not report focus changes, so there is no such event with
+    /// it.
+    #[cfg(not(feature = "termwiz"))]
+    Focus(bool),
+    /// Anything else, such as pasted text
+    Other,
+}
+
+/// A key press.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct KeyEvent {
+    /// The key
+    pub code: KeyCode,
+    /// Modifier keys held down with it
+    pub modifiers: KeyModifiers,
+}
+
+/// A key on the keyboard.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum KeyCode {
+    Char(char),
+    Enter,
+    Esc,
+    Backspace,
+    Tab,
+    /// Shift-Tab
+    BackTab,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+    /// A function key, by number
+    F(u8),
+    /// A key the library reports that presentrs has no use for
+    Other,
+}
+
+/// Modifier keys held down with a key, as a set.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyModifiers(u8);
+
+impl KeyModifiers {
+    pub const NONE: Self = KeyModifiers(0);
+    pub const CONTROL: Self = KeyModifiers(1);
+    pub const ALT: Self = KeyModifiers(2);
+
+    /// Returns true if every modifier in `other` is held.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for KeyModifiers {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        KeyModifiers(self.0 | other.0)
+    }
+}
+
+/// A use of the mouse, at a cell counted from 0 at the top left.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct MouseEvent {
+    /// What the mouse did
+    pub kind: MouseEventKind,
+    /// Column of the cell under the pointer
+    pub column: u16,
+    /// Row of the cell under the pointer
+    pub row: u16,
+}
+
+/// What the mouse did.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MouseEventKind {
+    /// A button was pressed
+    Down(MouseButton),
+    /// The wheel was turned away from the user
+    ScrollUp,
+    /// The wheel was turned towards the user
+    ScrollDown,
+    /// A release, a move, or a drag
+    Other,
+}
+
+/// A mouse button.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+#[cfg(feature = "crossterm")]
+mod crossterm_backend {
+    use super::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    use crossterm::{
+        event::{
+            self, DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+            KeyEventKind,
+        },
+        execute,
+        terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    };
+    use ratatui::{Terminal, backend::CrosstermBackend};
+    use std::{
+        io::{self, Stdout},
+        time::Duration,
+    };
+
+    /// What ratatui draws to.
+    pub type Backend = CrosstermBackend<Stdout>;
+
+    /// Takes over the terminal: raw mode, the alternate screen, focus
+    /// reporting, and mouse reporting if `mouse` is true.
+    pub fn enter(mouse: bool) -> io::Result<Backend> {
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, EnableFocusChange)?;
+        if mouse {
+            execute!(stdout, EnableMouseCapture)?;
+        }
+        Ok(CrosstermBackend::new(stdout))
+    }
+
+    /// Hands the terminal back as it was before [`enter`].
+    pub fn leave(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+        disable_raw_mode()?;
+        execute!(
+            terminal.backend_mut(),
+            LeaveAlternateScreen,
+            DisableMouseCapture,
+            DisableFocusChange
+        )?;
+        terminal.show_cursor()
+    }
+
+    /// Waits up to `timeout`, or for as long as it takes without one, for
+    /// something to happen at the terminal.
+    pub fn next_event(
+        _terminal: &mut Terminal<Backend>,
+        timeout: Option<Duration>,
+    ) -> io::Result<Option<Event>> {
+        if let Some(timeout) = timeout
+            && !event::poll(timeout)?
+        {
+            return Ok(None);
+        }
+        Ok(Some(match event::read()? {
+            // Windows reports releases too, which would act twice.
+            event::Event::Key(key) if key.kind == KeyEventKind::Release => Event::Other,
+            event::Event::Key(key) => Event::Key(KeyEvent {
+                code: key_code(key.code),
+                modifiers: modifiers(key.modifiers),
+            }),
+            event::Event::Mouse(mouse) => Event::Mouse(MouseEvent {
+                kind: match mouse.kind {
+                    event::MouseEventKind::Down(button) => MouseEventKind::Down(match button {
+                        event::MouseButton::Left => MouseButton::Left,
+                        event::MouseButton::Right => MouseButton::Right,
+                        event::MouseButton::Middle => MouseButton::Middle,
+                    }),
+                    event::MouseEventKind::ScrollUp => MouseEventKind::ScrollUp,
+                    event::MouseEventKind::ScrollDown => MouseEventKind::ScrollDown,
+                    _ => MouseEventKind::Other,
+                },
+                column: mouse.column,
+                row: mouse.row,
+            }),
+            event::Event::Resize(width, height) => Event::Resize(width, height),
+            event::Event::FocusGained => Event::Focus(true),
+            event::Event::FocusLost => Event::Focus(false),
+            _ => Event::Other,
+        }))
+    }
+
+    /// Returns the terminal's width and height in cells.
+    pub fn size() -> io::Result<(u16, u16)> {
+        crossterm::terminal::size()
+    }
+
+    fn key_code(code: event::KeyCode) -> KeyCode {
+        match code {
+            event::KeyCode::Char(c) => KeyCode::Char(c),
+            event::KeyCode::Enter => KeyCode::Enter,
+            event::KeyCode::Esc => KeyCode::Esc,
+            event::KeyCode::Backspace => KeyCode::Backspace,
+            event::KeyCode::Tab => KeyCode::Tab,
+            event::KeyCode::BackTab => KeyCode::BackTab,
+            event::KeyCode::Left => KeyCode::Left,
+            event::KeyCode::Right => KeyCode::Right,
+            event::KeyCode::Up => KeyCode::Up,
+            event::KeyCode::Down => KeyCode::Down,
+            event::KeyCode::Home => KeyCode::Home,
+            event::KeyCode::End => KeyCode::End,
+            event::KeyCode::PageUp => KeyCode::PageUp,
+            event::KeyCode::PageDown => KeyCode::PageDown,
+            event::KeyCode::Insert => KeyCode::Insert,
+            event::KeyCode::Delete => KeyCode::Delete,
+            event::KeyCode::F(n) => KeyCode::F(n),
+            _ => KeyCode::Other,
+        }
+    }
+
+    fn modifiers(held: event::KeyModifiers) -> KeyModifiers {
+        let mut modifiers = KeyModifiers::NONE;
+        if held.contains(event::KeyModifiers::CONTROL) {
+            modifiers = modifiers | KeyModifiers::CONTROL;
+        }
+        if held.contains(event::KeyModifiers::ALT) {
+            modifiers = modifiers | KeyModifiers::ALT;
+        }
+        modifiers
+    }
+}
+
+#[cfg(feature = "termion")]
+mod termion_backend {
+    use super::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    use ratatui::{Terminal, backend::TermionBackend};
+    use std::{
+        io::{self, Stdout, Write},
+        sync::{
+            Mutex, OnceLock,
+            mpsc::{self, Receiver, RecvTimeoutError},
+        },
+        thread,
+        time::Duration,
+    };
+    use termion::{
+        event,
+        input::TermRead,
+        raw::{IntoRawMode, RawTerminal},
+        screen::{ToAlternateScreen, ToMainScreen},
+    };
+
+    /// What ratatui draws to.
+    pub type Backend = TermionBackend<Stdout>;
+
+    /// Turns on reporting of mouse presses, drags, and the wheel, in the
+    /// forms termion parses.
+    const MOUSE_ON: &str = "\x1b[?1000h\x1b[?1002h\x1b[?1015h\x1b[?1006h";
+
+    /// Turns mouse reporting off again.
+    const MOUSE_OFF: &str = "\x1b[?1006l\x1b[?1015l\x1b[?1002l\x1b[?1000l";
+
+    /// Turns on reporting of focus changes, which termion passes on as
+    /// unsupported events.
+    const FOCUS_ON: &str = "\x1b[?1004h";
+
+    /// Turns reporting of focus changes off again.
+    const FOCUS_OFF: &str = "\x1b[?1004l";
+
+    /// How often the terminal's size is checked, since termion does not
+    /// report resizes.
+    const RESIZE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Keeps the terminal in raw mode until it is dropped. ratatui only
+    /// lends out the writer it draws with behind an unstable feature, so
+    /// this is held apart from it.
+    static RAW_MODE: Mutex<Option<RawTerminal<Stdout>>> = Mutex::new(None);
+
+    /// Input read by the threads that wait on the terminal.
+    static EVENTS: OnceLock<Mutex<Receiver<Event>>> = OnceLock::new();
+
+    /// Takes over the terminal: raw mode, the alternate screen, focus
+    /// reporting, and mouse reporting if `mouse` is true.
+    pub fn enter(mouse: bool) -> io::Result<Backend> {
+        let raw = io::stdout().into_raw_mode()?;
+        *RAW_MODE
+            .lock()
+            .map_err(|_| io::Error::other("raw mode lock poisoned"))? = Some(raw);
+        let mut stdout = io::stdout();
+        write!(stdout, "{ToAlternateScreen}{FOCUS_ON}")?;
+        if mouse {
+            write!(stdout, "{MOUSE_ON}")?;
+        }
+        stdout.flush()?;
+        Ok(TermionBackend::new(stdout))
+    }
+
+    /// Hands the terminal back as it was before [`enter`].
+    pub fn leave(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+        let backend = terminal.backend_mut();
+        write!(backend, "{MOUSE_OFF}{FOCUS_OFF}{ToMainScreen}")?;
+        backend.flush()?;
+        // Dropping the raw terminal restores the modes it found.
+        RAW_MODE
+            .lock()
+            .map_err(|_| io::Error::other("raw mode lock poisoned"))?
+            .take();
+        terminal.show_cursor()
+    }
+
+    /// Waits up to `timeout`, or for as long as it takes without one, for
+    /// something to happen at the terminal.
+    pub fn next_event(
+        _terminal: &mut Terminal<Backend>,
+        timeout: Option<Duration>,
+    ) -> io::Result<Option<Event>> {
+        let events = EVENTS
+            .get_or_init(|| Mutex::new(listen()))
+            .lock()
+            .map_err(|_| io::Error::other("terminal input thread panicked"))?;
+        let gone = || io::Error::new(io::ErrorKind::UnexpectedEof, "terminal input closed");
+        match timeout {
+            Some(timeout) => match events.recv_timeout(timeout) {
+                Ok(event) => Ok(Some(event)),
+                Err(RecvTimeoutError::Timeout) => Ok(None),
+                Err(RecvTimeoutError::Disconnected) => Err(gone()),
+            },
+            None => events.recv().map(Some).map_err(|_| gone()),
+        }
+    }
+
+    /// Returns the terminal's width and height in cells.
+    pub fn size() -> io::Result<(u16, u16)> {
+        termion::terminal_size()
+    }
+
+    /// Starts the threads that read input and watch for resizes.
+    fn listen() -> Receiver<Event> {
+        let (sender, receiver) = mpsc::channel();
+        let input = sender.clone();
+        thread::spawn(move || {
+            for event in io::stdin().events().flatten() {
+                if input.send(convert(event)).is_err() {
+                    break;
+                }
+            }
+        });
+        thread::spawn(move || {
+            let mut last = size().ok();
+            loop {
+                thread::sleep(RESIZE_POLL_INTERVAL);
+                let now = size().ok();
+                if now != last
+                    && let Some((width, height)) = now
+                    && sender.send(Event::Resize(width, height)).is_err()
+                {
+                    break;
+                }
+                last = now;
+            }
+        });
+        receiver
+    }
+
+    fn convert(event: event::Event) -> Event {
+        let key = |code, modifiers| Event::Key(KeyEvent { code, modifiers });
+        match event {
+            event::Event::Key(event::Key::Char('\n')) => key(KeyCode::Enter, KeyModifiers::NONE),
+            event::Event::Key(event::Key::Char('\t')) => key(KeyCode::Tab, KeyModifiers::NONE),
+            event::Event::Key(event::Key::Char(c)) => key(KeyCode::Char(c), KeyModifiers::NONE),
+            event::Event::Key(event::Key::Ctrl(c)) => key(KeyCode::Char(c), KeyModifiers::CONTROL),
+            event::Event::Key(event::Key::Alt(c)) => key(KeyCode::Char(c), KeyModifiers::ALT),
+            event::Event::Key(code) => key(
+                match code {
+                    event::Key::Esc => KeyCode::Esc,
+                    event::Key::Backspace => KeyCode::Backspace,
+                    event::Key::BackTab => KeyCode::BackTab,
+                    event::Key::Left => KeyCode::Left,
+                    event::Key::Right => KeyCode::Right,
+                    event::Key::Up => KeyCode::Up,
+                    event::Key::Down => KeyCode::Down,
+                    event::Key::Home => KeyCode::Home,
+                    event::Key::End => KeyCode::End,
+                    event::Key::PageUp => KeyCode::PageUp,
+                    event::Key::PageDown => KeyCode::PageDown,
+                    event::Key::Insert => KeyCode::Insert,
+                    event::Key::Delete => KeyCode::Delete,
+                    event::Key::F(n) => KeyCode::F(n),
+                    _ => KeyCode::Other,
+                },
+                KeyModifiers::NONE,
+            ),
+            // termion counts cells from 1.
+            event::Event::Mouse(event::MouseEvent::Press(button, x, y)) => {
+                Event::Mouse(MouseEvent {
+                    kind: match button {
+                        event::MouseButton::Left => MouseEventKind::Down(MouseButton::Left),
+                        event::MouseButton::Right => MouseEventKind::Down(MouseButton::Right),
+                        event::MouseButton::Middle => MouseEventKind::Down(MouseButton::Middle),
+                        event::MouseButton::WheelUp => MouseEventKind::ScrollUp,
+                        event::MouseButton::WheelDown => MouseEventKind::ScrollDown,
+                        _ => MouseEventKind::Other,
+                    },
+                    column: x.saturating_sub(1),
+                    row: y.saturating_sub(1),
+                })
+            }
+            event::Event::Unsupported(bytes) if bytes == b"\x1b[I" => Event::Focus(true),
+            event::Event::Unsupported(bytes) if bytes == b"\x1b[O" => Event::Focus(false),
+            _ => Event::Other,
+        }
+    }
+}
+
+#[cfg(feature = "termwiz")]
+mod termwiz_backend {
+    use super::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    use ratatui::{Terminal, backend::TermwizBackend};
+    use std::{io, time::Duration};
+    use termwiz::{
+        caps::Capabilities,
+        input::{self, InputEvent, Modifiers, MouseButtons},
+        terminal::{Terminal as _, new_terminal},
+    };
+
+    /// What ratatui draws to.
+    pub type Backend = TermwizBackend;
+
+    /// Takes over the terminal: raw mode, the alternate screen, and mouse
+    /// reporting, which termwiz always turns on with raw mode.
+    pub fn enter(_mouse: bool) -> io::Result<Backend> {
+        TermwizBackend::new().map_err(other)
+    }
+
+    /// Hands the terminal back as it was before [`enter`].
+    pub fn leave(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+        let system = terminal.backend_mut().buffered_terminal_mut().terminal();
+        system.exit_alternate_screen().map_err(other)?;
+        system.set_cooked_mode().map_err(other)?;
+        terminal.show_cursor()
+    }
+
+    /// Waits up to `timeout`, or for as long as it takes without one, for
+    /// something to happen at the terminal.
+    pub fn next_event(
+        terminal: &mut Terminal<Backend>,
+        timeout: Option<Duration>,
+    ) -> io::Result<Option<Event>> {
+        let buffered = terminal.backend_mut().buffered_terminal_mut();
+        let Some(event) = buffered.terminal().poll_input(timeout).map_err(other)? else {
+            return Ok(None);
+        };
+        Ok(Some(match event {
+            InputEvent::Key(key) => Event::Key(KeyEvent {
+                code: match key.key {
+                    input::KeyCode::Char(c) => KeyCode::Char(c),
+                    input::KeyCode::Enter => KeyCode::Enter,
+                    input::KeyCode::Escape => KeyCode::Esc,
+                    input::KeyCode::Backspace => KeyCode::Backspace,
+                    input::KeyCode::Tab if key.modifiers.contains(Modifiers::SHIFT) => {
+                        KeyCode::BackTab
+                    }
+                    input::KeyCode::Tab => KeyCode::Tab,
+                    input::KeyCode::LeftArrow => KeyCode::Left,
+                    input::KeyCode::RightArrow => KeyCode::Right,
+                    input::KeyCode::UpArrow => KeyCode::Up,
+                    input::KeyCode::DownArrow => KeyCode::Down,
+                    input::KeyCode::Home => KeyCode::Home,
+                    input::KeyCode::End => KeyCode::End,
+                    input::KeyCode::PageUp => KeyCode::PageUp,
+                    input::KeyCode::PageDown => KeyCode::PageDown,
+                    input::KeyCode::Insert => KeyCode::Insert,
+                    input::KeyCode::Delete => KeyCode::Delete,
+                    input::KeyCode::Function(n) => KeyCode::F(n),
+                    _ => KeyCode::Other,
+                },
+                modifiers: modifiers(key.modifiers),
+            }),
+            // termwiz counts cells from 1, and reports the buttons held on
+            // every move, not presses.
+            InputEvent::Mouse(mouse) => Event::Mouse(MouseEvent {
+                kind: if mouse.mouse_buttons.contains(MouseButtons::LEFT) {
+                    MouseEventKind::Down(MouseButton::Left)
+                } else if mouse.mouse_buttons.contains(MouseButtons::RIGHT) {
+                    MouseEventKind::Down(MouseButton::Right)
+                } else if mouse.mouse_buttons.contains(MouseButtons::MIDDLE) {
+                    MouseEventKind::Down(MouseButton::Middle)
+                } else if mouse.mouse_buttons.contains(MouseButtons::VERT_WHEEL) {
+                    if mouse.mouse_buttons.contains(MouseButtons::WHEEL_POSITIVE) {
+                        MouseEventKind::ScrollUp
+                    } else {
+                        MouseEventKind::ScrollDown
+                    }
+                } else {
+                    MouseEventKind::Other
+                },
+                column: mouse.x.saturating_sub(1),
+                row: mouse.y.saturating_sub(1),
+            }),
+            InputEvent::Resized { cols, rows } => {
+                // The buffer ratatui draws into only learns the size from here.
+                buffered.resize(cols, rows);
+                Event::Resize(cols as u16, rows as u16)
+            }
+            _ => Event::Other,
+        }))
+    }
+
+    /// Returns the terminal's width and height in cells.
+    pub fn size() -> io::Result<(u16, u16)> {
+        let caps = Capabilities::new_from_env().map_err(other)?;
+        let size = new_terminal(caps)
+            .and_then(|mut terminal| terminal.get_screen_size())
+            .map_err(other)?;
+        Ok((size.cols as u16, size.rows as u16))
+    }
+
+    /// Wraps an error from termwiz, which need not be `Send`.
+    fn other(error: impl ToString) -> io::Error {
+        io::Error::other(error.to_string())
+    }
+
+    fn modifiers(held: Modifiers) -> KeyModifiers {
+        let mut modifiers = KeyModifiers::NONE;
+        if held.contains(Modifiers::CTRL) {
+            modifiers = modifiers | KeyModifiers::CONTROL;
+        }
+        if held.contains(Modifiers::ALT) {
+            modifiers = modifiers | KeyModifiers::ALT;
+        }
+        modifiers
+    }
+}
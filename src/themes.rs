@@ -0,0 +1,534 @@
+//! Color themes.
+//!
+//! A theme sets the colors slides are drawn in and the theme code blocks are
+//! highlighted with. A deck starts in the theme picked with `--theme` or the
+//! config file's `theme` key, and `c` / `C` switch to the next / previous
+//! theme while presenting, for when a projector washes the colors out:
+//!
+//! | Theme | Look |
+//! |-------|------|
+//! | `dark` | Bright text on the terminal's own background (the default) |
+//! | `light` | Dark text on white |
+//! | `high-contrast` | Vivid text on black |
+//!
+//! A slide can pick a theme of its own with `<!-- theme: light -->`, leaving
+//! the rest of the deck as it is. `<!-- theme: inverted -->` swaps the deck's
+//! text and background colors, which suits section breaks.
+//!
+//! `presentrs theme export light brand.toml` writes a theme to a file, which
+//! can be edited and shared. `--theme brand.toml` presents in a theme from a
+//! file, and files in the `themes` directory next to the config file are
+//! available by name like the built-in ones:
+//!
+//! ```toml
+//! version = 1
+//! name = "brand"
+//! background = "#ffffff"
+//! text = "#1e1e1e"
+//! primary = "#005aaa"
+//! secondary = "#782896"
+//! tertiary = "#00733c"
+//! accent = "#af5500"
+//! muted = "#8c8c8c"
+//! border = "#969696"
+//! code = "#a51e3c"
+//! code_background = "#ebebeb"
+//! code_theme = "InspiredGitHub"
+//! ```
+//!
+//! Colors are names such as `dark-gray` or hex codes. Leaving out
+//! `background` keeps the terminal's own. Two more keys set the shapes the
+//! theme draws with, and may be left out: `bullets` lists the markers of
+//! list items, one for each level of nesting (`["•"]` by default), and
+//! `borders` is `plain` (the default), `rounded`, `double`, or `thick`.
+//!
+//! Base16 schemes (`.yaml` files, see [`crate::base16`]) are loaded the same
+//! way, named after their files.
+
+use crate::{base16, config};
+use ratatui::{style::Color, widgets::BorderType};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+use syntect::highlighting::ThemeSet;
+
+/// Slide `presentrs themes` draws in each theme, touching every color.
+pub const SAMPLE: &str = "\
+# Sample Slide
+
+## Second-level heading
+
+Body text with **bold**, *italics*, `inline code`, and a [link](https://example.com).[^1]
+
+- A list item
+- Another list item
+
+### Third-level heading
+
+```rust
+fn main() {
+    println!(\"Hello, world!\");
+}
+```
+
+[^1]: A footnote.
+";
+
+/// Name that picks the deck's theme with its colors swapped.
+const INVERTED: &str = "inverted";
+
+/// Version of the theme file format, written as `version` in every file.
+const FORMAT_VERSION: u32 = 1;
+
+/// Colors a deck is drawn in.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Theme {
+    /// Name the theme is picked by; a theme file without one is named
+    /// after the file
+    #[serde(default)]
+    pub name: String,
+    /// Background of the whole screen, or `None` for the terminal's own
+    #[serde(default, with = "optional_color", skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
+    /// Body text
+    #[serde(with = "color")]
+    pub text: Color,
+    /// Top-level headings, footnote references, and the breadcrumb trail
+    #[serde(with = "color")]
+    pub primary: Color,
+    /// Second-level headings and links
+    #[serde(with = "color")]
+    pub secondary: Color,
+    /// Third-level headings
+    #[serde(with = "color")]
+    pub tertiary: Color,
+    /// Deeper headings, list markers, and the status bar
+    #[serde(with = "color")]
+    pub accent: Color,
+    /// Rules, footnote markers, and other text kept in the background
+    #[serde(with = "color")]
+    pub muted: Color,
+    /// Table borders
+    #[serde(with = "color")]
+    pub border: Color,
+    /// Inline code, and code blocks that are not highlighted
+    #[serde(with = "color")]
+    pub code: Color,
+    /// Background of inline code
+    #[serde(with = "color")]
+    pub code_background: Color,
+    /// Name of the syntect theme code blocks are highlighted with
+    pub code_theme: String,
+    /// Markers of list items, one for each level of nesting; deeper items
+    /// take the last
+    #[serde(default = "default_bullets")]
+    pub bullets: Vec<String>,
+    /// Lines the slide and the status bar are framed with
+    #[serde(default)]
+    pub borders: Borders,
+}
+
+/// Lines a frame is drawn with.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Borders {
+    /// Straight lines with square corners
+    #[default]
+    Plain,
+    /// Straight lines with rounded corners
+    Rounded,
+    /// Double lines
+    Double,
+    /// Heavy lines
+    Thick,
+}
+
+impl Borders {
+    /// The frame as drawn by ratatui.
+    pub fn border_type(self) -> BorderType {
+        match self {
+            Borders::Plain => BorderType::Plain,
+            Borders::Rounded => BorderType::Rounded,
+            Borders::Double => BorderType::Double,
+            Borders::Thick => BorderType::Thick,
+        }
+    }
+}
+
+/// Markers of list items in themes that do not set them.
+pub fn default_bullets() -> Vec<String> {
+    vec!["•".to_string()]
+}
+
+/// A theme as written to a file, with the format version first.
+#[derive(Serialize)]
+struct ThemeFile<'a> {
+    version: u32,
+    #[serde(flatten)]
+    theme: &'a Theme,
+}
+
+impl Theme {
+    /// Reads the theme file or base16 scheme at `path`, checking that its
+    /// code theme is one of `code_themes`.
+    fn load(path: &Path, code_themes: &ThemeSet) -> Result<Theme, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read theme '{}': {}", path.display(), e))?;
+        let scheme = is_scheme(path);
+        let theme = if scheme {
+            base16::parse(&text)
+        } else {
+            Theme::parse(&text, code_themes)
+        };
+        let mut theme = theme.map_err(|e| format!("Invalid theme '{}': {}", path.display(), e))?;
+        if scheme || theme.name.is_empty() {
+            theme.name = path
+                .file_stem()
+                .map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+        }
+        Ok(theme)
+    }
+
+    /// Parses the contents of a theme file.
+    fn parse(text: &str, code_themes: &ThemeSet) -> Result<Theme, String> {
+        let mut table: toml::Table = toml::from_str(text).map_err(|e| e.to_string())?;
+        match table.remove("version") {
+            Some(toml::Value::Integer(version)) if version == i64::from(FORMAT_VERSION) => {}
+            Some(version) => {
+                return Err(format!(
+                    "version {version} is not supported; this presentrs reads version \
+                     {FORMAT_VERSION} theme files"
+                ));
+            }
+            None => return Err(format!("missing `version = {FORMAT_VERSION}`")),
+        }
+        let theme = Theme::deserialize(toml::Value::Table(table))
+            .map_err(|e| e.to_string().trim_end().replace('\n', " "))?;
+        if theme.name == INVERTED {
+            return Err(format!("the name '{INVERTED}' is reserved"));
+        }
+        if !code_themes.themes.contains_key(&theme.code_theme) {
+            return Err(format!(
+                "unknown code_theme '{}' (available: {})",
+                theme.code_theme,
+                code_theme_names(code_themes).join(", ")
+            ));
+        }
+        Ok(theme)
+    }
+
+    /// Marker of a list item nested `depth` levels deep, from 0.
+    pub fn bullet(&self, depth: usize) -> &str {
+        self.bullets
+            .get(depth)
+            .or(self.bullets.last())
+            .map_or("•", String::as_str)
+    }
+
+    /// Writes the theme to `path` as a theme file.
+    pub fn export(&self, path: &Path) -> Result<(), String> {
+        let file = ThemeFile {
+            version: FORMAT_VERSION,
+            theme: self,
+        };
+        let text = toml::to_string(&file).map_err(|e| e.to_string())?;
+        fs::write(path, text)
+            .map_err(|e| format!("Failed to write theme '{}': {}", path.display(), e))
+    }
+
+    /// Returns the theme with its text and background colors swapped, and
+    /// all text in the one color, for slides that should stand out from the
+    /// rest of the deck.
+    fn inverted(&self) -> Theme {
+        let ink = self.background.unwrap_or(Color::Black);
+        Theme {
+            name: INVERTED.to_string(),
+            background: Some(self.text),
+            text: ink,
+            primary: ink,
+            secondary: ink,
+            tertiary: ink,
+            accent: ink,
+            muted: ink,
+            border: ink,
+            code: ink,
+            code_background: self.text,
+            code_theme: self.code_theme.clone(),
+            bullets: self.bullets.clone(),
+            borders: self.borders,
+        }
+    }
+}
+
+/// Themes a deck can be presented in, in the order `c` cycles through them.
+pub struct Themes(Vec<Theme>);
+
+impl Themes {
+    /// Loads the built-in themes, followed by those in the themes directory.
+    ///
+    /// A theme in the directory replaces a built-in one of the same name.
+    pub fn load() -> Result<Self, String> {
+        let mut themes = Themes::default();
+        let Some(dir) = config::themes_dir() else {
+            return Ok(themes);
+        };
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(themes),
+            Err(e) => {
+                return Err(format!(
+                    "Failed to read themes directory '{}': {}",
+                    dir.display(),
+                    e
+                ));
+            }
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| is_theme_file(path))
+            .collect();
+        if paths.is_empty() {
+            return Ok(themes);
+        }
+        paths.sort();
+        let code_themes = ThemeSet::load_defaults();
+        for path in paths {
+            themes.add(Theme::load(&path, &code_themes)?);
+        }
+        Ok(themes)
+    }
+
+    /// Returns the index of the theme called `name`, or of the theme loaded
+    /// from `name` if it is the path of a theme file or base16 scheme.
+    pub fn select(&mut self, name: &str) -> Result<usize, String> {
+        let path = Path::new(name);
+        if is_theme_file(path) {
+            let theme = Theme::load(path, &ThemeSet::load_defaults())?;
+            return Ok(self.add(theme));
+        }
+        self.find(name)
+            .ok_or_else(|| format!("Unknown theme '{}' (available: {})", name, self.names()))
+    }
+
+    /// Adds `theme`, replacing any theme of the same name, and returns its
+    /// index.
+    fn add(&mut self, theme: Theme) -> usize {
+        match self.find(&theme.name) {
+            Some(index) => {
+                self.0[index] = theme;
+                index
+            }
+            None => {
+                self.0.push(theme);
+                self.0.len() - 1
+            }
+        }
+    }
+
+    /// Returns the theme at `index`.
+    pub fn get(&self, index: usize) -> &Theme {
+        &self.0[index]
+    }
+
+    /// Index of the theme called `name`, if there is one.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.0.iter().position(|theme| theme.name == name)
+    }
+
+    /// Returns the theme a slide picking `name` is drawn in when the deck is
+    /// drawn in the theme at `current`, if there is one by that name.
+    pub fn resolve(&self, name: &str, current: usize) -> Option<Theme> {
+        if name == INVERTED {
+            return Some(self.get(current).inverted());
+        }
+        self.find(name).map(|index| self.get(index).clone())
+    }
+
+    /// Index of the theme after the one at `index`, or before it unless
+    /// `forward` is true, wrapping around at either end.
+    pub fn step(&self, index: usize, forward: bool) -> usize {
+        let len = self.0.len();
+        if forward {
+            (index + 1) % len
+        } else {
+            (index + len - 1) % len
+        }
+    }
+
+    /// Highlights the code blocks of every theme with the syntect theme
+    /// `name` from `code_themes`, whatever their own `code_theme`.
+    pub fn set_code_theme(&mut self, name: &str, code_themes: &ThemeSet) -> Result<(), String> {
+        if !code_themes.themes.contains_key(name) {
+            return Err(format!(
+                "Unknown code theme '{}' (available: {})",
+                name,
+                code_theme_names(code_themes).join(", ")
+            ));
+        }
+        for theme in &mut self.0 {
+            theme.code_theme = name.to_string();
+        }
+        Ok(())
+    }
+
+    /// Names of all the themes, separated by commas.
+    pub fn names(&self) -> String {
+        self.0
+            .iter()
+            .map(|theme| theme.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl Default for Themes {
+    fn default() -> Self {
+        Themes(vec![dark(), light(), high_contrast()])
+    }
+}
+
+/// Names of the syntect themes in `code_themes`, in alphabetical order.
+pub fn code_theme_names(code_themes: &ThemeSet) -> Vec<&str> {
+    let mut names: Vec<&str> = code_themes.themes.keys().map(String::as_str).collect();
+    names.sort_unstable();
+    names
+}
+
+/// Reads the TextMate theme at `path` into `code_themes`, named after the
+/// file, and returns its name.
+pub fn load_code_theme(path: &Path, code_themes: &mut ThemeSet) -> Result<String, String> {
+    let theme = ThemeSet::get_theme(path)
+        .map_err(|e| format!("Failed to load code theme '{}': {}", path.display(), e))?;
+    let name = path
+        .file_stem()
+        .map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+    code_themes.themes.insert(name.clone(), theme);
+    Ok(name)
+}
+
+/// The default theme, for dark terminals.
+fn dark() -> Theme {
+    Theme {
+        name: "dark".to_string(),
+        background: None,
+        text: Color::White,
+        primary: Color::Cyan,
+        secondary: Color::Blue,
+        tertiary: Color::Green,
+        accent: Color::Yellow,
+        muted: Color::DarkGray,
+        border: Color::Gray,
+        code: Color::Green,
+        code_background: Color::Rgb(40, 40, 40),
+        code_theme: "base16-ocean.dark".to_string(),
+        bullets: default_bullets(),
+        borders: Borders::Plain,
+    }
+}
+
+/// Dark text on white, which holds up on dim projectors.
+fn light() -> Theme {
+    Theme {
+        name: "light".to_string(),
+        background: Some(Color::Rgb(255, 255, 255)),
+        text: Color::Rgb(30, 30, 30),
+        primary: Color::Rgb(0, 90, 170),
+        secondary: Color::Rgb(120, 40, 150),
+        tertiary: Color::Rgb(0, 115, 60),
+        accent: Color::Rgb(175, 85, 0),
+        muted: Color::Rgb(140, 140, 140),
+        border: Color::Rgb(150, 150, 150),
+        code: Color::Rgb(165, 30, 60),
+        code_background: Color::Rgb(235, 235, 235),
+        code_theme: "InspiredGitHub".to_string(),
+        bullets: default_bullets(),
+        borders: Borders::Plain,
+    }
+}
+
+/// The brightest colors on black, for projectors that wash everything out.
+fn high_contrast() -> Theme {
+    Theme {
+        name: "high-contrast".to_string(),
+        background: Some(Color::Black),
+        text: Color::White,
+        primary: Color::LightYellow,
+        secondary: Color::LightCyan,
+        tertiary: Color::LightGreen,
+        accent: Color::LightMagenta,
+        muted: Color::Gray,
+        border: Color::White,
+        code: Color::LightGreen,
+        code_background: Color::Black,
+        code_theme: "base16-eighties.dark".to_string(),
+        bullets: default_bullets(),
+        borders: Borders::Plain,
+    }
+}
+
+/// Returns true if `path` names a theme file or a base16 scheme.
+fn is_theme_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "toml") || is_scheme(path)
+}
+
+/// Returns true if `path` names a base16 scheme.
+fn is_scheme(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml")
+}
+
+/// Reads and writes colors as names such as `dark-gray` or hex codes such as
+/// `#ff8800`.
+mod color {
+    use ratatui::style::Color;
+    use serde::{Deserialize, Deserializer, Serializer, de::Error as _};
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&name(*color))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Color::from_str(&name).map_err(|_| D::Error::custom(format!("unknown color '{name}'")))
+    }
+
+    /// Writes `color` the way theme files spell it, e.g. `light-magenta`.
+    fn name(color: Color) -> String {
+        let name = color.to_string();
+        let mut spelled = String::with_capacity(name.len() + 1);
+        for (i, c) in name.chars().enumerate() {
+            if i > 0 && c.is_ascii_uppercase() && !name.starts_with('#') {
+                spelled.push('-');
+            }
+            spelled.push(c.to_ascii_lowercase());
+        }
+        spelled
+    }
+}
+
+/// Reads and writes colors that may be left out, like [`color`].
+mod optional_color {
+    use ratatui::style::Color;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        color: &Option<Color>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match color {
+            Some(color) => super::color::serialize(color, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Color>, D::Error> {
+        super::color::deserialize(deserializer).map(Some)
+    }
+}
@@ -0,0 +1,71 @@
+//! A thin bar along the bottom of the screen that maps out the whole talk.
+//!
+//! Each slide is a segment of the bar as wide as its share of the talk's
+//! estimated length, so a long demo slide takes up more of the bar than a
+//! title card. Slides already presented, the current slide, and the slides
+//! still to come are drawn in different colors, which shows at a glance how
+//! much of the talk remains.
+//!
+//! A slide's length is estimated, in order of preference, from its
+//! `<!-- advance: ... -->` directive, from the latest run saved with
+//! `--rehearse` if the deck still has as many slides, or from the number of
+//! words on it.
+
+use crate::themes::Theme;
+use ratatui::{buffer::Buffer, layout::Rect, style::Style};
+use std::{cmp::Ordering, time::Duration};
+
+/// Speaking rate assumed for slides that were never rehearsed.
+const WORDS_PER_MINUTE: f64 = 130.0;
+
+/// Time assumed for introducing a slide, whatever is on it.
+const SECONDS_PER_SLIDE: f64 = 10.0;
+
+/// Estimates how long a slide with `words` words of content takes to
+/// present.
+pub fn estimate(words: usize) -> Duration {
+    Duration::from_secs_f64(SECONDS_PER_SLIDE + words as f64 * 60.0 / WORDS_PER_MINUTE)
+}
+
+/// Draws the bar for slides lasting `durations` into the first row of
+/// `area`, with slide `current` on screen.
+pub fn render(durations: &[Duration], current: usize, theme: &Theme, area: Rect, buf: &mut Buffer) {
+    let total: f64 = durations.iter().map(Duration::as_secs_f64).sum();
+    if area.is_empty() || total <= 0.0 {
+        return;
+    }
+    // Column of the bar where the slide that starts `elapsed` seconds in
+    // begins.
+    let column = |elapsed: f64| {
+        let x = (elapsed / total * f64::from(area.width)).round() as u16;
+        area.x + x.min(area.width)
+    };
+    let mut elapsed = 0.0;
+    for (slide, duration) in durations.iter().enumerate() {
+        let start = column(elapsed);
+        elapsed += duration.as_secs_f64();
+        let end = column(elapsed);
+        let color = match slide.cmp(&current) {
+            Ordering::Less => theme.accent,
+            Ordering::Equal => theme.primary,
+            Ordering::Greater => theme.muted,
+        };
+        // The current slide shows even when it is too short for a column.
+        let end = if slide == current && end == start && start < area.right() {
+            start + 1
+        } else {
+            end
+        };
+        for x in start..end {
+            // A thinner first cell sets each slide apart from the one before.
+            let symbol = if x == start && end - start > 1 {
+                "╺"
+            } else {
+                "━"
+            };
+            buf[(x, area.y)]
+                .set_symbol(symbol)
+                .set_style(Style::default().fg(color));
+        }
+    }
+}
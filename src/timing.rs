@@ -0,0 +1,395 @@
+//! Rehearsal timing: how long each slide was on screen during a run.
+//!
+//! The [`SlideTimer`] is fed every slide change while presenting. When the
+//! presentation ends it produces a [`Rehearsal`] report with per-slide
+//! durations and timestamps that can be exported as CSV or JSON, or saved to
+//! the deck's rehearsal history so runs can be compared over time.
+
+use crate::state;
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Tracks which slide is on screen and for how long.
+pub struct SlideTimer {
+    /// Wall-clock start of the run, in seconds since the Unix epoch
+    started_at: u64,
+    /// Monotonic start of the run
+    start: Instant,
+    /// Slide currently on screen and the moment it was shown
+    current: (usize, Instant),
+    /// Completed visits as `(slide, shown at, left at)` offsets in seconds
+    visits: Vec<(usize, f64, f64)>,
+    /// When the run was paused, if it is
+    paused_at: Option<Instant>,
+}
+
+impl SlideTimer {
+    /// Starts timing with `slide` on screen.
+    pub fn new(slide: usize) -> Self {
+        let now = Instant::now();
+        SlideTimer {
+            started_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            start: now,
+            current: (slide, now),
+            visits: Vec::new(),
+            paused_at: None,
+        }
+    }
+
+    /// Records that `slide` is now on screen.
+    pub fn enter(&mut self, slide: usize) {
+        let now = Instant::now();
+        self.close_visit(now);
+        self.current = (slide, now);
+    }
+
+    /// Time since the run started, leaving out the time it was paused.
+    pub fn elapsed(&self) -> Duration {
+        self.paused_at.unwrap_or_else(Instant::now) - self.start
+    }
+
+    /// Stops the clock, until [`SlideTimer::resume`].
+    #[cfg(not(feature = "termwiz"))]
+    pub fn pause(&mut self) {
+        self.paused_at.get_or_insert_with(Instant::now);
+    }
+
+    /// Starts the clock again, and returns how long it was paused for.
+    pub fn resume(&mut self) -> Duration {
+        let Some(paused_at) = self.paused_at.take() else {
+            return Duration::ZERO;
+        };
+        // The run and the current visit start that much later, as if the
+        // pause had never happened.
+        let paused = paused_at.elapsed();
+        self.start += paused;
+        self.current.1 += paused;
+        paused
+    }
+
+    /// Whether the clock is stopped.
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Ends the run and summarizes it per slide.
+    ///
+    /// # Arguments
+    ///
+    /// * `titles` - Title of every slide in the deck, used to label the report
+    pub fn finish(mut self, titles: &[Option<String>]) -> Rehearsal {
+        self.resume();
+        let now = Instant::now();
+        self.close_visit(now);
+
+        let slides = titles
+            .iter()
+            .enumerate()
+            .map(|(index, title)| {
+                let visits: Vec<_> = self.visits.iter().filter(|v| v.0 == index).collect();
+                SlideTiming {
+                    slide: index + 1,
+                    title: title.clone(),
+                    seconds: visits.iter().fold(0.0, |total, v| total + (v.2 - v.1)),
+                    visits: visits.len(),
+                    first_shown: visits.first().map(|v| v.1),
+                    last_left: visits.last().map(|v| v.2),
+                }
+            })
+            .collect();
+
+        Rehearsal {
+            started_at: self.started_at,
+            total_seconds: (now - self.start).as_secs_f64(),
+            slides,
+        }
+    }
+
+    fn close_visit(&mut self, now: Instant) {
+        let (slide, shown) = self.current;
+        self.visits.push((
+            slide,
+            (shown - self.start).as_secs_f64(),
+            (now - self.start).as_secs_f64(),
+        ));
+    }
+}
+
+/// Time spent on one slide during a run.
+#[derive(Serialize, Deserialize)]
+pub struct SlideTiming {
+    /// 1-based slide number
+    pub slide: usize,
+    /// Slide title, if the slide starts with an H1
+    pub title: Option<String>,
+    /// Total seconds the slide was on screen
+    pub seconds: f64,
+    /// Number of separate times the slide was shown
+    pub visits: usize,
+    /// Seconds from the start of the run until the slide was first shown
+    pub first_shown: Option<f64>,
+    /// Seconds from the start of the run until the slide was last left
+    pub last_left: Option<f64>,
+}
+
+/// Summary of one presentation run.
+#[derive(Serialize, Deserialize)]
+pub struct Rehearsal {
+    /// Wall-clock start of the run, in seconds since the Unix epoch
+    pub started_at: u64,
+    /// Length of the whole run in seconds
+    pub total_seconds: f64,
+    /// Per-slide timings, in deck order
+    pub slides: Vec<SlideTiming>,
+}
+
+impl Rehearsal {
+    /// Writes the report to `path`, as CSV if the extension is `.csv` and as
+    /// JSON otherwise.
+    pub fn export(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let contents = if is_csv {
+            self.to_csv()
+        } else {
+            serde_json::to_string_pretty(self)?
+        };
+        fs::write(path, contents)
+            .map_err(|e| format!("Failed to write timings '{}': {}", path.display(), e))?;
+        Ok(())
+    }
+
+    /// Saves the run to the rehearsal history of `deck` and returns its path.
+    pub fn save_run(&self, deck: &Path) -> Result<PathBuf, Box<dyn Error>> {
+        let dir = runs_dir(deck);
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+        let mut path = dir.join(format!("run-{}.json", self.started_at));
+        let mut n = 2;
+        while path.exists() {
+            path = dir.join(format!("run-{}-{}.json", self.started_at, n));
+            n += 1;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+        Ok(path)
+    }
+
+    /// Loads every saved run of `deck`, oldest first.
+    pub fn load_runs(deck: &Path) -> Result<Vec<Rehearsal>, Box<dyn Error>> {
+        let dir = runs_dir(deck);
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return Ok(Vec::new());
+        };
+        let mut runs = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let json = fs::read_to_string(&path)?;
+                let run: Rehearsal = serde_json::from_str(&json)
+                    .map_err(|e| format!("Invalid run '{}': {}", path.display(), e))?;
+                runs.push(run);
+            }
+        }
+        runs.sort_by_key(|run| run.started_at);
+        Ok(runs)
+    }
+
+    /// One-line summary of the run used when listing the history.
+    pub fn summary(&self) -> String {
+        let shown = self.slides.iter().filter(|s| s.visits > 0).count();
+        format!(
+            "{}  total {:>8}  {} of {} slides shown",
+            format_timestamp(self.started_at),
+            format_seconds(self.total_seconds),
+            shown,
+            self.slides.len()
+        )
+    }
+
+    /// Renders the report as CSV, ending with a `total` row.
+    fn to_csv(&self) -> String {
+        let optional = |value: Option<f64>| value.map(|v| format!("{v:.3}")).unwrap_or_default();
+        let mut out = String::from("slide,title,seconds,visits,first_shown,last_left\n");
+        for timing in &self.slides {
+            out.push_str(&format!(
+                "{},{},{:.3},{},{},{}\n",
+                timing.slide,
+                csv_field(timing.title.as_deref().unwrap_or("")),
+                timing.seconds,
+                timing.visits,
+                optional(timing.first_shown),
+                optional(timing.last_left),
+            ));
+        }
+        out.push_str(&format!("total,,{:.3},,,\n", self.total_seconds));
+        out
+    }
+}
+
+/// Quotes a CSV field if it contains a delimiter, quote, or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Directory holding the saved runs of `deck`.
+fn runs_dir(deck: &Path) -> PathBuf {
+    state::deck_dir(deck).join("runs")
+}
+
+/// Builds a per-slide drift report between two runs of the same deck.
+///
+/// Slides are matched by number; titles are taken from the later run.
+///
+/// # Arguments
+///
+/// * `before` - The earlier run and its 1-based number in the history
+/// * `after` - The later run and its 1-based number in the history
+pub fn compare_runs(before: (&Rehearsal, usize), after: (&Rehearsal, usize)) -> String {
+    let (old, old_no) = before;
+    let (new, new_no) = after;
+    let old_label = format!("Run {old_no}");
+    let new_label = format!("Run {new_no}");
+
+    let mut out = format!(
+        "Comparing run {} ({}) with run {} ({})\n\n",
+        old_no,
+        format_timestamp(old.started_at),
+        new_no,
+        format_timestamp(new.started_at)
+    );
+    out.push_str(&format!(
+        "{:>5}  {:<32}  {:>8}  {:>8}  {:>9}\n",
+        "Slide", "Title", old_label, new_label, "Drift"
+    ));
+
+    let mut changes = Vec::new();
+    let count = old.slides.len().max(new.slides.len());
+    for index in 0..count {
+        let old_secs = old.slides.get(index).map_or(0.0, |s| s.seconds);
+        let new_secs = new.slides.get(index).map_or(0.0, |s| s.seconds);
+        let title = new
+            .slides
+            .get(index)
+            .or_else(|| old.slides.get(index))
+            .and_then(|s| s.title.as_deref())
+            .unwrap_or("");
+        out.push_str(&format!(
+            "{:>5}  {:<32}  {:>8}  {:>8}  {:>9}\n",
+            index + 1,
+            truncate(title, 32),
+            format_seconds(old_secs),
+            format_seconds(new_secs),
+            format_drift(new_secs - old_secs)
+        ));
+        changes.push((index + 1, old_secs, new_secs));
+    }
+    out.push_str(&format!(
+        "{:>5}  {:<32}  {:>8}  {:>8}  {:>9}\n",
+        "",
+        "Total",
+        format_seconds(old.total_seconds),
+        format_seconds(new.total_seconds),
+        format_drift(new.total_seconds - old.total_seconds)
+    ));
+
+    changes.retain(|(_, old_secs, new_secs)| (new_secs - old_secs).abs() >= NOTABLE_DRIFT);
+    changes.sort_by(|a, b| (b.2 - b.1).abs().total_cmp(&(a.2 - a.1).abs()));
+    if !changes.is_empty() {
+        out.push_str("\nLargest changes:\n");
+        for (slide, old_secs, new_secs) in changes.into_iter().take(5) {
+            let verb = if new_secs > old_secs {
+                "grew"
+            } else {
+                "shrank"
+            };
+            out.push_str(&format!(
+                "  slide {} {} from {} to {}\n",
+                slide,
+                verb,
+                format_seconds(old_secs),
+                format_seconds(new_secs)
+            ));
+        }
+    }
+
+    out
+}
+
+/// Drift in seconds below which a slide is not called out as a notable change.
+const NOTABLE_DRIFT: f64 = 5.0;
+
+/// Formats seconds as a compact duration such as `45s`, `2m05s`, or `1h02m00s`.
+fn format_seconds(seconds: f64) -> String {
+    let total = seconds.round() as u64;
+    let (hours, minutes, secs) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{hours}h{minutes:02}m{secs:02}s")
+    } else if minutes > 0 {
+        format!("{minutes}m{secs:02}s")
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Formats a signed difference in seconds, e.g. `+1m20s` or `-8s`.
+fn format_drift(seconds: f64) -> String {
+    let sign = if seconds.round() > 0.0 {
+        "+"
+    } else if seconds.round() < 0.0 {
+        "-"
+    } else {
+        ""
+    };
+    format!("{}{}", sign, format_seconds(seconds.abs()))
+}
+
+/// Formats a Unix timestamp as a UTC date and time, e.g. `2025-06-01 14:30 UTC`.
+fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let minutes_of_day = timestamp % 86_400 / 60;
+
+    // Civil-from-days conversion (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        minutes_of_day / 60,
+        minutes_of_day % 60
+    )
+}
+
+/// Shortens `text` to at most `max` characters, marking the cut with `…`.
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        let mut short: String = text.chars().take(max - 1).collect();
+        short.push('…');
+        short
+    }
+}
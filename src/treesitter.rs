@@ -0,0 +1,165 @@
+//! Tree-sitter highlighting, in builds with the `tree-sitter` feature.
+//!
+//! Each grammar's highlight query tags parts of the code with names such as
+//! `keyword` or `function.method`. The names are matched to the TextMate
+//! scopes syntect's themes are written for, so the code takes its colors
+//! from the same code theme as it would with syntect.
+
+use crate::highlight::span_style;
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+use syntect::{
+    highlighting::{Highlighter as ScopeStyler, Theme as CodeTheme},
+    parsing::Scope,
+};
+use tree_sitter::Language;
+use tree_sitter_highlight::{HighlightConfiguration, HighlightEvent, Highlighter};
+
+/// Highlight names understood by the grammars, each with the TextMate scope
+/// whose color it takes. A tag uses the longest name that it starts with.
+const NAMES: &[(&str, &str)] = &[
+    ("attribute", "entity.other.attribute-name"),
+    ("comment", "comment"),
+    ("constant", "constant"),
+    ("constant.builtin", "constant.language"),
+    ("constructor", "entity.name.type"),
+    ("escape", "constant.character.escape"),
+    ("function", "entity.name.function"),
+    ("function.builtin", "support.function"),
+    ("function.macro", "support.macro"),
+    ("keyword", "keyword"),
+    ("label", "entity.name.label"),
+    ("number", "constant.numeric"),
+    ("operator", "keyword.operator"),
+    ("property", "variable.other.member"),
+    ("punctuation", "punctuation"),
+    ("string", "string"),
+    ("string.special", "string.regexp"),
+    ("tag", "entity.name.tag"),
+    ("type", "entity.name.type"),
+    ("type.builtin", "storage.type"),
+    ("variable", "variable"),
+    ("variable.builtin", "variable.language"),
+    ("variable.parameter", "variable.parameter"),
+];
+
+/// A grammar and the code block languages it highlights.
+struct Grammar {
+    /// Names a code block can give the language by
+    langs: &'static [&'static str],
+    /// The grammar with its highlight query
+    config: HighlightConfiguration,
+}
+
+/// The tree-sitter grammars built into presentrs.
+pub struct Grammars(Vec<Grammar>);
+
+impl Grammars {
+    /// Compiles the highlight queries of every built-in grammar.
+    pub fn load() -> Result<Self, String> {
+        let grammar =
+            |langs: &'static [&'static str], language: Language, highlights: &str, locals: &str| {
+                let mut config = HighlightConfiguration::new(language, highlights, "", locals)
+                    .map_err(|e| format!("Invalid tree-sitter query for {}: {:?}", langs[0], e))?;
+                let names: Vec<&str> = NAMES.iter().map(|(name, _)| *name).collect();
+                config.configure(&names);
+                Ok::<_, String>(Grammar { langs, config })
+            };
+        Ok(Grammars(vec![
+            grammar(
+                &["rust", "rs"],
+                tree_sitter_rust::language(),
+                tree_sitter_rust::HIGHLIGHT_QUERY,
+                "",
+            )?,
+            grammar(
+                &["python", "py"],
+                tree_sitter_python::language(),
+                tree_sitter_python::HIGHLIGHT_QUERY,
+                "",
+            )?,
+            grammar(
+                &["javascript", "js", "jsx"],
+                tree_sitter_javascript::language(),
+                tree_sitter_javascript::HIGHLIGHT_QUERY,
+                tree_sitter_javascript::LOCALS_QUERY,
+            )?,
+            grammar(
+                &["go", "golang"],
+                tree_sitter_go::language(),
+                tree_sitter_go::HIGHLIGHT_QUERY,
+                "",
+            )?,
+            grammar(
+                &["c", "h"],
+                tree_sitter_c::language(),
+                tree_sitter_c::HIGHLIGHT_QUERY,
+                "",
+            )?,
+        ]))
+    }
+
+    /// Whether there is a grammar for `lang`.
+    pub fn has(&self, lang: &str) -> bool {
+        let lang = lang.to_lowercase();
+        self.0.iter().any(|g| g.langs.contains(&lang.as_str()))
+    }
+
+    /// Highlights `content` as `lang` in the colors of `code_theme`, one
+    /// line per line of code, or returns `None` if no grammar is for `lang`.
+    pub fn highlight(
+        &self,
+        content: &str,
+        lang: &str,
+        code_theme: &CodeTheme,
+    ) -> Option<Vec<Line<'static>>> {
+        let lang = lang.to_lowercase();
+        let grammar = self.0.iter().find(|g| g.langs.contains(&lang.as_str()))?;
+        let mut highlighter = Highlighter::new();
+        let events = highlighter
+            .highlight(&grammar.config, content.as_bytes(), None, |_| None)
+            .ok()?;
+
+        let styler = ScopeStyler::new(code_theme);
+        let plain = span_style(styler.get_default());
+        let styles: Vec<Style> = NAMES
+            .iter()
+            .map(|(_, scope)| match Scope::new(scope) {
+                Ok(scope) => span_style(styler.style_for_stack(&[scope])),
+                Err(_) => plain,
+            })
+            .collect();
+
+        let mut lines = Vec::new();
+        let mut spans = Vec::new();
+        let mut stack = Vec::new();
+        for event in events {
+            match event.ok()? {
+                HighlightEvent::HighlightStart(highlight) => stack.push(styles[highlight.0]),
+                HighlightEvent::HighlightEnd => {
+                    stack.pop();
+                }
+                HighlightEvent::Source { start, end } => {
+                    let style = stack.last().copied().unwrap_or(plain);
+                    let mut pieces = content[start..end].split('\n').peekable();
+                    while let Some(piece) = pieces.next() {
+                        let piece = piece.trim_end_matches('\r');
+                        if !piece.is_empty() {
+                            spans.push(Span::styled(piece.to_string(), style));
+                        }
+                        if pieces.peek().is_some() {
+                            lines.push(Line::from(std::mem::take(&mut spans)));
+                        }
+                    }
+                }
+            }
+        }
+        // Like syntect, a final line without a newline is still a line.
+        if !content.is_empty() && !content.ends_with('\n') {
+            lines.push(Line::from(spans));
+        }
+        Some(lines)
+    }
+}
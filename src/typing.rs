@@ -0,0 +1,172 @@
+//! Simulated typing for fake-live terminal demos.
+//!
+//! A fenced block tagged with the `typing` modifier (e.g. ```` ```bash typing ````)
+//! is played back as a terminal session when its slide is shown. Every line
+//! starting with `$ ` is a command that gets typed out keystroke by keystroke;
+//! the lines following it are its pre-recorded output, which appear once the
+//! command has been "run".
+
+use crate::{highlight::Highlighter, highlight_code_block, themes::Theme};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use std::time::Duration;
+use syntect::highlighting::Theme as CodeTheme;
+
+/// Delay between the slide appearing (or the previous demo ending) and the first prompt.
+const BLOCK_DELAY: Duration = Duration::from_millis(600);
+/// Pause before the first keystroke after a prompt appears.
+const THINK_DELAY: Duration = Duration::from_millis(500);
+/// Pause between the last keystroke and the command's output appearing.
+const RUN_DELAY: Duration = Duration::from_millis(400);
+/// Pause between a command's output and the next prompt.
+const PROMPT_DELAY: Duration = Duration::from_millis(700);
+
+/// Reveal schedule for one line of a typing demo, relative to slide entry.
+pub struct TypedLine {
+    /// Index of the line within the slide
+    pub line: usize,
+    /// Offset at which the line (or its prompt) appears
+    pub start: Duration,
+    /// Number of leading characters shown as soon as the line appears
+    pub prompt_len: usize,
+    /// Offsets at which each character of the command is typed; empty for output lines
+    pub keystrokes: Vec<Duration>,
+}
+
+impl TypedLine {
+    /// Offset at which the line is fully shown.
+    pub fn finished_at(&self) -> Duration {
+        match self.keystrokes.last() {
+            Some(last) => *last + RUN_DELAY,
+            None => self.start,
+        }
+    }
+
+    /// Returns how `line` looks `elapsed` after its slide was entered.
+    pub fn render(&self, line: &Line<'static>, elapsed: Duration) -> Line<'static> {
+        if elapsed < self.start {
+            return Line::default();
+        }
+        if elapsed >= self.finished_at() {
+            return line.clone();
+        }
+        let typed = self.keystrokes.iter().filter(|t| **t <= elapsed).count();
+        let mut partial = truncate_line(line, self.prompt_len + typed);
+        // The cursor takes the color of the slide's text.
+        partial.spans.push(Span::styled(
+            "▌",
+            Style::default().add_modifier(Modifier::SLOW_BLINK),
+        ));
+        partial
+    }
+}
+
+/// Renders a `typing` demo block and computes its reveal schedule.
+///
+/// # Arguments
+///
+/// * `content` - Raw contents of the fenced block
+/// * `lang` - Language used to highlight the typed commands
+/// * `theme` - Colors to draw the block in
+/// * `code_theme` - Syntax highlighting theme
+/// * `highlighter` - Highlighter for the typed commands
+/// * `first_line` - Index within the slide of the block's first line
+/// * `start` - Offset from slide entry at which the previous demo finished
+///
+/// # Returns
+///
+/// The fully rendered lines, their schedule, and the offset at which the demo ends
+pub fn typing_block(
+    content: &str,
+    lang: Option<&str>,
+    theme: &Theme,
+    code_theme: &CodeTheme,
+    highlighter: &Highlighter,
+    first_line: usize,
+    start: Duration,
+) -> (Vec<Line<'static>>, Vec<TypedLine>, Duration) {
+    let prompt_style = Style::default()
+        .fg(theme.code)
+        .add_modifier(Modifier::BOLD);
+    let output_style = Style::default().fg(Color::Gray);
+
+    let mut lines = Vec::new();
+    let mut schedule = Vec::new();
+    let mut clock = start + BLOCK_DELAY;
+    let mut after_command = false;
+
+    for (i, raw) in content.lines().enumerate() {
+        if let Some(command) = raw.strip_prefix("$ ") {
+            if after_command {
+                clock += PROMPT_DELAY;
+            }
+            let mut spans = vec![Span::styled("$ ", prompt_style)];
+            if let Some(highlighted) = highlight_code_block(command, lang, theme, code_theme, highlighter)
+                .into_iter()
+                .next()
+            {
+                spans.extend(highlighted.spans);
+            }
+            lines.push(Line::from(spans));
+
+            let mut keystrokes = Vec::new();
+            let mut at = clock + THINK_DELAY;
+            for (n, c) in command.chars().enumerate() {
+                at += keystroke_delay(c, n);
+                keystrokes.push(at);
+            }
+            let line = TypedLine {
+                line: first_line + i,
+                start: clock,
+                prompt_len: 2,
+                keystrokes,
+            };
+            clock = line.finished_at();
+            schedule.push(line);
+            after_command = true;
+        } else {
+            lines.push(Line::from(Span::styled(raw.to_string(), output_style)));
+            schedule.push(TypedLine {
+                line: first_line + i,
+                start: clock,
+                prompt_len: 0,
+                keystrokes: Vec::new(),
+            });
+        }
+    }
+
+    (lines, schedule, clock)
+}
+
+/// Time taken to type the `n`th character `c` of a command.
+///
+/// A deterministic jitter keeps the rhythm human-looking while making every
+/// run of the demo identical; word boundaries get an extra pause.
+fn keystroke_delay(c: char, n: usize) -> Duration {
+    let jitter = (n.wrapping_mul(2_654_435_761) >> 7) % 70;
+    let pause = if c == ' ' { 90 } else { 0 };
+    Duration::from_millis(45 + jitter as u64 + pause)
+}
+
+/// Keeps only the first `chars` characters of a styled line.
+fn truncate_line(line: &Line<'static>, chars: usize) -> Line<'static> {
+    let mut remaining = chars;
+    let mut spans = Vec::new();
+    for span in &line.spans {
+        if remaining == 0 {
+            break;
+        }
+        let count = span.content.chars().count();
+        if count <= remaining {
+            spans.push(span.clone());
+            remaining -= count;
+        } else {
+            let text: String = span.content.chars().take(remaining).collect();
+            spans.push(Span::styled(text, span.style));
+            remaining = 0;
+        }
+    }
+    Line::from(spans)
+}
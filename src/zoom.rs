@@ -0,0 +1,116 @@
+//! Changing the terminal's font size from inside the presentation, to suit
+//! the room.
+//!
+//! Only some terminals let a program change their font size. kitty does
+//! through its remote control protocol, which takes commands as escape
+//! sequences when `allow_remote_control` is on in `kitty.conf`, and xterm
+//! does through OSC 50 when its `allowFontOps` resource is set. Inside tmux,
+//! the sequences are wrapped to pass through to the terminal, which needs
+//! `set -g allow-passthrough on`. Elsewhere, zooming does nothing.
+//!
+//! Zooming is done in relative steps, so the font goes back to exactly the
+//! size it had when the presentation started, however it was set.
+
+use std::{
+    env,
+    io::{self, Write},
+};
+
+/// Points kitty grows or shrinks the font by in each step, as its own
+/// `ctrl+shift+equal` shortcut does.
+const KITTY_STEP: u32 = 2;
+
+/// A way of asking the terminal to change its font size.
+#[derive(Clone, Copy)]
+enum Protocol {
+    /// kitty's remote control protocol
+    Kitty,
+    /// xterm's font menu, through OSC 50
+    Xterm,
+}
+
+/// The font size of the terminal the presentation runs in, as changed with
+/// the zoom keys.
+#[derive(Default)]
+pub struct Zoom {
+    /// How to change the font size, if the terminal is known to allow it
+    protocol: Option<Protocol>,
+    /// Whether sequences must be wrapped to get through tmux
+    tmux: bool,
+    /// Steps the font has been grown by, negative when it was shrunk
+    steps: i32,
+}
+
+impl Zoom {
+    /// Recognizes the terminal from the environment it set.
+    pub fn detect() -> Self {
+        let term = env::var("TERM").unwrap_or_default();
+        let protocol = if env::var_os("KITTY_WINDOW_ID").is_some() || term == "xterm-kitty" {
+            Some(Protocol::Kitty)
+        } else if env::var_os("XTERM_VERSION").is_some() {
+            Some(Protocol::Xterm)
+        } else {
+            None
+        };
+        Zoom {
+            protocol,
+            tmux: env::var_os("TMUX").is_some(),
+            steps: 0,
+        }
+    }
+
+    /// Grows the font by `steps` steps, or shrinks it when `steps` is
+    /// negative. Returns whether the terminal is one that can be asked to.
+    pub fn step(&mut self, out: &mut impl Write, steps: i32) -> io::Result<bool> {
+        if !self.request(out, steps)? {
+            return Ok(false);
+        }
+        self.steps += steps;
+        Ok(true)
+    }
+
+    /// Puts the font back to the size it had when the presentation started.
+    /// Returns whether the terminal is one that can be asked to.
+    pub fn reset(&mut self, out: &mut impl Write) -> io::Result<bool> {
+        self.step(out, -self.steps)
+    }
+
+    /// Steps the font has been grown by, negative when it was shrunk.
+    pub fn steps(&self) -> i32 {
+        self.steps
+    }
+
+    /// Asks the terminal to change its font size by `steps` steps.
+    fn request(&self, out: &mut impl Write, steps: i32) -> io::Result<bool> {
+        let Some(protocol) = self.protocol else {
+            return Ok(false);
+        };
+        if steps == 0 {
+            return Ok(true);
+        }
+        let sign = if steps > 0 { '+' } else { '-' };
+        let sequence = match protocol {
+            // kitty answers commands unless told not to, and the answer
+            // would arrive as key presses.
+            Protocol::Kitty => format!(
+                "\x1bP@kitty-cmd{{\"cmd\":\"set-font-size\",\"version\":[0,26,0],\
+                 \"no_response\":true,\"payload\":{{\"size\":{},\"increment_op\":\"{}\"}}}}\
+                 \x1b\\",
+                steps.unsigned_abs() * KITTY_STEP,
+                sign
+            ),
+            Protocol::Xterm => format!("\x1b]50;#{}{}\x07", sign, steps.unsigned_abs()),
+        };
+        if self.tmux {
+            write!(
+                out,
+                "\x1bPtmux;{}\x1b\\",
+                sequence.replace('\x1b', "\x1b\x1b")
+            )?;
+        } else {
+            out.write_all(sequence.as_bytes())?;
+        }
+        out.flush()?;
+        Ok(true)
+    }
+}